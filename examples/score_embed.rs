@@ -0,0 +1,45 @@
+//! Turn an osu!api v2 score into display-ready data, e.g. for a Discord embed.
+//!
+//! Run with `cargo run --example score_embed --features embed`.
+
+#![cfg_attr(any(feature = "async_std", feature = "async_tokio"), allow(unused))]
+
+use murasame_pp::embed::ScoreEmbed;
+
+const SCORE_JSON: &str = r#"{
+    "ruleset_id": 0,
+    "mods": [{ "acronym": "HD" }, { "acronym": "DT" }],
+    "max_combo": 500,
+    "statistics": {
+        "great": 590,
+        "ok": 8,
+        "meh": 0,
+        "miss": 2
+    }
+}"#;
+
+#[cfg(not(any(feature = "async_std", feature = "async_tokio")))]
+fn main() {
+    let map_bytes = std::fs::read("./maps/2785319.osu").expect("failed to read map");
+
+    let embed = match ScoreEmbed::from_bytes(&map_bytes, SCORE_JSON.as_bytes()) {
+        Ok(embed) => embed,
+        Err(err) => return eprintln!("{}", err),
+    };
+
+    println!("{:?} | {:.2}%", embed.grade, embed.accuracy);
+    println!(
+        "{:.2}pp ({:.2}pp for FC, {:.2}pp for SS)",
+        embed.pp, embed.if_fc_pp, embed.max_pp
+    );
+    println!("{}/{}x | {:.2}*", embed.combo, embed.max_combo, embed.stars);
+    println!(
+        "AR {:.2} | OD {:.2} | CS {:.2} | HP {:.2} | {:.0} BPM",
+        embed.ar, embed.od, embed.cs, embed.hp, embed.bpm
+    );
+}
+
+#[cfg(any(feature = "async_std", feature = "async_tokio"))]
+fn main() {
+    eprintln!("This example parses the map synchronously, disable the async features to run it");
+}
@@ -0,0 +1,998 @@
+use std::ops::{Range, RangeInclusive};
+
+use crate::{
+    beatmap::difficulty_range,
+    osu,
+    parse::{HitObjectKind, Pos2},
+    taiko, AnyStars, Beatmap, DifficultyAttributes, GameMode, Mods, OsuStars, Strains, TaikoStars,
+};
+
+/// Maximum rotations per second that count towards a spinner.
+const MAX_ROTATIONS_PER_SECOND: f64 = 477.0 / 60.0;
+
+/// Bonus spins only start after this many spins above the required amount.
+const BONUS_SPINS_GAP: u32 = 2;
+
+/// Difficulty changing mod combinations that are checked by [`mods_in_star_range`].
+const STANDARD_MODS: [u32; 13] = [
+    0,
+    u32::EZ,
+    u32::HR,
+    u32::HT,
+    u32::DT,
+    u32::EZ + u32::HT,
+    u32::EZ + u32::DT,
+    u32::HR + u32::HT,
+    u32::HR + u32::DT,
+    u32::FL,
+    u32::HR + u32::FL,
+    u32::DT + u32::FL,
+    u32::HR + u32::DT + u32::FL,
+];
+
+/// Timing and spin requirements of a spinner.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpinnerInfo {
+    /// The start time in milliseconds, adjusted to the clock rate.
+    pub start_time: f64,
+    /// The duration in milliseconds, adjusted to the clock rate.
+    pub duration: f64,
+    /// The amount of spins required to clear the spinner.
+    ///
+    /// Not affected by the clock rate.
+    pub required_spins: u32,
+    /// The maximum amount of spins that award bonus score.
+    pub max_bonus_spins: u32,
+}
+
+/// Return the [`SpinnerInfo`] of each spinner in the map.
+///
+/// The amount of required spins depends on OD, which is affected by the mods,
+/// and on the duration of the spinner before the clock rate is applied since
+/// spins are counted in gameplay time.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, analysis};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// for spinner in analysis::spinners(&map, 64) {
+///     println!("{}ms: {} spins", spinner.duration, spinner.required_spins);
+/// }
+/// ```
+pub fn spinners(map: &Beatmap, mods: u32) -> Vec<SpinnerInfo> {
+    let clock_rate = mods.clock_rate();
+    let od = (map.od * mods.od_ar_hp_multiplier() as f32).min(10.0) as f64;
+    let min_rotations_per_second = difficulty_range(od, 3.0, 5.0, 7.5);
+
+    map.hit_objects
+        .iter()
+        .filter_map(|h| match h.kind {
+            HitObjectKind::Spinner { end_time } => Some((h.start_time, end_time)),
+            _ => None,
+        })
+        .map(|(start_time, end_time)| {
+            let seconds = (end_time - start_time) / 1000.0;
+
+            let required_spins = (seconds * min_rotations_per_second) as u32;
+            let max_bonus_spins =
+                (((MAX_ROTATIONS_PER_SECOND - min_rotations_per_second) * seconds) as u32)
+                    .saturating_sub(BONUS_SPINS_GAP);
+
+            SpinnerInfo {
+                start_time: start_time / clock_rate,
+                duration: (end_time - start_time) / clock_rate,
+                required_spins,
+                max_bonus_spins,
+            }
+        })
+        .collect()
+}
+
+/// Travel values of a slider as they are used in the osu!standard difficulty calculation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SliderInfo {
+    /// The start time in milliseconds, adjusted to the clock rate.
+    pub start_time: f64,
+    /// The duration in milliseconds, adjusted to the clock rate.
+    pub duration: f64,
+    /// The amount of repeats.
+    pub repeats: usize,
+    /// The distance the cursor has to travel to follow the slider,
+    /// in normalized units where the circle radius is 50.
+    pub travel_dist: f64,
+    /// The time in milliseconds until the last nested object,
+    /// adjusted to the clock rate and capped at 25ms.
+    pub travel_time: f64,
+    /// The average cursor velocity across the slider i.e. `travel_dist / travel_time`.
+    pub velocity: f64,
+}
+
+/// Return the [`SliderInfo`] of each slider in an osu!standard map.
+///
+/// Maps of other modes return no sliders.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, analysis};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let sliders = analysis::sliders(&map, 0);
+/// let fastest = sliders.iter().map(|slider| slider.velocity).fold(0.0, f64::max);
+///
+/// println!("Fastest slider: {:.2} units/ms", fastest);
+/// ```
+pub fn sliders(map: &Beatmap, mods: u32) -> Vec<SliderInfo> {
+    if map.mode != GameMode::Osu {
+        return Vec::new();
+    }
+
+    osu::slider_infos(OsuStars::new(map).mods(mods))
+}
+
+/// Position of a hit object after stacking as it is used in the osu!standard difficulty calculation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StackedObject {
+    /// The position before stacking, flipped vertically if HR is enabled.
+    pub pos: Pos2,
+    /// The height of the stack below the object.
+    pub stack_height: f32,
+    /// The position after applying the stack offset.
+    pub stacked_pos: Pos2,
+}
+
+/// Return the [`StackedObject`] of each hit object in an osu!standard map.
+///
+/// Maps of other modes return no objects.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, analysis};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// for (i, obj) in analysis::stacked_objects(&map, 16).iter().enumerate() {
+///     println!("Object {} at {:?}", i, obj.stacked_pos);
+/// }
+/// ```
+pub fn stacked_objects(map: &Beatmap, mods: u32) -> Vec<StackedObject> {
+    if map.mode != GameMode::Osu {
+        return Vec::new();
+    }
+
+    osu::stacked_objects(OsuStars::new(map).mods(mods))
+}
+
+/// A strain section of a map ranked by [`hardest_sections`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct HardSection {
+    /// The start time in milliseconds, adjusted to the clock rate.
+    pub start_time: f64,
+    /// The end time in milliseconds, adjusted to the clock rate.
+    pub end_time: f64,
+    /// The value the section was ranked by.
+    ///
+    /// This is the sum of the skills' peaks where flashlight is only included with the FL mod
+    /// and osu!standard's aim without sliders is not included at all.
+    pub difficulty: f64,
+    /// The strain peak of each skill within the section.
+    pub peaks: Vec<(&'static str, f64)>,
+}
+
+/// Return the `n` most difficult strain sections of the map, hardest first.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, analysis};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// for section in analysis::hardest_sections(&map, 0, 3) {
+///     println!("{}ms - {}ms: {:.2}", section.start_time, section.end_time, section.difficulty);
+/// }
+/// ```
+pub fn hardest_sections(map: &Beatmap, mods: u32, n: usize) -> Vec<HardSection> {
+    let strains = AnyStars::new(map).mods(mods).strains();
+    let mut sections = strain_sections(&strains, mods);
+
+    sections.sort_by(|a, b| b.difficulty.total_cmp(&a.difficulty));
+    sections.truncate(n);
+
+    sections
+}
+
+/// Turn the strain peaks into [`HardSection`]s in chronological order.
+fn strain_sections(strains: &Strains, mods: u32) -> Vec<HardSection> {
+    let start_time = strains.start_time();
+    let section_len = strains.section_len();
+
+    let skills: Vec<(&'static str, &[f64])> = match strains {
+        Strains::Osu(strains) => vec![
+            ("aim", &strains.aim),
+            ("aim_no_sliders", &strains.aim_no_sliders),
+            ("speed", &strains.speed),
+            ("flashlight", &strains.flashlight),
+        ],
+        Strains::Taiko(strains) => vec![
+            ("color", &strains.color),
+            ("rhythm", &strains.rhythm),
+            ("stamina", &strains.stamina),
+        ],
+        Strains::Catch(strains) => vec![("movement", &strains.movement)],
+        Strains::Mania(strains) => vec![("strain", &strains.strains)],
+    };
+
+    let ranked = |skill: &str| match skill {
+        "aim_no_sliders" => false,
+        "flashlight" => mods.fl(),
+        _ => true,
+    };
+
+    (0..strains.len())
+        .map(|i| {
+            let peaks: Vec<_> = skills
+                .iter()
+                .map(|(skill, peaks)| (*skill, peaks.get(i).copied().unwrap_or(0.0)))
+                .collect();
+
+            let difficulty = peaks
+                .iter()
+                .filter(|(skill, _)| ranked(skill))
+                .map(|(_, peak)| peak)
+                .sum();
+
+            HardSection {
+                start_time: start_time + i as f64 * section_len,
+                end_time: start_time + (i + 1) as f64 * section_len,
+                difficulty,
+                peaks,
+            }
+        })
+        .collect()
+}
+
+/// How [`sections`] splits a map.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SectionSplit {
+    /// Split whenever kiai time starts or ends and around breaks.
+    Markers,
+    /// Split every given amount of bars, assuming 4/4 time signatures.
+    Bars(u32),
+}
+
+/// A musical section of a map as returned by [`sections`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MapSection {
+    /// The start time in milliseconds, adjusted to the clock rate.
+    pub start_time: f64,
+    /// The end time in milliseconds, adjusted to the clock rate.
+    pub end_time: f64,
+    /// Whether the section starts in kiai time.
+    pub kiai: bool,
+    /// The amount of hit objects in the section.
+    pub n_objects: usize,
+    /// The star rating of the section's hit objects on their own.
+    pub stars: f64,
+}
+
+/// Split the map into musical sections and calculate the star rating of each section on its own.
+///
+/// Sections without hit objects, e.g. breaks, are skipped.
+/// The sections are in chronological order so sort them by stars to find e.g. the hardest chorus.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, analysis::{self, SectionSplit}};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let hardest_kiai = analysis::sections(&map, 0, SectionSplit::Markers)
+///     .into_iter()
+///     .filter(|section| section.kiai)
+///     .max_by(|a, b| a.stars.total_cmp(&b.stars));
+///
+/// if let Some(section) = hardest_kiai {
+///     println!("Hardest chorus at {}ms: {:.2}*", section.start_time, section.stars);
+/// }
+/// ```
+pub fn sections(map: &Beatmap, mods: u32, split: SectionSplit) -> Vec<MapSection> {
+    let (first, last) = match (map.hit_objects.first(), map.hit_objects.last()) {
+        (Some(first), Some(last)) => (first.start_time, last.end_time()),
+        _ => return Vec::new(),
+    };
+
+    let mut boundaries = match split {
+        SectionSplit::Markers => {
+            let mut boundaries: Vec<_> = map
+                .effect_points
+                .windows(2)
+                .filter(|w| w[0].kiai != w[1].kiai)
+                .map(|w| w[1].time)
+                .collect();
+
+            if let Some(point) = map.effect_points.first().filter(|point| point.kiai) {
+                boundaries.push(point.time);
+            }
+
+            boundaries.extend(map.breaks.iter().flat_map(|b| [b.start_time, b.end_time]));
+
+            boundaries
+        }
+        SectionSplit::Bars(bars) => bar_boundaries(map, bars.max(1), last),
+    };
+
+    boundaries.retain(|&time| time > first && time <= last);
+    boundaries.sort_by(f64::total_cmp);
+    boundaries.dedup();
+    boundaries.push(f64::INFINITY);
+
+    let clock_rate = mods.clock_rate();
+    let mut sections = Vec::with_capacity(boundaries.len());
+    let mut start_time = first;
+    let mut start_idx = 0;
+
+    for end_time in boundaries {
+        let end_idx =
+            start_idx + map.hit_objects[start_idx..].partition_point(|h| h.start_time < end_time);
+
+        if end_idx > start_idx {
+            let section_map = section_map(map, start_idx..end_idx);
+
+            sections.push(MapSection {
+                start_time: start_time / clock_rate,
+                end_time: end_time.min(last) / clock_rate,
+                kiai: map.effect_point_at(start_time).is_some_and(|p| p.kiai),
+                n_objects: end_idx - start_idx,
+                stars: AnyStars::new(&section_map).mods(mods).calculate().stars(),
+            });
+        }
+
+        start_time = end_time;
+        start_idx = end_idx;
+    }
+
+    sections
+}
+
+/// Timestamps of every `bars`-th bar until `end_time`.
+fn bar_boundaries(map: &Beatmap, bars: u32, end_time: f64) -> Vec<f64> {
+    let mut boundaries = Vec::new();
+
+    for (i, point) in map.timing_points.iter().enumerate() {
+        let len = point.beat_len * 4.0 * bars as f64;
+
+        if len < 1.0 {
+            continue;
+        }
+
+        let next = map
+            .timing_points
+            .get(i + 1)
+            .map_or(end_time, |next| next.time.min(end_time));
+
+        let mut time = point.time;
+
+        while time < next {
+            boundaries.push(time);
+            time += len;
+        }
+    }
+
+    boundaries
+}
+
+/// A copy of the map that only contains the hit objects within the index range.
+fn section_map(map: &Beatmap, range: Range<usize>) -> Beatmap {
+    let hit_objects = map.hit_objects[range.clone()].to_vec();
+    let sounds = map.sounds.get(range).map_or_else(Vec::new, <[u8]>::to_vec);
+
+    let n_circles = hit_objects.iter().filter(|h| h.is_circle()).count() as u32;
+    let n_spinners = hit_objects.iter().filter(|h| h.is_spinner()).count() as u32;
+    let n_sliders = hit_objects.len() as u32 - n_circles - n_spinners;
+
+    Beatmap {
+        n_circles,
+        n_sliders,
+        n_spinners,
+        hit_objects,
+        sounds,
+        timing_points: map.timing_points.clone(),
+        difficulty_points: map.difficulty_points.clone(),
+        effect_points: map.effect_points.clone(),
+        breaks: Vec::new(),
+        creator: String::new(),
+        ..*map
+    }
+}
+
+/// Skill ratings closer than this factor are considered balanced.
+const BALANCED_RATIO: f64 = 1.15;
+
+/// Maps whose average section is at least this fraction of their hardest section
+/// are considered stamina maps.
+const STAMINA_CONSISTENCY: f64 = 0.6;
+
+/// The skill set a map mostly demands, see [`MapProfile`].
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum MapStyle {
+    /// osu!standard maps that are mostly about aim.
+    Aim,
+    /// osu!standard maps that are mostly about tapping speed.
+    Speed,
+    /// osu!standard maps with FL that are mostly about memorization.
+    Flashlight,
+    /// osu!taiko maps that are mostly about reading color changes.
+    Colour,
+    /// osu!taiko maps that are mostly about rhythm changes.
+    Rhythm,
+    /// Maps whose difficulty is spread evenly across the whole map.
+    ///
+    /// For osu!taiko maps this is the stamina skill instead.
+    Stamina,
+    /// osu!catch and osu!mania maps whose difficulty is concentrated in a few sections.
+    Burst,
+    /// No skill stands out.
+    Balanced,
+}
+
+/// A coarse classification of a map, e.g. for recommendations.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MapProfile {
+    /// The skill set the map mostly demands.
+    pub style: MapStyle,
+    /// The star rating of the map.
+    pub stars: f64,
+    /// How much the highest skill rating exceeds the second highest one, at least `1.0`.
+    ///
+    /// Always `1.0` for osu!catch and osu!mania since they only have a single skill.
+    pub dominance: f64,
+    /// The average difficulty of all strain sections relative to the hardest section,
+    /// between `0.0` and `1.0`.
+    pub consistency: f64,
+}
+
+/// Classify which skill set the map demands with the given mods.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, analysis};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let profile = analysis::profile(&map, 64);
+///
+/// println!("{:?} map with {:.2}*", profile.style, profile.stars);
+/// ```
+pub fn profile(map: &Beatmap, mods: u32) -> MapProfile {
+    let stars = AnyStars::new(map).mods(mods);
+    let attributes = stars.clone().calculate();
+    let sections = strain_sections(&stars.strains(), mods);
+
+    let (max, sum, count) = sections
+        .iter()
+        .map(|section| section.difficulty)
+        .filter(|&difficulty| difficulty > 0.0)
+        .fold((0.0_f64, 0.0, 0), |(max, sum, count), difficulty| {
+            (max.max(difficulty), sum + difficulty, count + 1)
+        });
+
+    let consistency = if count > 0 {
+        sum / count as f64 / max
+    } else {
+        0.0
+    };
+
+    let mut ratings = match attributes {
+        DifficultyAttributes::Osu(attrs) => {
+            let mut ratings = vec![(MapStyle::Aim, attrs.aim), (MapStyle::Speed, attrs.speed)];
+
+            if mods.fl() {
+                ratings.push((MapStyle::Flashlight, attrs.flashlight));
+            }
+
+            ratings
+        }
+        DifficultyAttributes::Taiko(attrs) => vec![
+            (MapStyle::Colour, attrs.colour),
+            (MapStyle::Rhythm, attrs.rhythm),
+            (MapStyle::Stamina, attrs.stamina),
+        ],
+        DifficultyAttributes::Catch(_) | DifficultyAttributes::Mania(_) => {
+            let style = if consistency >= STAMINA_CONSISTENCY {
+                MapStyle::Stamina
+            } else {
+                MapStyle::Burst
+            };
+
+            vec![(style, attributes.stars())]
+        }
+    };
+
+    ratings.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    let dominance = match ratings.as_slice() {
+        [(_, first), (_, second), ..] if *second > 0.0 => first / second,
+        _ => 1.0,
+    };
+
+    let style = if ratings.len() > 1 && dominance < BALANCED_RATIO {
+        MapStyle::Balanced
+    } else {
+        ratings[0].0
+    };
+
+    MapProfile {
+        style,
+        stars: attributes.stars(),
+        dominance,
+        consistency,
+    }
+}
+
+/// A mod combination whose star rating lies within the range of [`mods_in_star_range`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StarRangeMatch {
+    /// The mods as bit values.
+    pub mods: u32,
+    /// The clock rate, either a custom one or the one of the mods.
+    pub clock_rate: f64,
+    /// The star rating with the mods and clock rate.
+    pub stars: f64,
+}
+
+/// Return all standard mod combinations for which the star rating of the map lies within `stars`.
+///
+/// Checked combinations are nomod, EZ, HR, HT, DT, their combinations, and FL
+/// with HR and DT. For each rate in `custom_rates`, all combinations without
+/// HT and DT are checked again with that clock rate.
+///
+/// Matches are sorted by their star rating.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, analysis};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// for found in analysis::mods_in_star_range(&map, 6.0..=6.5, &[1.2, 1.3]) {
+///     println!("{} at {}x: {:.2}*", found.mods, found.clock_rate, found.stars);
+/// }
+/// ```
+pub fn mods_in_star_range(
+    map: &Beatmap,
+    stars: RangeInclusive<f64>,
+    custom_rates: &[f64],
+) -> Vec<StarRangeMatch> {
+    let standard = STANDARD_MODS.iter().map(|&mods| (mods, mods.clock_rate()));
+
+    let custom = custom_rates.iter().flat_map(|&clock_rate| {
+        STANDARD_MODS
+            .iter()
+            .filter(|&&mods| mods & (u32::DT | u32::HT) == 0)
+            .map(move |&mods| (mods, clock_rate))
+    });
+
+    let mut matches: Vec<_> = standard
+        .chain(custom)
+        .map(|(mods, clock_rate)| StarRangeMatch {
+            mods,
+            clock_rate,
+            stars: AnyStars::new(map)
+                .mods(mods)
+                .clock_rate(clock_rate)
+                .calculate()
+                .stars(),
+        })
+        .filter(|found| stars.contains(&found.stars))
+        .collect();
+
+    matches.sort_by(|a, b| a.stars.total_cmp(&b.stars));
+
+    matches
+}
+
+/// Colour pattern statistics of an osu!taiko map, see [`taiko_patterns`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TaikoPatterns {
+    /// The amount of mono streaks, i.e. consecutive notes of the same colour, per length.
+    ///
+    /// The `i`-th entry counts the streaks of length `i + 1`.
+    pub mono_streak_lengths: Vec<usize>,
+    /// The fraction of consecutive notes with different colours, between `0.0` and `1.0`.
+    pub colour_change_rate: f64,
+    /// The repeating hit patterns with the most notes per second, densest first.
+    pub densest_sections: Vec<PatternSection>,
+}
+
+/// A repeating hit pattern of an osu!taiko map as returned by [`taiko_patterns`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PatternSection {
+    /// The start time of the first note in milliseconds, adjusted to the clock rate.
+    pub start_time: f64,
+    /// The start time of the last note in milliseconds, adjusted to the clock rate.
+    pub end_time: f64,
+    /// The amount of notes in the pattern.
+    pub n_notes: usize,
+    /// The average amount of notes per second.
+    pub notes_per_second: f64,
+}
+
+/// Return the mono streak and colour pattern statistics of an osu!taiko map or convert
+/// including its `n` densest patterns.
+///
+/// The statistics are based on the colour encoding of the difficulty calculation
+/// so, just like for the difficulty, the first two hit objects are not considered.
+/// Patterns with less than four notes are too short to be ranked by density.
+///
+/// osu!catch and osu!mania maps return empty statistics.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, analysis};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let patterns = analysis::taiko_patterns(&map, 0, 5);
+///
+/// println!("{:.0}% colour changes", patterns.colour_change_rate * 100.0);
+/// ```
+pub fn taiko_patterns(map: &Beatmap, mods: u32, n: usize) -> TaikoPatterns {
+    if matches!(map.mode, GameMode::Catch | GameMode::Mania) {
+        return TaikoPatterns::default();
+    }
+
+    let mut patterns = taiko::patterns(TaikoStars::new(map).mods(mods));
+
+    patterns
+        .densest_sections
+        .sort_by(|a, b| b.notes_per_second.total_cmp(&a.notes_per_second));
+    patterns.densest_sections.truncate(n);
+
+    patterns
+}
+
+/// Length of the window in which objects are counted for [`ApproximateDifficulty::density`].
+const DENSITY_WINDOW: f64 = 1000.0;
+
+/// Minimum time between two objects for [`ApproximateDifficulty::spacing`]
+/// so that stacks and chords don't blow up the velocity.
+const MIN_DELTA_TIME: f64 = 25.0;
+
+/// Fraction of the densest and widest spaced objects that is averaged.
+const PEAK_FRACTION: f64 = 0.1;
+
+/// A rough estimate of a map's difficulty, see [`approximate_difficulty`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ApproximateDifficulty {
+    /// The average amount of objects per second in the densest parts of the map.
+    pub density: f64,
+    /// The average distance in circle radii per second between consecutive objects
+    /// in the widest spaced parts of the map.
+    ///
+    /// Only osu!standard maps consider spacing, this is `0.0` for all other modes.
+    pub spacing: f64,
+    /// The approximate star rating.
+    ///
+    /// This is **not** the actual star rating; it is only accurate enough to bucket maps
+    /// e.g. by whole stars and can be off by more than a star for unusual maps.
+    pub stars: f64,
+}
+
+/// Estimate the difficulty of the map from its object density and spacing
+/// without running any skill calculation.
+///
+/// This is orders of magnitude faster than [`AnyStars`] and meant for indexing large amounts
+/// of maps, e.g. for a search backend that only needs to filter by rough star ranges and
+/// calculates the exact attributes lazily afterwards. The result must not be displayed as
+/// the star rating of the map.
+///
+/// The estimate always uses the mode of the map itself, converts are not considered.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, analysis};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let estimate = analysis::approximate_difficulty(&map, 64);
+///
+/// println!("~{}*", estimate.stars.round());
+/// ```
+pub fn approximate_difficulty(map: &Beatmap, mods: u32) -> ApproximateDifficulty {
+    let attrs = map.attributes().mods(mods).build();
+    let clock_rate = attrs.clock_rate;
+
+    let times: Vec<_> = map
+        .hit_objects
+        .iter()
+        .map(|h| h.start_time / clock_rate)
+        .collect();
+
+    let mut window_start = 0;
+
+    let counts = times.iter().enumerate().map(|(i, &time)| {
+        while times[window_start] < time - DENSITY_WINDOW {
+            window_start += 1;
+        }
+
+        (i + 1 - window_start) as f64 * 1000.0 / DENSITY_WINDOW
+    });
+
+    let density = peak_average(counts.collect());
+
+    let spacing = if map.mode == GameMode::Osu {
+        let radius = osu::ScalingFactor::new(attrs.cs).radius() as f64;
+
+        let velocities = map
+            .hit_objects
+            .windows(2)
+            .zip(times.windows(2))
+            .filter(|(objects, _)| !objects[0].is_spinner() && !objects[1].is_spinner())
+            .map(|(objects, times)| {
+                let dist = objects[1].pos.distance(objects[0].pos) as f64;
+                let delta_time = (times[1] - times[0]).max(MIN_DELTA_TIME);
+
+                dist / radius * 1000.0 / delta_time
+            });
+
+        peak_average(velocities.collect())
+    } else {
+        0.0
+    };
+
+    let stars = match map.mode {
+        GameMode::Osu => 0.3 * density + 0.075 * spacing,
+        GameMode::Taiko => 0.39 * density,
+        GameMode::Catch => 0.4 * density,
+        GameMode::Mania => 0.225 * density,
+    };
+
+    ApproximateDifficulty {
+        density,
+        spacing,
+        stars,
+    }
+}
+
+fn peak_average(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    values.sort_by(|a, b| b.total_cmp(a));
+    let n = ((values.len() as f64 * PEAK_FRACTION).ceil() as usize).max(1);
+
+    values[..n].iter().sum::<f64>() / n as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::HitObject;
+
+    #[test]
+    fn spinner_requirements() {
+        let map = Beatmap {
+            od: 5.0,
+            hit_objects: vec![HitObject {
+                pos: Pos2::default(),
+                start_time: 1000.0,
+                kind: HitObjectKind::Spinner { end_time: 4000.0 },
+            }],
+            ..Default::default()
+        };
+
+        let nomod = spinners(&map, 0);
+        assert_eq!(nomod[0].duration, 3000.0);
+        assert_eq!(nomod[0].required_spins, 15);
+        assert_eq!(nomod[0].max_bonus_spins, 6);
+
+        let dt = spinners(&map, 64);
+        assert_eq!(dt[0].duration, 2000.0);
+        assert_eq!(dt[0].required_spins, 15);
+        assert_eq!(dt[0].max_bonus_spins, 6);
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn slider_travel() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let nomod = sliders(&map, 0);
+        let dt = sliders(&map, 64);
+
+        assert_eq!(nomod.len(), map.n_sliders as usize);
+        assert!(nomod.iter().any(|slider| slider.travel_dist > 0.0));
+        assert_eq!(nomod[0].travel_dist, dt[0].travel_dist);
+        assert!((nomod[0].duration / 1.5 - dt[0].duration).abs() < 1e-9);
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn stacking_offsets() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let nomod = stacked_objects(&map, 0);
+        let hr = stacked_objects(&map, u32::HR);
+
+        assert_eq!(nomod.len(), map.hit_objects.len());
+        assert!(nomod.iter().any(|obj| obj.stack_height > 0.0));
+        assert!(nomod
+            .iter()
+            .filter(|obj| obj.stack_height == 0.0)
+            .all(|obj| obj.pos == obj.stacked_pos));
+        assert_eq!(nomod[0].pos.y, osu::PLAYFIELD_BASE_SIZE.y - hr[0].pos.y);
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn hardest_sections_are_sorted() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        let sections = hardest_sections(&map, 0, 5);
+
+        assert_eq!(sections.len(), 5);
+        assert_eq!(sections[0].peaks.len(), 3);
+        assert!(sections
+            .windows(2)
+            .all(|pair| pair[0].difficulty >= pair[1].difficulty));
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn hardest_section_contains_hardest_object() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let hardest_object = crate::OsuStars::new(&map)
+            .object_difficulties()
+            .max_by(|a, b| a.aim.total_cmp(&b.aim))
+            .unwrap();
+
+        let peak = |section: &HardSection| section.peaks[0].1;
+
+        let hardest_section = hardest_sections(&map, 0, usize::MAX)
+            .into_iter()
+            .max_by(|a, b| peak(a).total_cmp(&peak(b)))
+            .unwrap();
+
+        assert_eq!(peak(&hardest_section), hardest_object.aim);
+        assert!(hardest_section.start_time < hardest_object.start_time);
+        assert!(hardest_object.start_time <= hardest_section.end_time);
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn musical_sections() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let whole = AnyStars::new(&map).calculate().stars();
+
+        for split in [SectionSplit::Markers, SectionSplit::Bars(8)] {
+            let sections = sections(&map, 0, split);
+
+            let n_objects: usize = sections.iter().map(|section| section.n_objects).sum();
+            assert_eq!(n_objects, map.hit_objects.len());
+            assert!(sections.iter().all(|section| section.stars <= whole));
+            assert!(sections
+                .windows(2)
+                .all(|pair| pair[0].end_time <= pair[1].start_time));
+        }
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn profile_styles() {
+        let map = Beatmap::from_path("./maps/1974394.osu").unwrap();
+        let mania = profile(&map, 0);
+
+        assert!(matches!(mania.style, MapStyle::Stamina | MapStyle::Burst));
+        assert_eq!(mania.dominance, 1.0);
+        assert!(mania.consistency > 0.0 && mania.consistency <= 1.0);
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let osu = profile(&map, 0);
+
+        assert!(osu.dominance >= 1.0);
+        assert_ne!(osu.style, MapStyle::Colour);
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn star_range_lookup() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let nomod = AnyStars::new(&map).calculate().stars();
+        let dt = AnyStars::new(&map).mods(u32::DT).calculate().stars();
+
+        let found = mods_in_star_range(&map, dt - 0.01..=dt + 0.01, &[1.5]);
+        assert!(found.iter().any(|m| m.mods == u32::DT));
+        assert!(found.iter().any(|m| m.mods == 0 && m.clock_rate == 1.5));
+        assert!(found.iter().all(|m| m.mods != 0 || m.clock_rate != 1.0));
+
+        let found = mods_in_star_range(&map, nomod..=nomod, &[]);
+        assert_eq!(found[0].mods, 0);
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn approximate_stars_are_close() {
+        for path in [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/1974394.osu",
+        ] {
+            let map = Beatmap::from_path(path).unwrap();
+
+            for mods in [0, u32::HR, u32::DT] {
+                let approx = approximate_difficulty(&map, mods).stars;
+                let exact = AnyStars::new(&map).mods(mods).calculate().stars();
+
+                assert!(
+                    (approx - exact).abs() < 1.0,
+                    "{} +{}: {} vs {}",
+                    path,
+                    mods,
+                    approx,
+                    exact
+                );
+            }
+        }
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn taiko_pattern_stats() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        let patterns = taiko_patterns(&map, 0, 5);
+
+        let n_notes: usize = (1..)
+            .zip(patterns.mono_streak_lengths.iter())
+            .map(|(len, count)| len * count)
+            .sum();
+
+        let first_hits = map.hit_objects[..2]
+            .iter()
+            .filter(|h| h.is_circle())
+            .count();
+        assert_eq!(n_notes + first_hits, taiko::max_combo(&map));
+
+        assert!(patterns.colour_change_rate > 0.0 && patterns.colour_change_rate < 1.0);
+        assert_eq!(patterns.densest_sections.len(), 5);
+        assert!(patterns
+            .densest_sections
+            .windows(2)
+            .all(|pair| pair[0].notes_per_second >= pair[1].notes_per_second));
+
+        let dt = taiko_patterns(&map, u32::DT, 1);
+        let ratio =
+            dt.densest_sections[0].notes_per_second / patterns.densest_sections[0].notes_per_second;
+        assert!((ratio - 1.5).abs() < 1e-9);
+
+        let mania = Beatmap::from_path("./maps/1974394.osu").unwrap();
+        assert_eq!(taiko_patterns(&mania, 0, 5), TaikoPatterns::default());
+    }
+}
@@ -0,0 +1,84 @@
+use serde::{Serialize, Serializer};
+
+use crate::{
+    beatmap::DifficultySettings,
+    catch::{CatchDifficultyAttributes, CatchPerformanceAttributes},
+    mania::{ManiaDifficultyAttributes, ManiaPerformanceAttributes},
+    osu::{OsuDifficultyAttributes, OsuPerformanceAttributes},
+    taiko::{TaikoDifficultyAttributes, TaikoPerformanceAttributes},
+    DifficultyAttributes, PerformanceAttributes,
+};
+
+/// Serializes the wrapped attributes with all floating point values rounded
+/// to the precision of the osu!api, i.e. the same values as their `api_rounded` method.
+///
+/// The attributes themselves keep their full precision.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{ApiRounded, Beatmap, OsuStars};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let attrs = OsuStars::new(&map).calculate();
+///
+/// let json = serde_json::to_string(&ApiRounded(&attrs)).unwrap();
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct ApiRounded<'a, T>(pub &'a T);
+
+macro_rules! impl_serialize {
+    ( $( $ty:ty ),* ) => {
+        $(
+            impl Serialize for ApiRounded<'_, $ty> {
+                #[inline]
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    self.0.api_rounded().serialize(serializer)
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize!(
+    OsuDifficultyAttributes,
+    OsuPerformanceAttributes,
+    TaikoDifficultyAttributes,
+    TaikoPerformanceAttributes,
+    CatchDifficultyAttributes,
+    CatchPerformanceAttributes,
+    ManiaDifficultyAttributes,
+    ManiaPerformanceAttributes,
+    DifficultyAttributes,
+    PerformanceAttributes,
+    DifficultySettings
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_rounded_values() {
+        let attrs = OsuDifficultyAttributes {
+            aim: 2.345_678,
+            stars: 5.678_901,
+            ..Default::default()
+        };
+
+        let json = serde_json::to_value(ApiRounded(&attrs)).unwrap();
+
+        assert_eq!(json["aim"], 2.35);
+        assert_eq!(json["stars"], 5.68);
+
+        let wrapped = DifficultyAttributes::Osu(attrs);
+        let json = serde_json::to_value(ApiRounded(&wrapped)).unwrap();
+        assert_eq!(json, serde_json::to_value(wrapped.api_rounded()).unwrap());
+
+        // The attributes themselves are unchanged
+        assert_eq!(serde_json::to_value(attrs).unwrap()["aim"], 2.345_678);
+    }
+}
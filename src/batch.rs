@@ -0,0 +1,238 @@
+use std::{iter::FromIterator, slice, thread, vec};
+
+use crate::{AnyStars, Beatmap, DifficultyAttributes, Mods};
+
+/// Mods that never affect difficulty attributes.
+const IRRELEVANT_MODS: u32 = u32::NF | u32::SD | u32::RX | u32::NC | u32::SO | u32::AP | u32::PF;
+
+/// Strip all mods that don't affect difficulty attributes and turn NC into DT.
+///
+/// Two mod combinations with the same normalized mods have the same difficulty attributes.
+///
+/// ```
+/// use murasame_pp::batch::normalize_mods;
+///
+/// // HDNC and HDDTSD both normalize to HDDT
+/// assert_eq!(normalize_mods(8 + 64 + 512), 8 + 64);
+/// assert_eq!(normalize_mods(8 + 64 + 32), 8 + 64);
+/// ```
+#[inline]
+pub fn normalize_mods(mods: u32) -> u32 {
+    let dt = if mods & u32::NC > 0 { u32::DT } else { 0 };
+
+    (mods | dt) & !IRRELEVANT_MODS
+}
+
+/// Identifies an entry of an [`AttributeBatch`].
+///
+/// Keys are ordered by map first and by mods second.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BatchKey<K> {
+    /// The key of the map, e.g. its id or md5 hash.
+    pub map: K,
+    /// The normalized mods, see [`normalize_mods`].
+    pub mods: u32,
+}
+
+/// Difficulty attributes of multiple maps and mods, sorted by their [`BatchKey`].
+///
+/// The order does not depend on the order in which attributes were calculated
+/// so it stays the same across runs, e.g. for database upserts.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{batch::AttributeBatch, Beatmap};
+///
+/// # /*
+/// let maps: Vec<(String, Beatmap)> = ...
+/// # */
+/// # let maps = vec![("md5".to_owned(), Beatmap::default())];
+/// let batch = AttributeBatch::calculate(
+///     maps.iter().map(|(md5, map)| (md5.clone(), map)),
+///     &[0, 8 + 64, 16],
+/// );
+///
+/// for (key, attrs) in batch.iter() {
+///     println!("{} +{}: {:.2}*", key.map, key.mods, attrs.stars());
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AttributeBatch<K> {
+    entries: Vec<(BatchKey<K>, DifficultyAttributes)>,
+}
+
+impl<K: Ord> AttributeBatch<K> {
+    /// Calculate the attributes of every map for every mod combination,
+    /// distributing the work across all available threads.
+    ///
+    /// Mod combinations with the same normalized mods are only calculated once.
+    pub fn calculate<'m, I>(maps: I, mods: &[u32]) -> Self
+    where
+        I: IntoIterator<Item = (K, &'m Beatmap)>,
+        K: Clone + Send + Sync,
+    {
+        let mut mods: Vec<_> = mods.iter().copied().map(normalize_mods).collect();
+        mods.sort_unstable();
+        mods.dedup();
+
+        let jobs: Vec<_> = maps
+            .into_iter()
+            .flat_map(|(key, map)| {
+                mods.iter().map(move |&mods| {
+                    let key = BatchKey {
+                        map: key.clone(),
+                        mods,
+                    };
+
+                    (key, map)
+                })
+            })
+            .collect();
+
+        let n_threads = thread::available_parallelism().map_or(1, usize::from);
+        let chunk_len = jobs.len().div_ceil(n_threads).max(1);
+
+        let entries = thread::scope(|scope| {
+            let handles: Vec<_> = jobs
+                .chunks(chunk_len)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|(key, map)| {
+                                let attrs = AnyStars::new(map).mods(key.mods).calculate();
+
+                                (key.clone(), attrs)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                })
+                .collect()
+        });
+
+        Self::from_entries(entries)
+    }
+
+    /// Return the attributes for the map key and mods.
+    ///
+    /// The mods don't need to be normalized.
+    #[inline]
+    pub fn get(&self, map: &K, mods: u32) -> Option<&DifficultyAttributes> {
+        let mods = normalize_mods(mods);
+
+        self.entries
+            .binary_search_by(|(key, _)| (&key.map, key.mods).cmp(&(map, mods)))
+            .ok()
+            .map(|idx| &self.entries[idx].1)
+    }
+
+    fn from_entries(mut entries: Vec<(BatchKey<K>, DifficultyAttributes)>) -> Self {
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries.dedup_by(|(a, _), (b, _)| a == b);
+
+        Self { entries }
+    }
+}
+
+impl<K> AttributeBatch<K> {
+    /// The amount of entries.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the batch contains no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over all keys in order.
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = &BatchKey<K>> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    /// Iterate over all entries in order.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, (BatchKey<K>, DifficultyAttributes)> {
+        self.entries.iter()
+    }
+}
+
+/// Collects already calculated attributes, normalizing their mods and sorting them.
+///
+/// If a key occurs multiple times, the first occurrence is kept.
+impl<K: Ord> FromIterator<(BatchKey<K>, DifficultyAttributes)> for AttributeBatch<K> {
+    fn from_iter<I: IntoIterator<Item = (BatchKey<K>, DifficultyAttributes)>>(iter: I) -> Self {
+        let entries = iter
+            .into_iter()
+            .map(|(key, attrs)| {
+                let key = BatchKey {
+                    map: key.map,
+                    mods: normalize_mods(key.mods),
+                };
+
+                (key, attrs)
+            })
+            .collect();
+
+        Self::from_entries(entries)
+    }
+}
+
+impl<K> IntoIterator for AttributeBatch<K> {
+    type Item = (BatchKey<K>, DifficultyAttributes);
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, K> IntoIterator for &'a AttributeBatch<K> {
+    type Item = &'a (BatchKey<K>, DifficultyAttributes);
+    type IntoIter = slice::Iter<'a, (BatchKey<K>, DifficultyAttributes)>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_is_sorted_and_deduplicated() {
+        let taiko = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        let osu = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let maps = [(2785319, &osu), (1028484, &taiko)];
+        let batch = AttributeBatch::calculate(maps, &[64 + 512, 0, 64, 1 + 16]);
+
+        let keys: Vec<_> = batch.keys().map(|key| (key.map, key.mods)).collect();
+        let expected = [(1028484, 0), (1028484, 16), (1028484, 64)];
+        assert_eq!(&keys[..3], expected);
+        assert_eq!(batch.len(), 6);
+
+        let dt = AnyStars::new(&osu).mods(64).calculate();
+        assert_eq!(batch.get(&2785319, 64 + 512), Some(&dt));
+
+        let collected: AttributeBatch<_> = batch.clone().into_iter().rev().collect();
+        assert_eq!(collected, batch);
+    }
+}
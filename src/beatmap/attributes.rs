@@ -1,4 +1,4 @@
-use crate::{Beatmap, GameMode, Mods};
+use crate::{util::round_api, Beatmap, CustomMod, GameMode, Mods};
 
 /// Summary struct for a [`Beatmap`]'s attributes.
 #[derive(Clone, Debug, PartialEq)]
@@ -26,6 +26,59 @@ pub struct BeatmapHitWindows {
     pub od: f64,
 }
 
+/// Difficulty settings after applying mods and clock rate.
+///
+/// Contained in the difficulty attributes of every mode so that the settings
+/// can be displayed next to the star rating without a separate calculation.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DifficultySettings {
+    /// The approach rate.
+    pub ar: f64,
+    /// The overall difficulty.
+    pub od: f64,
+    /// The circle size.
+    pub cs: f64,
+    /// The health drain rate.
+    pub hp: f64,
+    /// The clock rate with respect to mods.
+    pub clock_rate: f64,
+    /// The time in milliseconds that hit objects appear before their start time.
+    pub preempt: f64,
+    /// The hit window for a 300 ("Great") in milliseconds.
+    ///
+    /// `0.0` for osu!catch since it has no timing-based judgements.
+    pub great_hit_window: f64,
+    /// The hit window for a 100 ("Ok") in milliseconds.
+    ///
+    /// `0.0` for osu!catch.
+    pub ok_hit_window: f64,
+    /// The hit window for a 50 ("Meh") in milliseconds.
+    ///
+    /// `0.0` for osu!taiko and osu!catch.
+    pub meh_hit_window: f64,
+    /// The beats per minute of the map's first timing point, adjusted to the clock rate.
+    pub bpm: f64,
+}
+
+impl DifficultySettings {
+    /// Round all values to the precision of the osu!api.
+    pub fn api_rounded(self) -> Self {
+        Self {
+            ar: round_api(self.ar),
+            od: round_api(self.od),
+            cs: round_api(self.cs),
+            hp: round_api(self.hp),
+            clock_rate: self.clock_rate,
+            preempt: round_api(self.preempt),
+            great_hit_window: round_api(self.great_hit_window),
+            ok_hit_window: round_api(self.ok_hit_window),
+            meh_hit_window: round_api(self.meh_hit_window),
+            bpm: round_api(self.bpm),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 /// Specify values for this builder to get [`BeatmapAttributes`] or [`BeatmapHitWindows`] based on
 /// mods & co.
@@ -36,8 +89,10 @@ pub struct BeatmapAttributesBuilder {
     cs: f32,
     hp: f32,
     mods: Option<u32>,
+    custom_mod: CustomMod,
     clock_rate: Option<f64>,
     converted: bool,
+    bpm: f64,
 }
 
 impl BeatmapAttributesBuilder {
@@ -49,6 +104,9 @@ impl BeatmapAttributesBuilder {
     const TAIKO_AVG: f64 = 35.0;
     const TAIKO_MAX: f64 = 20.0;
 
+    const MANIA_OK_OFFSET: f32 = 63.0;
+    const MANIA_MEH_OFFSET: f32 = 87.0;
+
     #[inline]
     /// Create a new [`BeatmapAttributesBuilder`].
     pub fn new(map: &Beatmap) -> Self {
@@ -103,6 +161,14 @@ impl BeatmapAttributesBuilder {
         self
     }
 
+    #[inline]
+    /// Specify a [`CustomMod`] whose effects apply on top of the mods.
+    pub fn custom_mod(&mut self, custom_mod: CustomMod) -> &mut Self {
+        self.custom_mod = custom_mod;
+
+        self
+    }
+
     #[inline]
     /// Specify a custom clock rate.
     pub fn clock_rate(&mut self, clock_rate: f64) -> &mut Self {
@@ -123,32 +189,21 @@ impl BeatmapAttributesBuilder {
     #[inline]
     /// Calculate the AR and OD hit windows.
     pub fn hit_windows(&self) -> BeatmapHitWindows {
-        let mods = self.mods.unwrap_or(0);
-        let clock_rate = self.clock_rate.unwrap_or_else(|| mods.clock_rate());
-
-        let mod_mult = |val: f32| {
-            if mods.hr() {
-                (val * 1.4).min(10.0)
-            } else if mods.ez() {
-                val * 0.5
-            } else {
-                val
-            }
-        };
+        let clock_rate = self.mods_clock_rate();
 
-        let raw_ar = mod_mult(self.ar);
+        let raw_ar = self.mod_mult(self.ar);
         let preempt = difficulty_range(raw_ar as f64, 1800.0, 1200.0, 450.0) / clock_rate;
 
         // OD
         let hit_window = match self.mode {
             GameMode::Osu | GameMode::Catch => {
-                let raw_od = mod_mult(self.od);
+                let raw_od = self.mod_mult(self.od);
 
                 difficulty_range(raw_od as f64, Self::OSU_MIN, Self::OSU_AVG, Self::OSU_MAX)
                     / clock_rate
             }
             GameMode::Taiko => {
-                let raw_od = mod_mult(self.od);
+                let raw_od = self.mod_mult(self.od);
 
                 let diff_range = difficulty_range(
                     raw_od as f64,
@@ -159,23 +214,7 @@ impl BeatmapAttributesBuilder {
 
                 diff_range / clock_rate
             }
-            GameMode::Mania => {
-                let mut value = if !self.converted {
-                    34.0 + 3.0 * (10.0 - self.od).clamp(0.0, 10.0)
-                } else if self.od > 4.0 {
-                    34.0
-                } else {
-                    47.0
-                };
-
-                if mods.hr() {
-                    value /= 1.4;
-                } else if mods.ez() {
-                    value *= 1.4;
-                }
-
-                ((value as f64 * clock_rate).floor() / clock_rate).ceil()
-            }
+            GameMode::Mania => self.mania_hit_window(0.0),
         };
 
         BeatmapHitWindows {
@@ -184,13 +223,113 @@ impl BeatmapAttributesBuilder {
         }
     }
 
+    /// Calculate the [`DifficultySettings`], i.e. the [`BeatmapAttributes`]
+    /// alongside all hit windows, the preempt time, and the BPM.
+    pub fn settings(&self) -> DifficultySettings {
+        let BeatmapAttributes {
+            ar,
+            od,
+            cs,
+            hp,
+            clock_rate,
+            hit_windows,
+        } = self.build();
+
+        let raw_od = self.mod_mult(self.od) as f64;
+
+        let (great_hit_window, ok_hit_window, meh_hit_window) = match self.mode {
+            GameMode::Osu => (
+                hit_windows.od,
+                difficulty_range(raw_od, 140.0, 100.0, 60.0) / clock_rate,
+                difficulty_range(raw_od, 200.0, 150.0, 100.0) / clock_rate,
+            ),
+            GameMode::Taiko => (
+                hit_windows.od,
+                difficulty_range(raw_od, 120.0, 80.0, 50.0) / clock_rate,
+                0.0,
+            ),
+            GameMode::Catch => (0.0, 0.0, 0.0),
+            GameMode::Mania => (
+                hit_windows.od,
+                self.mania_hit_window(Self::MANIA_OK_OFFSET),
+                self.mania_hit_window(Self::MANIA_MEH_OFFSET),
+            ),
+        };
+
+        DifficultySettings {
+            ar,
+            od,
+            cs,
+            hp,
+            clock_rate,
+            preempt: hit_windows.ar,
+            great_hit_window,
+            ok_hit_window,
+            meh_hit_window,
+            bpm: self.bpm * clock_rate,
+        }
+    }
+
+    /// The specified clock rate or the one of the mods and the custom mod.
+    fn mods_clock_rate(&self) -> f64 {
+        let mods = self.mods.unwrap_or(0);
+
+        self.clock_rate
+            .unwrap_or_else(|| mods.clock_rate() * self.custom_mod.clock_rate)
+    }
+
+    /// Apply the difficulty multiplier of the mods to an AR or OD value.
+    fn mod_mult(&self, val: f32) -> f32 {
+        let mods = self.mods.unwrap_or(0);
+        let custom_mult = self.custom_mod.difficulty_multiplier as f32;
+
+        let val = if mods.hr() {
+            (val * 1.4).min(10.0)
+        } else if mods.ez() {
+            val * 0.5
+        } else {
+            val
+        };
+
+        if (custom_mult - 1.0).abs() > 0.0 {
+            (val * custom_mult).min(10.0)
+        } else {
+            val
+        }
+    }
+
+    /// The osu!mania hit window whose base value is `offset` ms larger than the great window.
+    fn mania_hit_window(&self, offset: f32) -> f64 {
+        let mods = self.mods.unwrap_or(0);
+        let clock_rate = self.mods_clock_rate();
+
+        let mut value = if !self.converted {
+            34.0 + 3.0 * (10.0 - self.od).clamp(0.0, 10.0)
+        } else if self.od > 4.0 {
+            34.0
+        } else {
+            47.0
+        };
+
+        value += offset;
+
+        if mods.hr() {
+            value /= 1.4;
+        } else if mods.ez() {
+            value *= 1.4;
+        }
+
+        ((value as f64 * clock_rate).floor() / clock_rate).ceil()
+    }
+
     /// Calculate the [`BeatmapAttributes`].
     pub fn build(&self) -> BeatmapAttributes {
         let mods = self.mods.unwrap_or(0);
-        let clock_rate = self.clock_rate.unwrap_or_else(|| mods.clock_rate());
+        let clock_rate = self.mods_clock_rate();
 
         // HP
-        let hp = (self.hp * mods.od_ar_hp_multiplier() as f32).min(10.0);
+        let hp_mult = mods.od_ar_hp_multiplier() * self.custom_mod.difficulty_multiplier;
+        let hp = (self.hp * hp_mult as f32).min(10.0);
 
         // CS
         let mut cs = self.cs;
@@ -201,6 +340,12 @@ impl BeatmapAttributesBuilder {
             cs *= 0.5;
         }
 
+        let custom_cs_mult = self.custom_mod.cs_multiplier as f32;
+
+        if (custom_cs_mult - 1.0).abs() > 0.0 {
+            cs = (cs * custom_cs_mult).min(10.0);
+        }
+
         let hit_windows = self.hit_windows();
         let BeatmapHitWindows { ar, od } = hit_windows;
 
@@ -239,13 +384,15 @@ impl From<&Beatmap> for BeatmapAttributesBuilder {
             cs: map.cs,
             hp: map.hp,
             mods: None,
+            custom_mod: CustomMod::default(),
             clock_rate: None,
             converted: false,
+            bpm: map.bpm(),
         }
     }
 }
 
-fn difficulty_range(difficulty: f64, min: f64, mid: f64, max: f64) -> f64 {
+pub(crate) fn difficulty_range(difficulty: f64, min: f64, mid: f64, max: f64) -> f64 {
     if difficulty > 5.0 {
         mid + (max - mid) * (difficulty - 5.0) / 5.0
     } else if difficulty < 5.0 {
@@ -68,7 +68,7 @@ impl DifficultyPoint {
         Self {
             time,
             slider_vel: speed_multiplier.clamp(0.1, 10.0),
-            bpm_mult: bpm_multiplier as f64,
+            bpm_mult: bpm_multiplier,
             generate_ticks: !beat_len.is_nan(),
         }
     }
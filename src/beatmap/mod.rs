@@ -1,19 +1,24 @@
 use std::{borrow::Cow, cmp::Ordering};
 
-use crate::{parse::HitObject, util::SortedVec};
+use crate::{catch, mania, osu, parse::HitObject, taiko, util::SortedVec};
 
 pub use self::{
-    attributes::{BeatmapAttributes, BeatmapAttributesBuilder, BeatmapHitWindows},
+    attributes::{
+        BeatmapAttributes, BeatmapAttributesBuilder, BeatmapHitWindows, DifficultySettings,
+    },
     breaks::Break,
     control_points::{DifficultyPoint, EffectPoint, TimingPoint},
     mode::GameMode,
 };
 
+pub(crate) use self::{attributes::difficulty_range, overrides::DifficultyOverrides};
+
 mod attributes;
 mod breaks;
 mod control_points;
 mod converts;
 mod mode;
+mod overrides;
 
 /// The main beatmap struct containing all data relevant
 /// for difficulty and performance calculation
@@ -62,6 +67,9 @@ pub struct Beatmap {
     /// the stack offset for stacked positions.
     pub stack_leniency: f32,
 
+    /// Milliseconds of silence before the audio starts playing.
+    pub audio_lead_in: f64,
+
     /// All break points of the beatmap.
     pub breaks: Vec<Break>,
 
@@ -88,13 +96,75 @@ impl Beatmap {
         }
     }
 
+    /// The audio lead-in in milliseconds, adjusted to the clock rate.
+    #[inline]
+    pub fn audio_lead_in_time(&self, clock_rate: f64) -> f64 {
+        self.audio_lead_in / clock_rate
+    }
+
+    /// The start time of the first hit object in milliseconds, adjusted to the clock rate.
+    ///
+    /// Returns `0.0` if the map has no hit objects.
+    #[inline]
+    pub fn first_object_time(&self, clock_rate: f64) -> f64 {
+        self.hit_objects
+            .first()
+            .map_or(0.0, |h| h.start_time / clock_rate)
+    }
+
+    /// The latest end time of all hit objects in milliseconds, adjusted to the clock rate.
+    ///
+    /// Returns `0.0` if the map has no hit objects.
+    #[inline]
+    pub fn last_object_end_time(&self, clock_rate: f64) -> f64 {
+        osu::last_object_end_time(self) / clock_rate
+    }
+
     /// Sum up the duration of all breaks (in milliseconds).
     #[inline]
     pub fn total_break_time(&self) -> f64 {
         self.breaks.iter().map(Break::duration).sum()
     }
 
+    /// The maximum combo of the map in the given mode.
+    ///
+    /// Only creates the hit objects and counts their nested objects such as slider ticks
+    /// and repeats without any difficulty calculation so it's suitable to validate scores
+    /// before their pp are calculated. Mods don't affect the maximum combo.
+    ///
+    /// Like [`AnyStars::mode`](crate::AnyStars::mode), only osu!standard maps can be converted;
+    /// maps of other modes always use their own mode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use murasame_pp::{Beatmap, GameMode};
+    ///
+    /// # /*
+    /// let map: Beatmap = ...
+    /// # */
+    /// # let map = Beatmap::default();
+    /// let max_combo = map.max_combo(GameMode::Taiko);
+    /// ```
+    pub fn max_combo(&self, mode: GameMode) -> usize {
+        let mode = if self.mode == GameMode::Osu {
+            mode
+        } else {
+            self.mode
+        };
+
+        match mode {
+            GameMode::Osu => osu::max_combo(self),
+            GameMode::Taiko => taiko::max_combo(&self.convert_mode(GameMode::Taiko)),
+            GameMode::Catch => catch::max_combo(self),
+            GameMode::Mania => mania::max_combo(&self.convert_mode(GameMode::Mania)),
+        }
+    }
+
     /// Return the [`TimingPoint`] for the given timestamp.
+    ///
+    /// Like in osu!stable, timestamps before the first timing point use the first timing point.
+    /// If the map has no timing points at all, the default timing point is returned.
     #[inline]
     pub fn timing_point_at(&self, time: f64) -> TimingPoint {
         let idx_result = self
@@ -134,22 +204,40 @@ impl Beatmap {
     ///
     /// # Note
     /// - Since hitsounds are irrelevant for difficulty and performance calculations
-    /// in osu!mania, the resulting map of a conversion to mania will not contain hitsounds.
+    ///   in osu!mania, the resulting map of a conversion to mania will not contain hitsounds.
     /// - To avoid having to clone the map for osu!catch conversions, the field `Beatmap::mode`
-    /// will not be adjusted in a osu!catch-converted map.
+    ///   will not be adjusted in a osu!catch-converted map.
     #[inline]
     pub fn convert_mode(&self, mode: GameMode) -> Cow<'_, Self> {
         if mode == self.mode {
             return Cow::Borrowed(self);
         }
 
+        profile!(Conversion);
+
         match mode {
             GameMode::Osu | GameMode::Catch => Cow::Borrowed(self),
             GameMode::Taiko => Cow::Owned(self.convert_to_taiko()),
-            GameMode::Mania => Cow::Owned(self.convert_to_mania()),
+            GameMode::Mania => Cow::Owned(self.convert_to_mania(None)),
         }
     }
 
+    /// Convert the map into an osu!mania map with the given amount of keys,
+    /// e.g. to preview a convert played with a key mod.
+    ///
+    /// If `key_count` is `None`, the key count is chosen the same way as in
+    /// [`convert_mode`](Beatmap::convert_mode). The key count only affects converts,
+    /// osu!mania maps always keep their own.
+    pub fn convert_to_mania_with_keys(&self, key_count: Option<u8>) -> Cow<'_, Self> {
+        if self.mode == GameMode::Mania {
+            return Cow::Borrowed(self);
+        }
+
+        profile!(Conversion);
+
+        Cow::Owned(self.convert_to_mania(key_count))
+    }
+
     fn clone_without_hit_objects(&self, with_sounds: bool) -> Self {
         Self {
             mode: self.mode,
@@ -169,9 +257,157 @@ impl Beatmap {
             difficulty_points: self.difficulty_points.clone(),
             effect_points: self.effect_points.clone(),
             stack_leniency: self.stack_leniency,
+            audio_lead_in: self.audio_lead_in,
             breaks: self.breaks.clone(),
             creator: self.creator.clone(),
             beatmap_id: self.beatmap_id,
         }
     }
 }
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnyStars, BeatmapExt};
+
+    #[test]
+    fn objects_before_first_timing_point() {
+        // The first note is at 108ms while the first timing point is at 4936ms
+        let map = Beatmap::from_path("./maps/1974394.osu").unwrap();
+        let first = &map.hit_objects[0];
+
+        assert!(first.start_time < map.timing_points[0].time);
+        assert_eq!(map.timing_point_at(first.start_time), map.timing_points[0]);
+
+        let attrs = AnyStars::new(&map).calculate();
+        assert!(attrs.stars().is_finite());
+        assert!(attrs.is_current());
+        assert!(map.pp().calculate().pp().is_finite());
+    }
+
+    const SINGLE_SLIDER: &[u8] = b"osu file format v14
+
+[Difficulty]
+HPDrainRate:5
+CircleSize:4
+OverallDifficulty:8
+ApproachRate:9
+SliderMultiplier:1.4
+SliderTickRate:1
+
+[TimingPoints]
+1000,500,4,2,0,100,1,0
+
+[HitObjects]
+256,192,0,2,0,L|356:192,1,140
+";
+
+    #[test]
+    fn settings_in_all_modes() {
+        let map = Beatmap::from_bytes(SINGLE_SLIDER).unwrap();
+
+        let osu = match AnyStars::new(&map).mods(64).calculate() {
+            crate::DifficultyAttributes::Osu(attrs) => attrs,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(osu.settings.bpm, 180.0);
+        assert_eq!(osu.settings.great_hit_window, osu.great_hit_window);
+        assert_eq!(osu.settings.ok_hit_window, osu.ok_hit_window);
+        assert_eq!(osu.settings.meh_hit_window, osu.meh_hit_window);
+
+        for mode in [GameMode::Taiko, GameMode::Catch, GameMode::Mania] {
+            let settings = match AnyStars::new(&map).mode(mode).mods(16).calculate() {
+                crate::DifficultyAttributes::Osu(attrs) => attrs.settings,
+                crate::DifficultyAttributes::Taiko(attrs) => attrs.settings,
+                crate::DifficultyAttributes::Catch(attrs) => attrs.settings,
+                crate::DifficultyAttributes::Mania(attrs) => attrs.settings,
+            };
+
+            if mode != GameMode::Mania {
+                assert!((settings.cs - 5.2).abs() < 1e-6, "{:?}", mode);
+            }
+
+            assert_eq!(settings.bpm, 120.0, "{:?}", mode);
+            assert!(
+                settings.ok_hit_window >= settings.great_hit_window,
+                "{:?}",
+                mode
+            );
+        }
+    }
+
+    #[test]
+    fn max_combo_without_calculation() {
+        let osu = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        for mode in [
+            GameMode::Osu,
+            GameMode::Taiko,
+            GameMode::Catch,
+            GameMode::Mania,
+        ] {
+            let expected = AnyStars::new(&osu).mode(mode).calculate().max_combo();
+            assert_eq!(osu.max_combo(mode), expected, "{:?}", mode);
+        }
+
+        for path in [
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ] {
+            let map = Beatmap::from_path(path).unwrap();
+            let expected = AnyStars::new(&map).calculate().max_combo();
+            assert_eq!(map.max_combo(GameMode::Osu), expected, "{}", path);
+        }
+    }
+
+    #[test]
+    fn last_object_end_time() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        // Last object is a slider at 115398ms with a length of 63.75px,
+        // a beat length of 352.94ms, a slider velocity of 1.5, and
+        // a slider multiplier of 1.7
+        let duration = 63.75 * (6000.0 / 17.0) / (100.0 * 1.7 * 1.5);
+        let expected = 115_398.0 + duration;
+
+        assert!((map.last_object_end_time(1.0) - expected).abs() < 1e-6);
+        assert!((map.last_object_end_time(1.5) - expected / 1.5).abs() < 1e-6);
+
+        let mania = Beatmap::from_path("./maps/1974394.osu").unwrap();
+
+        let expected = mania
+            .hit_objects
+            .iter()
+            .map(HitObject::end_time)
+            .fold(0.0, f64::max);
+
+        assert!((mania.last_object_end_time(1.0) - expected).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn mania_convert_with_keys() {
+        let osu = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let default = osu.convert_to_mania_with_keys(None);
+        let expected = osu.convert_mode(GameMode::Mania);
+        assert_eq!(default.hit_objects.len(), expected.hit_objects.len());
+        assert!((default.cs - expected.cs).abs() < f32::EPSILON);
+
+        let converted = osu.convert_to_mania_with_keys(Some(4));
+        assert_eq!(converted.mode, GameMode::Mania);
+        assert!((converted.cs - 4.0).abs() < f32::EPSILON);
+        assert!(converted
+            .hit_objects
+            .iter()
+            .all(|h| h.pos.x >= 0.0 && h.pos.x < 512.0));
+
+        let mania = Beatmap::from_path("./maps/1974394.osu").unwrap();
+        assert!(matches!(
+            mania.convert_to_mania_with_keys(Some(9)),
+            Cow::Borrowed(_)
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{CustomMod, Mods};
+
+use super::{Beatmap, BeatmapAttributesBuilder};
+
+/// Custom difficulty settings that replace the values of a [`Beatmap`],
+/// e.g. through the Difficulty Adjust mod.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(crate) struct DifficultyOverrides {
+    pub(crate) ar: Option<f32>,
+    pub(crate) od: Option<f32>,
+    pub(crate) cs: Option<f32>,
+    pub(crate) hp: Option<f32>,
+    pub(crate) custom_mod: CustomMod,
+}
+
+impl DifficultyOverrides {
+    /// Return the map's attribute builder with all overrides applied.
+    ///
+    /// The map itself is left untouched so its hit objects don't need to be cloned.
+    pub(crate) fn attributes(&self, map: &Beatmap) -> BeatmapAttributesBuilder {
+        let mut builder = map.attributes();
+
+        if let Some(ar) = self.ar {
+            builder.ar(ar);
+        }
+
+        if let Some(od) = self.od {
+            builder.od(od);
+        }
+
+        if let Some(cs) = self.cs {
+            builder.cs(cs);
+        }
+
+        if let Some(hp) = self.hp {
+            builder.hp(hp);
+        }
+
+        builder.custom_mod(self.custom_mod);
+
+        builder
+    }
+
+    /// The given clock rate or, if none is given, the clock rate of the mods and the custom mod.
+    pub(crate) fn clock_rate(&self, clock_rate: Option<f64>, mods: u32) -> f64 {
+        clock_rate.unwrap_or_else(|| mods.clock_rate() * self.custom_mod.clock_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_only_given_values() {
+        let map = Beatmap::default();
+
+        assert_eq!(
+            DifficultyOverrides::default().attributes(&map),
+            map.attributes()
+        );
+
+        let overrides = DifficultyOverrides {
+            ar: Some(9.5),
+            ..Default::default()
+        };
+
+        let adjusted = overrides.attributes(&map).build();
+
+        assert!((adjusted.ar - 9.5).abs() < f64::EPSILON);
+        assert!((adjusted.od - map.od as f64).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn custom_mod_effects() {
+        let map = Beatmap {
+            od: 5.0,
+            cs: 4.0,
+            hp: 5.0,
+            ..Default::default()
+        };
+
+        let overrides = DifficultyOverrides {
+            custom_mod: CustomMod::new()
+                .clock_rate(1.25)
+                .difficulty_multiplier(1.2)
+                .cs_multiplier(1.15),
+            ..Default::default()
+        };
+
+        assert!((overrides.clock_rate(None, u32::DT) - 1.875).abs() < f64::EPSILON);
+        assert!((overrides.clock_rate(Some(1.1), u32::DT) - 1.1).abs() < f64::EPSILON);
+
+        let adjusted = overrides.attributes(&map).mods(u32::HR).build();
+
+        assert!((adjusted.clock_rate - 1.25).abs() < f64::EPSILON);
+        assert!((adjusted.hp - 8.4).abs() < 1e-6);
+        assert!((adjusted.cs - 4.0 * 1.3 * 1.15).abs() < 1e-6);
+
+        // The regular mods are unaffected without a custom mod
+        assert_eq!(
+            DifficultyOverrides::default().clock_rate(None, u32::DT),
+            u32::DT.clock_rate()
+        );
+    }
+}
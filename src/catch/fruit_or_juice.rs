@@ -31,7 +31,20 @@ pub(crate) enum FruitOrJuice {
 }
 
 impl FruitOrJuice {
+    #[inline]
     pub(crate) fn new(h: &HitObject, params: &mut FruitParams<'_>) -> Option<Self> {
+        Self::new_partial(h, params, usize::MAX)
+    }
+
+    /// Same as [`new`](Self::new) but a juice stream only contains its first `max_objects`
+    /// fruits and droplets, e.g. for a partial play that ends in the middle of it.
+    ///
+    /// Only the remaining objects and the tiny droplets preceding them are counted.
+    pub(crate) fn new_partial(
+        h: &HitObject,
+        params: &mut FruitParams<'_>,
+        max_objects: usize,
+    ) -> Option<Self> {
         match &h.kind {
             HitObjectKind::Circle => {
                 let mut h = CatchObject::new((h.pos, h.start_time));
@@ -93,6 +106,8 @@ impl FruitOrJuice {
                 let target = pixel_len - tick_dist / 8.0;
 
                 let mut slider_objects = vec![(h.pos, h.start_time)];
+                let mut tiny_droplets = TinyDroplets::default();
+                let mut n_ticks = 0;
 
                 if tick_dist > 0.0 {
                     params.ticks.reserve((target / tick_dist) as usize);
@@ -106,10 +121,12 @@ impl FruitOrJuice {
                         curr_dist += tick_dist;
                     }
 
+                    n_ticks = params.ticks.len();
+
                     if pixel_len > 0.0 {
                         let time_add = total_duration * tick_dist / (pixel_len * span_count);
 
-                        params.attributes.n_tiny_droplets += tiny_droplet_count(
+                        tiny_droplets = TinyDroplets::new(
                             h.start_time,
                             time_add,
                             total_duration,
@@ -163,9 +180,38 @@ impl FruitOrJuice {
                 let pos = h.pos + curve.position_at(progress);
                 slider_objects.push((pos, h.start_time + total_duration));
 
-                let new_fruits = 2 + (tick_dist > 0.0) as usize * *repeats;
-                params.attributes.n_fruits += new_fruits;
-                params.attributes.n_droplets += slider_objects.len() - new_fruits;
+                // Without ticks there are no reverses either
+                let n_spans = if tick_dist > 0.0 {
+                    span_count as usize
+                } else {
+                    1
+                };
+
+                if slider_objects.len() > max_objects {
+                    slider_objects.truncate(max_objects.max(1));
+                    params.attributes.n_fruits += 1;
+
+                    // The head is followed by the ticks and the reverse or tail of each span
+                    for i in 1..slider_objects.len() {
+                        let span_idx = (i - 1) / (n_ticks + 1);
+
+                        if (i - 1) % (n_ticks + 1) < n_ticks {
+                            params.attributes.n_droplets += 1;
+                            params.attributes.n_tiny_droplets += tiny_droplets.per_tick;
+                        } else if span_idx + 1 < n_spans {
+                            params.attributes.n_fruits += 1;
+                            params.attributes.n_tiny_droplets += tiny_droplets.per_reverse;
+                        } else {
+                            params.attributes.n_fruits += 1;
+                            params.attributes.n_tiny_droplets += tiny_droplets.before_tail;
+                        }
+                    }
+                } else {
+                    let new_fruits = 2 + (tick_dist > 0.0) as usize * *repeats;
+                    params.attributes.n_fruits += new_fruits;
+                    params.attributes.n_droplets += slider_objects.len() - new_fruits;
+                    params.attributes.n_tiny_droplets += tiny_droplets.total(n_ticks, n_spans);
+                }
 
                 let iter = slider_objects
                     .into_iter()
@@ -208,58 +254,76 @@ impl ExactSizeIterator for FruitOrJuice {
     }
 }
 
+/// The amount of tiny droplets preceding each kind of nested object of a juice stream.
+#[derive(Copy, Clone, Debug, Default)]
+struct TinyDroplets {
+    per_tick: usize,
+    per_reverse: usize,
+    before_tail: usize,
+}
+
 // BUG: Sometimes there are off-by-one errors,
 // presumably caused by floating point inaccuracies
-fn tiny_droplet_count(
-    start_time: f64,
-    time_between_ticks: f64,
-    duration: f64,
-    span_count: usize,
-    ticks: &[(Pos2, f64)],
-) -> usize {
-    // tiny droplets preceeding a _tick_
-    let per_tick = if !ticks.is_empty() && time_between_ticks > 80.0 {
-        let time_between_tiny = shrink_down(time_between_ticks);
-
-        // add a little for floating point inaccuracies
-        let start = time_between_tiny + 0.001;
-
-        count_iterations(start, time_between_tiny, time_between_ticks)
-    } else {
-        0
-    };
-
-    // tiny droplets preceeding a _reverse_
-    let last = ticks.last().map_or(start_time, |(_, last)| *last);
-    let repeat_time = start_time + duration / span_count as f64;
-    let since_last_tick = repeat_time - last;
-
-    let span_last_section = if since_last_tick > 80.0 {
-        let time_between_tiny = shrink_down(since_last_tick);
-
-        count_iterations(time_between_tiny, time_between_tiny, since_last_tick)
-    } else {
-        0
-    };
-
-    // tiny droplets preceeding the slider tail
-    // necessary to handle distinctly because of the legacy last tick
-    let last = ticks.last().map_or(start_time, |(_, last)| *last);
-    let end_time = start_time + duration / span_count as f64 - LEGACY_LAST_TICK_OFFSET;
-    let since_last_tick = end_time - last;
-
-    let last_section = if since_last_tick > 80.0 {
-        let time_between_tiny = shrink_down(since_last_tick);
-
-        count_iterations(time_between_tiny, time_between_tiny, since_last_tick)
-    } else {
-        0
-    };
-
-    // Combine tiny droplets counts
-    per_tick * ticks.len() * span_count
-        + span_last_section * (span_count.saturating_sub(1))
-        + last_section
+impl TinyDroplets {
+    fn new(
+        start_time: f64,
+        time_between_ticks: f64,
+        duration: f64,
+        span_count: usize,
+        ticks: &[(Pos2, f64)],
+    ) -> Self {
+        // tiny droplets preceeding a _tick_
+        let per_tick = if !ticks.is_empty() && time_between_ticks > 80.0 {
+            let time_between_tiny = shrink_down(time_between_ticks);
+
+            // add a little for floating point inaccuracies
+            let start = time_between_tiny + 0.001;
+
+            count_iterations(start, time_between_tiny, time_between_ticks)
+        } else {
+            0
+        };
+
+        // tiny droplets preceeding a _reverse_
+        let last = ticks.last().map_or(start_time, |(_, last)| *last);
+        let repeat_time = start_time + duration / span_count as f64;
+        let since_last_tick = repeat_time - last;
+
+        let span_last_section = if since_last_tick > 80.0 {
+            let time_between_tiny = shrink_down(since_last_tick);
+
+            count_iterations(time_between_tiny, time_between_tiny, since_last_tick)
+        } else {
+            0
+        };
+
+        // tiny droplets preceeding the slider tail
+        // necessary to handle distinctly because of the legacy last tick
+        let last = ticks.last().map_or(start_time, |(_, last)| *last);
+        let end_time = start_time + duration / span_count as f64 - LEGACY_LAST_TICK_OFFSET;
+        let since_last_tick = end_time - last;
+
+        let last_section = if since_last_tick > 80.0 {
+            let time_between_tiny = shrink_down(since_last_tick);
+
+            count_iterations(time_between_tiny, time_between_tiny, since_last_tick)
+        } else {
+            0
+        };
+
+        Self {
+            per_tick,
+            per_reverse: span_last_section,
+            before_tail: last_section,
+        }
+    }
+
+    /// Combine tiny droplets counts
+    fn total(&self, n_ticks: usize, span_count: usize) -> usize {
+        self.per_tick * n_ticks * span_count
+            + self.per_reverse * (span_count.saturating_sub(1))
+            + self.before_tail
+    }
 }
 
 #[inline]
@@ -1,10 +1,10 @@
-use std::{iter, slice::Iter};
+use std::slice::Iter;
 
 use crate::{
     catch::{difficulty_object::DifficultyObject, SECTION_LENGTH, STAR_SCALING_FACTOR},
     curve::CurveBuffers,
     parse::{HitObject, Pos2},
-    Beatmap, Mods,
+    Beatmap, GameMode, Mods, ATTRIBUTES_VERSION,
 };
 
 use super::{
@@ -29,7 +29,7 @@ use super::{
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{Beatmap, catch::CatchGradualDifficultyAttributes};
+/// use murasame_pp::{Beatmap, catch::CatchGradualDifficultyAttributes};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -57,6 +57,7 @@ pub struct CatchGradualDifficultyAttributes<'map> {
     half_catcher_width: f64,
     last_direction: i8,
     last_excess: f64,
+    n_hyperdashes: usize,
     curr_section_end: f64,
     strain_peak_buf: Vec<f64>,
 }
@@ -65,9 +66,13 @@ impl<'map> CatchGradualDifficultyAttributes<'map> {
     /// Create a new difficulty attributes iterator for osu!catch maps.
     pub fn new(map: &'map Beatmap, mods: u32) -> Self {
         let map_attributes = map.attributes().mods(mods).build();
+        let settings = map.attributes().mode(GameMode::Catch).mods(mods).settings();
 
         let attributes = CatchDifficultyAttributes {
             ar: map_attributes.ar,
+            is_convert: map.mode != GameMode::Catch,
+            settings,
+            version: ATTRIBUTES_VERSION,
             ..Default::default()
         };
 
@@ -90,6 +95,7 @@ impl<'map> CatchGradualDifficultyAttributes<'map> {
             half_catcher_width,
             last_direction,
             last_excess,
+            n_hyperdashes: 0,
             curr_section_end: 0.0,
             strain_peak_buf: Vec::new(),
         }
@@ -119,6 +125,7 @@ impl Iterator for CatchGradualDifficultyAttributes<'_> {
         }
 
         self.init_hyper_dash(&curr);
+        self.n_hyperdashes += self.prev.hyper_dash as usize;
 
         let h = DifficultyObject::new(
             &curr,
@@ -144,8 +151,7 @@ impl Iterator for CatchGradualDifficultyAttributes<'_> {
         self.prev = curr;
 
         let len = self.movement.strain_peaks.len();
-        let missing = len + 1 - self.strain_peak_buf.len();
-        self.strain_peak_buf.extend(iter::repeat(0.0).take(missing));
+        self.strain_peak_buf.resize(len + 1, 0.0);
 
         self.strain_peak_buf[..len].copy_from_slice(&self.movement.strain_peaks);
 
@@ -154,6 +160,9 @@ impl Iterator for CatchGradualDifficultyAttributes<'_> {
         }
 
         let mut attributes = self.hit_objects.attributes();
+        attributes.n_hyperdashes = self.n_hyperdashes;
+        attributes.n_direction_changes = self.movement.n_direction_changes;
+        attributes.n_edge_dashes = self.movement.n_edge_dashes;
         attributes.stars =
             Movement::difficulty_value(&mut self.strain_peak_buf).sqrt() * STAR_SCALING_FACTOR;
 
@@ -188,7 +197,7 @@ impl<'map> CatchObjectIter<'map> {
     }
 
     fn attributes(&self) -> CatchDifficultyAttributes {
-        self.params.attributes.clone()
+        self.params.attributes
     }
 }
 
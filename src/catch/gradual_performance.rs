@@ -1,4 +1,4 @@
-use crate::{Beatmap, CatchPP};
+use crate::{Beatmap, CatchPP, Grade};
 
 use super::{CatchGradualDifficultyAttributes, CatchPerformanceAttributes};
 
@@ -6,7 +6,8 @@ use super::{CatchGradualDifficultyAttributes, CatchPerformanceAttributes};
 /// maximum combo so far and what are the current hitresults.
 ///
 /// This struct is used for [`CatchGradualPerformanceAttributes`].
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchScoreState {
     /// Maximum combo that the score has had so far.
     /// **Not** the maximum possible combo of the map so far.
@@ -30,6 +31,25 @@ impl CatchScoreState {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Calculate the accuracy between `0.0` and `1.0` for this state.
+    #[inline]
+    pub fn accuracy(&self) -> f64 {
+        let n_hits = self.n_fruits + self.n_droplets + self.n_tiny_droplets;
+        let total_hits = n_hits + self.n_tiny_droplet_misses + self.n_misses;
+
+        if total_hits == 0 {
+            return 0.0;
+        }
+
+        n_hits as f64 / total_hits as f64
+    }
+
+    /// Determine the grade of this state for the given mods.
+    #[inline]
+    pub fn grade(&self, mods: u32) -> Grade {
+        Grade::from_accuracy(self.accuracy(), [0.98, 0.94, 0.9, 0.85], mods)
+    }
 }
 
 /// Gradually calculate the performance attributes of an osu!catch map.
@@ -52,7 +72,7 @@ impl CatchScoreState {
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{Beatmap, catch::{CatchGradualPerformanceAttributes, CatchScoreState}};
+/// use murasame_pp::{Beatmap, catch::{CatchGradualPerformanceAttributes, CatchScoreState}};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -1,6 +1,6 @@
-mod catch_object;
+pub(crate) mod catch_object;
 mod difficulty_object;
-mod fruit_or_juice;
+pub(crate) mod fruit_or_juice;
 mod gradual_difficulty;
 mod gradual_performance;
 mod movement;
@@ -14,12 +14,18 @@ pub use gradual_performance::*;
 use movement::Movement;
 pub use pp::*;
 
-use crate::{catch::fruit_or_juice::FruitParams, curve::CurveBuffers, Beatmap, Mods, OsuStars};
+use crate::{
+    beatmap::{DifficultyOverrides, DifficultySettings},
+    catch::fruit_or_juice::FruitParams,
+    curve::CurveBuffers,
+    util::{downsample, first_section_start, round_api},
+    Beatmap, GameMode, Mods, OsuStars, StrainAggregation, ATTRIBUTES_VERSION,
+};
 
 const SECTION_LENGTH: f64 = 750.0;
 const STAR_SCALING_FACTOR: f64 = 0.153;
 
-const ALLOWED_CATCH_RANGE: f32 = 0.8;
+pub(crate) const ALLOWED_CATCH_RANGE: f32 = 0.8;
 const CATCHER_SIZE: f32 = 106.75;
 
 /// Difficulty calculator on osu!catch maps.
@@ -27,7 +33,7 @@ const CATCHER_SIZE: f32 = 106.75;
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{CatchStars, Beatmap};
+/// use murasame_pp::{CatchStars, Beatmap};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -46,6 +52,7 @@ pub struct CatchStars<'map> {
     mods: u32,
     passed_objects: Option<usize>,
     clock_rate: Option<f64>,
+    overrides: DifficultyOverrides,
 }
 
 impl<'map> CatchStars<'map> {
@@ -57,6 +64,7 @@ impl<'map> CatchStars<'map> {
             mods: 0,
             passed_objects: None,
             clock_rate: None,
+            overrides: DifficultyOverrides::default(),
         }
     }
 
@@ -92,6 +100,14 @@ impl<'map> CatchStars<'map> {
         self
     }
 
+    /// Override the map's difficulty settings, e.g. for the Difficulty Adjust mod.
+    #[inline]
+    pub(crate) fn overrides(mut self, overrides: DifficultyOverrides) -> Self {
+        self.overrides = overrides;
+
+        self
+    }
+
     /// Calculate all difficulty related values, including stars.
     #[inline]
     pub fn calculate(self) -> CatchDifficultyAttributes {
@@ -107,21 +123,128 @@ impl<'map> CatchStars<'map> {
     /// Suitable to plot the difficulty of a map over time.
     #[inline]
     pub fn strains(self) -> CatchStrains {
-        let (movement, _) = calculate_movement(self);
+        let mut first_start_time = None;
+
+        let (movement, _) = calculate_movement_with(self, |h, _| {
+            first_start_time.get_or_insert(h.start_time);
+        });
 
         CatchStrains {
             section_len: SECTION_LENGTH,
+            start_time: first_section_start(first_start_time, SECTION_LENGTH),
             movement: movement.strain_peaks,
         }
     }
+
+    /// Calculate the difficulty attributes and the skill strains in one go.
+    ///
+    /// Both share the same skill processing so this is cheaper than calling
+    /// [`calculate`](Self::calculate) and [`strains`](Self::strains) separately.
+    pub fn calculate_with_strains(self) -> (CatchDifficultyAttributes, CatchStrains) {
+        let mut first_start_time = None;
+
+        let (mut movement, mut attributes) = calculate_movement_with(self, |h, _| {
+            first_start_time.get_or_insert(h.start_time);
+        });
+
+        let strains = CatchStrains {
+            section_len: SECTION_LENGTH,
+            start_time: first_section_start(first_start_time, SECTION_LENGTH),
+            movement: movement.strain_peaks.clone(),
+        };
+
+        attributes.stars =
+            Movement::difficulty_value(&mut movement.strain_peaks).sqrt() * STAR_SCALING_FACTOR;
+
+        (attributes, strains)
+    }
+
+    /// Calculate the movement strain of each fruit and droplet, together with its hyperdash flag.
+    ///
+    /// Tiny droplets are not considered by the difficulty calculation and the first
+    /// object does not generate a strain so they are skipped.
+    /// Suitable to find difficulty spikes or to annotate replays.
+    ///
+    /// ```
+    /// use murasame_pp::{Beatmap, CatchStars};
+    ///
+    /// # /*
+    /// let map: Beatmap = ...
+    /// # */
+    /// # let map = Beatmap::default();
+    /// let hardest = CatchStars::new(&map)
+    ///     .object_strains()
+    ///     .max_by(|a, b| a.movement.total_cmp(&b.movement));
+    ///
+    /// if let Some(object) = hardest {
+    ///     println!("Hardest movement at {}ms: {:.2}", object.start_time, object.movement);
+    /// }
+    /// ```
+    pub fn object_strains(self) -> std::vec::IntoIter<CatchObjectStrain> {
+        let mut strains: Vec<CatchObjectStrain> = Vec::new();
+
+        calculate_movement_with(self, |h, movement| {
+            // The hyperdash flag of the previous object is only known now
+            if let Some(last) = strains.last_mut() {
+                last.hyper_dash = h.last.hyper_dash;
+            }
+
+            strains.push(CatchObjectStrain {
+                idx: strains.len() + 1,
+                start_time: h.start_time,
+                pos: h.base.pos,
+                hyper_dash: false,
+                movement: movement.curr_strain(),
+            });
+        });
+
+        strains.into_iter()
+    }
+}
+
+/// The movement strain of a single fruit or droplet of an osu!catch map.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CatchObjectStrain {
+    /// The index of the object among all fruits and droplets.
+    pub idx: usize,
+    /// The start time of the object in ms, adjusted by the clock rate.
+    pub start_time: f64,
+    /// The horizontal position of the object.
+    pub pos: f32,
+    /// Whether the catcher has to hyperdash from this object to the next one.
+    pub hyper_dash: bool,
+    /// The movement strain after processing the object.
+    pub movement: f64,
+}
+
+/// Count the maximum combo by only creating fruits and juice streams.
+pub(crate) fn max_combo(map: &Beatmap) -> usize {
+    let mut params = FruitParams {
+        attributes: CatchDifficultyAttributes::default(),
+        curve_bufs: CurveBuffers::default(),
+        last_pos: None,
+        last_time: 0.0,
+        map,
+        ticks: Vec::new(),
+        with_hr: false,
+    };
+
+    for h in map.hit_objects.iter() {
+        FruitOrJuice::new(h, &mut params);
+    }
+
+    params.attributes.max_combo()
 }
 
 /// The result of calculating the strains on a osu!catch map.
 /// Suitable to plot the difficulty of a map over time.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchStrains {
     /// Time in ms inbetween two strains.
     pub section_len: f64,
+    /// Start time in ms of the first strain peak's section, adjusted by the clock rate.
+    pub start_time: f64,
     /// Strain peaks of the movement skill.
     pub movement: Vec<f64>,
 }
@@ -133,22 +256,59 @@ impl CatchStrains {
     pub fn len(&self) -> usize {
         self.movement.len()
     }
+
+    /// Merge the strain peaks into exactly `n_buckets` points, e.g. for a fixed-width graph.
+    ///
+    /// The `section_len` of the result is adjusted so that the `i`-th point
+    /// still starts at `start_time + i * section_len` ms.
+    pub fn downsample(&self, n_buckets: usize, aggregation: StrainAggregation) -> Self {
+        let section_len = if n_buckets > 0 {
+            self.section_len * self.len() as f64 / n_buckets as f64
+        } else {
+            self.section_len
+        };
+
+        Self {
+            section_len,
+            start_time: self.start_time,
+            movement: downsample(&self.movement, n_buckets, aggregation),
+        }
+    }
 }
 
 fn calculate_movement(params: CatchStars<'_>) -> (Movement, CatchDifficultyAttributes) {
+    calculate_movement_with(params, |_, _| {})
+}
+
+/// Same as [`calculate_movement`] but `inspect` is called after each processed object.
+fn calculate_movement_with(
+    params: CatchStars<'_>,
+    mut inspect: impl FnMut(&DifficultyObject<'_>, &Movement),
+) -> (Movement, CatchDifficultyAttributes) {
+    profile!(Skills);
+
     let CatchStars {
         map,
         mods,
         passed_objects,
         clock_rate,
+        overrides,
     } = params;
 
-    let take = passed_objects.unwrap_or(usize::MAX);
-    let clock_rate = clock_rate.unwrap_or_else(|| mods.clock_rate());
-    let map_attributes = map.attributes().mods(mods).clock_rate(clock_rate).build();
+    let mut remaining = passed_objects.unwrap_or(usize::MAX);
+    let clock_rate = overrides.clock_rate(clock_rate, mods);
+    let settings = overrides
+        .attributes(map)
+        .mode(GameMode::Catch)
+        .mods(mods)
+        .clock_rate(clock_rate)
+        .settings();
 
     let attributes = CatchDifficultyAttributes {
-        ar: map_attributes.ar,
+        ar: settings.ar,
+        is_convert: map.mode != GameMode::Catch,
+        settings,
+        version: ATTRIBUTES_VERSION,
         ..Default::default()
     };
 
@@ -166,18 +326,28 @@ fn calculate_movement(params: CatchStars<'_>) -> (Movement, CatchDifficultyAttri
     let mut hit_objects = map
         .hit_objects
         .iter()
-        .filter_map(|h| FruitOrJuice::new(h, &mut params))
-        .flatten()
-        .take(take);
+        .filter_map(|h| {
+            if remaining == 0 {
+                return None;
+            }
+
+            // Juice streams are cut off so that their later objects are not counted
+            let h = FruitOrJuice::new_partial(h, &mut params, remaining)?;
+            remaining -= h.len();
+
+            Some(h)
+        })
+        .flatten();
 
     // Hyper dash business
     let half_catcher_width =
-        (calculate_catch_width(map_attributes.cs as f32) / 2.0 / ALLOWED_CATCH_RANGE) as f64;
+        (calculate_catch_width(settings.cs as f32) / 2.0 / ALLOWED_CATCH_RANGE) as f64;
     let mut last_direction = 0;
     let mut last_excess = half_catcher_width;
+    let mut n_hyperdashes = 0;
 
     // Strain business
-    let mut movement = Movement::new(map_attributes.cs as f32);
+    let mut movement = Movement::new(settings.cs as f32);
 
     let (mut prev, curr) = match (hit_objects.next(), hit_objects.next()) {
         (Some(prev), Some(curr)) => (prev, curr),
@@ -194,10 +364,13 @@ fn calculate_movement(params: CatchStars<'_>) -> (Movement, CatchDifficultyAttri
         &mut last_excess,
     );
 
+    n_hyperdashes += prev.hyper_dash as usize;
+
     // Handle first object distinctly
     let h = DifficultyObject::new(&curr, &prev, movement.half_catcher_width, clock_rate);
 
     movement.process(&h);
+    inspect(&h, &movement);
     prev = curr;
 
     // Handle all other objects
@@ -209,6 +382,8 @@ fn calculate_movement(params: CatchStars<'_>) -> (Movement, CatchDifficultyAttri
             &mut last_excess,
         );
 
+        n_hyperdashes += prev.hyper_dash as usize;
+
         let h = DifficultyObject::new(&curr, &prev, movement.half_catcher_width, clock_rate);
 
         let base_time = h.base.time / clock_rate;
@@ -220,12 +395,20 @@ fn calculate_movement(params: CatchStars<'_>) -> (Movement, CatchDifficultyAttri
         }
 
         movement.process(&h);
+        inspect(&h, &movement);
         prev = curr;
     }
 
     movement.save_current_peak();
 
-    (movement, params.attributes)
+    let attributes = CatchDifficultyAttributes {
+        n_hyperdashes,
+        n_direction_changes: movement.n_direction_changes,
+        n_edge_dashes: movement.n_edge_dashes,
+        ..params.attributes
+    };
+
+    (movement, attributes)
 }
 
 #[inline]
@@ -236,7 +419,8 @@ pub(crate) fn calculate_catch_width(cs: f32) -> f32 {
 }
 
 /// The result of a difficulty calculation on an osu!catch map.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchDifficultyAttributes {
     /// The final star rating
     pub stars: f64,
@@ -248,26 +432,105 @@ pub struct CatchDifficultyAttributes {
     pub n_droplets: usize,
     /// The amount of tiny droplets.
     pub n_tiny_droplets: usize,
+    /// The amount of fruits and droplets that require a hyperdash to be caught from the previous one.
+    pub n_hyperdashes: usize,
+    /// The amount of times the catcher has to reverse its movement direction.
+    pub n_direction_changes: usize,
+    /// The amount of dashes that come within 20 units of requiring a hyperdash.
+    pub n_edge_dashes: usize,
+    /// Whether the map was a convert from osu!standard.
+    pub is_convert: bool,
+    /// The difficulty settings after applying mods.
+    pub settings: DifficultySettings,
+    /// The [`ATTRIBUTES_VERSION`](crate::ATTRIBUTES_VERSION) the attributes were calculated with.
+    ///
+    /// `0` if the attributes were not calculated by this crate.
+    pub version: u32,
 }
 
 impl CatchDifficultyAttributes {
+    /// Round all floating point values to the precision of the osu!api
+    /// so that they can be compared to or stored alongside official values.
+    ///
+    /// Ties are rounded to the nearest even digit like osu!lazer does.
+    pub fn api_rounded(mut self) -> Self {
+        self.stars = round_api(self.stars);
+        self.ar = round_api(self.ar);
+        self.settings = self.settings.api_rounded();
+
+        self
+    }
+
     /// Return the maximum combo.
     #[inline]
     pub fn max_combo(&self) -> usize {
         self.n_fruits + self.n_droplets
     }
+
+    /// Return the amount of objects that receive a hitresult
+    /// i.e. fruits, droplets, and tiny droplets.
+    #[inline]
+    pub fn n_objects(&self) -> usize {
+        self.n_fruits + self.n_droplets + self.n_tiny_droplets
+    }
+
+    /// Return the amount of fruits.
+    #[inline]
+    pub fn n_fruits(&self) -> usize {
+        self.n_fruits
+    }
+
+    /// Return the amount of droplets.
+    #[inline]
+    pub fn n_droplets(&self) -> usize {
+        self.n_droplets
+    }
+
+    /// Return the amount of tiny droplets.
+    #[inline]
+    pub fn n_tiny_droplets(&self) -> usize {
+        self.n_tiny_droplets
+    }
+
+    /// Return the ratio of direction changes to objects between `0.0` and `1.0`.
+    ///
+    /// Technical maps tend to have a higher rate than maps with long streams in one direction.
+    #[inline]
+    pub fn direction_change_rate(&self) -> f64 {
+        let n_objects = self.n_objects();
+
+        if n_objects == 0 {
+            return 0.0;
+        }
+
+        self.n_direction_changes as f64 / n_objects as f64
+    }
 }
 
 /// The result of a performance calculation on an osu!catch map.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatchPerformanceAttributes {
     /// The difficulty attributes that were used for the performance calculation
     pub difficulty: CatchDifficultyAttributes,
     /// The final performance points.
     pub pp: f64,
+    /// The hitresults and combo that were used for the performance calculation.
+    pub state: CatchScoreState,
 }
 
 impl CatchPerformanceAttributes {
+    /// Round all floating point values, including the difficulty attributes,
+    /// to the precision of the osu!api.
+    ///
+    /// See [`CatchDifficultyAttributes::api_rounded`].
+    pub fn api_rounded(mut self) -> Self {
+        self.difficulty = self.difficulty.api_rounded();
+        self.pp = round_api(self.pp);
+
+        self
+    }
+
     /// Return the star value.
     #[inline]
     pub fn stars(&self) -> f64 {
@@ -285,6 +548,12 @@ impl CatchPerformanceAttributes {
     pub fn max_combo(&self) -> usize {
         self.difficulty.max_combo()
     }
+
+    /// Return whether the map was a convert from osu!standard.
+    #[inline]
+    pub fn is_convert(&self) -> bool {
+        self.difficulty.is_convert
+    }
 }
 
 impl From<CatchPerformanceAttributes> for CatchDifficultyAttributes {
@@ -302,6 +571,8 @@ impl<'map> From<OsuStars<'map>> for CatchStars<'map> {
             mods,
             passed_objects,
             clock_rate,
+            overrides,
+            ..
         } = osu;
 
         Self {
@@ -309,6 +580,7 @@ impl<'map> From<OsuStars<'map>> for CatchStars<'map> {
             mods,
             passed_objects,
             clock_rate,
+            overrides,
         }
     }
 }
@@ -23,6 +23,9 @@ pub(crate) struct Movement {
 
     pub(crate) strain_peaks: Vec<f64>,
     prev_time: Option<f64>,
+
+    pub(crate) n_direction_changes: usize,
+    pub(crate) n_edge_dashes: usize,
 }
 
 impl Movement {
@@ -43,9 +46,17 @@ impl Movement {
 
             strain_peaks: Vec::with_capacity(128),
             prev_time: None,
+
+            n_direction_changes: 0,
+            n_edge_dashes: 0,
         }
     }
 
+    #[inline]
+    pub(crate) fn curr_strain(&self) -> f64 {
+        self.current_strain
+    }
+
     #[inline]
     pub(crate) fn save_current_peak(&mut self) {
         self.strain_peaks.push(self.curr_section_peak);
@@ -95,6 +106,8 @@ impl Movement {
             if self.last_distance_moved.abs() > 0.1
                 && dist_moved.signum() != self.last_distance_moved.signum()
             {
+                self.n_direction_changes += 1;
+
                 let bonus_factor = (dist_moved.abs().min(50.0) / 50.0) as f64;
                 let anti_flow_factor =
                     (self.last_distance_moved.abs().min(70.0) / 70.0).max(0.38) as f64;
@@ -114,6 +127,7 @@ impl Movement {
 
         if current.last.hyper_dist <= 20.0 {
             if !current.last.hyper_dash {
+                self.n_edge_dashes += 1;
                 edge_dash_bonus += 5.7;
             } else {
                 pos = current.normalized_pos;
@@ -1,12 +1,15 @@
 use super::{CatchDifficultyAttributes, CatchPerformanceAttributes, CatchScoreState, CatchStars};
-use crate::{Beatmap, DifficultyAttributes, Mods, OsuPP, PerformanceAttributes};
+use crate::{
+    beatmap::DifficultyOverrides, invalid_score, pp::combo_range, util::FloatExt,
+    AttributeProvider, Beatmap, CustomMod, InvalidScore, Mods, OsuPP,
+};
 
 /// Performance calculator on osu!catch maps.
 ///
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{CatchPP, Beatmap};
+/// use murasame_pp::{CatchPP, Beatmap};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -43,8 +46,10 @@ pub struct CatchPP<'map> {
     pub(crate) n_tiny_droplets: Option<usize>,
     pub(crate) n_tiny_droplet_misses: Option<usize>,
     pub(crate) n_misses: Option<usize>,
+    acc: Option<f64>,
     passed_objects: Option<usize>,
     clock_rate: Option<f64>,
+    overrides: DifficultyOverrides,
 }
 
 impl<'map> CatchPP<'map> {
@@ -62,8 +67,10 @@ impl<'map> CatchPP<'map> {
             n_tiny_droplets: None,
             n_tiny_droplet_misses: None,
             n_misses: None,
+            acc: None,
             passed_objects: None,
             clock_rate: None,
+            overrides: DifficultyOverrides::default(),
         }
     }
 
@@ -159,6 +166,56 @@ impl<'map> CatchPP<'map> {
         self
     }
 
+    /// Override the map's approach rate before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn ar(mut self, ar: f32) -> Self {
+        self.overrides.ar = Some(ar);
+
+        self
+    }
+
+    /// Override the map's overall difficulty before calculating its difficulty.
+    ///
+    /// Irrelevant for osu!catch.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn od(mut self, od: f32) -> Self {
+        self.overrides.od = Some(od);
+
+        self
+    }
+
+    /// Override the map's circle size before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn cs(mut self, cs: f32) -> Self {
+        self.overrides.cs = Some(cs);
+
+        self
+    }
+
+    /// Override the map's drain rate before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn hp(mut self, hp: f32) -> Self {
+        self.overrides.hp = Some(hp);
+
+        self
+    }
+
+    /// Apply the effects of a [`CustomMod`] on top of the mods, e.g. a private server's own mod.
+    #[inline]
+    pub fn custom_mod(mut self, custom_mod: CustomMod) -> Self {
+        self.overrides.custom_mod = custom_mod;
+
+        self
+    }
+
     /// Provide parameters through an [`CatchScoreState`].
     #[inline]
     pub fn state(mut self, state: CatchScoreState) -> Self {
@@ -185,19 +242,10 @@ impl<'map> CatchPP<'map> {
     /// Generate the hit results with respect to the given accuracy between `0.0` and `100.0`.
     ///
     /// Be sure to set `misses` beforehand! Also, if available, set `attributes` beforehand.
+    /// The same goes for any difficulty overrides such as [`ar`](CatchPP::ar).
     pub fn accuracy(mut self, mut acc: f64) -> Self {
         if self.attributes.is_none() {
-            let mut calculator = CatchStars::new(self.map).mods(self.mods);
-
-            if let Some(passed_objects) = self.passed_objects {
-                calculator = calculator.passed_objects(passed_objects);
-            }
-
-            if let Some(clock_rate) = self.clock_rate {
-                calculator = calculator.clock_rate(clock_rate);
-            }
-
-            self.attributes = Some(calculator.calculate());
+            self.attributes = Some(self.take_attributes());
         }
 
         let attributes = self.attributes.as_ref().unwrap();
@@ -218,11 +266,15 @@ impl<'map> CatchPP<'map> {
 
         let max_tiny_droplets = attributes.n_tiny_droplets;
         acc /= 100.0;
+        self.acc = Some(acc);
 
+        // Misses already lower the accuracy so the target might be
+        // unreachable even if all tiny droplets are hit.
         let n_tiny_droplets = self.n_tiny_droplets.unwrap_or_else(|| {
             ((acc * (max_combo + max_tiny_droplets) as f64).round() as usize)
                 .saturating_sub(n_fruits)
                 .saturating_sub(n_droplets)
+                .min(max_tiny_droplets)
         });
 
         let n_tiny_droplet_misses = max_tiny_droplets.saturating_sub(n_tiny_droplets);
@@ -310,21 +362,184 @@ impl<'map> CatchPP<'map> {
         }
     }
 
-    /// Calculate all performance related values, including pp and stars.
-    pub fn calculate(mut self) -> CatchPerformanceAttributes {
-        let attributes = self.attributes.take().unwrap_or_else(|| {
-            let mut calculator = CatchStars::new(self.map).mods(self.mods);
+    fn take_attributes(&mut self) -> CatchDifficultyAttributes {
+        self.attributes
+            .take()
+            .unwrap_or_else(|| self.calculate_difficulty(self.passed_objects))
+    }
 
-            if let Some(passed_objects) = self.passed_objects {
-                calculator = calculator.passed_objects(passed_objects);
-            }
+    fn calculate_difficulty(&self, passed_objects: Option<usize>) -> CatchDifficultyAttributes {
+        let mut calculator = CatchStars::new(self.map)
+            .mods(self.mods)
+            .overrides(self.overrides);
 
-            if let Some(clock_rate) = self.clock_rate {
-                calculator = calculator.clock_rate(clock_rate);
+        if let Some(passed_objects) = passed_objects {
+            calculator = calculator.passed_objects(passed_objects);
+        }
+
+        if let Some(clock_rate) = self.clock_rate {
+            calculator = calculator.clock_rate(clock_rate);
+        }
+
+        calculator.calculate()
+    }
+
+    /// Copy the settings of the calculator without the values of a specific score.
+    fn without_score(&self) -> CatchPP<'map> {
+        CatchPP {
+            map: self.map,
+            attributes: None,
+            mods: self.mods,
+            combo: None,
+            n_fruits: None,
+            n_droplets: None,
+            n_tiny_droplets: None,
+            n_tiny_droplet_misses: None,
+            n_misses: None,
+            acc: None,
+            passed_objects: self.passed_objects,
+            clock_rate: self.clock_rate,
+            overrides: self.overrides,
+        }
+    }
+
+    /// Generate hitresults for the given accuracy between `0.0` and `100.0` the same way
+    /// [osu-tools](https://github.com/ppy/osu-tools)' `simulate` command does.
+    ///
+    /// Specified droplets, tiny droplets, misses, and combo are respected.
+    /// Unlike [`accuracy`](CatchPP::accuracy), infeasible inputs are not corrected
+    /// except that negative counts are clamped to zero.
+    pub fn simulate(mut self, acc: f64) -> CatchScoreState {
+        let attrs = self.take_attributes();
+
+        let max_combo = attrs.max_combo() as isize;
+        let max_fruits = attrs.n_fruits as isize;
+        let max_droplets = attrs.n_droplets as isize;
+        let max_tiny_droplets = attrs.n_tiny_droplets as isize;
+        let n_misses = self.n_misses.unwrap_or(0) as isize;
+
+        let n_droplets = match self.n_droplets {
+            Some(n_droplets) => n_droplets as isize,
+            None => (max_droplets - n_misses).max(0),
+        };
+
+        // Misses that are not already covered by droplets are fruit misses
+        let n_fruits = max_fruits - (n_misses - (max_droplets - n_droplets));
+
+        let n_tiny_droplets = match self.n_tiny_droplets {
+            Some(n_tiny_droplets) => n_tiny_droplets as isize,
+            None => {
+                (acc / 100.0 * (max_combo + max_tiny_droplets) as f64).round_even() as isize
+                    - n_fruits
+                    - n_droplets
             }
+        };
 
-            calculator.calculate()
-        });
+        CatchScoreState {
+            max_combo: self.combo.unwrap_or(attrs.max_combo()),
+            n_fruits: n_fruits.max(0) as usize,
+            n_droplets: n_droplets.max(0) as usize,
+            n_tiny_droplets: n_tiny_droplets.max(0) as usize,
+            n_tiny_droplet_misses: (max_tiny_droplets - n_tiny_droplets).max(0) as usize,
+            n_misses: n_misses as usize,
+        }
+    }
+
+    /// Same as [`calculate`](CatchPP::calculate) but the specified values are
+    /// first validated against the map.
+    ///
+    /// Returns an [`InvalidScore`] describing the first inconsistent value, e.g. if
+    /// the combo exceeds the map's max combo or there are more droplets than the map contains.
+    pub fn try_calculate(mut self) -> Result<CatchPerformanceAttributes, InvalidScore> {
+        invalid_score::check_accuracy(self.acc)?;
+
+        let attributes = self.take_attributes();
+        let max_combo = attributes.max_combo();
+
+        // Passed objects count fruits and droplets, not hit objects
+        invalid_score::check_passed_objects(self.passed_objects, max_combo)?;
+        invalid_score::check_combo(self.combo, max_combo)?;
+        invalid_score::check_hitresult("fruits", self.n_fruits, attributes.n_fruits)?;
+        invalid_score::check_hitresult("droplets", self.n_droplets, attributes.n_droplets)?;
+
+        let combo_hitresults = [
+            ("fruits", self.n_fruits),
+            ("droplets", self.n_droplets),
+            ("misses", self.n_misses),
+        ];
+
+        invalid_score::check_hitresults(&combo_hitresults, max_combo)?;
+
+        let tiny_hitresults = [
+            ("tiny_droplets", self.n_tiny_droplets),
+            ("tiny_droplet_misses", self.n_tiny_droplet_misses),
+        ];
+
+        invalid_score::check_hitresults(&tiny_hitresults, attributes.n_tiny_droplets)?;
+
+        Ok(self.attributes(attributes).calculate())
+    }
+
+    /// Calculate the performance attributes for each of the given score states.
+    ///
+    /// The difficulty attributes are calculated only once and shared by all scores.
+    /// Values of a specific score that were specified on the calculator itself,
+    /// e.g. hitresults or accuracy, are ignored.
+    pub fn calculate_many(mut self, states: &[CatchScoreState]) -> Vec<CatchPerformanceAttributes> {
+        let attrs = self.take_attributes();
+
+        states
+            .iter()
+            .map(|state| {
+                self.without_score()
+                    .attributes(attrs)
+                    .state(*state)
+                    .calculate()
+            })
+            .collect()
+    }
+
+    /// Calculate the performance attributes for the worst and best possible combo
+    /// in case the combo of the score is unknown.
+    ///
+    /// The worst case assumes the misses split the combo evenly,
+    /// the best case assumes a full combo on all objects that were not missed.
+    /// A specified combo is ignored.
+    pub fn calculate_range(mut self) -> (CatchPerformanceAttributes, CatchPerformanceAttributes) {
+        let attrs = self.take_attributes();
+        let (min_combo, max_combo) = combo_range(attrs.max_combo(), self.n_misses.unwrap_or(0));
+
+        let min = self.clone().attributes(attrs).combo(min_combo).calculate();
+        let max = self.attributes(attrs).combo(max_combo).calculate();
+
+        (min, max)
+    }
+
+    /// Calculate the performance attributes of the score and of an SS
+    /// on the same map with the same mods.
+    ///
+    /// The difficulty attributes are calculated only once and shared by both calculations
+    /// unless `passed_objects` is specified, in which case the SS is calculated on the full map.
+    pub fn calculate_with_max(
+        mut self,
+    ) -> (CatchPerformanceAttributes, CatchPerformanceAttributes) {
+        let attrs = self.take_attributes();
+
+        let max_attrs = match self.passed_objects {
+            Some(_) => self.calculate_difficulty(None),
+            None => attrs,
+        };
+
+        let mut max = self.without_score();
+        max.passed_objects = None;
+        let max = max.attributes(max_attrs).calculate();
+
+        (self.attributes(attrs).calculate(), max)
+    }
+
+    /// Calculate all performance related values, including pp and stars.
+    pub fn calculate(mut self) -> CatchPerformanceAttributes {
+        let attributes = self.take_attributes();
 
         self.assert_hitresults(attributes).calculate()
     }
@@ -343,6 +558,8 @@ struct CatchPPInner {
 
 impl CatchPPInner {
     fn calculate(self) -> CatchPerformanceAttributes {
+        profile!(Performance);
+
         let attributes = &self.attributes;
         let stars = attributes.stars;
         let max_combo = attributes.max_combo();
@@ -403,9 +620,19 @@ impl CatchPPInner {
             pp *= 0.9;
         }
 
+        let state = CatchScoreState {
+            max_combo: self.combo.unwrap_or(max_combo),
+            n_fruits: self.n_fruits,
+            n_droplets: self.n_droplets,
+            n_tiny_droplets: self.n_tiny_droplets,
+            n_tiny_droplet_misses: self.n_tiny_droplet_misses,
+            n_misses: self.n_misses,
+        };
+
         CatchPerformanceAttributes {
             difficulty: self.attributes,
             pp,
+            state,
         }
     }
 
@@ -431,9 +658,7 @@ impl CatchPPInner {
         if total_hits == 0 {
             1.0
         } else {
-            (self.successful_hits() as f64 / total_hits as f64)
-                .max(0.0)
-                .min(1.0)
+            (self.successful_hits() as f64 / total_hits as f64).clamp(0.0, 1.0)
         }
     }
 }
@@ -450,8 +675,10 @@ impl<'map> From<OsuPP<'map>> for CatchPP<'map> {
             n100,
             n50,
             n_misses,
+            n_katu,
             passed_objects,
             clock_rate,
+            overrides,
             ..
         } = osu;
 
@@ -463,10 +690,12 @@ impl<'map> From<OsuPP<'map>> for CatchPP<'map> {
             n_fruits: n300,
             n_droplets: n100,
             n_tiny_droplets: n50,
-            n_tiny_droplet_misses: None,
+            n_tiny_droplet_misses: n_katu,
             n_misses,
+            acc: None,
             passed_objects,
             clock_rate,
+            overrides,
         };
 
         match acc {
@@ -477,46 +706,17 @@ impl<'map> From<OsuPP<'map>> for CatchPP<'map> {
 }
 
 /// Abstract type to provide flexibility when passing difficulty attributes to a performance calculation.
+///
+/// Implemented for all types implementing [`AttributeProvider`].
 pub trait CatchAttributeProvider {
     /// Provide the actual difficulty attributes.
     fn attributes(self) -> Option<CatchDifficultyAttributes>;
 }
 
-impl CatchAttributeProvider for CatchDifficultyAttributes {
-    #[inline]
-    fn attributes(self) -> Option<CatchDifficultyAttributes> {
-        Some(self)
-    }
-}
-
-impl CatchAttributeProvider for CatchPerformanceAttributes {
-    #[inline]
-    fn attributes(self) -> Option<CatchDifficultyAttributes> {
-        Some(self.difficulty)
-    }
-}
-
-impl CatchAttributeProvider for DifficultyAttributes {
-    #[inline]
-    fn attributes(self) -> Option<CatchDifficultyAttributes> {
-        #[allow(irrefutable_let_patterns)]
-        if let Self::Catch(attributes) = self {
-            Some(attributes)
-        } else {
-            None
-        }
-    }
-}
-
-impl CatchAttributeProvider for PerformanceAttributes {
+impl<T: AttributeProvider> CatchAttributeProvider for T {
     #[inline]
     fn attributes(self) -> Option<CatchDifficultyAttributes> {
-        #[allow(irrefutable_let_patterns)]
-        if let Self::Catch(attributes) = self {
-            Some(attributes.difficulty)
-        } else {
-            None
-        }
+        self.mode_attributes().ok()
     }
 }
 
@@ -563,6 +763,48 @@ mod test {
         );
     }
 
+    #[test]
+    fn simulate_matches_osu_tools() {
+        let map = Beatmap::default();
+
+        let state = CatchPP::new(&map)
+            .attributes(attributes())
+            .misses(5)
+            .simulate(95.0);
+
+        let expected = CatchScoreState {
+            max_combo: 1801,
+            n_fruits: 1234,
+            n_droplets: 562,
+            n_tiny_droplets: 2143,
+            n_tiny_droplet_misses: 202,
+            n_misses: 5,
+        };
+
+        assert_eq!(state, expected);
+
+        let attributes = CatchDifficultyAttributes {
+            n_fruits: 6,
+            n_droplets: 2,
+            n_tiny_droplets: 8,
+            ..Default::default()
+        };
+
+        // The 14.5 caught objects are rounded to the nearest even integer like in C#
+        let state = CatchPP::new(&map).attributes(attributes).simulate(90.625);
+
+        let expected = CatchScoreState {
+            max_combo: 8,
+            n_fruits: 6,
+            n_droplets: 2,
+            n_tiny_droplets: 6,
+            n_tiny_droplet_misses: 2,
+            n_misses: 0,
+        };
+
+        assert_eq!(state, expected);
+    }
+
     #[test]
     fn fruits_accuracy_droplets_and_tiny_droplets() {
         let map = Beatmap::default();
@@ -617,14 +859,14 @@ mod test {
         let n_misses = 2;
 
         let calculator = CatchPP::new(&map)
-            .attributes(attributes.clone())
+            .attributes(attributes)
             .passed_objects(total_objects)
             .fruits(n_fruits)
             .droplets(n_droplets)
             .tiny_droplets(n_tiny_droplets)
             .tiny_droplet_misses(n_tiny_droplet_misses)
             .misses(n_misses)
-            .assert_hitresults(attributes.clone());
+            .assert_hitresults(attributes);
 
         assert!(
             (attributes.n_fruits as i32 - calculator.n_fruits as i32).abs() <= n_misses as i32,
@@ -650,4 +892,164 @@ mod test {
             calculator.n_tiny_droplets + calculator.n_tiny_droplet_misses,
         );
     }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn hyperdashes_and_strains() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+
+        let (attrs, strains) = crate::CatchStars::new(&map).calculate_with_strains();
+
+        assert_eq!(attrs, crate::CatchStars::new(&map).calculate());
+        assert_eq!(strains, crate::CatchStars::new(&map).strains());
+        assert!(attrs.n_hyperdashes > 0);
+        assert!(attrs.n_hyperdashes < attrs.max_combo());
+
+        let gradual = crate::catch::CatchGradualDifficultyAttributes::new(&map, 0);
+        assert_eq!(gradual.last().unwrap().n_hyperdashes, attrs.n_hyperdashes);
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn direction_changes() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+        let attrs = crate::CatchStars::new(&map).mods(16).calculate();
+
+        assert!(attrs.n_direction_changes > 0);
+        assert!(attrs.n_edge_dashes > 0);
+        assert!((0.0..=1.0).contains(&attrs.direction_change_rate()));
+
+        let gradual = crate::catch::CatchGradualDifficultyAttributes::new(&map, 16);
+        assert_eq!(gradual.last(), Some(attrs));
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn accuracy_with_misses() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+        let attrs = crate::CatchStars::new(&map).calculate();
+
+        for &acc in &[100.0, 99.0, 80.0, 0.0] {
+            let state = CatchPP::new(&map)
+                .attributes(attrs)
+                .misses(5)
+                .accuracy(acc)
+                .calculate()
+                .state;
+
+            assert_eq!(
+                state.n_tiny_droplets + state.n_tiny_droplet_misses,
+                attrs.n_tiny_droplets,
+                "{}",
+                acc
+            );
+            assert_eq!(state.n_fruits + state.n_droplets + 5, attrs.max_combo());
+        }
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn passed_objects_mid_juice_stream() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+        let attrs = crate::CatchStars::new(&map).calculate();
+        let mut prev_tiny_droplets = 0;
+
+        // Most of these end in the middle of a juice stream
+        for passed_objects in 1..=100 {
+            let partial = crate::CatchStars::new(&map)
+                .passed_objects(passed_objects)
+                .calculate();
+
+            assert_eq!(partial.max_combo(), passed_objects);
+            assert!(partial.n_tiny_droplets >= prev_tiny_droplets);
+            prev_tiny_droplets = partial.n_tiny_droplets;
+
+            let state = CatchPP::new(&map)
+                .passed_objects(passed_objects)
+                .calculate()
+                .state;
+
+            assert_eq!(state.n_fruits + state.n_droplets, passed_objects);
+            assert_eq!(state.n_tiny_droplets, partial.n_tiny_droplets);
+        }
+
+        let full = crate::CatchStars::new(&map)
+            .passed_objects(attrs.max_combo())
+            .calculate();
+
+        assert_eq!(full, attrs);
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn try_calculate_passed_droplets() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+        let max_combo = crate::CatchStars::new(&map).calculate().max_combo();
+        let passed_objects = map.hit_objects.len() + 10;
+
+        let attrs = CatchPP::new(&map)
+            .passed_objects(passed_objects)
+            .try_calculate()
+            .unwrap();
+
+        assert_eq!(
+            attrs,
+            CatchPP::new(&map)
+                .passed_objects(passed_objects)
+                .calculate()
+        );
+
+        let err = CatchPP::new(&map)
+            .passed_objects(max_combo + 1)
+            .try_calculate()
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            crate::InvalidScore::PassedObjects {
+                passed_objects: max_combo + 1,
+                n_objects: max_combo,
+            }
+        );
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn convert_flag() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+        let converted = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        assert!(!CatchPP::new(&map).calculate().is_convert());
+        assert!(CatchPP::new(&converted).calculate().is_convert());
+
+        let gradual = crate::catch::CatchGradualDifficultyAttributes::new(&converted, 0);
+        assert!(gradual.last().unwrap().is_convert);
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn object_strains() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+        let (attrs, strains) = crate::CatchStars::new(&map)
+            .mods(16)
+            .calculate_with_strains();
+        let objects: Vec<_> = crate::CatchStars::new(&map)
+            .mods(16)
+            .object_strains()
+            .collect();
+
+        assert_eq!(objects.len(), attrs.max_combo() - 1);
+
+        let n_hyperdashes = objects.iter().filter(|object| object.hyper_dash).count();
+        assert!(n_hyperdashes <= attrs.n_hyperdashes);
+        assert!(n_hyperdashes + 1 >= attrs.n_hyperdashes);
+
+        let max_object = objects
+            .iter()
+            .map(|object| object.movement)
+            .fold(0.0, f64::max);
+        let max_peak = strains.movement.iter().copied().fold(0.0, f64::max);
+        assert!(max_object > 0.0);
+        assert!(max_object <= max_peak + f64::EPSILON);
+    }
 }
@@ -0,0 +1,407 @@
+use std::{convert::TryFrom, error::Error as StdError, fmt};
+
+use crate::{
+    beatmap::DifficultySettings, catch::CatchDifficultyAttributes,
+    mania::ManiaDifficultyAttributes, osu::OsuDifficultyAttributes,
+    taiko::TaikoDifficultyAttributes, DifficultyAttributes, GameMode,
+};
+
+/// Version of the binary layout.
+///
+/// Must be incremented whenever the encoded fields of any mode change.
+const LAYOUT_VERSION: u8 = 1;
+
+/// Layout version, mode tag, and attributes version.
+const HEADER_LEN: usize = 1 + 1 + 4;
+const SETTINGS_LEN: usize = 10 * 8;
+
+const OSU_LEN: usize = HEADER_LEN + 11 * 8 + 4 * 4 + SETTINGS_LEN;
+const TAIKO_LEN: usize = HEADER_LEN + 7 * 8 + 4 + 1 + SETTINGS_LEN;
+const CATCH_LEN: usize = HEADER_LEN + 2 * 8 + 6 * 4 + 1 + SETTINGS_LEN;
+const MANIA_LEN: usize = HEADER_LEN + 3 * 8 + 4 * 4 + 1 + SETTINGS_LEN;
+
+impl DifficultyAttributes {
+    /// Encode the attributes into a fixed binary layout, e.g. to store them in a key-value store.
+    ///
+    /// The first byte is the version of the layout and the second byte is the mode,
+    /// followed by the [`version`](DifficultyAttributes::version) as little-endian `u32`.
+    /// All remaining fields follow in declaration order with floats as little-endian `f64`,
+    /// counts as little-endian `u32`, and booleans as a single byte.
+    /// The encoding is lossless and its length only depends on the mode:
+    /// 190 bytes for osu!standard, 147 for osu!taiko, and 127 for osu!catch and osu!mania.
+    ///
+    /// The AR, OD, HP, and hit windows of osu!standard are only stored as part of the
+    /// [`DifficultySettings`] since the separate fields are copies of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use murasame_pp::{AnyStars, Beatmap, DifficultyAttributes};
+    ///
+    /// # /*
+    /// let map: Beatmap = ...
+    /// # */
+    /// # let map = Beatmap::default();
+    /// let attrs = AnyStars::new(&map).mods(64).calculate();
+    /// let bytes = attrs.to_bytes();
+    ///
+    /// let decoded = DifficultyAttributes::from_bytes(&bytes).unwrap();
+    /// assert_eq!(decoded, attrs);
+    ///
+    /// // Attributes of an older calculation should be recalculated
+    /// if !decoded.is_current() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (mode, len) = match self {
+            Self::Osu(_) => (GameMode::Osu, OSU_LEN),
+            Self::Taiko(_) => (GameMode::Taiko, TAIKO_LEN),
+            Self::Catch(_) => (GameMode::Catch, CATCH_LEN),
+            Self::Mania(_) => (GameMode::Mania, MANIA_LEN),
+        };
+
+        let mut writer = Writer(Vec::with_capacity(len));
+        writer.0.push(LAYOUT_VERSION);
+        writer.0.push(mode as u8);
+        writer.0.extend_from_slice(&self.version().to_le_bytes());
+
+        match self {
+            Self::Osu(attrs) => {
+                writer.f64(attrs.aim);
+                writer.f64(attrs.speed);
+                writer.f64(attrs.flashlight);
+                writer.f64(attrs.aim_no_sliders);
+                writer.f64(attrs.slider_factor);
+                writer.f64(attrs.aim_difficult_slider_count);
+                writer.f64(attrs.aim_difficult_strain_count);
+                writer.f64(attrs.speed_difficult_strain_count);
+                writer.f64(attrs.speed_note_count);
+                writer.f64(attrs.rhythm_rating);
+                writer.count(attrs.n_circles);
+                writer.count(attrs.n_sliders);
+                writer.count(attrs.n_spinners);
+                writer.f64(attrs.stars);
+                writer.count(attrs.max_combo);
+                writer.settings(&attrs.settings);
+            }
+            Self::Taiko(attrs) => {
+                writer.f64(attrs.stamina);
+                writer.f64(attrs.rhythm);
+                writer.f64(attrs.colour);
+                writer.f64(attrs.peak);
+                writer.f64(attrs.hit_window);
+                writer.f64(attrs.ok_hit_window);
+                writer.f64(attrs.stars);
+                writer.count(attrs.max_combo);
+                writer.0.push(attrs.is_convert as u8);
+                writer.settings(&attrs.settings);
+            }
+            Self::Catch(attrs) => {
+                writer.f64(attrs.stars);
+                writer.f64(attrs.ar);
+                writer.count(attrs.n_fruits);
+                writer.count(attrs.n_droplets);
+                writer.count(attrs.n_tiny_droplets);
+                writer.count(attrs.n_hyperdashes);
+                writer.count(attrs.n_direction_changes);
+                writer.count(attrs.n_edge_dashes);
+                writer.0.push(attrs.is_convert as u8);
+                writer.settings(&attrs.settings);
+            }
+            Self::Mania(attrs) => {
+                writer.f64(attrs.stars);
+                writer.f64(attrs.hit_window);
+                writer.count(attrs.max_combo);
+                writer.count(attrs.n_objects);
+                writer.count(attrs.n_hold_notes);
+                writer.f64(attrs.hold_duration);
+                writer.count(attrs.n_keys);
+                writer.0.push(attrs.is_convert as u8);
+                writer.settings(&attrs.settings);
+            }
+        }
+
+        writer.0
+    }
+
+    /// Decode attributes that were encoded through [`DifficultyAttributes::to_bytes`].
+    ///
+    /// Only the current layout can be decoded, bytes of a different layout
+    /// are rejected with [`DecodeError::UnsupportedLayout`].
+    /// Use [`DifficultyAttributes::is_current`] to check whether decoded attributes
+    /// should be recalculated.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (layout, mode, rest) = match bytes {
+            [layout, mode, rest @ ..] => (*layout, *mode, rest),
+            _ => {
+                return Err(DecodeError::Length {
+                    expected: HEADER_LEN,
+                    actual: bytes.len(),
+                })
+            }
+        };
+
+        if layout != LAYOUT_VERSION {
+            return Err(DecodeError::UnsupportedLayout {
+                found: layout,
+                expected: LAYOUT_VERSION,
+            });
+        }
+
+        let expected = match mode {
+            0 => OSU_LEN,
+            1 => TAIKO_LEN,
+            2 => CATCH_LEN,
+            3 => MANIA_LEN,
+            _ => return Err(DecodeError::Mode(mode)),
+        };
+
+        if bytes.len() != expected {
+            return Err(DecodeError::Length {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+
+        let mut reader = Reader(rest);
+        let version = reader.u32();
+
+        let attrs = match mode {
+            0 => {
+                let aim = reader.f64();
+                let speed = reader.f64();
+                let flashlight = reader.f64();
+                let aim_no_sliders = reader.f64();
+                let slider_factor = reader.f64();
+                let aim_difficult_slider_count = reader.f64();
+                let aim_difficult_strain_count = reader.f64();
+                let speed_difficult_strain_count = reader.f64();
+                let speed_note_count = reader.f64();
+                let rhythm_rating = reader.f64();
+
+                let mut attrs = OsuDifficultyAttributes {
+                    aim,
+                    speed,
+                    flashlight,
+                    aim_no_sliders,
+                    slider_factor,
+                    aim_difficult_slider_count,
+                    aim_difficult_strain_count,
+                    speed_difficult_strain_count,
+                    speed_note_count,
+                    rhythm_rating,
+                    n_circles: reader.count(),
+                    n_sliders: reader.count(),
+                    n_spinners: reader.count(),
+                    stars: reader.f64(),
+                    max_combo: reader.count(),
+                    version,
+                    ..Default::default()
+                };
+
+                attrs.set_settings(reader.settings());
+
+                Self::Osu(attrs)
+            }
+            1 => Self::Taiko(TaikoDifficultyAttributes {
+                stamina: reader.f64(),
+                rhythm: reader.f64(),
+                colour: reader.f64(),
+                peak: reader.f64(),
+                hit_window: reader.f64(),
+                ok_hit_window: reader.f64(),
+                stars: reader.f64(),
+                max_combo: reader.count(),
+                is_convert: reader.bytes::<1>()[0] != 0,
+                settings: reader.settings(),
+                version,
+            }),
+            2 => Self::Catch(CatchDifficultyAttributes {
+                stars: reader.f64(),
+                ar: reader.f64(),
+                n_fruits: reader.count(),
+                n_droplets: reader.count(),
+                n_tiny_droplets: reader.count(),
+                n_hyperdashes: reader.count(),
+                n_direction_changes: reader.count(),
+                n_edge_dashes: reader.count(),
+                is_convert: reader.bytes::<1>()[0] != 0,
+                settings: reader.settings(),
+                version,
+            }),
+            _ => Self::Mania(ManiaDifficultyAttributes {
+                stars: reader.f64(),
+                hit_window: reader.f64(),
+                max_combo: reader.count(),
+                n_objects: reader.count(),
+                n_hold_notes: reader.count(),
+                hold_duration: reader.f64(),
+                n_keys: reader.count(),
+                is_convert: reader.bytes::<1>()[0] != 0,
+                settings: reader.settings(),
+                version,
+            }),
+        };
+
+        Ok(attrs)
+    }
+}
+
+/// The reason [`DifficultyAttributes::from_bytes`] failed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes were encoded with a different layout.
+    UnsupportedLayout {
+        /// The layout version of the given bytes.
+        found: u8,
+        /// The layout version of this crate.
+        expected: u8,
+    },
+    /// The mode byte is not a valid mode.
+    Mode(u8),
+    /// The amount of bytes does not match the layout of the mode.
+    Length {
+        /// The length of the mode's layout.
+        expected: usize,
+        /// The length of the given bytes.
+        actual: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedLayout { found, expected } => write!(
+                f,
+                "unsupported layout version {}, expected {}",
+                found, expected
+            ),
+            Self::Mode(mode) => write!(f, "invalid mode {}", mode),
+            Self::Length { expected, actual } => {
+                write!(f, "expected {} bytes but got {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl StdError for DecodeError {}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn f64(&mut self, value: f64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn count(&mut self, count: usize) {
+        let count = u32::try_from(count).unwrap_or(u32::MAX);
+        self.0.extend_from_slice(&count.to_le_bytes());
+    }
+
+    fn settings(&mut self, settings: &DifficultySettings) {
+        self.f64(settings.ar);
+        self.f64(settings.od);
+        self.f64(settings.cs);
+        self.f64(settings.hp);
+        self.f64(settings.clock_rate);
+        self.f64(settings.preempt);
+        self.f64(settings.great_hit_window);
+        self.f64(settings.ok_hit_window);
+        self.f64(settings.meh_hit_window);
+        self.f64(settings.bpm);
+    }
+}
+
+/// Reads from bytes whose length was already checked.
+struct Reader<'b>(&'b [u8]);
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&mut self) -> [u8; N] {
+        let (bytes, rest) = self.0.split_at(N);
+        self.0 = rest;
+
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+
+        array
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.bytes())
+    }
+
+    fn f64(&mut self) -> f64 {
+        f64::from_le_bytes(self.bytes())
+    }
+
+    fn count(&mut self) -> usize {
+        self.u32() as usize
+    }
+
+    fn settings(&mut self) -> DifficultySettings {
+        DifficultySettings {
+            ar: self.f64(),
+            od: self.f64(),
+            cs: self.f64(),
+            hp: self.f64(),
+            clock_rate: self.f64(),
+            preempt: self.f64(),
+            great_hit_window: self.f64(),
+            ok_hit_window: self.f64(),
+            meh_hit_window: self.f64(),
+            bpm: self.f64(),
+        }
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnyStars, Beatmap};
+
+    #[test]
+    fn roundtrip_all_modes() {
+        let paths = [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ];
+
+        for path in paths {
+            let map = Beatmap::from_path(path).unwrap();
+            let attrs = AnyStars::new(&map).mods(8 + 64).calculate();
+            let bytes = attrs.to_bytes();
+
+            assert_eq!(DifficultyAttributes::from_bytes(&bytes), Ok(attrs));
+            assert_eq!(
+                DifficultyAttributes::from_bytes(&bytes[..bytes.len() - 1]),
+                Err(DecodeError::Length {
+                    expected: bytes.len(),
+                    actual: bytes.len() - 1
+                })
+            );
+        }
+
+        assert_eq!(
+            DifficultyAttributes::from_bytes(&[LAYOUT_VERSION, 4]),
+            Err(DecodeError::Mode(4))
+        );
+    }
+
+    #[test]
+    fn stale_layout() {
+        let mut bytes = DifficultyAttributes::Osu(Default::default()).to_bytes();
+        bytes[0] = LAYOUT_VERSION - 1;
+
+        assert_eq!(
+            DifficultyAttributes::from_bytes(&bytes),
+            Err(DecodeError::UnsupportedLayout {
+                found: LAYOUT_VERSION - 1,
+                expected: LAYOUT_VERSION,
+            })
+        );
+    }
+}
@@ -31,16 +31,10 @@ impl BezierBuffers {
             return;
         }
 
-        let additional = len - self.left.len();
-
-        self.left
-            .extend(iter::repeat(Pos2::zero()).take(additional));
-        self.right
-            .extend(iter::repeat(Pos2::zero()).take(additional));
-        self.midpoints
-            .extend(iter::repeat(Pos2::zero()).take(additional));
-        self.left_child
-            .extend(iter::repeat(Pos2::zero()).take(additional));
+        self.left.resize(len, Pos2::zero());
+        self.right.resize(len, Pos2::zero());
+        self.midpoints.resize(len, Pos2::zero());
+        self.left_child.resize(len, Pos2::zero());
     }
 }
 
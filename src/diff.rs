@@ -0,0 +1,157 @@
+use crate::PerformanceAttributes;
+
+/// A value before and after a change, e.g. a pp value before and after a recalculation.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ValueDiff {
+    /// The previous value.
+    pub before: f64,
+    /// The new value.
+    pub after: f64,
+}
+
+impl ValueDiff {
+    /// The absolute change, positive if the value increased.
+    #[inline]
+    pub fn change(&self) -> f64 {
+        self.after - self.before
+    }
+
+    /// The change relative to the previous value in percent, e.g. `10.0` for an increase by 10%.
+    ///
+    /// Returns `0.0` if both values are `0.0` and infinity if only the previous value is `0.0`.
+    #[inline]
+    pub fn percent_change(&self) -> f64 {
+        if self.before == 0.0 {
+            if self.after == 0.0 {
+                0.0
+            } else {
+                f64::INFINITY.copysign(self.after)
+            }
+        } else {
+            self.change() / self.before.abs() * 100.0
+        }
+    }
+}
+
+/// A part of the total pp of a score.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum PpComponent {
+    /// The aim pp of osu!standard scores.
+    Aim,
+    /// The speed pp of osu!standard scores.
+    Speed,
+    /// The flashlight pp of osu!standard scores.
+    Flashlight,
+    /// The accuracy pp of osu!standard and osu!taiko scores.
+    Accuracy,
+    /// The difficulty pp of osu!taiko and osu!mania scores.
+    Difficulty,
+}
+
+/// The changes between two [`PerformanceAttributes`] of the same score,
+/// see [`PerformanceAttributes::diff`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PerformanceDiff {
+    /// The change of the total pp.
+    pub pp: ValueDiff,
+    /// The change of the star rating.
+    pub stars: ValueDiff,
+    /// The change of each pp component of the mode.
+    ///
+    /// Empty if the attributes belong to different modes.
+    pub components: Vec<(PpComponent, ValueDiff)>,
+}
+
+impl PerformanceDiff {
+    /// Return the change of the given component, if the mode has such component.
+    #[inline]
+    pub fn component(&self, component: PpComponent) -> Option<ValueDiff> {
+        self.components
+            .iter()
+            .find(|(c, _)| *c == component)
+            .map(|(_, diff)| *diff)
+    }
+}
+
+impl PerformanceAttributes {
+    /// Compare the attributes with the attributes of the same score after a change,
+    /// e.g. after recalculating a database with a new version of this crate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use murasame_pp::{AnyPP, Beatmap, PerformanceAttributes};
+    ///
+    /// # /*
+    /// let map: Beatmap = ...
+    /// let stored: PerformanceAttributes = ...
+    /// # */
+    /// # let map = Beatmap::default();
+    /// # let stored = AnyPP::new(&map).calculate();
+    /// let recalculated = AnyPP::new(&map).calculate();
+    /// let diff = stored.diff(&recalculated);
+    ///
+    /// println!("{:+.2}pp ({:+.1}%)", diff.pp.change(), diff.pp.percent_change());
+    ///
+    /// for (component, change) in diff.components.iter() {
+    ///     println!("{:?}: {:+.2}pp", component, change.change());
+    /// }
+    /// ```
+    pub fn diff(&self, other: &Self) -> PerformanceDiff {
+        let diff = |before, after| ValueDiff { before, after };
+
+        let components = match (self, other) {
+            (Self::Osu(before), Self::Osu(after)) => vec![
+                (PpComponent::Aim, diff(before.pp_aim, after.pp_aim)),
+                (PpComponent::Speed, diff(before.pp_speed, after.pp_speed)),
+                (
+                    PpComponent::Flashlight,
+                    diff(before.pp_flashlight, after.pp_flashlight),
+                ),
+                (PpComponent::Accuracy, diff(before.pp_acc, after.pp_acc)),
+            ],
+            (Self::Taiko(before), Self::Taiko(after)) => vec![
+                (
+                    PpComponent::Difficulty,
+                    diff(before.pp_difficulty, after.pp_difficulty),
+                ),
+                (PpComponent::Accuracy, diff(before.pp_acc, after.pp_acc)),
+            ],
+            (Self::Mania(before), Self::Mania(after)) => vec![(
+                PpComponent::Difficulty,
+                diff(before.pp_difficulty, after.pp_difficulty),
+            )],
+            _ => Vec::new(),
+        };
+
+        PerformanceDiff {
+            pp: diff(self.pp(), other.pp()),
+            stars: diff(self.stars(), other.stars()),
+            components,
+        }
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnyPP, Beatmap};
+
+    #[test]
+    fn diff_after_mod_change() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let nomod = AnyPP::new(&map).calculate();
+        let dt = AnyPP::new(&map).mods(64).calculate();
+
+        let diff = nomod.diff(&dt);
+        assert_eq!(diff.pp.before, nomod.pp());
+        assert!(diff.pp.change() > 0.0);
+        assert!(diff.stars.percent_change() > 0.0);
+        assert_eq!(diff.components.len(), 4);
+        assert!(diff.component(PpComponent::Difficulty).is_none());
+
+        let same = dt.diff(&dt);
+        assert!(same.components.iter().all(|(_, diff)| diff.change() == 0.0));
+    }
+}
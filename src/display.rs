@@ -0,0 +1,257 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::{
+    catch::{CatchDifficultyAttributes, CatchPerformanceAttributes},
+    mania::{ManiaDifficultyAttributes, ManiaPerformanceAttributes},
+    osu::{OsuDifficultyAttributes, OsuPerformanceAttributes},
+    taiko::{TaikoDifficultyAttributes, TaikoPerformanceAttributes},
+    DifficultyAttributes, PerformanceAttributes,
+};
+
+// The breakdowns are meant for humans, e.g. CLI output or chat messages,
+// and their exact layout is not considered stable.
+
+impl Display for OsuDifficultyAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "Stars: {:.2}", self.stars)?;
+        writeln!(
+            f,
+            "Aim: {:.2} | Speed: {:.2} | Flashlight: {:.2}",
+            self.aim, self.speed, self.flashlight
+        )?;
+        writeln!(
+            f,
+            "AR: {:.2} | OD: {:.2} | HP: {:.2}",
+            self.ar, self.od, self.hp
+        )?;
+
+        write!(
+            f,
+            "Max combo: {} | Circles: {} | Sliders: {} | Spinners: {}",
+            self.max_combo, self.n_circles, self.n_sliders, self.n_spinners
+        )
+    }
+}
+
+impl Display for TaikoDifficultyAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "Stars: {:.2}", self.stars)?;
+        writeln!(
+            f,
+            "Stamina: {:.2} | Rhythm: {:.2} | Colour: {:.2} | Peak: {:.2}",
+            self.stamina, self.rhythm, self.colour, self.peak
+        )?;
+
+        write!(
+            f,
+            "Max combo: {} | Hit window: {:.2}ms",
+            self.max_combo, self.hit_window
+        )
+    }
+}
+
+impl Display for CatchDifficultyAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "Stars: {:.2}", self.stars)?;
+        writeln!(f, "AR: {:.2}", self.ar)?;
+
+        write!(
+            f,
+            "Max combo: {} | Fruits: {} | Droplets: {} | Tiny droplets: {}",
+            self.max_combo(),
+            self.n_fruits,
+            self.n_droplets,
+            self.n_tiny_droplets
+        )
+    }
+}
+
+impl Display for ManiaDifficultyAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "Stars: {:.2}", self.stars)?;
+        writeln!(
+            f,
+            "Keys: {}{} | Hit window: {:.2}ms",
+            self.n_keys,
+            if self.is_convert { " (convert)" } else { "" },
+            self.hit_window
+        )?;
+
+        write!(f, "Max combo: {}", self.max_combo)
+    }
+}
+
+impl Display for OsuPerformanceAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let state = &self.state;
+
+        writeln!(f, "PP: {:.2} | Stars: {:.2}", self.pp, self.stars())?;
+        writeln!(
+            f,
+            "Aim: {:.2}pp | Speed: {:.2}pp | Accuracy: {:.2}pp | Flashlight: {:.2}pp",
+            self.pp_aim, self.pp_speed, self.pp_acc, self.pp_flashlight
+        )?;
+
+        write!(
+            f,
+            "Accuracy: {:.2}% | Combo: {}/{}x | {}/{}/{}/{}",
+            state.accuracy() * 100.0,
+            state.max_combo,
+            self.max_combo(),
+            state.n300,
+            state.n100,
+            state.n50,
+            state.n_misses
+        )
+    }
+}
+
+impl Display for TaikoPerformanceAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let state = &self.state;
+
+        writeln!(f, "PP: {:.2} | Stars: {:.2}", self.pp, self.stars())?;
+        writeln!(
+            f,
+            "Difficulty: {:.2}pp | Accuracy: {:.2}pp",
+            self.pp_difficulty, self.pp_acc
+        )?;
+
+        write!(
+            f,
+            "Accuracy: {:.2}% | Combo: {}/{}x | {}/{}/{}",
+            state.accuracy() * 100.0,
+            state.max_combo,
+            self.max_combo(),
+            state.n300,
+            state.n100,
+            state.n_misses
+        )
+    }
+}
+
+impl Display for CatchPerformanceAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let state = &self.state;
+
+        writeln!(f, "PP: {:.2} | Stars: {:.2}", self.pp, self.stars())?;
+
+        write!(
+            f,
+            "Accuracy: {:.2}% | Combo: {}/{}x | {}/{}/{}/{}/{}",
+            state.accuracy() * 100.0,
+            state.max_combo,
+            self.max_combo(),
+            state.n_fruits,
+            state.n_droplets,
+            state.n_tiny_droplets,
+            state.n_tiny_droplet_misses,
+            state.n_misses
+        )
+    }
+}
+
+impl Display for ManiaPerformanceAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let state = &self.state;
+
+        writeln!(f, "PP: {:.2} | Stars: {:.2}", self.pp, self.stars())?;
+        writeln!(f, "Difficulty: {:.2}pp", self.pp_difficulty)?;
+
+        write!(
+            f,
+            "Accuracy: {:.2}% | {}/{}/{}/{}/{}/{}",
+            state.accuracy() * 100.0,
+            state.n320,
+            state.n300,
+            state.n200,
+            state.n100,
+            state.n50,
+            state.n_misses
+        )
+    }
+}
+
+impl Display for DifficultyAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Osu(attrs) => attrs.fmt(f),
+            Self::Taiko(attrs) => attrs.fmt(f),
+            Self::Catch(attrs) => attrs.fmt(f),
+            Self::Mania(attrs) => attrs.fmt(f),
+        }
+    }
+}
+
+impl Display for PerformanceAttributes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Osu(attrs) => attrs.fmt(f),
+            Self::Taiko(attrs) => attrs.fmt(f),
+            Self::Catch(attrs) => attrs.fmt(f),
+            Self::Mania(attrs) => attrs.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osu::OsuScoreState;
+
+    #[test]
+    fn osu_performance_breakdown() {
+        let attrs = OsuPerformanceAttributes {
+            difficulty: OsuDifficultyAttributes {
+                stars: 6.4213,
+                max_combo: 1234,
+                ..Default::default()
+            },
+            pp: 312.456,
+            pp_aim: 150.0,
+            pp_speed: 120.0,
+            pp_acc: 40.0,
+            pp_flashlight: 2.456,
+            state: OsuScoreState {
+                max_combo: 1200,
+                n300: 5,
+                n100: 1,
+                n50: 0,
+                n_misses: 0,
+            },
+            ..Default::default()
+        };
+
+        let expected = "PP: 312.46 | Stars: 6.42\n\
+            Aim: 150.00pp | Speed: 120.00pp | Accuracy: 40.00pp | Flashlight: 2.46pp\n\
+            Accuracy: 88.89% | Combo: 1200/1234x | 5/1/0/0";
+
+        assert_eq!(attrs.to_string(), expected);
+        assert_eq!(PerformanceAttributes::Osu(attrs).to_string(), expected);
+    }
+
+    #[test]
+    fn difficulty_breakdown_per_mode() {
+        let mania = ManiaDifficultyAttributes {
+            stars: 3.0,
+            hit_window: 34.0,
+            max_combo: 500,
+            n_keys: 7,
+            is_convert: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            DifficultyAttributes::Mania(mania).to_string(),
+            "Stars: 3.00\nKeys: 7 (convert) | Hit window: 34.00ms\nMax combo: 500"
+        );
+
+        let catch = CatchDifficultyAttributes {
+            n_fruits: 100,
+            n_droplets: 20,
+            ..Default::default()
+        };
+
+        assert!(catch.to_string().contains("Max combo: 120 |"));
+    }
+}
@@ -0,0 +1,466 @@
+use std::{error::Error as StdError, fmt};
+
+use serde::Deserialize;
+
+use crate::{AnyPP, Beatmap, GameMode, Grade, Mods, ParseError, ScoreState};
+
+/// A score as returned by the osu!api v2.
+///
+/// Only the fields required for a [`ScoreEmbed`] are deserialized.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ApiScore {
+    /// The ruleset of the score.
+    #[serde(alias = "ruleset_id")]
+    pub mode_int: u8,
+    /// The acronyms of the score's mods.
+    ///
+    /// Only mods with a bit value in [`Mods`] are considered,
+    /// all other acronyms are ignored.
+    #[serde(default)]
+    pub mods: Vec<ApiMod>,
+    /// The maximum combo of the score.
+    pub max_combo: usize,
+    /// The hitresults of the score.
+    pub statistics: ApiStatistics,
+}
+
+/// A mod as returned by the osu!api v2, either as plain acronym or as object.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ApiMod {
+    /// The mod's acronym, e.g. `"HD"`.
+    Acronym(String),
+    /// The mod as object containing its acronym.
+    Object {
+        /// The mod's acronym, e.g. `"HD"`.
+        acronym: String,
+    },
+}
+
+impl ApiMod {
+    fn acronym(&self) -> &str {
+        match self {
+            Self::Acronym(acronym) | Self::Object { acronym } => acronym,
+        }
+    }
+}
+
+/// The hitresults of an [`ApiScore`].
+///
+/// Both the legacy `count_*` names and the current names such as `great` or `ok` are accepted.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ApiStatistics {
+    /// Amount of gekis (n320 for osu!mania).
+    #[serde(default)]
+    pub count_geki: usize,
+    /// Amount of katus (tiny droplet misses for osu!catch / n200 for osu!mania).
+    #[serde(default)]
+    pub count_katu: usize,
+    /// Amount of 300s.
+    #[serde(default)]
+    pub count_300: usize,
+    /// Amount of 100s.
+    #[serde(default)]
+    pub count_100: usize,
+    /// Amount of 50s.
+    #[serde(default)]
+    pub count_50: usize,
+    /// Amount of misses.
+    #[serde(default)]
+    pub count_miss: usize,
+    /// Amount of perfect hits (n320 for osu!mania).
+    #[serde(default)]
+    pub perfect: usize,
+    /// Amount of great hits (n300 / fruits for osu!catch).
+    #[serde(default)]
+    pub great: usize,
+    /// Amount of good hits (n200 for osu!mania).
+    #[serde(default)]
+    pub good: usize,
+    /// Amount of ok hits (n100).
+    #[serde(default)]
+    pub ok: usize,
+    /// Amount of meh hits (n50).
+    #[serde(default)]
+    pub meh: usize,
+    /// Amount of misses.
+    #[serde(default)]
+    pub miss: usize,
+    /// Amount of hit large ticks (droplets for osu!catch).
+    #[serde(default)]
+    pub large_tick_hit: usize,
+    /// Amount of missed large ticks (missed droplets for osu!catch).
+    #[serde(default)]
+    pub large_tick_miss: usize,
+    /// Amount of hit small ticks (tiny droplets for osu!catch).
+    #[serde(default)]
+    pub small_tick_hit: usize,
+    /// Amount of missed small ticks (tiny droplet misses for osu!catch).
+    #[serde(default)]
+    pub small_tick_miss: usize,
+}
+
+impl ApiStatistics {
+    /// Whether none of the hitresults is known, e.g. because the names were not recognized.
+    fn is_empty(&self) -> bool {
+        let counts = [
+            self.count_geki,
+            self.count_katu,
+            self.count_300,
+            self.count_100,
+            self.count_50,
+            self.count_miss,
+            self.perfect,
+            self.great,
+            self.good,
+            self.ok,
+            self.meh,
+            self.miss,
+            self.large_tick_hit,
+            self.large_tick_miss,
+            self.small_tick_hit,
+            self.small_tick_miss,
+        ];
+
+        counts.iter().all(|&count| count == 0)
+    }
+
+    /// Convert the hitresults into a [`ScoreState`] of the given mode.
+    ///
+    /// The legacy and the current hitresults are added up
+    /// since the osu!api only provides one of them.
+    fn state(&self, mode: GameMode, max_combo: usize) -> ScoreState {
+        let mut state = ScoreState {
+            max_combo,
+            n_geki: self.count_geki,
+            n_katu: self.count_katu,
+            n300: self.count_300 + self.great,
+            n100: self.count_100,
+            n50: self.count_50,
+            n_misses: self.count_miss + self.miss,
+        };
+
+        match mode {
+            GameMode::Osu | GameMode::Taiko => {
+                state.n100 += self.ok;
+                state.n50 += self.meh;
+            }
+            GameMode::Catch => {
+                state.n100 += self.large_tick_hit;
+                state.n50 += self.small_tick_hit;
+                state.n_katu += self.small_tick_miss;
+                state.n_misses += self.large_tick_miss;
+            }
+            GameMode::Mania => {
+                state.n_geki += self.perfect;
+                state.n_katu += self.good;
+                state.n100 += self.ok;
+                state.n50 += self.meh;
+            }
+        }
+
+        state
+    }
+}
+
+/// Everything needed to display a score, e.g. in a Discord embed.
+///
+/// # Example
+///
+/// ```no_run
+/// use murasame_pp::{Beatmap, embed::ScoreEmbed};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// let score_json: &[u8] = ...
+/// # */
+/// # let map = Beatmap::default();
+/// # let score_json: &[u8] = b"";
+/// let embed = ScoreEmbed::from_json(&map, score_json).unwrap();
+///
+/// println!(
+///     "{:.2}pp ({:.2}pp if FC) | {:.2}* | {:?}",
+///     embed.pp, embed.if_fc_pp, embed.stars, embed.grade
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScoreEmbed {
+    /// The mode of the score.
+    pub mode: GameMode,
+    /// The mods of the score as bit values.
+    pub mods: u32,
+    /// The star rating of the map with the score's mods.
+    pub stars: f64,
+    /// The performance points of the score.
+    pub pp: f64,
+    /// The performance points if the score was a full combo.
+    pub if_fc_pp: f64,
+    /// The performance points of an SS.
+    pub max_pp: f64,
+    /// The grade of the score.
+    pub grade: Grade,
+    /// The accuracy of the score between `0.0` and `100.0`.
+    pub accuracy: f64,
+    /// The maximum combo of the score.
+    pub combo: usize,
+    /// The maximum combo of the map.
+    pub max_combo: usize,
+    /// The approach rate after applying mods.
+    pub ar: f64,
+    /// The overall difficulty after applying mods.
+    pub od: f64,
+    /// The circle size after applying mods.
+    pub cs: f64,
+    /// The health drain rate after applying mods.
+    pub hp: f64,
+    /// The beats per minute after applying mods.
+    pub bpm: f64,
+}
+
+impl ScoreEmbed {
+    /// Deserialize an osu!api v2 score and calculate its [`ScoreEmbed`] on the given map.
+    pub fn from_json(map: &Beatmap, score_json: &[u8]) -> Result<Self, EmbedError> {
+        let score: ApiScore = serde_json::from_slice(score_json).map_err(EmbedError::Json)?;
+
+        if score.statistics.is_empty() {
+            return Err(EmbedError::MissingHitResults);
+        }
+
+        Self::new(map, &score)
+    }
+
+    /// Parse the content of a `.osu` file and calculate the [`ScoreEmbed`]
+    /// of the given osu!api v2 score on it.
+    #[cfg(not(any(feature = "async_std", feature = "async_tokio")))]
+    pub fn from_bytes(map_bytes: &[u8], score_json: &[u8]) -> Result<Self, EmbedError> {
+        let map = Beatmap::from_bytes(map_bytes).map_err(EmbedError::Parse)?;
+
+        Self::from_json(&map, score_json)
+    }
+
+    /// Calculate the [`ScoreEmbed`] of a score on the given map.
+    ///
+    /// Fails if the score's mode is unknown. Mods without a bit value
+    /// such as Classic or Mirror are ignored, see [`ApiScore::mods`].
+    pub fn new(map: &Beatmap, score: &ApiScore) -> Result<Self, EmbedError> {
+        let mode = match score.mode_int {
+            0 => GameMode::Osu,
+            1 => GameMode::Taiko,
+            2 => GameMode::Catch,
+            3 => GameMode::Mania,
+            mode => return Err(EmbedError::UnknownMode(mode)),
+        };
+
+        let mods = score
+            .mods
+            .iter()
+            .fold(0, |mods, m| mods | mod_bits(m.acronym()));
+
+        let state = score.statistics.state(mode, score.max_combo);
+
+        let calculator = AnyPP::new(map).mode(mode).mods(mods);
+        let (performance, max) = calculator.clone().state(state).calculate_with_max();
+        let max_combo = performance.max_combo();
+
+        let mut fc_state = state;
+        fc_state.max_combo = max_combo;
+
+        if mode == GameMode::Mania {
+            fc_state.n_geki += fc_state.n_misses;
+        } else {
+            fc_state.n300 += fc_state.n_misses;
+        }
+
+        fc_state.n_misses = 0;
+
+        let if_fc = calculator
+            .attributes(performance)
+            .state(fc_state)
+            .calculate();
+
+        let grade = state.grade(mode, mods);
+        let accuracy = state.accuracy(mode);
+
+        let map_attrs = map
+            .attributes()
+            .mode(mode)
+            .converted(map.mode != mode)
+            .mods(mods)
+            .build();
+
+        Ok(Self {
+            mode,
+            mods,
+            stars: performance.stars(),
+            pp: performance.pp(),
+            if_fc_pp: if_fc.pp(),
+            max_pp: max.pp(),
+            grade,
+            accuracy: accuracy * 100.0,
+            combo: score.max_combo,
+            max_combo,
+            ar: map_attrs.ar,
+            od: map_attrs.od,
+            cs: map_attrs.cs,
+            hp: map_attrs.hp,
+            bpm: map.bpm() * map_attrs.clock_rate,
+        })
+    }
+}
+
+/// Convert a mod acronym into its bit value.
+///
+/// Acronyms without a bit value, e.g. `"CL"` for Classic, are ignored.
+fn mod_bits(acronym: &str) -> u32 {
+    match acronym {
+        "NF" => u32::NF,
+        "EZ" => u32::EZ,
+        "TD" => u32::TD,
+        "HD" => u32::HD,
+        "HR" => u32::HR,
+        "SD" => u32::SD,
+        "DT" => u32::DT,
+        "RX" => u32::RX,
+        "HT" => u32::HT,
+        "NC" => u32::DT | u32::NC,
+        "FL" => u32::FL,
+        "SO" => u32::SO,
+        "AP" => u32::AP,
+        "SV2" => u32::V2,
+        "PF" => u32::SD | u32::PF,
+        "1K" => u32::KEY1,
+        "2K" => u32::KEY2,
+        "3K" => u32::KEY3,
+        "4K" => u32::KEY4,
+        "5K" => u32::KEY5,
+        "6K" => u32::KEY6,
+        "7K" => u32::KEY7,
+        "8K" => u32::KEY8,
+        "9K" => u32::KEY9,
+        _ => 0,
+    }
+}
+
+/// Anything that could go wrong while creating a [`ScoreEmbed`].
+#[derive(Debug)]
+pub enum EmbedError {
+    /// Failed to deserialize the score.
+    Json(serde_json::Error),
+    /// Failed to parse the map.
+    Parse(ParseError),
+    /// The score's statistics contain no known hitresult.
+    MissingHitResults,
+    /// The score's `mode_int` or `ruleset_id` is not a known mode.
+    UnknownMode(u8),
+}
+
+impl fmt::Display for EmbedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(_) => f.write_str("failed to deserialize score"),
+            Self::Parse(_) => f.write_str("failed to parse map"),
+            Self::MissingHitResults => f.write_str("score statistics contain no hitresults"),
+            Self::UnknownMode(mode) => write!(f, "unknown mode {}", mode),
+        }
+    }
+}
+
+impl StdError for EmbedError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            Self::Parse(err) => Some(err),
+            Self::MissingHitResults | Self::UnknownMode(_) => None,
+        }
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embed_from_api_score() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let score_json = br#"{
+            "mode_int": 0,
+            "mods": ["HD", {"acronym": "DT"}],
+            "max_combo": 500,
+            "statistics": { "count_300": 590, "count_100": 8, "count_miss": 2 }
+        }"#;
+
+        let embed = ScoreEmbed::from_json(&map, score_json).unwrap();
+
+        assert_eq!(embed.mods, u32::HD | u32::DT);
+        assert!(embed.pp < embed.if_fc_pp);
+        assert!(embed.if_fc_pp < embed.max_pp);
+        assert_eq!(embed.grade, Grade::A);
+        assert!((embed.bpm - map.bpm() * 1.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn embed_from_current_api_score() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let legacy_json = br#"{
+            "ruleset_id": 2,
+            "max_combo": 300,
+            "statistics": {
+                "count_300": 400,
+                "count_100": 20,
+                "count_50": 150,
+                "count_katu": 5,
+                "count_miss": 3
+            }
+        }"#;
+
+        let current_json = br#"{
+            "ruleset_id": 2,
+            "max_combo": 300,
+            "statistics": {
+                "great": 400,
+                "large_tick_hit": 20,
+                "large_tick_miss": 1,
+                "small_tick_hit": 150,
+                "small_tick_miss": 5,
+                "miss": 2
+            }
+        }"#;
+
+        let legacy = ScoreEmbed::from_json(&map, legacy_json).unwrap();
+        let current = ScoreEmbed::from_json(&map, current_json).unwrap();
+
+        assert_eq!(legacy, current);
+        assert!(current.pp > 0.0);
+    }
+
+    #[test]
+    fn embed_without_hitresults() {
+        let map = Beatmap::default();
+        let score_json = br#"{ "ruleset_id": 0, "max_combo": 0, "statistics": { "foo": 1 } }"#;
+
+        let err = ScoreEmbed::from_json(&map, score_json).unwrap_err();
+
+        assert!(matches!(err, EmbedError::MissingHitResults));
+    }
+
+    #[test]
+    fn embed_with_unknown_mode() {
+        let map = Beatmap::default();
+        let score_json = br#"{ "ruleset_id": 4, "max_combo": 0, "statistics": { "great": 1 } }"#;
+
+        let err = ScoreEmbed::from_json(&map, score_json).unwrap_err();
+
+        assert!(matches!(err, EmbedError::UnknownMode(4)));
+    }
+
+    #[test]
+    fn mods_without_bits_are_ignored() {
+        assert_eq!(mod_bits("NC"), u32::DT | u32::NC);
+        assert_eq!(mod_bits("PF"), u32::SD | u32::PF);
+        assert_eq!(mod_bits("4K"), u32::KEY4);
+        assert_eq!(mod_bits("CL"), 0);
+    }
+}
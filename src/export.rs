@@ -0,0 +1,154 @@
+use std::io::{Result as IoResult, Write};
+
+use crate::{osu, Beatmap, GameMode, OsuStars};
+
+const HEADER: &str = "map,idx,start_time,delta_time,strain_time,jump_dist,travel_dist,angle,\
+    aim_strain,speed_strain,flashlight_strain,aim,speed,flashlight,stars";
+
+/// Writes per-object difficulty features of osu!standard maps as CSV.
+///
+/// Each row contains the values of one difficulty object such as its strains,
+/// distances, and delta time alongside the final ratings of its map.
+/// Only osu!standard maps are supported and CSV is the only format.
+/// The `start_time`, `delta_time`, and `strain_time` columns are in milliseconds
+/// and adjusted by the clock rate of the mods.
+/// The `idx` column is the index of the object's hit object within the map.
+/// The first hit object has no difficulty object so indices start at 1.
+/// The header is written before the first row.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, export::DatasetExporter};
+///
+/// # /*
+/// let maps: Vec<(String, Beatmap)> = ...
+/// # */
+/// # let maps = vec![(String::from("empty"), Beatmap::default())];
+/// let mut exporter = DatasetExporter::new(Vec::new()).mods(64);
+///
+/// for (name, map) in maps.iter() {
+///     exporter.export(name, map).unwrap();
+/// }
+///
+/// let csv = exporter.into_inner().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DatasetExporter<W> {
+    writer: W,
+    mods: u32,
+    wrote_header: bool,
+}
+
+impl<W: Write> DatasetExporter<W> {
+    /// Create a new exporter that writes into the given writer.
+    #[inline]
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            mods: 0,
+            wrote_header: false,
+        }
+    }
+
+    /// Specify mods through their bit values.
+    ///
+    /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// Write a row for each difficulty object of the map and return the amount of rows.
+    ///
+    /// The name is used to identify the map in the `map` column.
+    /// Maps that are not osu!standard maps are skipped.
+    pub fn export(&mut self, name: &str, map: &Beatmap) -> IoResult<usize> {
+        if map.mode != GameMode::Osu {
+            return Ok(0);
+        }
+
+        if !self.wrote_header {
+            writeln!(self.writer, "{}", HEADER)?;
+            self.wrote_header = true;
+        }
+
+        let (attrs, features) = osu::object_features(OsuStars::new(map).mods(self.mods));
+        let name = escape(name);
+
+        for object in features.iter() {
+            writeln!(
+                self.writer,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                name,
+                object.idx,
+                object.start_time,
+                object.delta_time,
+                object.strain_time,
+                object.jump_dist,
+                object.travel_dist,
+                object
+                    .angle
+                    .map_or_else(String::new, |angle| angle.to_string()),
+                object.aim_strain,
+                object.speed_strain,
+                object.flashlight_strain,
+                attrs.aim,
+                attrs.speed,
+                attrs.flashlight,
+                attrs.stars,
+            )?;
+        }
+
+        Ok(features.len())
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn into_inner(mut self) -> IoResult<W> {
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+fn escape(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_names() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(escape("a, \"b\""), "\"a, \"\"b\"\"\"");
+    }
+
+    #[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+    #[test]
+    fn idx_matches_hit_objects() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let mut exporter = DatasetExporter::new(Vec::new()).mods(64);
+        let n_rows = exporter.export("map", &map).unwrap();
+        let csv = String::from_utf8(exporter.into_inner().unwrap()).unwrap();
+
+        assert_eq!(n_rows, map.hit_objects.len() - 1);
+
+        for (i, row) in csv.lines().skip(1).enumerate() {
+            let mut values = row.split(',').skip(1);
+            let idx: usize = values.next().unwrap().parse().unwrap();
+            let start_time: f64 = values.next().unwrap().parse().unwrap();
+
+            assert_eq!(idx, i + 1);
+            assert_eq!(start_time, map.hit_objects[idx].start_time / 1.5);
+        }
+    }
+}
@@ -0,0 +1,392 @@
+mod pp;
+
+pub use self::pp::*;
+
+use crate::{
+    catch::{
+        calculate_catch_width,
+        catch_object::CatchObject,
+        fruit_or_juice::{FruitOrJuice, FruitParams},
+        ALLOWED_CATCH_RANGE,
+    },
+    curve::CurveBuffers,
+    Beatmap, GameMode, Mods,
+};
+
+const SECTION_LENGTH: f64 = 750.0;
+const STAR_SCALING_FACTOR: f64 = 0.145;
+const DECAY_WEIGHT: f64 = 0.94;
+const STRAIN_DECAY_BASE: f64 = 0.2;
+const SKILL_MULTIPLIER: f64 = 850.0;
+
+const ABSOLUTE_PLAYER_POSITIONING_ERROR: f32 = 16.0;
+const NORMALIZED_HITOBJECT_RADIUS: f32 = 41.0;
+const POSITION_EPSILON: f32 = NORMALIZED_HITOBJECT_RADIUS - ABSOLUTE_PLAYER_POSITIONING_ERROR;
+const DIRECTION_CHANGE_BONUS: f64 = 12.5;
+
+/// Difficulty calculator on osu!catch maps using the algorithm
+/// from before the 2020 rework.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, fruits_2020::CatchStars};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+///
+/// let difficulty_attrs = CatchStars::new(&map)
+///     .mods(8 + 64) // HDDT
+///     .calculate();
+///
+/// println!("Stars: {}", difficulty_attrs.stars);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CatchStars<'map> {
+    map: &'map Beatmap,
+    mods: u32,
+    passed_objects: Option<usize>,
+    clock_rate: Option<f64>,
+}
+
+impl<'map> CatchStars<'map> {
+    /// Create a new difficulty calculator for osu!catch maps.
+    #[inline]
+    pub fn new(map: &'map Beatmap) -> Self {
+        Self {
+            map,
+            mods: 0,
+            passed_objects: None,
+            clock_rate: None,
+        }
+    }
+
+    /// Specify mods through their bit values.
+    ///
+    /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// Amount of passed objects for partial plays, e.g. a fail.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects = Some(passed_objects);
+
+        self
+    }
+
+    /// Adjust the clock rate used in the calculation.
+    /// If none is specified, it will take the clock rate based on the mods
+    /// i.e. 1.5 for DT, 0.75 for HT and 1.0 otherwise.
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: f64) -> Self {
+        self.clock_rate = Some(clock_rate);
+
+        self
+    }
+
+    /// Calculate the star rating.
+    pub fn calculate(self) -> CatchDifficultyAttributes {
+        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+
+        let settings = self
+            .map
+            .attributes()
+            .mode(GameMode::Catch)
+            .mods(self.mods)
+            .clock_rate(clock_rate)
+            .settings();
+
+        let mut params = FruitParams {
+            attributes: Default::default(),
+            curve_bufs: CurveBuffers::default(),
+            last_pos: None,
+            last_time: 0.0,
+            map: self.map,
+            ticks: Vec::new(),
+            with_hr: self.mods.hr(),
+        };
+
+        let mut remaining = self.passed_objects.unwrap_or(usize::MAX);
+
+        let mut hit_objects = self
+            .map
+            .hit_objects
+            .iter()
+            .filter_map(|h| {
+                if remaining == 0 {
+                    return None;
+                }
+
+                // Juice streams are cut off so that their later objects are not counted
+                let h = FruitOrJuice::new_partial(h, &mut params, remaining)?;
+                remaining -= h.len();
+
+                Some(h)
+            })
+            .flatten();
+
+        let half_catcher_width =
+            (calculate_catch_width(settings.cs as f32) / 2.0 / ALLOWED_CATCH_RANGE) as f64;
+        let mut last_direction = 0;
+        let mut last_excess = half_catcher_width;
+
+        let mut movement = Movement::new(settings.cs as f32);
+        let mut strain_peaks = Vec::new();
+
+        if let Some(mut prev) = hit_objects.next() {
+            let mut curr_section_end = None;
+
+            for curr in hit_objects {
+                prev.init_hyper_dash(
+                    half_catcher_width,
+                    &curr,
+                    &mut last_direction,
+                    &mut last_excess,
+                );
+
+                let h =
+                    DifficultyObject::new(&curr, &prev, movement.half_catcher_width, clock_rate);
+                let section_end = curr_section_end
+                    .get_or_insert_with(|| (h.start_time / SECTION_LENGTH).ceil() * SECTION_LENGTH);
+
+                while h.start_time > *section_end {
+                    strain_peaks.push(movement.curr_section_peak);
+                    movement.start_new_section_from(*section_end);
+                    *section_end += SECTION_LENGTH;
+                }
+
+                movement.process(&h);
+                prev = curr;
+            }
+
+            strain_peaks.push(movement.curr_section_peak);
+        }
+
+        strain_peaks.sort_unstable_by(|a, b| b.total_cmp(a));
+
+        let mut difficulty = 0.0;
+        let mut weight = 1.0;
+
+        for strain in strain_peaks {
+            difficulty += strain * weight;
+            weight *= DECAY_WEIGHT;
+        }
+
+        let counts = params.attributes;
+
+        CatchDifficultyAttributes {
+            stars: difficulty.sqrt() * STAR_SCALING_FACTOR,
+            ar: settings.ar,
+            n_fruits: counts.n_fruits,
+            n_droplets: counts.n_droplets,
+            n_tiny_droplets: counts.n_tiny_droplets,
+        }
+    }
+}
+
+/// The result of a difficulty calculation with the pre-2020 osu!catch algorithm.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CatchDifficultyAttributes {
+    /// The final star rating.
+    pub stars: f64,
+    /// The approach rate.
+    pub ar: f64,
+    /// The amount of fruits.
+    pub n_fruits: usize,
+    /// The amount of droplets.
+    pub n_droplets: usize,
+    /// The amount of tiny droplets.
+    pub n_tiny_droplets: usize,
+}
+
+impl CatchDifficultyAttributes {
+    /// Return the maximum combo.
+    #[inline]
+    pub fn max_combo(&self) -> usize {
+        self.n_fruits + self.n_droplets
+    }
+}
+
+struct DifficultyObject<'o> {
+    last: &'o CatchObject,
+    delta: f64,
+    start_time: f64,
+    normalized_pos: f32,
+    last_normalized_pos: f32,
+    strain_time: f64,
+}
+
+impl<'o> DifficultyObject<'o> {
+    fn new(
+        base: &'o CatchObject,
+        last: &'o CatchObject,
+        half_catcher_width: f32,
+        clock_rate: f64,
+    ) -> Self {
+        let delta = (base.time - last.time) / clock_rate;
+        let scaling_factor = NORMALIZED_HITOBJECT_RADIUS / half_catcher_width;
+
+        Self {
+            last,
+            delta,
+            start_time: base.time / clock_rate,
+            normalized_pos: base.pos * scaling_factor,
+            last_normalized_pos: last.pos * scaling_factor,
+            // Every strain interval is capped at the equivalent of 600 BPM streams
+            strain_time: delta.max(25.0),
+        }
+    }
+}
+
+struct Movement {
+    half_catcher_width: f32,
+    last_player_position: Option<f32>,
+    last_distance_moved: f32,
+    current_strain: f64,
+    curr_section_peak: f64,
+    prev_time: f64,
+}
+
+impl Movement {
+    fn new(cs: f32) -> Self {
+        Self {
+            half_catcher_width: calculate_catch_width(cs) * 0.5,
+            last_player_position: None,
+            last_distance_moved: 0.0,
+            current_strain: 1.0,
+            curr_section_peak: 1.0,
+            prev_time: 0.0,
+        }
+    }
+
+    fn start_new_section_from(&mut self, time: f64) {
+        self.curr_section_peak = self.current_strain * strain_decay(time - self.prev_time);
+    }
+
+    fn process(&mut self, current: &DifficultyObject<'_>) {
+        self.current_strain *= strain_decay(current.delta);
+        self.current_strain += self.strain_value_of(current) * SKILL_MULTIPLIER;
+        self.curr_section_peak = self.current_strain.max(self.curr_section_peak);
+        self.prev_time = current.start_time;
+    }
+
+    fn strain_value_of(&mut self, current: &DifficultyObject<'_>) -> f64 {
+        let last_player_pos = self
+            .last_player_position
+            .unwrap_or(current.last_normalized_pos);
+
+        let mut pos = last_player_pos
+            .max(current.normalized_pos - POSITION_EPSILON)
+            .min(current.normalized_pos + POSITION_EPSILON);
+
+        let dist_moved = pos - last_player_pos;
+        let sqrt_strain = current.strain_time.sqrt();
+
+        let mut dist_addition = (dist_moved.abs().powf(1.3) / 500.0) as f64;
+        let mut bonus = 0.0;
+        let almost_hyper_dash = current.last.hyper_dist <= 10.0;
+
+        if dist_moved.abs() > 0.1 {
+            if self.last_distance_moved.abs() > 0.1
+                && dist_moved.signum() != self.last_distance_moved.signum()
+            {
+                let bonus_factor = (dist_moved.abs().min(ABSOLUTE_PLAYER_POSITIONING_ERROR)
+                    / ABSOLUTE_PLAYER_POSITIONING_ERROR) as f64;
+
+                dist_addition += DIRECTION_CHANGE_BONUS / sqrt_strain * bonus_factor;
+
+                // Bonus for tougher direction switches and "almost" hyperdashes
+                if almost_hyper_dash {
+                    bonus = 0.3 * bonus_factor;
+                }
+            }
+
+            // Base bonus for every movement, giving some weight to streams
+            dist_addition += (7.5 * dist_moved.abs().min(NORMALIZED_HITOBJECT_RADIUS * 2.0)
+                / (NORMALIZED_HITOBJECT_RADIUS * 6.0)) as f64
+                / sqrt_strain;
+        }
+
+        // Bonus for "almost" hyperdashes at corner points
+        if almost_hyper_dash {
+            if !current.last.hyper_dash {
+                bonus += 1.0;
+            } else {
+                // After a hyperdash the catcher is always in the correct position
+                pos = current.normalized_pos;
+            }
+
+            dist_addition *= 1.0 + bonus * ((10.0 - current.last.hyper_dist) / 10.0) as f64;
+        }
+
+        self.last_player_position = Some(pos);
+        self.last_distance_moved = dist_moved;
+
+        dist_addition / current.strain_time
+    }
+}
+
+#[inline]
+fn strain_decay(ms: f64) -> f64 {
+    STRAIN_DECAY_BASE.powf(ms / 1000.0)
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_stars_and_pp() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+
+        let nomod = CatchStars::new(&map).calculate();
+        let dt = CatchStars::new(&map).mods(64).calculate();
+        let current = crate::CatchStars::new(&map).calculate();
+
+        assert_eq!(nomod.max_combo(), current.max_combo());
+        assert_eq!(nomod.n_tiny_droplets, current.n_tiny_droplets);
+        assert!(nomod.stars > 0.0);
+        assert_ne!(nomod.stars, current.stars);
+        assert!(dt.stars > nomod.stars);
+        assert!(dt.ar > nomod.ar);
+
+        let ss = CatchPP::new(&map).attributes(nomod).calculate();
+        assert_eq!(ss.state.n_fruits + ss.state.n_droplets, nomod.max_combo());
+
+        let partial = CatchPP::new(&map)
+            .attributes(ss)
+            .misses(2)
+            .accuracy(97.0)
+            .calculate();
+
+        assert_eq!(partial.state.n_misses, 2);
+        assert!((partial.state.accuracy() - 0.97).abs() < 0.01);
+        assert!(partial.pp < ss.pp);
+    }
+
+    #[test]
+    fn legacy_passed_objects() {
+        let map = Beatmap::from_path("./maps/2118524.osu").unwrap();
+
+        for &passed_objects in &[10, 50, 100, 200] {
+            let legacy = CatchStars::new(&map)
+                .passed_objects(passed_objects)
+                .calculate();
+            let current = crate::CatchStars::new(&map)
+                .passed_objects(passed_objects)
+                .calculate();
+
+            assert_eq!(legacy.max_combo(), passed_objects);
+            assert_eq!(legacy.n_tiny_droplets, current.n_tiny_droplets);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use crate::{catch::CatchScoreState, Beatmap, Mods};
+
+use super::{CatchDifficultyAttributes, CatchStars};
+
+/// Performance calculator on osu!catch maps using the formulas
+/// from before the 2020 rework.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, fruits_2020::CatchPP};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+///
+/// let pp_result = CatchPP::new(&map)
+///     .mods(8 + 64) // HDDT
+///     .combo(1234)
+///     .misses(1)
+///     .accuracy(98.5)
+///     .calculate();
+///
+/// println!("PP: {} | Stars: {}", pp_result.pp, pp_result.stars());
+/// ```
+#[derive(Clone, Debug)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct CatchPP<'map> {
+    map: &'map Beatmap,
+    attributes: Option<CatchDifficultyAttributes>,
+    mods: u32,
+    combo: Option<usize>,
+    acc: Option<f64>,
+    passed_objects: Option<usize>,
+    clock_rate: Option<f64>,
+
+    n_fruits: Option<usize>,
+    n_droplets: Option<usize>,
+    n_tiny_droplets: Option<usize>,
+    n_tiny_droplet_misses: Option<usize>,
+    n_misses: usize,
+}
+
+impl<'map> CatchPP<'map> {
+    /// Create a new performance calculator for osu!catch maps.
+    #[inline]
+    pub fn new(map: &'map Beatmap) -> Self {
+        Self {
+            map,
+            attributes: None,
+            mods: 0,
+            combo: None,
+            acc: None,
+            passed_objects: None,
+            clock_rate: None,
+            n_fruits: None,
+            n_droplets: None,
+            n_tiny_droplets: None,
+            n_tiny_droplet_misses: None,
+            n_misses: 0,
+        }
+    }
+
+    /// Provide the result of a previous difficulty or performance calculation.
+    /// If you already calculated the attributes for the current map-mod combination,
+    /// be sure to put them in here so that they don't have to be recalculated.
+    #[inline]
+    pub fn attributes(mut self, attributes: impl Into<CatchDifficultyAttributes>) -> Self {
+        self.attributes = Some(attributes.into());
+
+        self
+    }
+
+    /// Specify mods through their bit values.
+    ///
+    /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// Specify the max combo of the play.
+    #[inline]
+    pub fn combo(mut self, combo: usize) -> Self {
+        self.combo = Some(combo);
+
+        self
+    }
+
+    /// Specify the amount of fruits of a play i.e. n300.
+    #[inline]
+    pub fn fruits(mut self, n_fruits: usize) -> Self {
+        self.n_fruits = Some(n_fruits);
+
+        self
+    }
+
+    /// Specify the amount of droplets of a play i.e. n100.
+    #[inline]
+    pub fn droplets(mut self, n_droplets: usize) -> Self {
+        self.n_droplets = Some(n_droplets);
+
+        self
+    }
+
+    /// Specify the amount of tiny droplets of a play i.e. n50.
+    #[inline]
+    pub fn tiny_droplets(mut self, n_tiny_droplets: usize) -> Self {
+        self.n_tiny_droplets = Some(n_tiny_droplets);
+
+        self
+    }
+
+    /// Specify the amount of tiny droplet misses of a play i.e. n_katu.
+    #[inline]
+    pub fn tiny_droplet_misses(mut self, n_tiny_droplet_misses: usize) -> Self {
+        self.n_tiny_droplet_misses = Some(n_tiny_droplet_misses);
+
+        self
+    }
+
+    /// Specify the amount of fruit and droplet misses of the play.
+    #[inline]
+    pub fn misses(mut self, n_misses: usize) -> Self {
+        self.n_misses = n_misses;
+
+        self
+    }
+
+    /// Set the accuracy between `0.0` and `100.0`.
+    ///
+    /// The accuracy only determines the amount of tiny droplets
+    /// and is ignored if tiny droplets are specified.
+    #[inline]
+    pub fn accuracy(mut self, acc: f64) -> Self {
+        self.acc = Some(acc / 100.0);
+
+        self
+    }
+
+    /// Amount of passed objects for partial plays, e.g. a fail.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects = Some(passed_objects);
+
+        self
+    }
+
+    /// Adjust the clock rate used in the calculation.
+    /// If none is specified, it will take the clock rate based on the mods
+    /// i.e. 1.5 for DT, 0.75 for HT and 1.0 otherwise.
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: f64) -> Self {
+        self.clock_rate = Some(clock_rate);
+
+        self
+    }
+
+    /// Calculate all performance related values, including pp and stars.
+    pub fn calculate(self) -> CatchPerformanceAttributes {
+        let difficulty = self.attributes.unwrap_or_else(|| {
+            let mut calculator = CatchStars::new(self.map).mods(self.mods);
+
+            if let Some(passed_objects) = self.passed_objects {
+                calculator = calculator.passed_objects(passed_objects);
+            }
+
+            if let Some(clock_rate) = self.clock_rate {
+                calculator = calculator.clock_rate(clock_rate);
+            }
+
+            calculator.calculate()
+        });
+
+        let state = self.generate_hitresults(&difficulty);
+        let max_combo = difficulty.max_combo();
+
+        // Relying heavily on aim
+        let mut pp = (5.0 * (difficulty.stars / 0.0049).max(1.0) - 4.0).powi(2) / 100_000.0;
+
+        let combo_hits = match state.n_fruits + state.n_droplets + state.n_misses {
+            0 => max_combo,
+            combo_hits => combo_hits,
+        };
+
+        // Longer maps are worth more
+        let len_bonus = 0.95
+            + 0.3 * (combo_hits as f64 / 2500.0).min(1.0)
+            + (combo_hits > 2500) as u8 as f64 * (combo_hits as f64 / 2500.0).log10() * 0.475;
+
+        pp *= len_bonus;
+
+        // Penalize misses exponentially
+        pp *= 0.97_f64.powi(state.n_misses as i32);
+
+        // Combo scaling
+        if max_combo > 0 {
+            pp *= (state.max_combo as f64 / max_combo as f64)
+                .powf(0.8)
+                .min(1.0);
+        }
+
+        // AR scaling
+        let ar = difficulty.ar;
+
+        if ar > 9.0 {
+            pp *= 1.0 + 0.1 * (ar - 9.0);
+        } else if ar < 8.0 {
+            pp *= 1.0 + 0.025 * (8.0 - ar);
+        }
+
+        // HD bonus
+        if self.mods.hd() {
+            pp *= 1.05 + 0.075 * (10.0 - ar.min(10.0));
+        }
+
+        // FL bonus
+        if self.mods.fl() {
+            pp *= 1.35 * len_bonus;
+        }
+
+        // Accuracy scaling
+        pp *= state.accuracy().powf(5.5);
+
+        // NF penalty
+        if self.mods.nf() {
+            pp *= 0.9;
+        }
+
+        CatchPerformanceAttributes {
+            difficulty,
+            pp,
+            state,
+        }
+    }
+
+    fn generate_hitresults(&self, attributes: &CatchDifficultyAttributes) -> CatchScoreState {
+        let max_combo = attributes.max_combo();
+        let n_misses = self.n_misses.min(max_combo);
+
+        let n_droplets = self
+            .n_droplets
+            .unwrap_or_else(|| attributes.n_droplets.saturating_sub(n_misses))
+            .min(max_combo - n_misses);
+
+        let n_fruits = self
+            .n_fruits
+            .unwrap_or(max_combo - n_misses - n_droplets)
+            .min(max_combo - n_misses - n_droplets);
+
+        let max_tiny_droplets = attributes.n_tiny_droplets;
+
+        let n_tiny_droplets = match (self.n_tiny_droplets, self.n_tiny_droplet_misses, self.acc) {
+            (Some(n_tiny_droplets), ..) => n_tiny_droplets,
+            (None, Some(n_tiny_droplet_misses), _) => {
+                max_tiny_droplets.saturating_sub(n_tiny_droplet_misses)
+            }
+            (None, None, Some(acc)) => ((acc * (max_combo + max_tiny_droplets) as f64).round()
+                as usize)
+                .saturating_sub(n_fruits + n_droplets),
+            (None, None, None) => max_tiny_droplets,
+        }
+        .min(max_tiny_droplets);
+
+        CatchScoreState {
+            max_combo: self
+                .combo
+                .map_or(max_combo - n_misses, |combo| combo.min(max_combo)),
+            n_fruits,
+            n_droplets,
+            n_tiny_droplets,
+            n_tiny_droplet_misses: max_tiny_droplets - n_tiny_droplets,
+            n_misses,
+        }
+    }
+}
+
+/// The result of a performance calculation with the pre-2020 osu!catch formulas.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CatchPerformanceAttributes {
+    /// The difficulty attributes that were used for the performance calculation.
+    pub difficulty: CatchDifficultyAttributes,
+    /// The final performance points.
+    pub pp: f64,
+    /// The hitresults and combo that were used for the performance calculation.
+    pub state: CatchScoreState,
+}
+
+impl CatchPerformanceAttributes {
+    /// Return the star value.
+    #[inline]
+    pub fn stars(&self) -> f64 {
+        self.difficulty.stars
+    }
+
+    /// Return the maximum combo of the map.
+    #[inline]
+    pub fn max_combo(&self) -> usize {
+        self.difficulty.max_combo()
+    }
+}
+
+impl From<CatchPerformanceAttributes> for CatchDifficultyAttributes {
+    #[inline]
+    fn from(attributes: CatchPerformanceAttributes) -> Self {
+        attributes.difficulty
+    }
+}
@@ -0,0 +1,121 @@
+use crate::Mods;
+
+/// The grade of a score.
+///
+/// Silver grades are achieved with HD or FL.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Grade {
+    /// Silver SS
+    XH,
+    /// SS
+    X,
+    /// Silver S
+    SH,
+    /// S
+    S,
+    /// A
+    A,
+    /// B
+    B,
+    /// C
+    C,
+    /// D
+    D,
+}
+
+impl Grade {
+    /// Whether the grade is a silver grade.
+    #[inline]
+    pub fn is_silver(self) -> bool {
+        matches!(self, Self::XH | Self::SH)
+    }
+
+    /// Grades of osu!standard and osu!taiko are based on the ratio of 300s.
+    pub(crate) fn from_ratios(
+        n300: usize,
+        n50: usize,
+        n_misses: usize,
+        total_hits: usize,
+        mods: u32,
+    ) -> Self {
+        if total_hits == 0 {
+            return Self::D;
+        }
+
+        let ratio300 = n300 as f64 / total_hits as f64;
+        let ratio50 = n50 as f64 / total_hits as f64;
+
+        if n300 == total_hits {
+            Self::X.with_mods(mods)
+        } else if ratio300 > 0.9 && ratio50 < 0.01 && n_misses == 0 {
+            Self::S.with_mods(mods)
+        } else if (ratio300 > 0.8 && n_misses == 0) || ratio300 > 0.9 {
+            Self::A
+        } else if (ratio300 > 0.7 && n_misses == 0) || ratio300 > 0.8 {
+            Self::B
+        } else if ratio300 > 0.6 {
+            Self::C
+        } else {
+            Self::D
+        }
+    }
+
+    /// Grades of osu!catch and osu!mania are based on accuracy.
+    ///
+    /// The thresholds are the minimum accuracies for S, A, B, and C.
+    pub(crate) fn from_accuracy(acc: f64, thresholds: [f64; 4], mods: u32) -> Self {
+        let [s, a, b, c] = thresholds;
+
+        if (acc - 1.0).abs() <= f64::EPSILON {
+            Self::X.with_mods(mods)
+        } else if acc > s {
+            Self::S.with_mods(mods)
+        } else if acc > a {
+            Self::A
+        } else if acc > b {
+            Self::B
+        } else if acc > c {
+            Self::C
+        } else {
+            Self::D
+        }
+    }
+
+    fn with_mods(self, mods: u32) -> Self {
+        if !(mods.hd() || mods.fl()) {
+            return self;
+        }
+
+        match self {
+            Self::X => Self::XH,
+            Self::S => Self::SH,
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::osu::OsuScoreState;
+
+    #[test]
+    fn osu_grades() {
+        let mut state = OsuScoreState {
+            max_combo: 100,
+            n300: 100,
+            ..Default::default()
+        };
+
+        assert_eq!(state.grade(0), Grade::X);
+        assert_eq!(state.grade(8), Grade::XH);
+
+        state.n300 = 95;
+        state.n100 = 5;
+        assert_eq!(state.grade(0), Grade::S);
+
+        state.n100 = 4;
+        state.n_misses = 1;
+        assert_eq!(state.grade(0), Grade::A);
+    }
+}
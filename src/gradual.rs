@@ -3,7 +3,7 @@ use crate::{
     mania::{ManiaGradualDifficultyAttributes, ManiaGradualPerformanceAttributes, ManiaScoreState},
     osu::{OsuGradualDifficultyAttributes, OsuGradualPerformanceAttributes, OsuScoreState},
     taiko::{TaikoGradualDifficultyAttributes, TaikoGradualPerformanceAttributes, TaikoScoreState},
-    Beatmap, DifficultyAttributes, GameMode, PerformanceAttributes,
+    Beatmap, DifficultyAttributes, GameMode, Grade, PerformanceAttributes,
 };
 
 /// Gradually calculate the difficulty attributes on maps of any mode.
@@ -18,7 +18,7 @@ use crate::{
 /// # Example
 ///
 /// ```no_run
-/// use rosu_pp::{Beatmap, GradualDifficultyAttributes};
+/// use murasame_pp::{Beatmap, GradualDifficultyAttributes};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -52,13 +52,51 @@ pub enum GradualDifficultyAttributes<'map> {
 impl<'map> GradualDifficultyAttributes<'map> {
     /// Create a new gradual difficulty calculator for maps of any mode.
     pub fn new(map: &'map Beatmap, mods: u32) -> Self {
-        match map.mode {
+        Self::new_with_mode(map, mods, map.mode)
+    }
+
+    /// Create a new gradual difficulty calculator that converts osu!standard maps to the given mode.
+    ///
+    /// Maps of other modes can't be converted so their own mode is used instead.
+    pub fn new_with_mode(map: &'map Beatmap, mods: u32, mode: GameMode) -> Self {
+        let mode = if map.mode == GameMode::Osu {
+            mode
+        } else {
+            map.mode
+        };
+
+        match mode {
             GameMode::Osu => Self::Osu(OsuGradualDifficultyAttributes::new(map, mods)),
             GameMode::Taiko => Self::Taiko(TaikoGradualDifficultyAttributes::new(map, mods)),
             GameMode::Catch => Self::Catch(CatchGradualDifficultyAttributes::new(map, mods)),
             GameMode::Mania => Self::Mania(ManiaGradualDifficultyAttributes::new(map, mods)),
         }
     }
+
+    /// Additionally yield the change of the attributes caused by each hit object.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use murasame_pp::{Beatmap, GradualDifficultyAttributes};
+    ///
+    /// # /*
+    /// let map: Beatmap = ...
+    /// # */
+    /// # let map = Beatmap::default();
+    /// let gradual = GradualDifficultyAttributes::new(&map, 0).with_deltas();
+    ///
+    /// for (i, (_, delta)) in gradual.enumerate() {
+    ///     println!("Object {} added {:.4} stars", i, delta.stars);
+    /// }
+    /// ```
+    #[inline]
+    pub fn with_deltas(self) -> GradualDifficultyDeltas<'map> {
+        GradualDifficultyDeltas {
+            gradual: self,
+            prev: None,
+        }
+    }
 }
 
 impl Iterator for GradualDifficultyAttributes<'_> {
@@ -85,12 +123,99 @@ impl Iterator for GradualDifficultyAttributes<'_> {
     }
 }
 
+/// The change of difficulty attributes between two consecutive hit objects.
+///
+/// Skills that do not exist in the mode of the attributes are always `0.0`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DifficultyDelta {
+    /// The change in star rating.
+    pub stars: f64,
+    /// The change in the osu!standard aim rating.
+    pub aim: f64,
+    /// The change in the osu!standard speed rating.
+    pub speed: f64,
+    /// The change in the osu!standard flashlight rating.
+    pub flashlight: f64,
+    /// The change in the osu!taiko stamina rating.
+    pub stamina: f64,
+    /// The change in the osu!taiko rhythm rating.
+    pub rhythm: f64,
+    /// The change in the osu!taiko colour rating.
+    pub colour: f64,
+}
+
+impl DifficultyDelta {
+    fn new(prev: Option<&DifficultyAttributes>, curr: &DifficultyAttributes) -> Self {
+        let prev_stars = prev.map_or(0.0, DifficultyAttributes::stars);
+
+        let mut delta = Self {
+            stars: curr.stars() - prev_stars,
+            ..Default::default()
+        };
+
+        match (prev, curr) {
+            (prev, DifficultyAttributes::Osu(curr)) => {
+                let prev = match prev {
+                    Some(DifficultyAttributes::Osu(prev)) => *prev,
+                    _ => Default::default(),
+                };
+
+                delta.aim = curr.aim - prev.aim;
+                delta.speed = curr.speed - prev.speed;
+                delta.flashlight = curr.flashlight - prev.flashlight;
+            }
+            (prev, DifficultyAttributes::Taiko(curr)) => {
+                let prev = match prev {
+                    Some(DifficultyAttributes::Taiko(prev)) => *prev,
+                    _ => Default::default(),
+                };
+
+                delta.stamina = curr.stamina - prev.stamina;
+                delta.rhythm = curr.rhythm - prev.rhythm;
+                delta.colour = curr.colour - prev.colour;
+            }
+            (_, DifficultyAttributes::Catch(_) | DifficultyAttributes::Mania(_)) => {}
+        }
+
+        delta
+    }
+}
+
+/// Gradually calculate the difficulty attributes and their change per hit object.
+///
+/// Created through [`GradualDifficultyAttributes::with_deltas`].
+/// The delta of the first hit object is relative to empty attributes.
+#[derive(Debug)]
+pub struct GradualDifficultyDeltas<'map> {
+    gradual: GradualDifficultyAttributes<'map>,
+    prev: Option<DifficultyAttributes>,
+}
+
+impl Iterator for GradualDifficultyDeltas<'_> {
+    type Item = (DifficultyAttributes, DifficultyDelta);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let curr = self.gradual.next()?;
+        let delta = DifficultyDelta::new(self.prev.as_ref(), &curr);
+        self.prev = Some(curr);
+
+        Some((curr, delta))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.gradual.size_hint()
+    }
+}
+
 /// Aggregation for a score's current state i.e. what is
 /// the maximum combo so far, what are the current
 /// hitresults and what is the current score.
 ///
 /// This struct is used for [`GradualPerformanceAttributes`].
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScoreState {
     /// Maximum combo that the score has had so far.
     /// **Not** the maximum possible combo of the map so far.
@@ -118,6 +243,28 @@ impl ScoreState {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Return the accuracy between `0.0` and `1.0` of the hitresults in the given mode.
+    #[inline]
+    pub fn accuracy(&self, mode: GameMode) -> f64 {
+        match mode {
+            GameMode::Osu => OsuScoreState::from(*self).accuracy(),
+            GameMode::Taiko => TaikoScoreState::from(*self).accuracy(),
+            GameMode::Catch => CatchScoreState::from(*self).accuracy(),
+            GameMode::Mania => ManiaScoreState::from(*self).accuracy(),
+        }
+    }
+
+    /// Return the grade of the hitresults in the given mode.
+    #[inline]
+    pub fn grade(&self, mode: GameMode, mods: u32) -> Grade {
+        match mode {
+            GameMode::Osu => OsuScoreState::from(*self).grade(mods),
+            GameMode::Taiko => TaikoScoreState::from(*self).grade(mods),
+            GameMode::Catch => CatchScoreState::from(*self).grade(mods),
+            GameMode::Mania => ManiaScoreState::from(*self).grade(mods),
+        }
+    }
 }
 
 impl From<ScoreState> for OsuScoreState {
@@ -173,6 +320,63 @@ impl From<ScoreState> for ManiaScoreState {
     }
 }
 
+impl From<OsuScoreState> for ScoreState {
+    #[inline]
+    fn from(state: OsuScoreState) -> Self {
+        Self {
+            max_combo: state.max_combo,
+            n300: state.n300,
+            n100: state.n100,
+            n50: state.n50,
+            n_misses: state.n_misses,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<TaikoScoreState> for ScoreState {
+    #[inline]
+    fn from(state: TaikoScoreState) -> Self {
+        Self {
+            max_combo: state.max_combo,
+            n300: state.n300,
+            n100: state.n100,
+            n_misses: state.n_misses,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<CatchScoreState> for ScoreState {
+    #[inline]
+    fn from(state: CatchScoreState) -> Self {
+        Self {
+            max_combo: state.max_combo,
+            n300: state.n_fruits,
+            n100: state.n_droplets,
+            n50: state.n_tiny_droplets,
+            n_katu: state.n_tiny_droplet_misses,
+            n_misses: state.n_misses,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<ManiaScoreState> for ScoreState {
+    #[inline]
+    fn from(state: ManiaScoreState) -> Self {
+        Self {
+            n_geki: state.n320,
+            n300: state.n300,
+            n_katu: state.n200,
+            n100: state.n100,
+            n50: state.n50,
+            n_misses: state.n_misses,
+            ..Default::default()
+        }
+    }
+}
+
 /// Gradually calculate the performance attributes on maps of any mode.
 ///
 /// After each hit object you can call
@@ -199,7 +403,7 @@ impl From<ScoreState> for ManiaScoreState {
 /// # Example
 ///
 /// ```no_run
-/// use rosu_pp::{Beatmap, GradualPerformanceAttributes, ScoreState};
+/// use murasame_pp::{Beatmap, GradualPerformanceAttributes, ScoreState};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -283,7 +487,20 @@ pub enum GradualPerformanceAttributes<'map> {
 impl<'map> GradualPerformanceAttributes<'map> {
     /// Create a new gradual performance calculator for maps of any mode.
     pub fn new(map: &'map Beatmap, mods: u32) -> Self {
-        match map.mode {
+        Self::new_with_mode(map, mods, map.mode)
+    }
+
+    /// Create a new gradual performance calculator that converts osu!standard maps to the given mode.
+    ///
+    /// Maps of other modes can't be converted so their own mode is used instead.
+    pub fn new_with_mode(map: &'map Beatmap, mods: u32, mode: GameMode) -> Self {
+        let mode = if map.mode == GameMode::Osu {
+            mode
+        } else {
+            map.mode
+        };
+
+        match mode {
             GameMode::Osu => Self::Osu(OsuGradualPerformanceAttributes::new(map, mods)),
             GameMode::Taiko => Self::Taiko(TaikoGradualPerformanceAttributes::new(map, mods)),
             GameMode::Catch => Self::Catch(CatchGradualPerformanceAttributes::new(map, mods)),
@@ -324,3 +541,59 @@ impl<'map> GradualPerformanceAttributes<'map> {
         }
     }
 }
+
+/// The inputs and result of the previous step of a gradual performance calculation.
+///
+/// If neither the difficulty attributes nor the score state changed since the previous
+/// step, the previous result can be reused. This only happens in osu!taiko where the
+/// initial difficulty attributes already include the first hits.
+#[derive(Clone, Debug)]
+pub(crate) struct PerformanceCache<D, S, P> {
+    last: Option<(D, S, P)>,
+}
+
+impl<D: PartialEq, S: PartialEq, P: Copy> PerformanceCache<D, S, P> {
+    pub(crate) fn new() -> Self {
+        Self { last: None }
+    }
+
+    pub(crate) fn get(&self, difficulty: &D, state: &S) -> Option<P> {
+        match self.last {
+            Some((ref d, ref s, performance)) if d == difficulty && s == state => Some(performance),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn difficulty(&self) -> Option<&D> {
+        self.last.as_ref().map(|(difficulty, ..)| difficulty)
+    }
+
+    pub(crate) fn insert(&mut self, difficulty: D, state: S, performance: P) {
+        self.last = Some((difficulty, state, performance));
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AnyStars;
+
+    #[test]
+    fn gradual_with_mode_converts() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let expected = AnyStars::new(&map).mode(GameMode::Taiko).calculate();
+        let last = GradualDifficultyAttributes::new_with_mode(&map, 0, GameMode::Taiko)
+            .last()
+            .unwrap();
+
+        assert!(matches!(last, DifficultyAttributes::Taiko(_)));
+        assert!((last.stars() - expected.stars()).abs() < 1e-6);
+
+        let mut gradual = GradualPerformanceAttributes::new_with_mode(&map, 0, GameMode::Mania);
+        let attrs = gradual.process_next_object(ScoreState::new()).unwrap();
+
+        assert!(matches!(attrs, PerformanceAttributes::Mania(_)));
+    }
+}
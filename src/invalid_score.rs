@@ -0,0 +1,183 @@
+use std::{error::Error as StdError, fmt};
+
+/// An inconsistency between a score and the map it was set on.
+///
+/// Returned by the `try_calculate` methods of the performance calculators.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum InvalidScore {
+    /// The accuracy was not between `0.0` and `100.0`.
+    Accuracy(f64),
+    /// The combo is larger than the map's maximum combo.
+    Combo {
+        /// The specified combo.
+        combo: usize,
+        /// The maximum combo of the map.
+        max_combo: usize,
+    },
+    /// The amount of a single hitresult is larger than the amount of objects that can provide it.
+    HitResult {
+        /// The name of the inconsistent field.
+        field: &'static str,
+        /// The specified amount.
+        count: usize,
+        /// The maximum possible amount.
+        max: usize,
+    },
+    /// All hitresults combined are more than the amount of objects.
+    TotalHitResults {
+        /// The sum of all specified hitresults.
+        total: usize,
+        /// The amount of objects.
+        n_objects: usize,
+    },
+    /// The amount of passed objects is larger than the amount of objects in the map.
+    PassedObjects {
+        /// The specified amount of passed objects.
+        passed_objects: usize,
+        /// The amount of objects in the map.
+        n_objects: usize,
+    },
+}
+
+impl fmt::Display for InvalidScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Accuracy(acc) => write!(f, "accuracy {} is not between 0 and 100", acc),
+            Self::Combo { combo, max_combo } => {
+                write!(f, "combo {} exceeds the max combo {}", combo, max_combo)
+            }
+            Self::HitResult { field, count, max } => {
+                write!(f, "`{}` is {} but can be at most {}", field, count, max)
+            }
+            Self::TotalHitResults { total, n_objects } => write!(
+                f,
+                "hitresults add up to {} but there are only {} objects",
+                total, n_objects
+            ),
+            Self::PassedObjects {
+                passed_objects,
+                n_objects,
+            } => write!(
+                f,
+                "{} passed objects but the map only has {} objects",
+                passed_objects, n_objects
+            ),
+        }
+    }
+}
+
+impl StdError for InvalidScore {}
+
+/// Check that the accuracy, given between `0.0` and `1.0`, is in range.
+pub(crate) fn check_accuracy(acc: Option<f64>) -> Result<(), InvalidScore> {
+    match acc {
+        Some(acc) if !(0.0..=1.0).contains(&acc) => Err(InvalidScore::Accuracy(acc * 100.0)),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn check_combo(combo: Option<usize>, max_combo: usize) -> Result<(), InvalidScore> {
+    match combo {
+        Some(combo) if combo > max_combo => Err(InvalidScore::Combo { combo, max_combo }),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn check_passed_objects(
+    passed_objects: Option<usize>,
+    n_objects: usize,
+) -> Result<(), InvalidScore> {
+    match passed_objects {
+        Some(passed_objects) if passed_objects > n_objects => Err(InvalidScore::PassedObjects {
+            passed_objects,
+            n_objects,
+        }),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn check_hitresult(
+    field: &'static str,
+    count: Option<usize>,
+    max: usize,
+) -> Result<(), InvalidScore> {
+    match count {
+        Some(count) if count > max => Err(InvalidScore::HitResult { field, count, max }),
+        _ => Ok(()),
+    }
+}
+
+/// Check each hitresult individually and then their sum against the amount of objects.
+pub(crate) fn check_hitresults(
+    hitresults: &[(&'static str, Option<usize>)],
+    n_objects: usize,
+) -> Result<(), InvalidScore> {
+    for (field, count) in hitresults {
+        check_hitresult(field, *count, n_objects)?;
+    }
+
+    let total = hitresults.iter().filter_map(|(_, count)| *count).sum();
+
+    if total > n_objects {
+        return Err(InvalidScore::TotalHitResults { total, n_objects });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Beatmap, CatchPP, ManiaPP, OsuPP};
+
+    #[test]
+    fn combo_above_max_combo() {
+        let map = Beatmap::default();
+        let err = OsuPP::new(&map).combo(5).try_calculate().unwrap_err();
+
+        assert_eq!(
+            err,
+            InvalidScore::Combo {
+                combo: 5,
+                max_combo: 0
+            }
+        );
+    }
+
+    #[test]
+    fn accuracy_out_of_range() {
+        let map = Beatmap::default();
+        let err = ManiaPP::new(&map)
+            .accuracy(101.0)
+            .try_calculate()
+            .unwrap_err();
+
+        assert!(matches!(err, InvalidScore::Accuracy(_)));
+    }
+
+    #[test]
+    fn catch_accuracy_out_of_range() {
+        let map = Beatmap::default();
+        let err = CatchPP::new(&map)
+            .accuracy(-5.0)
+            .try_calculate()
+            .unwrap_err();
+
+        assert!(matches!(err, InvalidScore::Accuracy(_)));
+    }
+
+    #[test]
+    fn too_many_hitresults() {
+        let map = Beatmap::default();
+        let err = OsuPP::new(&map).n300(1).try_calculate().unwrap_err();
+
+        assert_eq!(
+            err,
+            InvalidScore::HitResult {
+                field: "n300",
+                count: 1,
+                max: 0
+            }
+        );
+    }
+}
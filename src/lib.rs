@@ -5,7 +5,7 @@
 //! ## Usage
 //!
 //! ```no_run
-//! use rosu_pp::{Beatmap, BeatmapExt};
+//! use murasame_pp::{Beatmap, BeatmapExt};
 //!
 //! # /*
 //! // Parse the map yourself
@@ -53,7 +53,7 @@
 //! If either the `async_tokio` or `async_std` feature is enabled, beatmap parsing will be async.
 //!
 //! ```no_run
-//! use rosu_pp::{Beatmap, BeatmapExt};
+//! use murasame_pp::{Beatmap, BeatmapExt};
 //!
 //! # /*
 //! // Parse the map asynchronously
@@ -82,7 +82,7 @@
 //! Instead, you should use [`GradualDifficultyAttributes`] and [`GradualPerformanceAttributes`]:
 //!
 //! ```no_run
-//! use rosu_pp::{
+//! use murasame_pp::{
 //!     Beatmap, BeatmapExt, GradualPerformanceAttributes, ScoreState,
 //!     taiko::TaikoScoreState,
 //! };
@@ -166,6 +166,11 @@
 //! | `default` | Beatmap parsing will be non-async |
 //! | `async_tokio` | Beatmap parsing will be async through [tokio](https://github.com/tokio-rs/tokio) |
 //! | `async_std` | Beatmap parsing will be async through [async-std](https://github.com/async-rs/async-std) |
+//! | `export` | Enables the [`export`] module to write per-object difficulty features of osu!standard maps as CSV |
+//! | `embed` | Enables the [`embed`] module to turn osu!api scores into display-ready data |
+//! | `profiling` | Enables the [`profiling`] module to measure time spent parsing, converting, and calculating |
+//! | `rhythm` | Calculates the experimental `rhythm_rating` of [`OsuDifficultyAttributes`](osu::OsuDifficultyAttributes) |
+//! | `serde` | Implements `Serialize` and `Deserialize` of [serde](https://github.com/serde-rs/serde) for all attributes, strains, and score states, and adds [`ApiRounded`] to serialize attributes with osu!api precision |
 //!
 
 #![cfg_attr(docsrs, feature(doc_cfg), deny(broken_intra_doc_links))]
@@ -178,6 +183,15 @@
     missing_debug_implementations
 )]
 
+/// Start timing a calculation phase until the end of the current scope
+/// if the `profiling` feature is enabled.
+macro_rules! profile {
+    ($phase:ident) => {
+        #[cfg(feature = "profiling")]
+        let _timer = crate::profiling::Timer::start(crate::profiling::Phase::$phase);
+    };
+}
+
 /// Everything about osu!catch.
 pub mod catch;
 
@@ -190,22 +204,92 @@ pub mod osu;
 /// Everything about osu!taiko.
 pub mod taiko;
 
+/// The single-strain osu!taiko difficulty and pp calculation from before the 2020 rework.
+///
+/// Meant for servers whose osu!taiko leaderboards still use the old system.
+/// The calculators can be used alongside the ones of [`taiko`] so the system
+/// can be chosen at runtime.
+pub mod taiko_2020;
+
+/// The osu!catch difficulty and pp calculation from before the 2020 rework.
+///
+/// Meant for servers whose osu!catch leaderboards still use the old system.
+/// The calculators can be used alongside the ones of [`catch`] so the system
+/// can be chosen at runtime.
+pub mod fruits_2020;
+
+/// The score-based osu!mania pp calculation from before the 2022 rework.
+///
+/// Meant for servers whose osu!mania leaderboards still use the old system.
+/// The calculators can be used alongside the ones of [`mania`] so the system
+/// can be chosen at runtime.
+pub mod mania_2020;
+
 /// Beatmap parsing
 pub mod parse;
 
+/// Analyzing beatmaps beyond difficulty and performance
+pub mod analysis;
+
+/// Verifying submitted scores
+pub mod verify;
+
+/// Legacy ScoreV1 total score
+pub mod score;
+
+/// Processing submitted scores
+pub mod submission;
+
+/// Calculating attributes of many maps at once
+pub mod batch;
+
+/// Exporting difficulty features of osu!standard maps as CSV datasets
+#[cfg(feature = "export")]
+pub mod export;
+
+/// Turning osu!api scores into display-ready data
+#[cfg(feature = "embed")]
+pub mod embed;
+
+/// Timing the phases of calculations
+#[cfg(feature = "profiling")]
+pub mod profiling;
+
 /// Beatmap and contained types
 pub mod beatmap;
 pub use beatmap::{Beatmap, GameMode};
 
 mod gradual;
-pub use gradual::{GradualDifficultyAttributes, GradualPerformanceAttributes, ScoreState};
+pub use gradual::{
+    DifficultyDelta, GradualDifficultyAttributes, GradualDifficultyDeltas,
+    GradualPerformanceAttributes, ScoreState,
+};
+
+mod grade;
+pub use grade::Grade;
+
+mod invalid_score;
+pub use invalid_score::InvalidScore;
 
 mod pp;
-pub use pp::{AnyPP, AttributeProvider, HitResultPriority};
+pub use pp::{AnyPP, AttributeProvider, HitResultPriority, ModeMismatch};
 
 mod stars;
 pub use stars::AnyStars;
 
+mod codec;
+pub use codec::DecodeError;
+
+#[cfg(feature = "serde")]
+mod api_rounded;
+#[cfg(feature = "serde")]
+pub use api_rounded::ApiRounded;
+
+mod diff;
+pub use diff::{PerformanceDiff, PpComponent, ValueDiff};
+
+mod display;
+
 mod curve;
 mod mods;
 mod util;
@@ -215,9 +299,15 @@ pub use mania::{ManiaPP, ManiaStars};
 pub use osu::{OsuPP, OsuStars};
 pub use taiko::{TaikoPP, TaikoStars};
 
-pub use mods::Mods;
+pub use mods::{CustomMod, Mods};
 pub use parse::{ParseError, ParseResult};
-pub use util::SortedVec;
+pub use util::{SortedVec, StrainAggregation};
+
+/// Version of the difficulty calculation, stored in the `version` field of all difficulty attributes.
+///
+/// Increased whenever a change causes different attributes for the same map and mods
+/// so that cached attributes of an older version can be recalculated.
+pub const ATTRIBUTES_VERSION: u32 = 1;
 
 /// Provides some additional methods on [`Beatmap`].
 pub trait BeatmapExt {
@@ -227,6 +317,8 @@ pub trait BeatmapExt {
     /// Calculate the max pp of a beatmap.
     ///
     /// If you seek more fine-tuning you can use the [`pp`](BeatmapExt::pp) method.
+    /// To get the max pp alongside the pp of a score, use
+    /// [`AnyPP::calculate_with_max`] so that the difficulty is only calculated once.
     fn max_pp(&self, mods: u32) -> PerformanceAttributes;
 
     /// Returns a builder for performance calculation.
@@ -308,7 +400,8 @@ impl BeatmapExt for Beatmap {
 
 /// The result of calculating the strains on a map.
 /// Suitable to plot the difficulty of a map over time.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Strains {
     /// osu!standard strain values.
     Osu(osu::OsuStrains),
@@ -332,6 +425,17 @@ impl Strains {
         }
     }
 
+    /// Start time in ms of the first strain peak's section, adjusted by the clock rate.
+    #[inline]
+    pub fn start_time(&self) -> f64 {
+        match self {
+            Strains::Osu(strains) => strains.start_time,
+            Strains::Taiko(strains) => strains.start_time,
+            Strains::Catch(strains) => strains.start_time,
+            Strains::Mania(strains) => strains.start_time,
+        }
+    }
+
     /// Returns the number of strain peaks per skill.
     #[inline]
     #[allow(clippy::len_without_is_empty)]
@@ -343,10 +447,33 @@ impl Strains {
             Strains::Mania(strains) => strains.len(),
         }
     }
+
+    /// Merge the strain peaks into exactly `n_buckets` points, e.g. for a fixed-width graph.
+    ///
+    /// See [`Strains::timestamps`] for the start time of each point.
+    #[inline]
+    pub fn downsample(&self, n_buckets: usize, aggregation: StrainAggregation) -> Self {
+        match self {
+            Strains::Osu(strains) => Strains::Osu(strains.downsample(n_buckets, aggregation)),
+            Strains::Taiko(strains) => Strains::Taiko(strains.downsample(n_buckets, aggregation)),
+            Strains::Catch(strains) => Strains::Catch(strains.downsample(n_buckets, aggregation)),
+            Strains::Mania(strains) => Strains::Mania(strains.downsample(n_buckets, aggregation)),
+        }
+    }
+
+    /// The start time in ms of each strain peak, adjusted by the clock rate.
+    #[inline]
+    pub fn timestamps(&self) -> impl Iterator<Item = f64> {
+        let start_time = self.start_time();
+        let section_len = self.section_len();
+
+        (0..self.len()).map(move |i| start_time + i as f64 * section_len)
+    }
 }
 
 /// The result of a difficulty calculation based on the mode.
-#[derive(Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DifficultyAttributes {
     /// osu!standard difficulty calculation result.
     Osu(osu::OsuDifficultyAttributes),
@@ -359,6 +486,17 @@ pub enum DifficultyAttributes {
 }
 
 impl DifficultyAttributes {
+    /// The mode of the attributes.
+    #[inline]
+    pub fn mode(&self) -> GameMode {
+        match self {
+            Self::Osu(_) => GameMode::Osu,
+            Self::Taiko(_) => GameMode::Taiko,
+            Self::Catch(_) => GameMode::Catch,
+            Self::Mania(_) => GameMode::Mania,
+        }
+    }
+
     /// The star value.
     #[inline]
     pub fn stars(&self) -> f64 {
@@ -380,6 +518,49 @@ impl DifficultyAttributes {
             Self::Mania(attrs) => attrs.max_combo,
         }
     }
+
+    /// The amount of objects that receive a hitresult.
+    ///
+    /// See the `n_objects` method of the mode's difficulty attributes for which objects count.
+    #[inline]
+    pub fn n_objects(&self) -> usize {
+        match self {
+            Self::Osu(attrs) => attrs.n_objects(),
+            Self::Taiko(attrs) => attrs.n_objects(),
+            Self::Catch(attrs) => attrs.n_objects(),
+            Self::Mania(attrs) => attrs.n_objects(),
+        }
+    }
+
+    /// The [`ATTRIBUTES_VERSION`] the attributes were calculated with.
+    #[inline]
+    pub fn version(&self) -> u32 {
+        match self {
+            Self::Osu(attrs) => attrs.version,
+            Self::Taiko(attrs) => attrs.version,
+            Self::Catch(attrs) => attrs.version,
+            Self::Mania(attrs) => attrs.version,
+        }
+    }
+
+    /// Whether the attributes were calculated with the current [`ATTRIBUTES_VERSION`]
+    /// i.e. cached attributes don't need to be recalculated.
+    #[inline]
+    pub fn is_current(&self) -> bool {
+        self.version() == ATTRIBUTES_VERSION
+    }
+
+    /// Round all floating point values to the precision of the osu!api
+    /// so that they can be compared to or stored alongside official values.
+    #[inline]
+    pub fn api_rounded(self) -> Self {
+        match self {
+            Self::Osu(attrs) => Self::Osu(attrs.api_rounded()),
+            Self::Taiko(attrs) => Self::Taiko(attrs.api_rounded()),
+            Self::Catch(attrs) => Self::Catch(attrs.api_rounded()),
+            Self::Mania(attrs) => Self::Mania(attrs.api_rounded()),
+        }
+    }
 }
 
 impl From<osu::OsuDifficultyAttributes> for DifficultyAttributes {
@@ -411,7 +592,8 @@ impl From<mania::ManiaDifficultyAttributes> for DifficultyAttributes {
 }
 
 /// The result of a performance calculation based on the mode.
-#[derive(Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PerformanceAttributes {
     /// osu!standard performance calculation result.
     Osu(osu::OsuPerformanceAttributes),
@@ -424,6 +606,17 @@ pub enum PerformanceAttributes {
 }
 
 impl PerformanceAttributes {
+    /// The mode of the attributes.
+    #[inline]
+    pub fn mode(&self) -> GameMode {
+        match self {
+            Self::Osu(_) => GameMode::Osu,
+            Self::Taiko(_) => GameMode::Taiko,
+            Self::Catch(_) => GameMode::Catch,
+            Self::Mania(_) => GameMode::Mania,
+        }
+    }
+
     /// The pp value.
     #[inline]
     pub fn pp(&self) -> f64 {
@@ -450,9 +643,9 @@ impl PerformanceAttributes {
     #[inline]
     pub fn difficulty_attributes(&self) -> DifficultyAttributes {
         match self {
-            Self::Osu(attrs) => DifficultyAttributes::Osu(attrs.difficulty.clone()),
-            Self::Taiko(attrs) => DifficultyAttributes::Taiko(attrs.difficulty.clone()),
-            Self::Catch(attrs) => DifficultyAttributes::Catch(attrs.difficulty.clone()),
+            Self::Osu(attrs) => DifficultyAttributes::Osu(attrs.difficulty),
+            Self::Taiko(attrs) => DifficultyAttributes::Taiko(attrs.difficulty),
+            Self::Catch(attrs) => DifficultyAttributes::Catch(attrs.difficulty),
             Self::Mania(attrs) => DifficultyAttributes::Mania(attrs.difficulty),
         }
     }
@@ -467,6 +660,32 @@ impl PerformanceAttributes {
             Self::Mania(attrs) => attrs.difficulty.max_combo,
         }
     }
+
+    /// The hitresults and combo that were used for the performance calculation.
+    ///
+    /// Passing this state alongside the attributes to a performance calculator
+    /// reproduces the same result.
+    #[inline]
+    pub fn state(&self) -> ScoreState {
+        match self {
+            Self::Osu(attrs) => attrs.state.into(),
+            Self::Taiko(attrs) => attrs.state.into(),
+            Self::Catch(attrs) => attrs.state.into(),
+            Self::Mania(attrs) => attrs.state.into(),
+        }
+    }
+
+    /// Round all floating point values to the precision of the osu!api
+    /// so that they can be compared to or stored alongside official values.
+    #[inline]
+    pub fn api_rounded(self) -> Self {
+        match self {
+            Self::Osu(attrs) => Self::Osu(attrs.api_rounded()),
+            Self::Taiko(attrs) => Self::Taiko(attrs.api_rounded()),
+            Self::Catch(attrs) => Self::Catch(attrs.api_rounded()),
+            Self::Mania(attrs) => Self::Mania(attrs.api_rounded()),
+        }
+    }
 }
 
 impl From<PerformanceAttributes> for DifficultyAttributes {
@@ -1,10 +1,11 @@
 use std::borrow::Cow;
 
 use crate::{
-    beatmap::BeatmapHitWindows,
+    beatmap::DifficultySettings,
+    mods,
     parse::{HitObject, HitObjectKind},
     util::FloatExt,
-    Beatmap, GameMode, Mods,
+    Beatmap, Mods, ATTRIBUTES_VERSION,
 };
 
 use super::{
@@ -26,7 +27,7 @@ use super::{
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{Beatmap, mania::ManiaGradualDifficultyAttributes};
+/// use murasame_pp::{Beatmap, mania::ManiaGradualDifficultyAttributes};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -48,27 +49,32 @@ use super::{
 pub struct ManiaGradualDifficultyAttributes<'map> {
     pub(crate) idx: usize,
     map: Cow<'map, Beatmap>,
-    hit_window: f64,
+    settings: DifficultySettings,
     strain: Strain,
     diff_objects: Vec<ManiaDifficultyObject>,
     curr_combo: usize,
+    n_hold_notes: usize,
+    hold_duration: f64,
     clock_rate: f64,
+    n_keys: usize,
+    is_convert: bool,
 }
 
 impl<'map> ManiaGradualDifficultyAttributes<'map> {
     /// Create a new difficulty attributes iterator for osu!mania maps.
     pub fn new(map: &'map Beatmap, mods: u32) -> Self {
-        let map = map.convert_mode(GameMode::Mania);
+        let map = map.convert_to_mania_with_keys(mods::key_count(mods));
         let total_columns = map.cs.round_even().max(1.0);
         let clock_rate = mods.clock_rate();
         let strain = Strain::new(total_columns as usize);
+        let is_convert = matches!(map, Cow::Owned(_));
 
-        let BeatmapHitWindows { od: hit_window, .. } = map
+        let settings = map
             .attributes()
             .mods(mods)
-            .converted(matches!(map, Cow::Owned(_)))
+            .converted(is_convert)
             .clock_rate(clock_rate)
-            .hit_windows();
+            .settings();
 
         let mut params = ObjectParameters::new(map.as_ref());
         let mut hit_objects = map.hit_objects.iter();
@@ -79,16 +85,22 @@ impl<'map> ManiaGradualDifficultyAttributes<'map> {
                 return Self {
                     idx: 0,
                     map,
-                    hit_window,
+                    settings,
                     strain,
                     diff_objects: Vec::new(),
                     curr_combo: 0,
+                    n_hold_notes: 0,
+                    hold_duration: 0.0,
                     clock_rate,
+                    n_keys: total_columns as usize,
+                    is_convert,
                 }
             }
         };
 
         let curr_combo = params.max_combo;
+        let n_hold_notes = usize::from(!map.hit_objects[0].is_circle());
+        let hold_duration = Self::hold_duration(&map.hit_objects[0], clock_rate);
 
         let diff_objects_iter = hit_objects.enumerate().scan(first, |last, (i, h)| {
             let base = ManiaObject::new(h, total_columns, &mut params);
@@ -104,14 +116,22 @@ impl<'map> ManiaGradualDifficultyAttributes<'map> {
         Self {
             idx: 0,
             map,
-            hit_window,
+            settings,
             strain,
             diff_objects,
             curr_combo,
+            n_hold_notes,
+            hold_duration,
             clock_rate,
+            n_keys: total_columns as usize,
+            is_convert,
         }
     }
 
+    fn hold_duration(h: &HitObject, clock_rate: f64) -> f64 {
+        (h.end_time() - h.start_time) / clock_rate
+    }
+
     fn increment_combo(
         h: &HitObject,
         diff_obj: &ManiaDifficultyObject,
@@ -140,14 +160,23 @@ impl Iterator for ManiaGradualDifficultyAttributes<'_> {
 
         if let Some(h) = self.map.hit_objects.get(self.idx) {
             Self::increment_combo(h, curr, &mut self.curr_combo, self.clock_rate);
+            self.n_hold_notes += usize::from(!h.is_circle());
+            self.hold_duration += Self::hold_duration(h, self.clock_rate);
         }
 
         self.strain.process(curr, &self.diff_objects);
 
         Some(ManiaDifficultyAttributes {
             stars: self.strain.clone().difficulty_value() * STAR_SCALING_FACTOR,
-            hit_window: self.hit_window,
+            hit_window: self.settings.great_hit_window,
             max_combo: self.curr_combo,
+            n_objects: self.idx + 1,
+            n_hold_notes: self.n_hold_notes,
+            hold_duration: self.hold_duration,
+            n_keys: self.n_keys,
+            is_convert: self.is_convert,
+            settings: self.settings,
+            version: ATTRIBUTES_VERSION,
         })
     }
 
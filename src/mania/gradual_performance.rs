@@ -1,4 +1,4 @@
-use crate::{Beatmap, ManiaPP};
+use crate::{Beatmap, Grade, ManiaPP};
 
 use super::{ManiaGradualDifficultyAttributes, ManiaPerformanceAttributes};
 
@@ -6,7 +6,8 @@ use super::{ManiaGradualDifficultyAttributes, ManiaPerformanceAttributes};
 /// i.e. what are the current hitresults.
 ///
 /// This struct is used for [`ManiaGradualPerformanceAttributes`].
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ManiaScoreState {
     /// Amount of current 320s.
     pub n320: usize,
@@ -49,6 +50,12 @@ impl ManiaScoreState {
 
         numerator as f64 / denominator as f64
     }
+
+    /// Determine the grade of this state for the given mods.
+    #[inline]
+    pub fn grade(&self, mods: u32) -> Grade {
+        Grade::from_accuracy(self.accuracy(), [0.95, 0.9, 0.8, 0.7], mods)
+    }
 }
 
 /// Gradually calculate the performance attributes of an osu!mania map.
@@ -68,7 +75,7 @@ impl ManiaScoreState {
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{Beatmap, mania::{ManiaGradualPerformanceAttributes, ManiaScoreState}};
+/// use murasame_pp::{Beatmap, mania::{ManiaGradualPerformanceAttributes, ManiaScoreState}};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -133,7 +140,10 @@ impl<'map> ManiaGradualPerformanceAttributes<'map> {
     /// Create a new gradual performance calculator for osu!mania maps.
     pub fn new(map: &'map Beatmap, mods: u32) -> Self {
         let difficulty = ManiaGradualDifficultyAttributes::new(map, mods);
-        let performance = ManiaPP::new(map).mods(mods).passed_objects(0);
+        let mut performance = ManiaPP::new(map).mods(mods).passed_objects(0);
+
+        // Convert once upfront instead of on every processed object
+        performance.convert_with_keys();
 
         Self {
             difficulty,
@@ -163,10 +173,9 @@ impl<'map> ManiaGradualPerformanceAttributes<'map> {
     ) -> Option<ManiaPerformanceAttributes> {
         let sub = (self.difficulty.idx == 0) as usize;
         let difficulty = self.difficulty.nth(n.saturating_sub(sub))?;
-
         let performance = self
             .performance
-            .clone()
+            .borrowed()
             .attributes(difficulty)
             .state(state)
             .passed_objects(self.difficulty.idx)
@@ -2,14 +2,20 @@ mod difficulty_object;
 mod gradual_difficulty;
 mod gradual_performance;
 mod mania_object;
+mod patterns;
 mod pp;
 mod skills;
 
 use std::borrow::Cow;
 
-use crate::{beatmap::BeatmapHitWindows, util::FloatExt, Beatmap, GameMode, Mods, OsuStars};
+use crate::{
+    beatmap::{DifficultyOverrides, DifficultySettings},
+    mods,
+    util::{downsample, first_section_start, round_api, FloatExt},
+    Beatmap, GameMode, OsuStars, StrainAggregation, ATTRIBUTES_VERSION,
+};
 
-pub use self::{gradual_difficulty::*, gradual_performance::*, pp::*};
+pub use self::{gradual_difficulty::*, gradual_performance::*, patterns::*, pp::*};
 
 pub(crate) use self::mania_object::ManiaObject;
 
@@ -27,7 +33,7 @@ const STAR_SCALING_FACTOR: f64 = 0.018;
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{ManiaStars, Beatmap};
+/// use murasame_pp::{ManiaStars, Beatmap};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -42,26 +48,27 @@ const STAR_SCALING_FACTOR: f64 = 0.018;
 /// ```
 #[derive(Clone, Debug)]
 pub struct ManiaStars<'map> {
-    map: Cow<'map, Beatmap>,
+    map: &'map Beatmap,
     mods: u32,
     passed_objects: Option<usize>,
     clock_rate: Option<f64>,
     is_convert: bool,
+    keys: Option<u8>,
+    overrides: DifficultyOverrides,
 }
 
 impl<'map> ManiaStars<'map> {
     /// Create a new difficulty calculator for osu!mania maps.
     #[inline]
     pub fn new(map: &'map Beatmap) -> Self {
-        let map = map.convert_mode(GameMode::Mania);
-        let is_convert = matches!(map, Cow::Owned(_));
-
         Self {
             map,
             mods: 0,
             passed_objects: None,
             clock_rate: None,
-            is_convert,
+            is_convert: map.mode != GameMode::Mania,
+            keys: None,
+            overrides: DifficultyOverrides::default(),
         }
     }
 
@@ -105,27 +112,62 @@ impl<'map> ManiaStars<'map> {
         self
     }
 
+    /// Specify the amount of keys that an osu!standard map is converted into.
+    ///
+    /// If none is specified, the key mods or the default conversion decide the key count.
+    /// osu!mania maps always keep their own key count.
+    #[inline]
+    pub fn keys(mut self, keys: u8) -> Self {
+        self.keys = Some(keys);
+
+        self
+    }
+
+    /// Override the map's difficulty settings, e.g. for the Difficulty Adjust mod.
+    #[inline]
+    pub(crate) fn overrides(mut self, overrides: DifficultyOverrides) -> Self {
+        self.overrides = overrides;
+
+        self
+    }
+
     /// Calculate all difficulty related values, including stars.
     #[inline]
     pub fn calculate(self) -> ManiaDifficultyAttributes {
-        let is_convert = self.is_convert || matches!(self.map, Cow::Owned(_));
+        let map = self.converted_map();
+        let is_convert = self.is_convert || matches!(map, Cow::Owned(_));
 
-        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+        let clock_rate = self.overrides.clock_rate(self.clock_rate, self.mods);
 
-        let BeatmapHitWindows { od: hit_window, .. } = self
-            .map
-            .attributes()
+        let settings = self
+            .overrides
+            .attributes(&map)
             .mods(self.mods)
             .converted(is_convert)
             .clock_rate(clock_rate)
-            .hit_windows();
+            .settings();
 
-        let ManiaResult { strain, max_combo } = calculate_result(self);
+        let ManiaResult {
+            strain,
+            max_combo,
+            n_objects,
+            n_hold_notes,
+            hold_duration,
+            n_keys,
+            ..
+        } = calculate_result(&map, &self);
 
         ManiaDifficultyAttributes {
             stars: strain.difficulty_value() * STAR_SCALING_FACTOR,
-            hit_window,
+            hit_window: settings.great_hit_window,
             max_combo,
+            n_objects,
+            n_hold_notes,
+            hold_duration,
+            n_keys,
+            is_convert,
+            settings,
+            version: ATTRIBUTES_VERSION,
         }
     }
 
@@ -134,21 +176,66 @@ impl<'map> ManiaStars<'map> {
     /// Suitable to plot the difficulty of a map over time.
     #[inline]
     pub fn strains(self) -> ManiaStrains {
-        let ManiaResult { strain, .. } = calculate_result(self);
+        let map = self.converted_map();
+        let ManiaResult {
+            strain, start_time, ..
+        } = calculate_result(&map, &self);
 
         ManiaStrains {
             section_len: SECTION_LEN,
+            start_time,
             strains: strain.strain_peaks,
         }
     }
+
+    /// Calculate chord and jack statistics of the map.
+    ///
+    /// Suitable to classify maps or to recommend them based on the skills they require.
+    pub fn patterns(self) -> ManiaPatterns {
+        let map = self.converted_map();
+        let take = self
+            .passed_objects
+            .unwrap_or(map.hit_objects.len())
+            .min(map.hit_objects.len());
+
+        let total_columns = self.total_columns(&map);
+        let clock_rate = self.overrides.clock_rate(self.clock_rate, self.mods);
+
+        ManiaPatterns::new(&map.hit_objects[..take], total_columns, clock_rate)
+    }
+
+    fn converted_map(&self) -> Cow<'map, Beatmap> {
+        let key_count = self.keys.or_else(|| mods::key_count(self.mods));
+
+        self.map.convert_to_mania_with_keys(key_count)
+    }
+
+    fn total_columns(&self, map: &Beatmap) -> f32 {
+        self.overrides.cs.unwrap_or(map.cs).round_even().max(1.0)
+    }
+}
+
+/// Count the maximum combo of an already converted map.
+pub(crate) fn max_combo(map: &Beatmap) -> usize {
+    let total_columns = map.cs.round_even().max(1.0);
+    let mut params = ObjectParameters::new(map);
+
+    for h in map.hit_objects.iter() {
+        ManiaObject::new(h, total_columns, &mut params);
+    }
+
+    params.max_combo
 }
 
 /// The result of calculating the strains on a osu!taiko map.
 /// Suitable to plot the difficulty of a map over time.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ManiaStrains {
     /// Time in ms inbetween two strains.
     pub section_len: f64,
+    /// Start time in ms of the first strain peak's section, adjusted by the clock rate.
+    pub start_time: f64,
     /// Strain peaks of the strain skill.
     pub strains: Vec<f64>,
 }
@@ -160,23 +247,43 @@ impl ManiaStrains {
     pub fn len(&self) -> usize {
         self.strains.len()
     }
+
+    /// Merge the strain peaks into exactly `n_buckets` points, e.g. for a fixed-width graph.
+    ///
+    /// The `section_len` of the result is adjusted so that the `i`-th point
+    /// still starts at `start_time + i * section_len` ms.
+    pub fn downsample(&self, n_buckets: usize, aggregation: StrainAggregation) -> Self {
+        let section_len = if n_buckets > 0 {
+            self.section_len * self.len() as f64 / n_buckets as f64
+        } else {
+            self.section_len
+        };
+
+        Self {
+            section_len,
+            start_time: self.start_time,
+            strains: downsample(&self.strains, n_buckets, aggregation),
+        }
+    }
 }
 
-fn calculate_result(params: ManiaStars<'_>) -> ManiaResult {
+fn calculate_result(map: &Beatmap, params: &ManiaStars<'_>) -> ManiaResult {
+    profile!(Skills);
+
     let ManiaStars {
-        map,
         mods,
         passed_objects,
         clock_rate,
-        is_convert: _,
-    } = params;
+        overrides,
+        ..
+    } = *params;
 
     let take = passed_objects.unwrap_or(map.hit_objects.len());
-    let total_columns = map.cs.round_even().max(1.0);
+    let total_columns = params.total_columns(map);
 
-    let clock_rate = clock_rate.unwrap_or_else(|| mods.clock_rate());
+    let clock_rate = overrides.clock_rate(clock_rate, mods);
     let mut strain = Strain::new(total_columns as usize);
-    let mut params = ObjectParameters::new(map.as_ref());
+    let mut params = ObjectParameters::new(map);
     let mut hit_objects = map.hit_objects.iter().take(take);
 
     let first = match hit_objects.next() {
@@ -185,6 +292,11 @@ fn calculate_result(params: ManiaStars<'_>) -> ManiaResult {
             return ManiaResult {
                 strain,
                 max_combo: 0,
+                n_objects: 0,
+                n_hold_notes: 0,
+                hold_duration: 0.0,
+                n_keys: total_columns as usize,
+                start_time: 0.0,
             }
         }
     };
@@ -204,19 +316,39 @@ fn calculate_result(params: ManiaStars<'_>) -> ManiaResult {
         strain.process(curr, &diff_objects);
     }
 
+    let passed = &map.hit_objects[..take.min(map.hit_objects.len())];
+
+    let hold_duration = passed
+        .iter()
+        .filter(|h| !h.is_circle())
+        .map(|h| h.end_time() - h.start_time)
+        .sum::<f64>()
+        / clock_rate;
+
     ManiaResult {
         strain,
         max_combo: params.max_combo,
+        n_objects: passed.len(),
+        n_hold_notes: passed.iter().filter(|h| !h.is_circle()).count(),
+        hold_duration,
+        n_keys: total_columns as usize,
+        start_time: first_section_start(diff_objects.first().map(|h| h.start_time), SECTION_LEN),
     }
 }
 
 struct ManiaResult {
     strain: Strain,
     max_combo: usize,
+    n_objects: usize,
+    n_hold_notes: usize,
+    hold_duration: f64,
+    n_keys: usize,
+    start_time: f64,
 }
 
 /// The result of a difficulty calculation on an osu!mania map.
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ManiaDifficultyAttributes {
     /// The final star rating.
     pub stars: f64,
@@ -224,18 +356,86 @@ pub struct ManiaDifficultyAttributes {
     pub hit_window: f64,
     /// The maximum achievable combo.
     pub max_combo: usize,
+    /// The amount of hit objects i.e. notes and hold notes.
+    ///
+    /// For converts, this is the amount after the conversion.
+    pub n_objects: usize,
+    /// The amount of hold notes.
+    pub n_hold_notes: usize,
+    /// The summed duration of all hold notes in ms, adjusted by the clock rate.
+    pub hold_duration: f64,
+    /// The amount of keys.
+    ///
+    /// For converts, this is the key count chosen by the conversion.
+    pub n_keys: usize,
+    /// Whether the map was converted from osu!standard.
+    ///
+    /// Converts use a fixed hit window depending only on whether the OD is above 4
+    /// and their key count is derived from the map's circle size and object density.
+    pub is_convert: bool,
+    /// The difficulty settings after applying mods.
+    pub settings: DifficultySettings,
+    /// The [`ATTRIBUTES_VERSION`](crate::ATTRIBUTES_VERSION) the attributes were calculated with.
+    ///
+    /// `0` if the attributes were not calculated by this crate.
+    pub version: u32,
 }
 
 impl ManiaDifficultyAttributes {
+    /// Round all floating point values to the precision of the osu!api
+    /// so that they can be compared to or stored alongside official values.
+    ///
+    /// Ties are rounded to the nearest even digit like osu!lazer does.
+    pub fn api_rounded(mut self) -> Self {
+        self.stars = round_api(self.stars);
+        self.hit_window = round_api(self.hit_window);
+        self.settings = self.settings.api_rounded();
+
+        self
+    }
+
     /// Return the maximum combo.
     #[inline]
     pub fn max_combo(&self) -> usize {
         self.max_combo
     }
+
+    /// Return the amount of hit objects i.e. notes and hold notes.
+    ///
+    /// Each hit object receives one hitresult.
+    #[inline]
+    pub fn n_objects(&self) -> usize {
+        self.n_objects
+    }
+
+    /// Return the amount of notes without hold notes.
+    #[inline]
+    pub fn n_notes(&self) -> usize {
+        self.n_objects - self.n_hold_notes
+    }
+
+    /// Return the amount of hold notes.
+    #[inline]
+    pub fn n_hold_notes(&self) -> usize {
+        self.n_hold_notes
+    }
+
+    /// Return the share of hold notes among all hit objects between `0.0` and `1.0`.
+    ///
+    /// Useful to tell apart rice maps from LN-heavy maps.
+    #[inline]
+    pub fn ln_density(&self) -> f64 {
+        if self.n_objects == 0 {
+            return 0.0;
+        }
+
+        self.n_hold_notes as f64 / self.n_objects as f64
+    }
 }
 
 /// The result of a performance calculation on an osu!mania map.
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ManiaPerformanceAttributes {
     /// The difficulty attributes that were used for the performance calculation.
     pub difficulty: ManiaDifficultyAttributes,
@@ -243,9 +443,23 @@ pub struct ManiaPerformanceAttributes {
     pub pp: f64,
     /// The difficulty portion of the final pp.
     pub pp_difficulty: f64,
+    /// The hitresults that were used for the performance calculation.
+    pub state: ManiaScoreState,
 }
 
 impl ManiaPerformanceAttributes {
+    /// Round all floating point values, including the difficulty attributes,
+    /// to the precision of the osu!api.
+    ///
+    /// See [`ManiaDifficultyAttributes::api_rounded`].
+    pub fn api_rounded(mut self) -> Self {
+        self.difficulty = self.difficulty.api_rounded();
+        self.pp = round_api(self.pp);
+        self.pp_difficulty = round_api(self.pp_difficulty);
+
+        self
+    }
+
     /// Return the star value.
     #[inline]
     pub fn stars(&self) -> f64 {
@@ -280,14 +494,18 @@ impl<'map> From<OsuStars<'map>> for ManiaStars<'map> {
             mods,
             passed_objects,
             clock_rate,
+            overrides,
+            ..
         } = osu;
 
         Self {
-            map: map.convert_mode(GameMode::Mania),
+            map,
             mods,
             passed_objects,
             clock_rate,
             is_convert: true,
+            keys: None,
+            overrides,
         }
     }
 }
@@ -0,0 +1,101 @@
+use std::mem;
+
+use super::mania_object::ManiaObject;
+use crate::parse::HitObject;
+
+/// Chord and jack statistics of an osu!mania map.
+///
+/// Suitable to classify maps, e.g. to tell chordjack maps apart from stream maps.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManiaPatterns {
+    /// The amount of chords per chord size i.e. `chord_sizes[i]` is the amount of
+    /// points in time with exactly `i + 1` notes. Single notes count as chords of size 1.
+    pub chord_sizes: Vec<usize>,
+    /// The amount of jacks i.e. notes on a column that was also hit at the previous point in time.
+    pub n_jacks: usize,
+    /// The amount of jacks per second, adjusted by the clock rate.
+    pub jack_density: f64,
+}
+
+impl ManiaPatterns {
+    pub(crate) fn new(hit_objects: &[HitObject], total_columns: f32, clock_rate: f64) -> Self {
+        let mut chord_sizes = Vec::new();
+        let mut n_jacks = 0;
+
+        let mut prev_columns = vec![false; total_columns as usize];
+        let mut curr_columns = vec![false; total_columns as usize];
+        let mut chord_time = None;
+        let mut chord_size = 0;
+
+        for h in hit_objects {
+            if chord_time != Some(h.start_time) {
+                Self::add_chord(&mut chord_sizes, chord_size);
+                mem::swap(&mut prev_columns, &mut curr_columns);
+                curr_columns.iter_mut().for_each(|hit| *hit = false);
+                chord_time = Some(h.start_time);
+                chord_size = 0;
+            }
+
+            let column = ManiaObject::column(h.pos.x, total_columns);
+            n_jacks += prev_columns[column] as usize;
+            curr_columns[column] = true;
+            chord_size += 1;
+        }
+
+        Self::add_chord(&mut chord_sizes, chord_size);
+
+        let duration = match (hit_objects.first(), hit_objects.last()) {
+            (Some(first), Some(last)) => (last.start_time - first.start_time) / clock_rate,
+            _ => 0.0,
+        };
+
+        let jack_density = if duration > 0.0 {
+            n_jacks as f64 / (duration / 1000.0)
+        } else {
+            0.0
+        };
+
+        Self {
+            chord_sizes,
+            n_jacks,
+            jack_density,
+        }
+    }
+
+    fn add_chord(chord_sizes: &mut Vec<usize>, chord_size: usize) {
+        if chord_size == 0 {
+            return;
+        }
+
+        if chord_sizes.len() < chord_size {
+            chord_sizes.resize(chord_size, 0);
+        }
+
+        chord_sizes[chord_size - 1] += 1;
+    }
+
+    /// Return the total amount of chords, including single notes.
+    #[inline]
+    pub fn n_chords(&self) -> usize {
+        self.chord_sizes.iter().sum()
+    }
+
+    /// Return the average amount of notes per chord.
+    pub fn average_chord_size(&self) -> f64 {
+        let n_chords = self.n_chords();
+
+        if n_chords == 0 {
+            return 0.0;
+        }
+
+        let n_notes: usize = self
+            .chord_sizes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (i + 1) * n)
+            .sum();
+
+        n_notes as f64 / n_chords as f64
+    }
+}
@@ -2,7 +2,8 @@ use std::borrow::Cow;
 
 use super::{ManiaDifficultyAttributes, ManiaPerformanceAttributes, ManiaScoreState, ManiaStars};
 use crate::{
-    Beatmap, DifficultyAttributes, GameMode, HitResultPriority, Mods, OsuPP, PerformanceAttributes,
+    beatmap::DifficultyOverrides, invalid_score, mods, util::FloatExt, AttributeProvider, Beatmap,
+    CustomMod, GameMode, HitResultPriority, InvalidScore, Mods, OsuPP,
 };
 
 /// Performance calculator on osu!mania maps.
@@ -10,7 +11,7 @@ use crate::{
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{ManiaPP, Beatmap};
+/// use murasame_pp::{ManiaPP, Beatmap};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -42,10 +43,13 @@ use crate::{
 #[allow(clippy::upper_case_acronyms)]
 pub struct ManiaPP<'map> {
     map: Cow<'map, Beatmap>,
+    original: &'map Beatmap,
     attributes: Option<ManiaDifficultyAttributes>,
     mods: u32,
     passed_objects: Option<usize>,
     clock_rate: Option<f64>,
+    overrides: DifficultyOverrides,
+    keys: Option<u8>,
 
     pub(crate) n320: Option<usize>,
     pub(crate) n300: Option<usize>,
@@ -64,10 +68,13 @@ impl<'map> ManiaPP<'map> {
     pub fn new(map: &'map Beatmap) -> Self {
         Self {
             map: map.convert_mode(GameMode::Mania),
+            original: map,
             attributes: None,
             mods: 0,
             passed_objects: None,
             clock_rate: None,
+            overrides: DifficultyOverrides::default(),
+            keys: None,
             n320: None,
             n300: None,
             n200: None,
@@ -79,6 +86,28 @@ impl<'map> ManiaPP<'map> {
         }
     }
 
+    /// Copy the calculator while borrowing its map instead of cloning it.
+    pub(crate) fn borrowed(&self) -> ManiaPP<'_> {
+        ManiaPP {
+            map: Cow::Borrowed(self.map.as_ref()),
+            original: self.original,
+            attributes: self.attributes,
+            mods: self.mods,
+            passed_objects: self.passed_objects,
+            clock_rate: self.clock_rate,
+            overrides: self.overrides,
+            keys: self.keys,
+            n320: self.n320,
+            n300: self.n300,
+            n200: self.n200,
+            n100: self.n100,
+            n50: self.n50,
+            n_misses: self.n_misses,
+            acc: self.acc,
+            hitresult_priority: self.hitresult_priority,
+        }
+    }
+
     /// Provide the result of a previous difficulty or performance calculation.
     /// If you already calculated the attributes for the current map-mod combination,
     /// be sure to put them in here so that they don't have to be recalculated.
@@ -123,6 +152,69 @@ impl<'map> ManiaPP<'map> {
         self
     }
 
+    /// Override the map's approach rate before calculating its difficulty.
+    ///
+    /// Irrelevant for osu!mania.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn ar(mut self, ar: f32) -> Self {
+        self.overrides.ar = Some(ar);
+
+        self
+    }
+
+    /// Override the map's overall difficulty before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn od(mut self, od: f32) -> Self {
+        self.overrides.od = Some(od);
+
+        self
+    }
+
+    /// Override the map's circle size before calculating its difficulty.
+    ///
+    /// For osu!mania this is the amount of keys.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn cs(mut self, cs: f32) -> Self {
+        self.overrides.cs = Some(cs);
+
+        self
+    }
+
+    /// Override the map's drain rate before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn hp(mut self, hp: f32) -> Self {
+        self.overrides.hp = Some(hp);
+
+        self
+    }
+
+    /// Apply the effects of a [`CustomMod`] on top of the mods, e.g. a private server's own mod.
+    #[inline]
+    pub fn custom_mod(mut self, custom_mod: CustomMod) -> Self {
+        self.overrides.custom_mod = custom_mod;
+
+        self
+    }
+
+    /// Specify the amount of keys that an osu!standard map is converted into.
+    ///
+    /// If none is specified, the key mods or the default conversion decide the key count.
+    /// osu!mania maps always keep their own key count.
+    #[inline]
+    pub fn keys(mut self, keys: u8) -> Self {
+        self.keys = Some(keys);
+
+        self
+    }
+
     /// Specify the accuracy of a play between `0.0` and `100.0`.
     /// This will be used to generate matching hitresults.
     #[inline]
@@ -212,23 +304,166 @@ impl<'map> ManiaPP<'map> {
         self
     }
 
-    /// Calculate all performance related values, including pp and stars.
-    pub fn calculate(self) -> ManiaPerformanceAttributes {
-        let attrs = self.attributes.unwrap_or_else(|| {
-            let mut calculator = ManiaStars::new(self.map.as_ref())
-                .mods(self.mods)
-                .is_convert(matches!(self.map, Cow::Owned(_)));
-
-            if let Some(passed_objects) = self.passed_objects {
-                calculator = calculator.passed_objects(passed_objects);
-            }
+    /// Convert the map again if the key override or the key mods require
+    /// a different key count than the default conversion.
+    pub(crate) fn convert_with_keys(&mut self) {
+        let key_count = match self.keys.or_else(|| mods::key_count(self.mods)) {
+            Some(key_count) => key_count,
+            None => return,
+        };
 
-            if let Some(clock_rate) = self.clock_rate {
-                calculator = calculator.clock_rate(clock_rate);
-            }
+        if (self.map.cs - key_count as f32).abs() > f32::EPSILON {
+            self.map = self.original.convert_to_mania_with_keys(Some(key_count));
+        }
+    }
+
+    fn take_attributes(&mut self) -> ManiaDifficultyAttributes {
+        self.convert_with_keys();
+
+        self.attributes
+            .take()
+            .unwrap_or_else(|| self.calculate_difficulty(self.passed_objects))
+    }
+
+    /// The map must already be converted with the correct key count.
+    fn calculate_difficulty(&self, passed_objects: Option<usize>) -> ManiaDifficultyAttributes {
+        let mut calculator = ManiaStars::new(self.map.as_ref())
+            .mods(self.mods)
+            .overrides(self.overrides)
+            .is_convert(matches!(self.map, Cow::Owned(_)));
+
+        if let Some(passed_objects) = passed_objects {
+            calculator = calculator.passed_objects(passed_objects);
+        }
+
+        if let Some(clock_rate) = self.clock_rate {
+            calculator = calculator.clock_rate(clock_rate);
+        }
+
+        calculator.calculate()
+    }
+
+    /// Copy the settings of the calculator without the values of a specific score.
+    fn without_score(&self) -> ManiaPP<'_> {
+        ManiaPP {
+            map: Cow::Borrowed(self.map.as_ref()),
+            original: self.original,
+            attributes: None,
+            mods: self.mods,
+            passed_objects: self.passed_objects,
+            clock_rate: self.clock_rate,
+            overrides: self.overrides,
+            keys: self.keys,
+            n320: None,
+            n300: None,
+            n200: None,
+            n100: None,
+            n50: None,
+            n_misses: None,
+            acc: None,
+            hitresult_priority: None,
+        }
+    }
+
+    /// Generate hitresults for the given accuracy between `0.0` and `100.0` the same way
+    /// [osu-tools](https://github.com/ppy/osu-tools)' `simulate` command does.
+    ///
+    /// Only misses are respected and all other hitresults are derived from the accuracy.
+    /// Unlike [`accuracy`](ManiaPP::accuracy), neither 300s nor 200s are generated and
+    /// infeasible inputs are not corrected except that negative counts are clamped to zero.
+    pub fn simulate(self, acc: f64) -> ManiaScoreState {
+        let total = self.passed_objects.unwrap_or(self.map.hit_objects.len()) as isize;
+        let n_misses = self.n_misses.unwrap_or(0) as isize;
+
+        // Let 320=6, 100=2, 50=1, miss=0 and start by assuming every non-miss is a 50
+        let target_total = (acc / 100.0 * (total * 6) as f64).round_even() as isize;
+        let delta = target_total - (total - n_misses);
+
+        let n320 = delta / 5;
+        let n100 = delta % 5;
+        let n50 = total - n320 - n100 - n_misses;
+
+        ManiaScoreState {
+            n320: n320.max(0) as usize,
+            n300: 0,
+            n200: 0,
+            n100: n100.max(0) as usize,
+            n50: n50.max(0) as usize,
+            n_misses: n_misses as usize,
+        }
+    }
+
+    /// Same as [`calculate`](ManiaPP::calculate) but the specified values are
+    /// first validated against the map.
+    ///
+    /// Returns an [`InvalidScore`] describing the first inconsistent value,
+    /// e.g. if the hitresults exceed the amount of objects.
+    pub fn try_calculate(mut self) -> Result<ManiaPerformanceAttributes, InvalidScore> {
+        self.convert_with_keys();
+        invalid_score::check_passed_objects(self.passed_objects, self.map.hit_objects.len())?;
+        invalid_score::check_accuracy(self.acc)?;
+
+        let attrs = self.take_attributes();
+        let n_objects = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+
+        let hitresults = [
+            ("n320", self.n320),
+            ("n300", self.n300),
+            ("n200", self.n200),
+            ("n100", self.n100),
+            ("n50", self.n50),
+            ("n_misses", self.n_misses),
+        ];
+
+        invalid_score::check_hitresults(&hitresults, n_objects)?;
+
+        Ok(self.attributes(attrs).calculate())
+    }
+
+    /// Calculate the performance attributes for each of the given score states.
+    ///
+    /// The difficulty attributes are calculated only once and shared by all scores.
+    /// Values of a specific score that were specified on the calculator itself,
+    /// e.g. hitresults or accuracy, are ignored.
+    pub fn calculate_many(mut self, states: &[ManiaScoreState]) -> Vec<ManiaPerformanceAttributes> {
+        let attrs = self.take_attributes();
+
+        states
+            .iter()
+            .map(|state| {
+                self.without_score()
+                    .attributes(attrs)
+                    .state(*state)
+                    .calculate()
+            })
+            .collect()
+    }
+
+    /// Calculate the performance attributes of the score and of an SS
+    /// on the same map with the same mods.
+    ///
+    /// The difficulty attributes are calculated only once and shared by both calculations
+    /// unless `passed_objects` is specified, in which case the SS is calculated on the full map.
+    pub fn calculate_with_max(
+        mut self,
+    ) -> (ManiaPerformanceAttributes, ManiaPerformanceAttributes) {
+        let attrs = self.take_attributes();
+
+        let max_attrs = match self.passed_objects {
+            Some(_) => self.calculate_difficulty(None),
+            None => attrs,
+        };
+
+        let mut max = self.without_score();
+        max.passed_objects = None;
+        let max = max.attributes(max_attrs).calculate();
+
+        (self.attributes(attrs).calculate(), max)
+    }
 
-            calculator.calculate()
-        });
+    /// Calculate all performance related values, including pp and stars.
+    pub fn calculate(mut self) -> ManiaPerformanceAttributes {
+        let attrs = self.take_attributes();
 
         let inner = ManiaPpInner {
             attrs,
@@ -505,6 +740,8 @@ struct ManiaPpInner {
 
 impl ManiaPpInner {
     fn calculate(self) -> ManiaPerformanceAttributes {
+        profile!(Performance);
+
         // * Arbitrary initial value for scaling pp in order to standardize distributions across game modes.
         // * The specific number has no intrinsic meaning and can be adjusted as needed.
         let mut multiplier = 8.0;
@@ -524,6 +761,7 @@ impl ManiaPpInner {
             difficulty: self.attrs,
             pp,
             pp_difficulty: difficulty_value,
+            state: self.state,
         }
     }
 
@@ -570,26 +808,41 @@ impl<'map> From<OsuPP<'map>> for ManiaPP<'map> {
             map,
             attributes: _,
             mods,
+            blinds: _,
+            relax: _,
+            hit_errors: _,
+            hit_windows: _,
+            slider_breaks: _,
+            slider_tick_misses: _,
+            slider_end_misses: _,
+            tuning: _,
+            mod_multipliers: _,
             acc,
             combo: _,
             n300,
             n100,
             n50,
             n_misses,
+            n_geki,
+            n_katu,
             passed_objects,
             clock_rate,
+            overrides,
             hitresult_priority,
         } = osu;
 
         Self {
             map: map.convert_mode(GameMode::Mania),
+            original: map,
             attributes: None,
             mods,
             passed_objects,
             clock_rate,
-            n320: None,
+            overrides,
+            keys: None,
+            n320: n_geki,
             n300,
-            n200: None,
+            n200: n_katu,
             n100,
             n50,
             n_misses,
@@ -600,44 +853,17 @@ impl<'map> From<OsuPP<'map>> for ManiaPP<'map> {
 }
 
 /// Abstract type to provide flexibility when passing difficulty attributes to a performance calculation.
+///
+/// Implemented for all types implementing [`AttributeProvider`].
 pub trait ManiaAttributeProvider {
     /// Provide the actual difficulty attributes.
     fn attributes(self) -> Option<ManiaDifficultyAttributes>;
 }
 
-impl ManiaAttributeProvider for ManiaDifficultyAttributes {
-    #[inline]
-    fn attributes(self) -> Option<ManiaDifficultyAttributes> {
-        Some(self)
-    }
-}
-
-impl ManiaAttributeProvider for ManiaPerformanceAttributes {
-    #[inline]
-    fn attributes(self) -> Option<ManiaDifficultyAttributes> {
-        Some(self.difficulty)
-    }
-}
-
-impl ManiaAttributeProvider for DifficultyAttributes {
-    #[inline]
-    fn attributes(self) -> Option<ManiaDifficultyAttributes> {
-        if let Self::Mania(attrs) = self {
-            Some(attrs)
-        } else {
-            None
-        }
-    }
-}
-
-impl ManiaAttributeProvider for PerformanceAttributes {
+impl<T: AttributeProvider> ManiaAttributeProvider for T {
     #[inline]
     fn attributes(self) -> Option<ManiaDifficultyAttributes> {
-        if let Self::Mania(attrs) = self {
-            Some(attrs.difficulty)
-        } else {
-            None
-        }
+        self.mode_attributes().ok()
     }
 }
 
@@ -655,6 +881,9 @@ mod tests {
             stars: 4.824631127426499,
             hit_window: 40.0,
             max_combo: 5064,
+            n_keys: 4,
+            is_convert: false,
+            ..Default::default()
         };
 
         (map, attrs)
@@ -1060,4 +1289,192 @@ mod tests {
         assert_eq!(state, expected);
         assert_eq!(state.total_hits(), 3238);
     }
+
+    #[test]
+    fn calculate_many_matches_single_calculations() {
+        let (map, attrs) = test_data();
+
+        let states = [
+            ManiaScoreState {
+                n320: 5000,
+                n_misses: 64,
+                ..Default::default()
+            },
+            ManiaScoreState {
+                n320: 4000,
+                n100: 1064,
+                ..Default::default()
+            },
+        ];
+
+        let many = ManiaPP::new(&map).attributes(attrs).calculate_many(&states);
+
+        for (state, performance) in states.iter().zip(many) {
+            let single = ManiaPP::new(&map)
+                .attributes(attrs)
+                .state(*state)
+                .calculate();
+
+            assert_eq!(performance, single);
+        }
+    }
+
+    #[test]
+    fn convert_is_transparent() {
+        let (map, attrs) = test_data();
+        let stars = ManiaStars::new(&map).calculate();
+
+        assert_eq!(stars.n_keys, attrs.n_keys);
+        assert!(!stars.is_convert);
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let converted = ManiaStars::new(&map).calculate();
+
+        assert!(converted.is_convert);
+        assert!(converted.n_keys > 0);
+    }
+
+    #[test]
+    fn object_counts() {
+        let (map, _) = test_data();
+        let attrs = ManiaStars::new(&map).calculate();
+
+        assert_eq!(attrs.n_objects(), 3238);
+        assert_eq!(attrs.n_objects(), map.hit_objects.len());
+        assert_eq!(
+            attrs.n_hold_notes(),
+            map.hit_objects.iter().filter(|h| !h.is_circle()).count()
+        );
+
+        let last = crate::mania::ManiaGradualDifficultyAttributes::new(&map, 0)
+            .last()
+            .unwrap();
+
+        assert_eq!(last.n_objects, attrs.n_objects);
+        assert_eq!(last.n_hold_notes, attrs.n_hold_notes);
+    }
+
+    #[test]
+    fn key_count() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        for keys in 1..=9 {
+            let attrs = ManiaStars::new(&map).keys(keys).calculate();
+            assert_eq!(attrs.n_keys, keys as usize);
+        }
+
+        let key4 = ManiaStars::new(&map).mods(1 << 15).calculate();
+        assert_eq!(key4.n_keys, 4);
+
+        let overridden = ManiaStars::new(&map).mods(1 << 15).keys(7).calculate();
+        assert_eq!(overridden.n_keys, 7);
+
+        let result = ManiaPP::new(&map).mods(1 << 15).calculate();
+        assert_eq!(result.difficulty, key4);
+
+        let gradual = crate::mania::ManiaGradualDifficultyAttributes::new(&map, 1 << 15);
+        assert_eq!(gradual.last().unwrap().stars, key4.stars);
+
+        let (native, attrs) = test_data();
+        let native_attrs = ManiaStars::new(&native).mods(1 << 15).calculate();
+        assert_eq!(native_attrs.n_keys, attrs.n_keys);
+    }
+
+    #[test]
+    fn simulate_matches_osu_tools() {
+        let (map, attrs) = test_data();
+
+        let state = ManiaPP::new(&map)
+            .attributes(attrs)
+            .passed_objects(200)
+            .n_misses(3)
+            .simulate(95.0);
+
+        let expected = ManiaScoreState {
+            n320: 188,
+            n300: 0,
+            n200: 0,
+            n100: 3,
+            n50: 6,
+            n_misses: 3,
+        };
+
+        assert_eq!(state, expected);
+
+        // The target total of 52.5 is rounded to the nearest even integer like in C#
+        let state = ManiaPP::new(&map).passed_objects(10).simulate(87.5);
+
+        let expected = ManiaScoreState {
+            n320: 8,
+            n300: 0,
+            n200: 0,
+            n100: 2,
+            n50: 0,
+            n_misses: 0,
+        };
+
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn calculate_with_max_on_partial_convert() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let (score, max) = ManiaPP::new(&map)
+            .keys(7)
+            .passed_objects(100)
+            .n_misses(1)
+            .calculate_with_max();
+
+        assert_eq!(score.difficulty.n_objects, 100);
+        assert_eq!(max.difficulty.n_keys, 7);
+        assert_eq!(max, ManiaPP::new(&map).keys(7).calculate());
+    }
+
+    #[test]
+    fn hold_note_statistics() {
+        let (map, _) = test_data();
+        let nomod = ManiaStars::new(&map).calculate();
+        let dt = ManiaStars::new(&map).mods(64).calculate();
+
+        assert!(nomod.hold_duration > 0.0);
+        assert!((nomod.hold_duration / 1.5 - dt.hold_duration).abs() < 1e-6);
+
+        let ln_density = nomod.ln_density();
+        assert!(ln_density > 0.0 && ln_density < 1.0);
+        assert_eq!(
+            ln_density,
+            nomod.n_hold_notes as f64 / nomod.n_objects as f64
+        );
+
+        let last = crate::mania::ManiaGradualDifficultyAttributes::new(&map, 0)
+            .last()
+            .unwrap();
+
+        assert!((last.hold_duration - nomod.hold_duration).abs() < 1e-6);
+    }
+
+    #[test]
+    fn patterns() {
+        let (map, attrs) = test_data();
+        let nomod = ManiaStars::new(&map).patterns();
+        let dt = ManiaStars::new(&map).mods(64).patterns();
+
+        let n_notes: usize = nomod
+            .chord_sizes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (i + 1) * n)
+            .sum();
+
+        assert_eq!(n_notes, map.hit_objects.len());
+        assert!(nomod.chord_sizes.len() <= attrs.n_keys);
+        assert!(nomod.average_chord_size() >= 1.0);
+        assert!(nomod.n_jacks > 0);
+        assert_eq!(dt.n_jacks, nomod.n_jacks);
+        assert!((dt.jack_density - nomod.jack_density * 1.5).abs() < 1e-6);
+
+        let partial = ManiaStars::new(&map).passed_objects(100).patterns();
+        assert!(partial.n_chords() <= 100);
+    }
 }
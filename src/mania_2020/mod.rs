@@ -0,0 +1,53 @@
+mod pp;
+
+pub use self::pp::*;
+
+/// The star rating was not changed by the 2022 rework so the current
+/// difficulty calculation is used as is.
+pub use crate::mania::{ManiaDifficultyAttributes, ManiaStars};
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Beatmap;
+
+    #[test]
+    fn legacy_pp() {
+        let map = Beatmap::from_path("./maps/1974394.osu").unwrap();
+        let attrs = ManiaStars::new(&map).calculate();
+
+        let ss = ManiaPP::new(&map).attributes(attrs).calculate();
+        assert_eq!(ss.score, 1_000_000.0);
+        assert!(ss.pp_acc > 0.0);
+        assert!(ss.pp > ss.pp_strain * 0.8);
+
+        let lower = ManiaPP::new(&map)
+            .attributes(attrs)
+            .score(900_000)
+            .calculate();
+
+        assert_eq!(lower.pp_acc, 0.0);
+        assert!(lower.pp < ss.pp);
+
+        let worthless = ManiaPP::new(&map)
+            .attributes(attrs)
+            .score(500_000)
+            .calculate();
+
+        assert_eq!(worthless.pp, 0.0);
+
+        // NF halves the score so the same pp are reached with half the score
+        let nf = ManiaPP::new(&map)
+            .attributes(attrs)
+            .mods(1)
+            .score(450_000)
+            .calculate();
+
+        assert!((nf.pp_strain - lower.pp_strain).abs() < 1e-9);
+        assert!(nf.pp < lower.pp);
+
+        let current = crate::ManiaPP::new(&map).attributes(attrs).calculate();
+        assert_ne!(current.pp, ss.pp);
+    }
+}
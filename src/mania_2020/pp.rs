@@ -0,0 +1,226 @@
+use crate::{
+    mania::{ManiaDifficultyAttributes, ManiaStars},
+    Beatmap, Mods,
+};
+
+/// Performance calculator on osu!mania maps using the score-based formulas
+/// from before the 2022 rework.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, mania_2020::ManiaPP};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+///
+/// let pp_result = ManiaPP::new(&map)
+///     .mods(64) // DT
+///     .score(765_432)
+///     .calculate();
+///
+/// println!("PP: {} | Stars: {}", pp_result.pp, pp_result.stars());
+/// ```
+#[derive(Clone, Debug)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct ManiaPP<'map> {
+    map: &'map Beatmap,
+    attributes: Option<ManiaDifficultyAttributes>,
+    mods: u32,
+    score: Option<f64>,
+    passed_objects: Option<usize>,
+    clock_rate: Option<f64>,
+}
+
+impl<'map> ManiaPP<'map> {
+    /// Create a new performance calculator for osu!mania maps.
+    #[inline]
+    pub fn new(map: &'map Beatmap) -> Self {
+        Self {
+            map,
+            attributes: None,
+            mods: 0,
+            score: None,
+            passed_objects: None,
+            clock_rate: None,
+        }
+    }
+
+    /// Provide the result of a previous difficulty or performance calculation.
+    /// If you already calculated the attributes for the current map-mod combination,
+    /// be sure to put them in here so that they don't have to be recalculated.
+    #[inline]
+    pub fn attributes(mut self, attributes: impl Into<ManiaDifficultyAttributes>) -> Self {
+        self.attributes = Some(attributes.into());
+
+        self
+    }
+
+    /// Specify mods through their bit values.
+    ///
+    /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// Specify the ScoreV1 total score of the play.
+    ///
+    /// If none is specified, a score of 1,000,000 i.e. an SS is assumed.
+    #[inline]
+    pub fn score(mut self, score: u32) -> Self {
+        self.score = Some(score as f64);
+
+        self
+    }
+
+    /// Amount of passed objects for partial plays, e.g. a fail.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects = Some(passed_objects);
+
+        self
+    }
+
+    /// Adjust the clock rate used in the calculation.
+    /// If none is specified, it will take the clock rate based on the mods
+    /// i.e. 1.5 for DT, 0.75 for HT and 1.0 otherwise.
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: f64) -> Self {
+        self.clock_rate = Some(clock_rate);
+
+        self
+    }
+
+    /// Calculate all performance related values, including pp and stars.
+    pub fn calculate(self) -> ManiaPerformanceAttributes {
+        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+
+        let difficulty = self.attributes.unwrap_or_else(|| {
+            let mut calculator = ManiaStars::new(self.map)
+                .mods(self.mods)
+                .clock_rate(clock_rate);
+
+            if let Some(passed_objects) = self.passed_objects {
+                calculator = calculator.passed_objects(passed_objects);
+            }
+
+            calculator.calculate()
+        });
+
+        let mut score_multiplier = 1.0;
+
+        if self.mods.ez() {
+            score_multiplier *= 0.5;
+        }
+
+        if self.mods.nf() {
+            score_multiplier *= 0.5;
+        }
+
+        if self.mods.ht() {
+            score_multiplier *= 0.5;
+        }
+
+        let score = self.score.unwrap_or(1_000_000.0);
+        let scaled_score = score / score_multiplier;
+
+        let pp_strain = strain_value(&difficulty, scaled_score);
+
+        let mut hit_window = 34.0 + 3.0 * (10.0 - self.map.od as f64).clamp(0.0, 10.0);
+
+        if self.mods.ez() {
+            hit_window *= 1.4;
+        } else if self.mods.hr() {
+            hit_window /= 1.4;
+        }
+
+        let hit_window = ((hit_window * clock_rate).floor() / clock_rate).ceil();
+
+        let pp_acc = (0.2 - (hit_window - 34.0) * 0.006667).max(0.0)
+            * pp_strain
+            * ((scaled_score - 960_000.0).max(0.0) / 40_000.0).powf(1.1);
+
+        let mut multiplier = 0.8;
+
+        if self.mods.nf() {
+            multiplier *= 0.9;
+        }
+
+        if self.mods.ez() {
+            multiplier *= 0.5;
+        }
+
+        let pp = (pp_strain.powf(1.1) + pp_acc.powf(1.1)).powf(1.0 / 1.1) * multiplier;
+
+        ManiaPerformanceAttributes {
+            difficulty,
+            pp,
+            pp_acc,
+            pp_strain,
+            score,
+        }
+    }
+}
+
+fn strain_value(difficulty: &ManiaDifficultyAttributes, scaled_score: f64) -> f64 {
+    let mut strain_value = (5.0 * (difficulty.stars / 0.2).max(1.0) - 4.0).powf(2.2) / 135.0;
+
+    strain_value *= 1.0 + 0.1 * (difficulty.n_objects as f64 / 1500.0).min(1.0);
+
+    // Scores below 500k are worthless
+    if scaled_score <= 500_000.0 {
+        0.0
+    } else if scaled_score <= 600_000.0 {
+        strain_value * (scaled_score - 500_000.0) / 100_000.0 * 0.3
+    } else if scaled_score <= 700_000.0 {
+        strain_value * (0.3 + (scaled_score - 600_000.0) / 100_000.0 * 0.25)
+    } else if scaled_score <= 800_000.0 {
+        strain_value * (0.55 + (scaled_score - 700_000.0) / 100_000.0 * 0.2)
+    } else if scaled_score <= 900_000.0 {
+        strain_value * (0.75 + (scaled_score - 800_000.0) / 100_000.0 * 0.15)
+    } else {
+        strain_value * (0.9 + (scaled_score - 900_000.0) / 100_000.0 * 0.1)
+    }
+}
+
+/// The result of a performance calculation with the pre-2022 osu!mania formulas.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManiaPerformanceAttributes {
+    /// The difficulty attributes that were used for the performance calculation.
+    pub difficulty: ManiaDifficultyAttributes,
+    /// The final performance points.
+    pub pp: f64,
+    /// The accuracy portion of the final pp.
+    pub pp_acc: f64,
+    /// The strain portion of the final pp.
+    pub pp_strain: f64,
+    /// The ScoreV1 total score that was used for the performance calculation.
+    pub score: f64,
+}
+
+impl ManiaPerformanceAttributes {
+    /// Return the star value.
+    #[inline]
+    pub fn stars(&self) -> f64 {
+        self.difficulty.stars
+    }
+
+    /// Return the maximum combo of the map.
+    #[inline]
+    pub fn max_combo(&self) -> usize {
+        self.difficulty.max_combo
+    }
+}
+
+impl From<ManiaPerformanceAttributes> for ManiaDifficultyAttributes {
+    #[inline]
+    fn from(attributes: ManiaPerformanceAttributes) -> Self {
+        attributes.difficulty
+    }
+}
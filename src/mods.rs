@@ -15,12 +15,25 @@ pub trait Mods: Copy {
     const TD: u32 = 1 << 2;
     const HD: u32 = 1 << 3;
     const HR: u32 = 1 << 4;
+    const SD: u32 = 1 << 5;
     const DT: u32 = 1 << 6;
     const RX: u32 = 1 << 7;
     const HT: u32 = 1 << 8;
+    const NC: u32 = 1 << 9;
     const FL: u32 = 1 << 10;
     const SO: u32 = 1 << 12;
     const AP: u32 = 1 << 13;
+    const PF: u32 = 1 << 14;
+    const KEY4: u32 = 1 << 15;
+    const KEY5: u32 = 1 << 16;
+    const KEY6: u32 = 1 << 17;
+    const KEY7: u32 = 1 << 18;
+    const KEY8: u32 = 1 << 19;
+    const KEY9: u32 = 1 << 24;
+    const KEY1: u32 = 1 << 26;
+    const KEY3: u32 = 1 << 27;
+    const KEY2: u32 = 1 << 28;
+    const V2: u32 = 1 << 29;
 
     /// If the clock rate is affected by the mods.
     fn change_speed(self) -> bool;
@@ -88,3 +101,97 @@ impl Mods for u32 {
     impl_mods!(so, SO);
     impl_mods!(ap, AP);
 }
+
+/// The amount of keys of an osu!mania convert if a key mod is included.
+pub(crate) fn key_count(mods: u32) -> Option<u8> {
+    let keys = [
+        (u32::KEY1, 1),
+        (u32::KEY2, 2),
+        (u32::KEY3, 3),
+        (u32::KEY4, 4),
+        (u32::KEY5, 5),
+        (u32::KEY6, 6),
+        (u32::KEY7, 7),
+        (u32::KEY8, 8),
+        (u32::KEY9, 9),
+    ];
+
+    keys.iter()
+        .find(|(bit, _)| mods & bit > 0)
+        .map(|(_, key_count)| *key_count)
+}
+
+/// Effects of a custom mod that is not part of the regular mods, e.g. a private server's "half HR".
+///
+/// Pass it to a calculator, e.g. through [`OsuPP::custom_mod`](crate::OsuPP::custom_mod),
+/// so that its effects apply on top of the regular mods of that calculation.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, CustomMod, OsuPP};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let half_hr = CustomMod::new()
+///     .difficulty_multiplier(1.2)
+///     .cs_multiplier(1.15);
+///
+/// let pp_result = OsuPP::new(&map)
+///     .mods(8) // HD
+///     .custom_mod(half_hr)
+///     .calculate();
+///
+/// println!("PP: {}", pp_result.pp);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CustomMod {
+    pub(crate) clock_rate: f64,
+    pub(crate) difficulty_multiplier: f64,
+    pub(crate) cs_multiplier: f64,
+}
+
+impl CustomMod {
+    /// Create a custom mod without any effects.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Specify the factor by which the clock rate of the mods is multiplied.
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: f64) -> Self {
+        self.clock_rate = clock_rate;
+
+        self
+    }
+
+    /// Specify the factor by which AR, OD, and HP are multiplied, similar to HR or EZ.
+    #[inline]
+    pub fn difficulty_multiplier(mut self, multiplier: f64) -> Self {
+        self.difficulty_multiplier = multiplier;
+
+        self
+    }
+
+    /// Specify the factor by which CS is multiplied, similar to HR or EZ.
+    #[inline]
+    pub fn cs_multiplier(mut self, multiplier: f64) -> Self {
+        self.cs_multiplier = multiplier;
+
+        self
+    }
+}
+
+impl Default for CustomMod {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            clock_rate: 1.0,
+            difficulty_multiplier: 1.0,
+            cs_multiplier: 1.0,
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{beatmap::DifficultyOverrides, Beatmap, CustomMod, OsuStars};
+
+use super::{OsuDifficultyAttributes, ScratchBuffers};
+
+/// Reusable difficulty calculator for osu!standard maps.
+///
+/// In contrast to [`OsuStars`], the calculator is configured only once
+/// and can then calculate the difficulty of many maps.
+/// Internal buffers are kept in between calculations so they
+/// don't need to be allocated again for every map.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, osu::OsuDifficultyCalculator};
+///
+/// # /*
+/// let maps: Vec<Beatmap> = ...
+/// # */
+/// # let maps = vec![Beatmap::default(), Beatmap::default()];
+///
+/// let mut calculator = OsuDifficultyCalculator::new().mods(8 + 64); // HDDT
+///
+/// for map in maps.iter() {
+///     let attrs = calculator.calculate(map);
+///     println!("Stars: {}", attrs.stars);
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct OsuDifficultyCalculator {
+    mods: u32,
+    clock_rate: Option<f64>,
+    relax: bool,
+    overrides: DifficultyOverrides,
+    bufs: ScratchBuffers,
+}
+
+impl OsuDifficultyCalculator {
+    /// Create a new reusable difficulty calculator for osu!standard maps.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Specify mods through their bit values.
+    ///
+    /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// Adjust the clock rate used in the calculation.
+    /// If none is specified, it will take the clock rate based on the mods
+    /// i.e. 1.5 for DT, 0.75 for HT and 1.0 otherwise.
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: f64) -> Self {
+        self.clock_rate = Some(clock_rate);
+
+        self
+    }
+
+    /// Specify whether star ratings should be calculated for relax leaderboards.
+    ///
+    /// See [`OsuStars::relax`].
+    #[inline]
+    pub fn relax(mut self, relax: bool) -> Self {
+        self.relax = relax;
+
+        self
+    }
+
+    /// Override the approach rate of all maps before calculating their difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn ar(mut self, ar: f32) -> Self {
+        self.overrides.ar = Some(ar);
+
+        self
+    }
+
+    /// Override the overall difficulty of all maps before calculating their difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn od(mut self, od: f32) -> Self {
+        self.overrides.od = Some(od);
+
+        self
+    }
+
+    /// Override the circle size of all maps before calculating their difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn cs(mut self, cs: f32) -> Self {
+        self.overrides.cs = Some(cs);
+
+        self
+    }
+
+    /// Override the drain rate of all maps before calculating their difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn hp(mut self, hp: f32) -> Self {
+        self.overrides.hp = Some(hp);
+
+        self
+    }
+
+    /// Apply the effects of a [`CustomMod`] on top of the mods for all maps.
+    #[inline]
+    pub fn custom_mod(mut self, custom_mod: CustomMod) -> Self {
+        self.overrides.custom_mod = custom_mod;
+
+        self
+    }
+
+    /// Calculate all difficulty related values, including stars, for the given map.
+    pub fn calculate(&mut self, map: &Beatmap) -> OsuDifficultyAttributes {
+        let mut calculator = OsuStars::new(map)
+            .mods(self.mods)
+            .relax(self.relax)
+            .overrides(self.overrides);
+
+        if let Some(clock_rate) = self.clock_rate {
+            calculator = calculator.clock_rate(clock_rate);
+        }
+
+        calculator.calculate_with_buffers(&mut self.bufs)
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OsuPP;
+
+    #[test]
+    fn reused_buffers() {
+        let osu = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let catch = Beatmap::from_path("./maps/2118524.osu").unwrap();
+
+        let mut calculator = OsuDifficultyCalculator::new();
+
+        for map in [&osu, &catch, &osu] {
+            let expected = OsuStars::new(map).calculate();
+
+            assert_eq!(calculator.calculate(map), expected);
+        }
+    }
+
+    #[test]
+    fn reused_buffers_and_overrides() {
+        let osu = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let catch = Beatmap::from_path("./maps/2118524.osu").unwrap();
+
+        let mut calculator = OsuDifficultyCalculator::new()
+            .mods(8 + 64)
+            .ar(9.5)
+            .od(8.0)
+            .cs(5.0)
+            .hp(6.0);
+
+        for map in [&osu, &catch, &osu] {
+            let expected = OsuPP::new(map)
+                .mods(8 + 64)
+                .ar(9.5)
+                .od(8.0)
+                .cs(5.0)
+                .hp(6.0)
+                .calculate()
+                .difficulty;
+
+            assert_eq!(calculator.calculate(map), expected);
+        }
+    }
+}
@@ -261,6 +261,6 @@ impl Distances {
 }
 
 pub(crate) struct SliderValues<'s> {
-    lazy_travel_dist: f32,
-    slider: &'s OsuSlider,
+    pub(crate) lazy_travel_dist: f32,
+    pub(crate) slider: &'s OsuSlider,
 }
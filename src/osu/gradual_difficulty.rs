@@ -3,7 +3,7 @@ use std::{
     mem,
 };
 
-use crate::{curve::CurveBuffers, Beatmap, Mods};
+use crate::{curve::CurveBuffers, Beatmap, Mods, ATTRIBUTES_VERSION};
 
 use super::{
     difficulty_object::{Distances, OsuDifficultyObject},
@@ -11,8 +11,8 @@ use super::{
     osu_object::{ObjectParameters, OsuObject, OsuObjectKind},
     scaling_factor::ScalingFactor,
     skills::{OsuStrainSkill, Skills},
-    stacking, OsuDifficultyAttributes, DIFFICULTY_MULTIPLIER, FADE_IN_DURATION_MULTIPLIER,
-    PERFORMANCE_BASE_MULTIPLIER, PREEMPT_MIN,
+    stacking, star_rating, OsuDifficultyAttributes, DIFFICULTY_MULTIPLIER,
+    FADE_IN_DURATION_MULTIPLIER, PREEMPT_MIN,
 };
 
 /// Gradually calculate the difficulty attributes of an osu!standard map.
@@ -27,7 +27,7 @@ use super::{
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{Beatmap, osu::OsuGradualDifficultyAttributes};
+/// use murasame_pp::{Beatmap, osu::OsuGradualDifficultyAttributes};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -49,6 +49,7 @@ use super::{
 pub struct OsuGradualDifficultyAttributes {
     pub(crate) idx: usize,
     mods: u32,
+    relax: bool,
     attrs: OsuDifficultyAttributes,
     // Unused but `diff_objects`' lifetimes secretly depend on it
     #[allow(unused)]
@@ -72,11 +73,11 @@ impl OsuGradualDifficultyAttributes {
     /// Create a new difficulty attributes iterator for osu!standard maps.
     pub fn new(map: &Beatmap, mods: u32) -> Self {
         let clock_rate = mods.clock_rate();
-        let map_attrs = map.attributes().mods(mods).build();
-        let scaling_factor = ScalingFactor::new(map_attrs.cs);
+        let settings = map.attributes().mods(mods).settings();
+        let scaling_factor = ScalingFactor::new(settings.cs);
         let hr = mods.hr();
-        let hit_window = 2.0 * map_attrs.hit_windows.od;
-        let time_preempt = (map_attrs.hit_windows.ar * clock_rate) as f32 as f64;
+        let hit_window = 2.0 * settings.great_hit_window;
+        let time_preempt = (settings.preempt * clock_rate) as f32 as f64;
 
         // * Preempt time can go below 450ms. Normally, this is achieved via the DT mod
         // * which uniformly speeds up all animations game wide regardless of AR.
@@ -93,12 +94,12 @@ impl OsuGradualDifficultyAttributes {
         };
 
         let mut attrs = OsuDifficultyAttributes {
-            ar: map_attrs.ar,
-            hp: map_attrs.hp,
-            od: map_attrs.od,
+            version: ATTRIBUTES_VERSION,
             ..Default::default()
         };
 
+        attrs.set_settings(settings);
+
         let mut params = ObjectParameters {
             map,
             attrs: &mut attrs,
@@ -125,6 +126,7 @@ impl OsuGradualDifficultyAttributes {
             old_stacking(&mut hit_objects, stack_threshold);
         }
 
+        #[allow(clippy::manual_inspect)]
         let mut hit_objects_iter = hit_objects.iter_mut().map(|h| {
             h.post_process(hr, &scaling_factor);
 
@@ -145,6 +147,7 @@ impl OsuGradualDifficultyAttributes {
                 return Self {
                     idx: 0,
                     mods,
+                    relax: false,
                     attrs,
                     hit_objects: Vec::new(),
                     diff_objects: Vec::new(),
@@ -188,6 +191,7 @@ impl OsuGradualDifficultyAttributes {
         Self {
             idx: 0,
             mods,
+            relax: false,
             attrs,
             diff_objects: extend_lifetime(diff_objects),
             hit_objects,
@@ -195,6 +199,17 @@ impl OsuGradualDifficultyAttributes {
         }
     }
 
+    /// Specify whether the star rating should be calculated for relax leaderboards.
+    ///
+    /// Only affects attributes that are returned after the call.
+    /// See [`OsuStars::relax`](crate::OsuStars::relax).
+    #[inline]
+    pub fn relax(mut self, relax: bool) -> Self {
+        self.relax = relax;
+
+        self
+    }
+
     fn increment_combo(h: &OsuObject, attrs: &mut OsuDifficultyAttributes) {
         attrs.max_combo += 1;
 
@@ -233,10 +248,12 @@ impl Iterator for OsuGradualDifficultyAttributes {
             mut aim_no_sliders,
             mut speed,
             mut flashlight,
+            #[cfg(feature = "rhythm")]
+            mut rhythm,
         } = self.skills.clone();
 
         let mut aim_rating = aim.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
-        let aim_rating_no_sliders =
+        let mut aim_rating_no_sliders =
             aim_no_sliders.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
 
         let speed_notes = speed.relevant_note_count();
@@ -252,42 +269,35 @@ impl Iterator for OsuGradualDifficultyAttributes {
 
         if self.mods.td() {
             aim_rating = aim_rating.powf(0.8);
+            aim_rating_no_sliders = aim_rating_no_sliders.powf(0.8);
             flashlight_rating = flashlight_rating.powf(0.8);
         }
 
-        let base_aim_performance = (5.0 * (aim_rating / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
-        let base_speed_performance =
-            (5.0 * (speed_rating / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
-
-        let base_flashlight_performance = if self.mods.fl() {
-            flashlight_rating * flashlight_rating * 25.0
-        } else {
-            0.0
-        };
-
-        let base_performance = ((base_aim_performance).powf(1.1)
-            + (base_speed_performance).powf(1.1)
-            + (base_flashlight_performance).powf(1.1))
-        .powf(1.0 / 1.1);
-
-        let star_rating = if base_performance > 0.00001 {
-            PERFORMANCE_BASE_MULTIPLIER.cbrt()
-                * 0.027
-                * ((100_000.0 / 2.0_f64.powf(1.0 / 1.1) * base_performance).cbrt() + 4.0)
-        } else {
-            0.0
-        };
+        let star_rating = star_rating(
+            aim_rating,
+            speed_rating,
+            flashlight_rating,
+            self.mods,
+            self.relax,
+        );
 
-        let mut attrs = self.attrs.clone();
+        let mut attrs = self.attrs;
         attrs.aim = aim_rating;
+        attrs.aim_no_sliders = aim_rating_no_sliders;
         attrs.speed = speed_rating;
         attrs.flashlight = flashlight_rating;
         attrs.slider_factor = slider_factor;
+        attrs.aim_difficult_slider_count = aim.count_difficult_sliders();
         attrs.stars = star_rating;
         attrs.speed_note_count = speed_notes;
         attrs.aim_difficult_strain_count = aim.count_difficult_strains();
         attrs.speed_difficult_strain_count = speed.count_difficult_strains();
 
+        #[cfg(feature = "rhythm")]
+        {
+            attrs.rhythm_rating = rhythm.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+        }
+
         Some(attrs)
     }
 
@@ -1,4 +1,4 @@
-use crate::{Beatmap, OsuPP};
+use crate::{Beatmap, Grade, OsuPP};
 
 use super::{OsuGradualDifficultyAttributes, OsuPerformanceAttributes};
 
@@ -6,7 +6,8 @@ use super::{OsuGradualDifficultyAttributes, OsuPerformanceAttributes};
 /// maximum combo so far and what are the current hitresults.
 ///
 /// This struct is used for [`OsuGradualPerformanceAttributes`].
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsuScoreState {
     /// Maximum combo that the score has had so far.
     /// **Not** the maximum possible combo of the map so far.
@@ -48,6 +49,12 @@ impl OsuScoreState {
 
         numerator as f64 / denominator as f64
     }
+
+    /// Determine the grade of this state for the given mods.
+    #[inline]
+    pub fn grade(&self, mods: u32) -> Grade {
+        Grade::from_ratios(self.n300, self.n50, self.n_misses, self.total_hits(), mods)
+    }
 }
 
 /// Gradually calculate the performance attributes of an osu!standard map.
@@ -67,7 +74,7 @@ impl OsuScoreState {
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{Beatmap, osu::{OsuGradualPerformanceAttributes, OsuScoreState}};
+/// use murasame_pp::{Beatmap, osu::{OsuGradualPerformanceAttributes, OsuScoreState}};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -154,6 +161,17 @@ impl<'map> OsuGradualPerformanceAttributes<'map> {
         }
     }
 
+    /// Specify whether the star rating should be calculated for relax leaderboards.
+    ///
+    /// See [`OsuStars::relax`](crate::OsuStars::relax).
+    #[inline]
+    pub fn relax(mut self, relax: bool) -> Self {
+        self.difficulty = self.difficulty.relax(relax);
+        self.performance = self.performance.relax(relax);
+
+        self
+    }
+
     /// Process the next hit object and calculate the
     /// performance attributes for the resulting score state.
     pub fn process_next_object(
@@ -176,7 +194,6 @@ impl<'map> OsuGradualPerformanceAttributes<'map> {
     ) -> Option<OsuPerformanceAttributes> {
         let sub = (self.difficulty.idx == 0) as usize;
         let difficulty = self.difficulty.nth(n.saturating_sub(sub))?;
-
         let performance = self
             .performance
             .clone()
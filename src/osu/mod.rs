@@ -1,41 +1,67 @@
+mod calculator;
 mod difficulty_object;
 mod gradual_difficulty;
 mod gradual_performance;
-mod osu_object;
+pub(crate) mod osu_object;
 mod pp;
 mod scaling_factor;
 mod skills;
+mod tuning;
+
+use std::mem;
 
 use skills::OsuStrainSkill;
 
-use crate::{curve::CurveBuffers, parse::Pos2, AnyStars, Beatmap, GameMode, Mods};
+use crate::{
+    analysis::{SliderInfo, StackedObject},
+    beatmap::{DifficultyOverrides, DifficultySettings},
+    curve::CurveBuffers,
+    parse::Pos2,
+    util::{downsample, first_section_start, round_api},
+    AnyStars, Beatmap, GameMode, Mods, StrainAggregation, ATTRIBUTES_VERSION,
+};
 
 use self::{
     difficulty_object::{Distances, OsuDifficultyObject},
     osu_object::{ObjectParameters, OsuObject},
-    scaling_factor::ScalingFactor,
     skills::Skills,
 };
 
-pub use self::{gradual_difficulty::*, gradual_performance::*, pp::*};
+pub use self::{
+    calculator::*,
+    gradual_difficulty::*,
+    gradual_performance::*,
+    pp::*,
+    scaling_factor::*,
+    skills::{CustomSkill, SkillObject, SkillObjectKind, StrainState},
+    tuning::*,
+};
 
 const SECTION_LEN: f64 = 400.0;
 const DIFFICULTY_MULTIPLIER: f64 = 0.0675;
+/// The radius that all hit objects are scaled to in normalized units.
 // * Change radius to 50 to make 100 the diameter. Easier for mental maths.
-const NORMALIZED_RADIUS: f32 = 50.0;
+pub const NORMALIZED_RADIUS: f32 = 50.0;
 const STACK_DISTANCE: f32 = 3.0;
 // * This is being adjusted to keep the final pp value scaled around what it used to be when changing things.
 const PERFORMANCE_BASE_MULTIPLIER: f64 = 1.14;
 const PREEMPT_MIN: f64 = 450.0;
 const FADE_IN_DURATION_MULTIPLIER: f64 = 0.4;
-const PLAYFIELD_BASE_SIZE: Pos2 = Pos2 { x: 512.0, y: 384.0 };
+/// The size of the playfield in osu!pixels.
+pub const PLAYFIELD_BASE_SIZE: Pos2 = Pos2 { x: 512.0, y: 384.0 };
+// Relax players don't need to tap so speed only contributes a fraction to relax star ratings.
+const RELAX_SPEED_WEIGHT: f64 = 0.4;
+// Autopilot scores get no aim pp so the remaining values are penalized as a whole
+// while speed is slightly buffed since tapping is all that is left for the player.
+const AUTOPILOT_MULTIPLIER: f64 = 0.9;
+const AUTOPILOT_SPEED_BONUS: f64 = 1.1;
 
 /// Difficulty calculator on osu!standard maps.
 ///
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{OsuStars, Beatmap};
+/// use murasame_pp::{OsuStars, Beatmap};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -54,6 +80,8 @@ pub struct OsuStars<'map> {
     pub(crate) mods: u32,
     pub(crate) passed_objects: Option<usize>,
     pub(crate) clock_rate: Option<f64>,
+    pub(crate) relax: bool,
+    pub(crate) overrides: DifficultyOverrides,
 }
 
 impl<'map> OsuStars<'map> {
@@ -65,6 +93,8 @@ impl<'map> OsuStars<'map> {
             mods: 0,
             passed_objects: None,
             clock_rate: None,
+            relax: false,
+            overrides: DifficultyOverrides::default(),
         }
     }
 
@@ -111,73 +141,99 @@ impl<'map> OsuStars<'map> {
         self
     }
 
-    /// Calculate all difficulty related values, including stars.
+    /// Specify whether the star rating should be calculated for relax leaderboards.
+    ///
+    /// If enabled, the speed skill is de-weighted when combining the skills into
+    /// the star rating so that it focuses on aim. The individual skill ratings stay the same.
     #[inline]
-    pub fn calculate(self) -> OsuDifficultyAttributes {
-        let mods = self.mods;
+    pub fn relax(mut self, relax: bool) -> Self {
+        self.relax = relax;
 
-        let (skills, mut attrs) = calculate_skills(self);
+        self
+    }
 
-        let Skills {
-            mut aim,
-            mut aim_no_sliders,
-            mut speed,
-            mut flashlight,
-        } = skills;
+    /// Override the map's difficulty settings, e.g. for the Difficulty Adjust mod.
+    #[inline]
+    pub(crate) fn overrides(mut self, overrides: DifficultyOverrides) -> Self {
+        self.overrides = overrides;
 
-        let mut aim_rating = aim.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
-        let aim_rating_no_sliders =
-            aim_no_sliders.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+        self
+    }
 
-        let speed_notes = speed.relevant_note_count();
-        let speed_rating = speed.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    /// Calculate all difficulty related values, including stars.
+    #[inline]
+    pub fn calculate(self) -> OsuDifficultyAttributes {
+        self.calculate_with_buffers(&mut ScratchBuffers::default())
+    }
 
-        let mut flashlight_rating = flashlight.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    pub(crate) fn calculate_with_buffers(
+        self,
+        bufs: &mut ScratchBuffers,
+    ) -> OsuDifficultyAttributes {
+        let mods = self.mods;
+        let relax = self.relax;
 
-        let slider_factor = if aim_rating > 0.0 {
-            aim_rating_no_sliders / aim_rating
-        } else {
-            1.0
-        };
+        let (skills, attrs) = calculate_skills(self, bufs);
 
-        if mods.td() {
-            aim_rating = aim_rating.powf(0.8);
-            flashlight_rating = flashlight_rating.powf(0.8);
-        }
-        
-        let base_aim_performance = (5.0 * (aim_rating / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
-        let base_speed_performance =
-            (5.0 * (speed_rating / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
+        difficulty_attributes(skills, attrs, mods, relax)
+    }
 
-        let base_flashlight_performance = if mods.fl() {
-            flashlight_rating * flashlight_rating * 25.0
-        } else {
-            0.0
-        };
+    /// Calculate the difficulty attributes while also processing the given custom skills.
+    ///
+    /// The custom skills receive the same objects as the built-in skills
+    /// but do not affect the returned attributes.
+    /// See [`CustomSkill`] for an example.
+    pub fn calculate_with_skills(
+        self,
+        custom_skills: &mut [&mut dyn CustomSkill],
+    ) -> OsuDifficultyAttributes {
+        let mods = self.mods;
+        let relax = self.relax;
+        let mut objects = Vec::with_capacity(self.map.hit_objects.len().saturating_sub(1));
 
-        let base_performance = ((base_aim_performance).powf(1.1)
-            + (base_speed_performance).powf(1.1)
-            + (base_flashlight_performance).powf(1.1))
-        .powf(1.0 / 1.1);
+        let (skills, attrs) =
+            calculate_skills_with(self, &mut ScratchBuffers::default(), |curr, _| {
+                objects.push(SkillObject::from(curr));
 
-        let star_rating = if base_performance > 0.00001 {
-            PERFORMANCE_BASE_MULTIPLIER.cbrt()
-                * 0.027
-                * ((100_000.0 / 2.0_f64.powf(1.0 / 1.1) * base_performance).cbrt() + 4.0)
-        } else {
-            0.0
-        };
+                if let Some((curr, previous)) = objects.split_last() {
+                    for skill in custom_skills.iter_mut() {
+                        skill.process(curr, previous);
+                    }
+                }
+            });
 
-        attrs.aim = aim_rating;
-        attrs.speed = speed_rating;
-        attrs.flashlight = flashlight_rating;
-        attrs.slider_factor = slider_factor;
-        attrs.stars = star_rating;
-        attrs.speed_note_count = speed_notes;
-        attrs.aim_difficult_strain_count = aim.count_difficult_strains();
-        attrs.speed_difficult_strain_count = speed.count_difficult_strains();
+        difficulty_attributes(skills, attrs, mods, relax)
+    }
+
+    /// Calculate the difficulty attributes both without and with the TD mod.
+    ///
+    /// The first attributes are calculated without TD, the second ones with TD,
+    /// regardless of whether the specified mods contain TD.
+    /// Both share the same skill processing so this is cheaper than calculating them separately,
+    /// e.g. to cache attributes for touchscreen leaderboards.
+    #[inline]
+    pub fn calculate_with_touch_device(
+        mut self,
+    ) -> (OsuDifficultyAttributes, OsuDifficultyAttributes) {
+        self.mods &= !u32::TD;
+        let mods = self.mods;
+        let relax = self.relax;
+
+        let regular = self.calculate();
+
+        let mut touch_device = regular;
+        touch_device.aim = regular.aim.powf(0.8);
+        touch_device.aim_no_sliders = regular.aim_no_sliders.powf(0.8);
+        touch_device.flashlight = regular.flashlight.powf(0.8);
+        touch_device.stars = star_rating(
+            touch_device.aim,
+            touch_device.speed,
+            touch_device.flashlight,
+            mods,
+            relax,
+        );
 
-        attrs
+        (regular, touch_device)
     }
 
     /// Calculate the skill strains.
@@ -185,31 +241,113 @@ impl<'map> OsuStars<'map> {
     /// Suitable to plot the difficulty of a map over time.
     #[inline]
     pub fn strains(self) -> OsuStrains {
-        let (skills, _) = calculate_skills(self);
+        let mut first_start_time = None;
+
+        let (skills, _) = calculate_skills_with(self, &mut ScratchBuffers::default(), |curr, _| {
+            first_start_time.get_or_insert(curr.start_time);
+        });
 
         let Skills {
             aim,
             aim_no_sliders,
             speed,
             flashlight,
+            ..
         } = skills;
 
         OsuStrains {
             section_len: SECTION_LEN,
+            start_time: first_section_start(first_start_time, SECTION_LEN),
             aim: aim.strain_peaks,
             aim_no_sliders: aim_no_sliders.strain_peaks,
             speed: speed.strain_peaks,
             flashlight: flashlight.strain_peaks,
         }
     }
+
+    /// Calculate the strain of each hit object in all skills.
+    ///
+    /// The first hit object does not generate a strain so it is skipped.
+    /// Suitable to find the hardest parts of a map or to display per-object overlays.
+    ///
+    /// ```
+    /// use murasame_pp::{Beatmap, OsuStars};
+    ///
+    /// # /*
+    /// let map: Beatmap = ...
+    /// # */
+    /// # let map = Beatmap::default();
+    /// let hardest_aim = OsuStars::new(&map)
+    ///     .object_difficulties()
+    ///     .max_by(|a, b| a.aim.total_cmp(&b.aim));
+    ///
+    /// if let Some(object) = hardest_aim {
+    ///     println!("Hardest aim at {}ms: {:.2}", object.start_time, object.aim);
+    /// }
+    /// ```
+    pub fn object_difficulties(self) -> std::vec::IntoIter<OsuObjectDifficulty> {
+        let mut difficulties = Vec::with_capacity(self.map.hit_objects.len().saturating_sub(1));
+
+        calculate_skills_with(self, &mut ScratchBuffers::default(), |curr, skills| {
+            let last_strain = |strains: &Vec<f64>| strains.last().copied().unwrap_or(0.0);
+
+            difficulties.push(OsuObjectDifficulty {
+                idx: curr.idx + 1,
+                start_time: curr.start_time,
+                aim: last_strain(skills.aim.strains()),
+                aim_no_sliders: last_strain(skills.aim_no_sliders.strains()),
+                speed: last_strain(skills.speed.strains()),
+                flashlight: skills.flashlight.curr_strain(),
+            });
+        });
+
+        difficulties.into_iter()
+    }
+}
+
+/// Count the maximum combo by only creating the hit objects and their nested objects.
+pub(crate) fn max_combo(map: &Beatmap) -> usize {
+    let mut attrs = OsuDifficultyAttributes::default();
+
+    let mut params = ObjectParameters {
+        map,
+        attrs: &mut attrs,
+        ticks: Vec::new(),
+        curve_bufs: CurveBuffers::default(),
+    };
+
+    for h in map.hit_objects.iter() {
+        OsuObject::new(h, &mut params);
+    }
+
+    attrs.max_combo
+}
+
+/// The latest end time of all hit objects without adjusting to the clock rate.
+pub(crate) fn last_object_end_time(map: &Beatmap) -> f64 {
+    let mut attrs = OsuDifficultyAttributes::default();
+
+    let mut params = ObjectParameters {
+        map,
+        attrs: &mut attrs,
+        ticks: Vec::new(),
+        curve_bufs: CurveBuffers::default(),
+    };
+
+    map.hit_objects.iter().fold(0.0, |end_time, h| {
+        end_time.max(OsuObject::new(h, &mut params).end_time())
+    })
 }
 
 /// The result of calculating the strains on a osu! map.
 /// Suitable to plot the difficulty of a map over time.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsuStrains {
     /// Time in ms inbetween two strains.
     pub section_len: f64, // TODO: remove field, make it a method
+    /// Start time in ms of the first strain peak's section, adjusted by the clock rate.
+    pub start_time: f64,
     /// Strain peaks of the aim skill.
     pub aim: Vec<f64>,
     /// Strain peaks of the aim skill without sliders.
@@ -227,24 +365,230 @@ impl OsuStrains {
     pub fn len(&self) -> usize {
         self.aim.len()
     }
+
+    /// Merge the strain peaks into exactly `n_buckets` points, e.g. for a fixed-width graph.
+    ///
+    /// The `section_len` of the result is adjusted so that the `i`-th point
+    /// still starts at `start_time + i * section_len` ms.
+    pub fn downsample(&self, n_buckets: usize, aggregation: StrainAggregation) -> Self {
+        let section_len = if n_buckets > 0 {
+            self.section_len * self.len() as f64 / n_buckets as f64
+        } else {
+            self.section_len
+        };
+
+        Self {
+            section_len,
+            start_time: self.start_time,
+            aim: downsample(&self.aim, n_buckets, aggregation),
+            aim_no_sliders: downsample(&self.aim_no_sliders, n_buckets, aggregation),
+            speed: downsample(&self.speed, n_buckets, aggregation),
+            flashlight: downsample(&self.flashlight, n_buckets, aggregation),
+        }
+    }
+}
+
+/// The strain of a single hit object in each skill of an osu!standard map.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct OsuObjectDifficulty {
+    /// The index of the hit object within the map.
+    pub idx: usize,
+    /// The start time of the hit object in ms, adjusted by the clock rate.
+    pub start_time: f64,
+    /// The aim strain after processing the hit object.
+    pub aim: f64,
+    /// The aim strain without sliders after processing the hit object.
+    pub aim_no_sliders: f64,
+    /// The speed strain after processing the hit object.
+    pub speed: f64,
+    /// The flashlight strain after processing the hit object.
+    pub flashlight: f64,
 }
 
-fn calculate_skills(params: OsuStars<'_>) -> (Skills, OsuDifficultyAttributes) {
+fn difficulty_attributes(
+    skills: Skills,
+    mut attrs: OsuDifficultyAttributes,
+    mods: u32,
+    relax: bool,
+) -> OsuDifficultyAttributes {
+    let Skills {
+        mut aim,
+        mut aim_no_sliders,
+        mut speed,
+        mut flashlight,
+        #[cfg(feature = "rhythm")]
+        mut rhythm,
+    } = skills;
+
+    let mut aim_rating = aim.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    let mut aim_rating_no_sliders =
+        aim_no_sliders.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+
+    let speed_notes = speed.relevant_note_count();
+    let speed_rating = speed.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+
+    let mut flashlight_rating = flashlight.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+
+    let slider_factor = if aim_rating > 0.0 {
+        aim_rating_no_sliders / aim_rating
+    } else {
+        1.0
+    };
+
+    if mods.td() {
+        aim_rating = aim_rating.powf(0.8);
+        aim_rating_no_sliders = aim_rating_no_sliders.powf(0.8);
+        flashlight_rating = flashlight_rating.powf(0.8);
+    }
+
+    let star_rating = star_rating(aim_rating, speed_rating, flashlight_rating, mods, relax);
+
+    attrs.aim = aim_rating;
+    attrs.aim_no_sliders = aim_rating_no_sliders;
+    attrs.speed = speed_rating;
+    attrs.flashlight = flashlight_rating;
+    attrs.slider_factor = slider_factor;
+    attrs.aim_difficult_slider_count = aim.count_difficult_sliders();
+    attrs.stars = star_rating;
+    attrs.speed_note_count = speed_notes;
+    attrs.aim_difficult_strain_count = aim.count_difficult_strains();
+    attrs.speed_difficult_strain_count = speed.count_difficult_strains();
+
+    #[cfg(feature = "rhythm")]
+    {
+        attrs.rhythm_rating = rhythm.difficulty_value().sqrt() * DIFFICULTY_MULTIPLIER;
+    }
+
+    attrs
+}
+
+fn star_rating(
+    aim_rating: f64,
+    speed_rating: f64,
+    flashlight_rating: f64,
+    mods: u32,
+    relax: bool,
+) -> f64 {
+    let speed_rating = if relax {
+        speed_rating * RELAX_SPEED_WEIGHT
+    } else {
+        speed_rating
+    };
+
+    let base_aim_performance = (5.0 * (aim_rating / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
+    let base_speed_performance = (5.0 * (speed_rating / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
+
+    let base_flashlight_performance = if mods.fl() {
+        flashlight_rating * flashlight_rating * 25.0
+    } else {
+        0.0
+    };
+
+    let base_performance = ((base_aim_performance).powf(1.1)
+        + (base_speed_performance).powf(1.1)
+        + (base_flashlight_performance).powf(1.1))
+    .powf(1.0 / 1.1);
+
+    if base_performance > 0.00001 {
+        PERFORMANCE_BASE_MULTIPLIER.cbrt()
+            * 0.027
+            * ((100_000.0 / 2.0_f64.powf(1.0 / 1.1) * base_performance).cbrt() + 4.0)
+    } else {
+        0.0
+    }
+}
+
+/// Buffers that can be reused across multiple difficulty calculations.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ScratchBuffers {
+    ticks: Vec<(Pos2, f64)>,
+    curve_bufs: CurveBuffers,
+}
+
+fn calculate_skills(
+    params: OsuStars<'_>,
+    bufs: &mut ScratchBuffers,
+) -> (Skills, OsuDifficultyAttributes) {
+    calculate_skills_with(params, bufs, |_, _| {})
+}
+
+/// Per-object values of an osu!standard difficulty calculation.
+#[cfg(feature = "export")]
+#[derive(Clone, Debug)]
+pub(crate) struct ObjectFeatures {
+    pub(crate) idx: usize,
+    pub(crate) start_time: f64,
+    pub(crate) delta_time: f64,
+    pub(crate) strain_time: f64,
+    pub(crate) jump_dist: f64,
+    pub(crate) travel_dist: f64,
+    pub(crate) angle: Option<f64>,
+    pub(crate) aim_strain: f64,
+    pub(crate) speed_strain: f64,
+    pub(crate) flashlight_strain: f64,
+}
+
+/// Calculate the difficulty attributes and collect the features of each difficulty object.
+#[cfg(feature = "export")]
+pub(crate) fn object_features(
+    params: OsuStars<'_>,
+) -> (OsuDifficultyAttributes, Vec<ObjectFeatures>) {
+    let mods = params.mods;
+    let relax = params.relax;
+    let mut features = Vec::with_capacity(params.map.hit_objects.len());
+
+    let (skills, attrs) =
+        calculate_skills_with(params, &mut ScratchBuffers::default(), |curr, skills| {
+            features.push(ObjectFeatures {
+                idx: curr.idx + 1,
+                start_time: curr.start_time,
+                delta_time: curr.delta_time,
+                strain_time: curr.strain_time,
+                jump_dist: curr.dists.lazy_jump_dist,
+                travel_dist: curr.dists.travel_dist,
+                angle: curr.dists.angle,
+                aim_strain: skills.aim.strains().last().copied().unwrap_or(0.0),
+                speed_strain: skills.speed.strains().last().copied().unwrap_or(0.0),
+                flashlight_strain: skills.flashlight.curr_strain(),
+            })
+        });
+
+    (difficulty_attributes(skills, attrs, mods, relax), features)
+}
+
+/// Hit objects of an osu!standard map after applying stacking and mods.
+struct OsuObjects {
+    hit_objects: Vec<OsuObject>,
+    attrs: OsuDifficultyAttributes,
+    scaling_factor: ScalingFactor,
+    clock_rate: f64,
+    hit_window: f64,
+    time_preempt: f64,
+    time_fade_in: f64,
+}
+
+fn prepare_objects(params: OsuStars<'_>, bufs: &mut ScratchBuffers) -> OsuObjects {
     let OsuStars {
         map,
         mods,
         passed_objects,
         clock_rate,
+        relax: _,
+        overrides,
     } = params;
 
     let take = passed_objects.unwrap_or(map.hit_objects.len());
-    let clock_rate = clock_rate.unwrap_or_else(|| mods.clock_rate());
-
-    let map_attrs = map.attributes().mods(mods).clock_rate(clock_rate).build();
-    let scaling_factor = ScalingFactor::new(map_attrs.cs);
+    let clock_rate = overrides.clock_rate(clock_rate, mods);
+
+    let settings = overrides
+        .attributes(map)
+        .mods(mods)
+        .clock_rate(clock_rate)
+        .settings();
+    let scaling_factor = ScalingFactor::new(settings.cs);
     let hr = mods.hr();
-    let hit_window = 2.0 * map_attrs.hit_windows.od;
-    let time_preempt = (map_attrs.hit_windows.ar * clock_rate) as f32 as f64;
+    let hit_window = 2.0 * settings.great_hit_window;
+    let time_preempt = (settings.preempt * clock_rate) as f32 as f64;
 
     // * Preempt time can go below 450ms. Normally, this is achieved via the DT mod
     // * which uniformly speeds up all animations game wide regardless of AR.
@@ -261,17 +605,17 @@ fn calculate_skills(params: OsuStars<'_>) -> (Skills, OsuDifficultyAttributes) {
     };
 
     let mut attrs = OsuDifficultyAttributes {
-        ar: map_attrs.ar,
-        hp: map_attrs.hp,
-        od: map_attrs.od,
+        version: ATTRIBUTES_VERSION,
         ..Default::default()
     };
 
+    attrs.set_settings(settings);
+
     let mut params = ObjectParameters {
         map,
         attrs: &mut attrs,
-        ticks: Vec::new(),
-        curve_bufs: CurveBuffers::default(),
+        ticks: mem::take(&mut bufs.ticks),
+        curve_bufs: mem::take(&mut bufs.curve_bufs),
     };
 
     let mut hit_objects: Vec<_> = map
@@ -281,6 +625,9 @@ fn calculate_skills(params: OsuStars<'_>) -> (Skills, OsuDifficultyAttributes) {
         .map(|h| OsuObject::new(h, &mut params))
         .collect();
 
+    bufs.ticks = params.ticks;
+    bufs.curve_bufs = params.curve_bufs;
+
     let stack_threshold = time_preempt * map.stack_leniency as f64;
 
     if map.version >= 6 {
@@ -289,11 +636,87 @@ fn calculate_skills(params: OsuStars<'_>) -> (Skills, OsuDifficultyAttributes) {
         old_stacking(&mut hit_objects, stack_threshold);
     }
 
-    let mut hit_objects = hit_objects.iter_mut().map(|h| {
+    for h in hit_objects.iter_mut() {
         h.post_process(hr, &scaling_factor);
+    }
+
+    OsuObjects {
+        hit_objects,
+        attrs,
+        scaling_factor,
+        clock_rate,
+        hit_window,
+        time_preempt,
+        time_fade_in,
+    }
+}
+
+/// Compute the travel values of each slider like the difficulty calculation does.
+pub(crate) fn slider_infos(params: OsuStars<'_>) -> Vec<SliderInfo> {
+    let OsuObjects {
+        mut hit_objects,
+        scaling_factor,
+        clock_rate,
+        ..
+    } = prepare_objects(params, &mut ScratchBuffers::default());
+
+    hit_objects
+        .iter_mut()
+        .filter_map(|h| {
+            let start_time = h.start_time / clock_rate;
+            let duration = (h.end_time() - h.start_time) / clock_rate;
+            let travel_time =
+                (h.lazy_travel_time() / clock_rate).max(OsuDifficultyObject::MIN_DELTA_TIME as f64);
+
+            let slider = Distances::compute_slider_cursor_pos(h, &scaling_factor)?;
+            let travel_dist = slider.lazy_travel_dist as f64;
+
+            Some(SliderInfo {
+                start_time,
+                duration,
+                repeats: slider.slider.repeat_count(),
+                travel_dist,
+                travel_time,
+                velocity: travel_dist / travel_time,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn stacked_objects(params: OsuStars<'_>) -> Vec<StackedObject> {
+    let OsuObjects { hit_objects, .. } = prepare_objects(params, &mut ScratchBuffers::default());
+
+    hit_objects
+        .iter()
+        .map(|h| StackedObject {
+            pos: h.pos(),
+            stack_height: h.stack_height,
+            stacked_pos: h.stacked_pos(),
+        })
+        .collect()
+}
 
-        h
-    });
+/// Calculate the skills while calling `inspect` after each processed difficulty object.
+fn calculate_skills_with(
+    params: OsuStars<'_>,
+    bufs: &mut ScratchBuffers,
+    mut inspect: impl FnMut(&OsuDifficultyObject<'_>, &Skills),
+) -> (Skills, OsuDifficultyAttributes) {
+    profile!(Skills);
+
+    let mods = params.mods;
+
+    let OsuObjects {
+        mut hit_objects,
+        attrs,
+        scaling_factor,
+        clock_rate,
+        hit_window,
+        time_preempt,
+        time_fade_in,
+    } = prepare_objects(params, bufs);
+
+    let mut hit_objects = hit_objects.iter_mut();
 
     let mut skills = Skills::new(
         mods,
@@ -340,6 +763,7 @@ fn calculate_skills(params: OsuStars<'_>) -> (Skills, OsuDifficultyAttributes) {
 
     for curr in diff_objects.iter() {
         skills.process(curr, &diff_objects);
+        inspect(curr, &skills);
     }
 
     (skills, attrs)
@@ -496,7 +920,8 @@ fn old_stacking(hit_objects: &mut [OsuObject], stack_threshold: f64) {
 }
 
 /// The result of a difficulty calculation on an osu!standard map.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsuDifficultyAttributes {
     /// The aim portion of the total strain.
     pub aim: f64,
@@ -504,20 +929,42 @@ pub struct OsuDifficultyAttributes {
     pub speed: f64,
     /// The flashlight portion of the total strain.
     pub flashlight: f64,
+    /// The aim portion of the total strain without considering sliders.
+    pub aim_no_sliders: f64,
     /// The ratio of the aim strain with and without considering sliders
     pub slider_factor: f64,
+    /// The number of sliders weighted by difficulty.
+    pub aim_difficult_slider_count: f64,
     /// The number of difficult aim strains.
+    ///
+    /// Each object's strain is weighted by how close it is to the strain of a map
+    /// whose strains were all identical, so consistently hard maps have a higher count
+    /// than maps with a single spike. Used to scale the aim miss penalty.
     pub aim_difficult_strain_count: f64,
     /// The number of difficult speed strains.
+    ///
+    /// Same as [`aim_difficult_strain_count`](Self::aim_difficult_strain_count)
+    /// but for speed. Used to scale the speed miss penalty.
     pub speed_difficult_strain_count: f64,
     /// The number of clickable objects weighted by difficulty.
     pub speed_note_count: f64,
+    /// The rhythm complexity of the map, independent of aim and speed.
+    ///
+    /// Experimental and not used for the star rating or pp.
+    /// Only calculated with the `rhythm` feature, `0.0` otherwise.
+    pub rhythm_rating: f64,
     /// The approach rate.
     pub ar: f64,
     /// The overall difficulty
     pub od: f64,
     /// The health drain rate.
     pub hp: f64,
+    /// The hit window for a 300 ("Great") in milliseconds, adjusted to the clock rate.
+    pub great_hit_window: f64,
+    /// The hit window for a 100 ("Ok") in milliseconds, adjusted to the clock rate.
+    pub ok_hit_window: f64,
+    /// The hit window for a 50 ("Meh") in milliseconds, adjusted to the clock rate.
+    pub meh_hit_window: f64,
     /// The amount of circles.
     pub n_circles: usize,
     /// The amount of sliders.
@@ -528,18 +975,89 @@ pub struct OsuDifficultyAttributes {
     pub stars: f64,
     /// The maximum combo.
     pub max_combo: usize,
+    /// The difficulty settings after applying mods.
+    pub settings: DifficultySettings,
+    /// The [`ATTRIBUTES_VERSION`](crate::ATTRIBUTES_VERSION) the attributes were calculated with.
+    ///
+    /// `0` if the attributes were not calculated by this crate.
+    pub version: u32,
 }
 
 impl OsuDifficultyAttributes {
+    /// Store the settings alongside the AR, OD, HP, and hit windows that are taken from them.
+    pub(crate) fn set_settings(&mut self, settings: DifficultySettings) {
+        self.ar = settings.ar;
+        self.od = settings.od;
+        self.hp = settings.hp;
+        self.great_hit_window = settings.great_hit_window;
+        self.ok_hit_window = settings.ok_hit_window;
+        self.meh_hit_window = settings.meh_hit_window;
+        self.settings = settings;
+    }
+
+    /// Round all floating point values to the precision of the osu!api
+    /// so that they can be compared to or stored alongside official values.
+    ///
+    /// Ties are rounded to the nearest even digit like osu!lazer does.
+    pub fn api_rounded(mut self) -> Self {
+        self.aim = round_api(self.aim);
+        self.speed = round_api(self.speed);
+        self.flashlight = round_api(self.flashlight);
+        self.aim_no_sliders = round_api(self.aim_no_sliders);
+        self.slider_factor = round_api(self.slider_factor);
+        self.aim_difficult_slider_count = round_api(self.aim_difficult_slider_count);
+        self.aim_difficult_strain_count = round_api(self.aim_difficult_strain_count);
+        self.speed_difficult_strain_count = round_api(self.speed_difficult_strain_count);
+        self.speed_note_count = round_api(self.speed_note_count);
+        self.ar = round_api(self.ar);
+        self.od = round_api(self.od);
+        self.hp = round_api(self.hp);
+        self.great_hit_window = round_api(self.great_hit_window);
+        self.ok_hit_window = round_api(self.ok_hit_window);
+        self.meh_hit_window = round_api(self.meh_hit_window);
+        self.stars = round_api(self.stars);
+        self.settings = self.settings.api_rounded();
+        self.rhythm_rating = round_api(self.rhythm_rating);
+
+        self
+    }
+
     /// Return the maximum combo.
     #[inline]
     pub fn max_combo(&self) -> usize {
         self.max_combo
     }
+
+    /// Return the amount of hit objects i.e. circles, sliders, and spinners.
+    ///
+    /// Each hit object receives one hitresult.
+    #[inline]
+    pub fn n_objects(&self) -> usize {
+        self.n_circles + self.n_sliders + self.n_spinners
+    }
+
+    /// Return the amount of circles.
+    #[inline]
+    pub fn n_circles(&self) -> usize {
+        self.n_circles
+    }
+
+    /// Return the amount of sliders.
+    #[inline]
+    pub fn n_sliders(&self) -> usize {
+        self.n_sliders
+    }
+
+    /// Return the amount of spinners.
+    #[inline]
+    pub fn n_spinners(&self) -> usize {
+        self.n_spinners
+    }
 }
 
 /// The result of a performance calculation on an osu!standard map.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OsuPerformanceAttributes {
     /// The difficulty attributes that were used for the performance calculation
     pub difficulty: OsuDifficultyAttributes,
@@ -555,9 +1073,37 @@ pub struct OsuPerformanceAttributes {
     pub pp_speed: f64,
     /// Misses including an approximated amount of slider breaks
     pub effective_miss_count: f64,
+    /// Estimated standard deviation of the hit errors on speed notes in milliseconds.
+    ///
+    /// `None` if the score does not contain any successful hits.
+    pub speed_deviation: Option<f64>,
+    /// Standard deviation of the specified hit errors in milliseconds, adjusted to the clock rate.
+    ///
+    /// `None` if no hit errors were specified.
+    pub hit_error_deviation: Option<f64>,
+    /// The hitresults and combo that were used for the performance calculation.
+    pub state: OsuScoreState,
 }
 
 impl OsuPerformanceAttributes {
+    /// Round all floating point values, including the difficulty attributes,
+    /// to the precision of the osu!api.
+    ///
+    /// See [`OsuDifficultyAttributes::api_rounded`].
+    pub fn api_rounded(mut self) -> Self {
+        self.difficulty = self.difficulty.api_rounded();
+        self.pp = round_api(self.pp);
+        self.pp_acc = round_api(self.pp_acc);
+        self.pp_aim = round_api(self.pp_aim);
+        self.pp_flashlight = round_api(self.pp_flashlight);
+        self.pp_speed = round_api(self.pp_speed);
+        self.effective_miss_count = round_api(self.effective_miss_count);
+        self.speed_deviation = self.speed_deviation.map(round_api);
+        self.hit_error_deviation = self.hit_error_deviation.map(round_api);
+
+        self
+    }
+
     /// Return the star value.
     #[inline]
     pub fn stars(&self) -> f64 {
@@ -575,6 +1121,14 @@ impl OsuPerformanceAttributes {
     pub fn max_combo(&self) -> usize {
         self.difficulty.max_combo
     }
+
+    /// Return the estimated unstable rate i.e. ten times the speed deviation.
+    ///
+    /// `None` if the score does not contain any successful hits.
+    #[inline]
+    pub fn estimated_unstable_rate(&self) -> Option<f64> {
+        self.speed_deviation.map(|deviation| deviation * 10.0)
+    }
 }
 
 impl From<OsuPerformanceAttributes> for OsuDifficultyAttributes {
@@ -1,9 +1,19 @@
+use std::{
+    f64::consts::{PI, SQRT_2},
+    sync::Arc,
+};
+
 use super::{
-    OsuDifficultyAttributes, OsuPerformanceAttributes, OsuScoreState, PERFORMANCE_BASE_MULTIPLIER,
+    DefaultModMultipliers, ModMultiplierInput, ModMultipliers, OsuDifficultyAttributes,
+    OsuPerformanceAttributes, OsuScoreState, PpTuning,
 };
 use crate::{
-    AnyPP, Beatmap, DifficultyAttributes, GameMode, HitResultPriority, Mods, OsuStars,
-    PerformanceAttributes,
+    beatmap::{DifficultyOverrides, DifficultySettings},
+    invalid_score,
+    pp::combo_range,
+    util::{erf, erf_inv, FloatExt},
+    AnyPP, AttributeProvider, Beatmap, CustomMod, GameMode, HitResultPriority, InvalidScore, Mods,
+    OsuStars,
 };
 
 /// Performance calculator on osu!standard maps.
@@ -11,7 +21,7 @@ use crate::{
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{OsuPP, Beatmap};
+/// use murasame_pp::{OsuPP, Beatmap};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -41,6 +51,15 @@ pub struct OsuPP<'map> {
     pub(crate) map: &'map Beatmap,
     pub(crate) attributes: Option<OsuDifficultyAttributes>,
     pub(crate) mods: u32,
+    pub(crate) blinds: bool,
+    pub(crate) relax: bool,
+    pub(crate) hit_errors: Option<Vec<f64>>,
+    pub(crate) hit_windows: Option<[f64; 3]>,
+    pub(crate) slider_breaks: Option<usize>,
+    pub(crate) slider_tick_misses: Option<usize>,
+    pub(crate) slider_end_misses: Option<usize>,
+    pub(crate) tuning: PpTuning,
+    pub(crate) mod_multipliers: Option<Arc<dyn ModMultipliers>>,
     pub(crate) acc: Option<f64>,
     pub(crate) combo: Option<usize>,
 
@@ -48,8 +67,11 @@ pub struct OsuPP<'map> {
     pub(crate) n100: Option<usize>,
     pub(crate) n50: Option<usize>,
     pub(crate) n_misses: Option<usize>,
+    pub(crate) n_geki: Option<usize>,
+    pub(crate) n_katu: Option<usize>,
     pub(crate) passed_objects: Option<usize>,
     pub(crate) clock_rate: Option<f64>,
+    pub(crate) overrides: DifficultyOverrides,
     pub(crate) hitresult_priority: Option<HitResultPriority>,
 }
 
@@ -61,6 +83,15 @@ impl<'map> OsuPP<'map> {
             map,
             attributes: None,
             mods: 0,
+            blinds: false,
+            relax: false,
+            hit_errors: None,
+            hit_windows: None,
+            slider_breaks: None,
+            slider_tick_misses: None,
+            slider_end_misses: None,
+            tuning: PpTuning::default(),
+            mod_multipliers: None,
             acc: None,
             combo: None,
 
@@ -68,8 +99,11 @@ impl<'map> OsuPP<'map> {
             n100: None,
             n50: None,
             n_misses: None,
+            n_geki: None,
+            n_katu: None,
             passed_objects: None,
             clock_rate: None,
+            overrides: DifficultyOverrides::default(),
             hitresult_priority: None,
         }
     }
@@ -115,6 +149,105 @@ impl<'map> OsuPP<'map> {
         self
     }
 
+    /// Specify the multipliers and exponents of the calculation.
+    ///
+    /// Only needed for customized formulas, see [`PpTuning`].
+    #[inline]
+    pub fn tuning(mut self, tuning: PpTuning) -> Self {
+        self.tuning = tuning;
+
+        self
+    }
+
+    /// Specify the strategy for the NF, SO, and RX multipliers.
+    ///
+    /// Only needed for customized formulas, see [`ModMultipliers`].
+    #[inline]
+    pub fn mod_multipliers(mut self, mod_multipliers: Arc<dyn ModMultipliers>) -> Self {
+        self.mod_multipliers = Some(mod_multipliers);
+
+        self
+    }
+
+    /// Specify whether the score was set with the Blinds mod.
+    ///
+    /// Blinds has no bit value so it must be specified separately.
+    /// If enabled, Blinds replaces the Hidden bonuses for aim, speed, and accuracy.
+    #[inline]
+    pub fn blinds(mut self, blinds: bool) -> Self {
+        self.blinds = blinds;
+
+        self
+    }
+
+    /// Specify whether the star rating should be calculated for relax leaderboards.
+    ///
+    /// Only applies if no attributes were specified, see [`OsuStars::relax`].
+    #[inline]
+    pub fn relax(mut self, relax: bool) -> Self {
+        self.relax = relax;
+
+        self
+    }
+
+    /// Specify the hit error of each hit object in milliseconds, e.g. retrieved from a replay.
+    ///
+    /// The hit errors should be unaffected by the clock rate i.e. as they are stored in a replay.
+    ///
+    /// **Experimental:** If specified, the accuracy pp will be based on the deviation
+    /// of the hit errors instead of the hitresults.
+    #[inline]
+    pub fn hit_errors(mut self, hit_errors: impl Into<Vec<f64>>) -> Self {
+        self.hit_errors = Some(hit_errors.into());
+
+        self
+    }
+
+    /// Override the hit windows for 300s, 100s, and 50s in milliseconds.
+    ///
+    /// The windows are used as they are, i.e. they are neither affected by
+    /// mods nor by the clock rate. The overall difficulty of the attributes
+    /// is adjusted to match the window for 300s.
+    #[inline]
+    pub fn hit_windows(mut self, great: f64, ok: f64, meh: f64) -> Self {
+        self.hit_windows = Some([great, ok, meh]);
+
+        self
+    }
+
+    /// Specify the amount of slider breaks, e.g. retrieved from a replay.
+    ///
+    /// If specified, the effective miss count will be the sum of misses and slider breaks
+    /// instead of being guessed from the combo. The sum is clamped to the amount of hits.
+    #[inline]
+    pub fn slider_breaks(mut self, slider_breaks: usize) -> Self {
+        self.slider_breaks = Some(slider_breaks);
+
+        self
+    }
+
+    /// Specify the amount of missed slider ticks and repeats, e.g. retrieved from a replay.
+    ///
+    /// Only affects scores with ScoreV2, see [`Mods::V2`](crate::Mods::V2).
+    /// If none is specified, all ticks and repeats are considered as hit.
+    #[inline]
+    pub fn slider_tick_misses(mut self, slider_tick_misses: usize) -> Self {
+        self.slider_tick_misses = Some(slider_tick_misses);
+
+        self
+    }
+
+    /// Specify the amount of missed slider ends, e.g. retrieved from a replay.
+    ///
+    /// Only affects scores with ScoreV2, see [`Mods::V2`](crate::Mods::V2).
+    /// If none is specified, all slider ends are considered as hit.
+    #[inline]
+    pub fn slider_end_misses(mut self, slider_end_misses: usize) -> Self {
+        self.slider_end_misses = Some(slider_end_misses);
+
+        self
+    }
+
     /// Specify how hitresults should be generated.
     ///
     /// Defauls to [`HitResultPriority::BestCase`].
@@ -149,6 +282,29 @@ impl<'map> OsuPP<'map> {
         self
     }
 
+    /// Specify the amount of gekis of a play.
+    ///
+    /// Irrelevant for osu!standard but kept when converting the calculator to
+    /// osu!mania, for which it represents the amount of n320.
+    #[inline]
+    pub fn n_geki(mut self, n_geki: usize) -> Self {
+        self.n_geki = Some(n_geki);
+
+        self
+    }
+
+    /// Specify the amount of katus of a play.
+    ///
+    /// Irrelevant for osu!standard but kept when converting the calculator to
+    /// osu!catch, for which it represents the amount of tiny droplet misses,
+    /// or to osu!mania, for which it represents the amount of n200.
+    #[inline]
+    pub fn n_katu(mut self, n_katu: usize) -> Self {
+        self.n_katu = Some(n_katu);
+
+        self
+    }
+
     /// Specify the amount of misses of a play.
     #[inline]
     pub fn n_misses(mut self, n_misses: usize) -> Self {
@@ -179,6 +335,54 @@ impl<'map> OsuPP<'map> {
         self
     }
 
+    /// Override the map's approach rate before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn ar(mut self, ar: f32) -> Self {
+        self.overrides.ar = Some(ar);
+
+        self
+    }
+
+    /// Override the map's overall difficulty before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn od(mut self, od: f32) -> Self {
+        self.overrides.od = Some(od);
+
+        self
+    }
+
+    /// Override the map's circle size before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn cs(mut self, cs: f32) -> Self {
+        self.overrides.cs = Some(cs);
+
+        self
+    }
+
+    /// Override the map's drain rate before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn hp(mut self, hp: f32) -> Self {
+        self.overrides.hp = Some(hp);
+
+        self
+    }
+
+    /// Apply the effects of a [`CustomMod`] on top of the mods, e.g. a private server's own mod.
+    #[inline]
+    pub fn custom_mod(mut self, custom_mod: CustomMod) -> Self {
+        self.overrides.custom_mod = custom_mod;
+
+        self
+    }
+
     /// Provide parameters through an [`OsuScoreState`].
     #[inline]
     pub fn state(mut self, state: OsuScoreState) -> Self {
@@ -348,28 +552,215 @@ impl<'map> OsuPP<'map> {
         }
     }
 
-    /// Calculate all performance related values, including pp and stars.
-    pub fn calculate(mut self) -> OsuPerformanceAttributes {
-        let attrs = self.attributes.take().unwrap_or_else(|| {
-            let mut calculator = OsuStars::new(self.map).mods(self.mods);
+    fn take_attributes(&mut self) -> OsuDifficultyAttributes {
+        self.attributes
+            .take()
+            .unwrap_or_else(|| self.calculate_difficulty(self.passed_objects))
+    }
 
-            if let Some(passed_objects) = self.passed_objects {
-                calculator = calculator.passed_objects(passed_objects);
-            }
+    fn calculate_difficulty(&self, passed_objects: Option<usize>) -> OsuDifficultyAttributes {
+        let mut calculator = OsuStars::new(self.map)
+            .mods(self.mods)
+            .relax(self.relax)
+            .overrides(self.overrides);
 
-            if let Some(clock_rate) = self.clock_rate {
-                calculator = calculator.clock_rate(clock_rate);
-            }
+        if let Some(passed_objects) = passed_objects {
+            calculator = calculator.passed_objects(passed_objects);
+        }
+
+        if let Some(clock_rate) = self.clock_rate {
+            calculator = calculator.clock_rate(clock_rate);
+        }
+
+        calculator.calculate()
+    }
+
+    /// Copy the settings of the calculator without the values of a specific score.
+    fn without_score(&self) -> OsuPP<'map> {
+        OsuPP {
+            map: self.map,
+            attributes: None,
+            mods: self.mods,
+            blinds: self.blinds,
+            relax: self.relax,
+            hit_errors: None,
+            hit_windows: self.hit_windows,
+            slider_breaks: None,
+            slider_tick_misses: None,
+            slider_end_misses: None,
+            tuning: self.tuning,
+            mod_multipliers: self.mod_multipliers.clone(),
+            acc: None,
+            combo: None,
+            n300: None,
+            n100: None,
+            n50: None,
+            n_misses: None,
+            n_geki: None,
+            n_katu: None,
+            passed_objects: self.passed_objects,
+            clock_rate: self.clock_rate,
+            overrides: self.overrides,
+            hitresult_priority: None,
+        }
+    }
+
+    /// Generate hitresults for the given accuracy between `0.0` and `100.0` the same way
+    /// [osu-tools](https://github.com/ppy/osu-tools)' `simulate` command does.
+    ///
+    /// Specified 100s, 50s, misses, and combo are respected. If neither 100s nor 50s
+    /// are specified, they are derived from the accuracy, otherwise the accuracy is ignored.
+    /// Unlike [`accuracy`](OsuPP::accuracy), infeasible inputs are not corrected
+    /// except that negative counts are clamped to zero.
+    pub fn simulate(mut self, acc: f64) -> OsuScoreState {
+        let attrs = self.take_attributes();
+
+        let total = self.passed_objects.unwrap_or(self.map.hit_objects.len()) as isize;
+        let n_misses = self.n_misses.unwrap_or(0) as isize;
+
+        let (n300, n100, n50) = if self.n100.is_some() || self.n50.is_some() {
+            let n100 = self.n100.unwrap_or(0) as isize;
+            let n50 = self.n50.unwrap_or(0) as isize;
+
+            (total - n100 - n50 - n_misses, n100, n50)
+        } else {
+            // Let 300=6, 100=2, 50=1, miss=0 and start by assuming every non-miss is a 50
+            let target_total = (acc / 100.0 * (total * 6) as f64).round_even() as isize;
+            let delta = target_total - (total - n_misses);
+
+            let n300 = delta / 5;
+            let n100 = delta % 5;
+
+            (n300, n100, total - n300 - n100 - n_misses)
+        };
+
+        OsuScoreState {
+            max_combo: self.combo.unwrap_or(attrs.max_combo),
+            n300: n300.max(0) as usize,
+            n100: n100.max(0) as usize,
+            n50: n50.max(0) as usize,
+            n_misses: n_misses as usize,
+        }
+    }
+
+    /// Same as [`calculate`](OsuPP::calculate) but the specified values are
+    /// first validated against the map.
+    ///
+    /// Returns an [`InvalidScore`] describing the first inconsistent value, e.g. if
+    /// the combo exceeds the map's max combo or the hitresults exceed the amount of objects.
+    pub fn try_calculate(mut self) -> Result<OsuPerformanceAttributes, InvalidScore> {
+        invalid_score::check_passed_objects(self.passed_objects, self.map.hit_objects.len())?;
+        invalid_score::check_accuracy(self.acc)?;
+
+        let attrs = self.take_attributes();
+        let n_objects = self.passed_objects.unwrap_or(self.map.hit_objects.len());
+
+        invalid_score::check_combo(self.combo, attrs.max_combo)?;
+
+        let hitresults = [
+            ("n300", self.n300),
+            ("n100", self.n100),
+            ("n50", self.n50),
+            ("n_misses", self.n_misses),
+        ];
+
+        invalid_score::check_hitresults(&hitresults, n_objects)?;
+
+        Ok(self.attributes(attrs).calculate())
+    }
+
+    /// Calculate the performance attributes for each of the given score states.
+    ///
+    /// The difficulty attributes are calculated only once and shared by all scores.
+    /// Values of a specific score that were specified on the calculator itself,
+    /// e.g. hitresults, accuracy, slider breaks, or hit errors, are ignored.
+    pub fn calculate_many(mut self, states: &[OsuScoreState]) -> Vec<OsuPerformanceAttributes> {
+        let attrs = self.take_attributes();
+
+        states
+            .iter()
+            .map(|state| {
+                self.without_score()
+                    .attributes(attrs)
+                    .state(*state)
+                    .calculate()
+            })
+            .collect()
+    }
+
+    /// Calculate the performance attributes for the worst and best possible combo
+    /// in case the combo of the score is unknown.
+    ///
+    /// The worst case assumes the misses split the combo evenly,
+    /// the best case assumes a full combo on all objects that were not missed.
+    /// A specified combo is ignored.
+    pub fn calculate_range(mut self) -> (OsuPerformanceAttributes, OsuPerformanceAttributes) {
+        let attrs = self.take_attributes();
+        let (min_combo, max_combo) = combo_range(attrs.max_combo(), self.n_misses.unwrap_or(0));
 
-            calculator.calculate()
-        });
+        let min = self.clone().attributes(attrs).combo(min_combo).calculate();
+        let max = self.attributes(attrs).combo(max_combo).calculate();
+
+        (min, max)
+    }
+
+    /// Calculate the performance attributes of the score and of an SS
+    /// on the same map with the same mods.
+    ///
+    /// The difficulty attributes are calculated only once and shared by both calculations
+    /// unless `passed_objects` is specified, in which case the SS is calculated on the full map.
+    pub fn calculate_with_max(mut self) -> (OsuPerformanceAttributes, OsuPerformanceAttributes) {
+        let attrs = self.take_attributes();
+
+        let max_attrs = match self.passed_objects {
+            Some(_) => self.calculate_difficulty(None),
+            None => attrs,
+        };
+
+        let mut max = self.without_score();
+        max.passed_objects = None;
+        let max = max.attributes(max_attrs).calculate();
+
+        (self.attributes(attrs).calculate(), max)
+    }
+
+    /// Calculate all performance related values, including pp and stars.
+    pub fn calculate(mut self) -> OsuPerformanceAttributes {
+        let mut attrs = self.take_attributes();
+
+        if let Some([great, ok, meh]) = self.hit_windows {
+            attrs.set_settings(DifficultySettings {
+                od: (80.0 - great) / 6.0,
+                great_hit_window: great,
+                ok_hit_window: ok,
+                meh_hit_window: meh,
+                ..attrs.settings
+            });
+        }
 
         let state = self.generate_hitresults(attrs.max_combo);
-        let effective_miss_count = calculate_effective_misses(&attrs, &state);
+        let effective_miss_count = calculate_effective_misses(&attrs, &state, self.slider_breaks);
+
+        let clock_rate = self.overrides.clock_rate(self.clock_rate, self.mods);
+
+        let hit_error_deviation = self
+            .hit_errors
+            .as_deref()
+            .and_then(standard_deviation)
+            .map(|deviation| deviation / clock_rate);
 
         let inner = OsuPpInner {
             attrs,
             mods: self.mods,
+            blinds: self.blinds,
+            tuning: self.tuning,
+            mod_multipliers: self
+                .mod_multipliers
+                .as_deref()
+                .unwrap_or(&DefaultModMultipliers),
+            hit_error_deviation,
+            slider_tick_misses: self.slider_tick_misses.unwrap_or(0),
+            slider_end_misses: self.slider_end_misses.unwrap_or(0),
             acc: state.accuracy(),
             state,
             effective_miss_count,
@@ -379,47 +770,62 @@ impl<'map> OsuPP<'map> {
     }
 }
 
-struct OsuPpInner {
+struct OsuPpInner<'m> {
     attrs: OsuDifficultyAttributes,
     mods: u32,
+    blinds: bool,
+    tuning: PpTuning,
+    mod_multipliers: &'m dyn ModMultipliers,
+    hit_error_deviation: Option<f64>,
+    slider_tick_misses: usize,
+    slider_end_misses: usize,
     acc: f64,
     state: OsuScoreState,
     effective_miss_count: f64,
 }
 
-impl OsuPpInner {
+impl OsuPpInner<'_> {
     fn calculate(self) -> OsuPerformanceAttributes {
+        profile!(Performance);
+
         let total_hits = self.state.total_hits();
 
         if total_hits == 0 {
             return OsuPerformanceAttributes {
                 difficulty: self.attrs,
+                state: self.state,
                 ..Default::default()
             };
         }
 
         let total_hits = total_hits as f64;
 
-        let mut multiplier = PERFORMANCE_BASE_MULTIPLIER;
+        let tuning = &self.tuning;
 
-        if self.mods.nf() {
-            multiplier *= (1.0 - 0.02 * self.effective_miss_count).max(0.9);
-        }
+        let input = ModMultiplierInput {
+            mods: self.mods,
+            total_hits,
+            effective_miss_count: self.effective_miss_count,
+            n_spinners: self.attrs.n_spinners,
+        };
 
-        if self.mods.so() && total_hits > 0.0 {
-            multiplier *= 1.0 - (self.attrs.n_spinners as f64 / total_hits).powf(0.85);
+        let mut multiplier = tuning.base_multiplier * input.multiplier(self.mod_multipliers);
+
+        if self.mods.ap() {
+            multiplier *= tuning.ap_multiplier;
         }
 
         let aim_value = self.compute_aim_value();
         let speed_value = self.compute_speed_value();
         let acc_value = self.compute_accuracy_value();
         let flashlight_value = self.compute_flashlight_value();
+        let speed_deviation = self.calculate_speed_deviation();
 
-        let pp = (aim_value.powf(1.1)
-            + speed_value.powf(1.1)
-            + acc_value.powf(1.1)
-            + flashlight_value.powf(1.1))
-        .powf(1.0 / 1.1)
+        let pp = (aim_value.powf(tuning.aim_exponent)
+            + speed_value.powf(tuning.speed_exponent)
+            + acc_value.powf(tuning.acc_exponent)
+            + flashlight_value.powf(tuning.flashlight_exponent))
+        .powf(1.0 / tuning.total_exponent)
             * multiplier;
 
         OsuPerformanceAttributes {
@@ -430,10 +836,18 @@ impl OsuPpInner {
             pp_speed: speed_value,
             pp,
             effective_miss_count: self.effective_miss_count,
+            speed_deviation,
+            hit_error_deviation: self.hit_error_deviation,
+            state: self.state,
         }
     }
 
     fn compute_aim_value(&self) -> f64 {
+        // Autopilot moves the cursor so there is no aim to reward
+        if self.mods.ap() {
+            return 0.0;
+        }
+
         let mut aim_value = (5.0 * (self.attrs.aim / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0;
 
         let total_hits = self.total_hits();
@@ -462,15 +876,27 @@ impl OsuPpInner {
         // * Buff for longer maps with high AR.
         aim_value *= 1.0 + ar_factor * len_bonus;
 
-        if self.mods.hd() {
+        if self.blinds {
+            // * Apply a penalty for Blinds
+            aim_value *= 1.3
+                + (total_hits
+                    * (0.0016 / (1.0 + 2.0 * self.effective_miss_count))
+                    * self.acc.powi(16))
+                    * (1.0 - 0.003 * self.attrs.hp * self.attrs.hp);
+        } else if self.mods.hd() {
             // * We want to give more reward for lower AR when it comes to aim and HD. This nerfs high AR and buffs lower AR.
             aim_value *= 1.0 + 0.04 * (12.0 - self.attrs.ar);
         }
 
-        // * We assume 15% of sliders in a map are difficult since there's no way to tell from the performance calculator.
-        let estimate_diff_sliders = self.attrs.n_sliders as f64 * 0.15;
+        // Attributes that were not calculated by this version of the crate may lack
+        // the difficult slider count in which case 15% of sliders are assumed to be difficult.
+        let estimate_diff_sliders = if self.attrs.aim_difficult_slider_count > 0.0 {
+            self.attrs.aim_difficult_slider_count
+        } else {
+            self.attrs.n_sliders as f64 * 0.15
+        };
 
-        if self.attrs.n_sliders > 0 {
+        if self.attrs.n_sliders > 0 && estimate_diff_sliders > 0.0 {
             let estimate_slider_ends_dropped =
                 ((self.state.n100 + self.state.n50 + self.state.n_misses)
                     .min(self.attrs.max_combo - self.state.max_combo) as f64)
@@ -517,12 +943,19 @@ impl OsuPpInner {
         // * Buff for longer maps with high AR.
         speed_value *= 1.0 + ar_factor * len_bonus;
 
-        if self.mods.hd() {
+        if self.blinds {
+            // * Increasing the speed value by object count for Blinds isn't ideal, so the minimum buff is given.
+            speed_value *= 1.12;
+        } else if self.mods.hd() {
             // * We want to give more reward for lower AR when it comes to aim and HD.
             // * This nerfs high AR and buffs lower AR.
             speed_value *= 1.0 + 0.04 * (12.0 - self.attrs.ar);
         }
 
+        if self.mods.ap() {
+            speed_value *= self.tuning.ap_speed_multiplier;
+        }
+
         // * Calculate accuracy assuming the worst case scenario
         let relevant_total_diff = total_hits - self.attrs.speed_note_count;
         let relevant_n300 = (self.state.n300 as f64 - relevant_total_diff).max(0.0);
@@ -556,9 +989,20 @@ impl OsuPpInner {
     fn compute_accuracy_value(&self) -> f64 {
         // * This percentage only considers HitCircles of any value - in this part
         // * of the calculation we focus on hitting the timing hit window.
-        let amount_hit_objects_with_acc = self.attrs.n_circles;
+        let mut amount_hit_objects_with_acc = self.attrs.n_circles;
+        let v2 = self.mods & u32::V2 > 0;
+
+        // ScoreV2 judges slider heads with the regular hit windows and
+        // scores slider ticks and ends separately so sliders count as well
+        if v2 {
+            amount_hit_objects_with_acc += self.attrs.n_sliders;
+        }
 
-        let better_acc_percentage = if amount_hit_objects_with_acc > 0 {
+        let better_acc_percentage = if let Some(deviation) = self.hit_error_deviation {
+            self.expected_circle_accuracy(deviation)
+        } else if v2 {
+            self.v2_accuracy()
+        } else if amount_hit_objects_with_acc > 0 {
             let sub = self.state.total_hits() - amount_hit_objects_with_acc;
 
             // * It is possible to reach a negative accuracy with this formula. Cap it at zero - zero points.
@@ -582,7 +1026,9 @@ impl OsuPpInner {
             .min(1.15);
 
         // * Increasing the accuracy value by object count for Blinds isn't ideal, so the minimum buff is given.
-        if self.mods.hd() {
+        if self.blinds {
+            acc_value *= 1.14;
+        } else if self.mods.hd() {
             acc_value *= 1.08;
         }
 
@@ -593,6 +1039,35 @@ impl OsuPpInner {
         acc_value
     }
 
+    /// The accuracy of all judgements, including slider ticks, repeats, and ends,
+    /// weighted like in ScoreV2.
+    fn v2_accuracy(&self) -> f64 {
+        let attrs = &self.attrs;
+        let n_ends = attrs.n_sliders;
+
+        // Each slider gives combo for its head, ticks, repeats, and end
+        let n_ticks = attrs
+            .max_combo
+            .saturating_sub(attrs.n_circles + attrs.n_spinners + 2 * n_ends);
+
+        let tick_hits = n_ticks.saturating_sub(self.slider_tick_misses);
+        let end_hits = n_ends.saturating_sub(self.slider_end_misses);
+
+        let max = 300 * self.state.total_hits() + 30 * n_ticks + 150 * n_ends;
+
+        if max == 0 {
+            return 0.0;
+        }
+
+        let sum = 300 * self.state.n300
+            + 100 * self.state.n100
+            + 50 * self.state.n50
+            + 30 * tick_hits
+            + 150 * end_hits;
+
+        sum as f64 / max as f64
+    }
+
     fn compute_flashlight_value(&self) -> f64 {
         if !self.mods.fl() {
             return 0.0;
@@ -624,6 +1099,134 @@ impl OsuPpInner {
         flashlight_value
     }
 
+    /// The hit windows for 300s, 100s, and 50s of the attributes.
+    ///
+    /// Attributes without hit windows, e.g. from an older version,
+    /// fall back to windows based on OD.
+    fn hit_windows(&self) -> [f64; 3] {
+        let attrs = &self.attrs;
+
+        if attrs.great_hit_window > 0.0 {
+            [
+                attrs.great_hit_window,
+                attrs.ok_hit_window,
+                attrs.meh_hit_window,
+            ]
+        } else {
+            let od = attrs.od;
+
+            [80.0 - 6.0 * od, 140.0 - 8.0 * od, 200.0 - 10.0 * od]
+        }
+    }
+
+    /// The expected accuracy on circles for normally distributed
+    /// hit errors with the given deviation.
+    fn expected_circle_accuracy(&self, deviation: f64) -> f64 {
+        if deviation <= 0.0 {
+            return 1.0;
+        }
+
+        let hit_ratio = |hit_window: f64| erf(hit_window.max(0.0) / (SQRT_2 * deviation));
+        let [great, ok, meh] = self.hit_windows().map(hit_ratio);
+
+        (6.0 * great + 2.0 * (ok - great) + (meh - ok)) / 6.0
+    }
+
+    /// Estimate the deviation of hit errors on speed notes
+    /// while assuming that all mistakes were on speed notes.
+    fn calculate_speed_deviation(&self) -> Option<f64> {
+        if self.state.n300 + self.state.n100 + self.state.n50 == 0 {
+            return None;
+        }
+
+        let mut speed_note_count = self.attrs.speed_note_count;
+        speed_note_count += (self.total_hits() - self.attrs.speed_note_count) * 0.1;
+
+        // * Assume worst case: all mistakes were on speed notes
+        let relevant_n_misses = (self.state.n_misses as f64).min(speed_note_count);
+        let relevant_n50 = (self.state.n50 as f64).min(speed_note_count - relevant_n_misses);
+        let relevant_n100 =
+            (self.state.n100 as f64).min(speed_note_count - relevant_n_misses - relevant_n50);
+        let relevant_n300 =
+            (speed_note_count - relevant_n_misses - relevant_n50 - relevant_n100).max(0.0);
+
+        self.calculate_deviation(
+            relevant_n300,
+            relevant_n100,
+            relevant_n50,
+            relevant_n_misses,
+        )
+    }
+
+    /// Estimate the deviation of hit errors assuming that 300s and 100s
+    /// are normally distributed and 50s are uniformly distributed.
+    fn calculate_deviation(
+        &self,
+        relevant_n300: f64,
+        relevant_n100: f64,
+        relevant_n50: f64,
+        relevant_n_misses: f64,
+    ) -> Option<f64> {
+        if relevant_n300 + relevant_n100 + relevant_n50 <= 0.0 {
+            return None;
+        }
+
+        let [great_hit_window, ok_hit_window, meh_hit_window] = self.hit_windows();
+
+        let n_objects = relevant_n300 + relevant_n100 + relevant_n50 + relevant_n_misses;
+
+        // * The probability that a player hits a circle is unknown, but we can estimate it to be
+        // * the number of greats on circles divided by the number of circles, and then add one
+        // * to the number of circles as a bias correction.
+        let n = (n_objects - relevant_n_misses - relevant_n50).max(1.0);
+
+        // * 99% critical value for the normal distribution (one-tailed).
+        const Z: f64 = 2.32634787404;
+
+        // * Proportion of greats hit on circles, ignoring misses and 50s.
+        let p = relevant_n300 / n;
+
+        // * We can be 99% confident that p is at least this value.
+        let p_lower_bound = (n * p + Z * Z / 2.0) / (n + Z * Z)
+            - Z / (n + Z * Z) * (n * p * (1.0 - p) + Z * Z / 4.0).sqrt();
+
+        // * Compute the deviation assuming greats and oks are normally distributed, and mehs are uniformly distributed.
+        // * Begin with greats and oks first. Ignoring mehs, we can be 99% confident that the deviation is not higher than:
+        let mut deviation = great_hit_window / (SQRT_2 * erf_inv(p_lower_bound));
+
+        let random_value =
+            (2.0 / PI).sqrt() * ok_hit_window * (-0.5 * (ok_hit_window / deviation).powi(2)).exp()
+                / (deviation * erf(ok_hit_window / (SQRT_2 * deviation)));
+
+        deviation *= (1.0 - random_value).sqrt();
+
+        // * Value deviation approach as greatCount approaches 0
+        let limit_value = ok_hit_window / 3.0_f64.sqrt();
+
+        // * If precision is not enough to compute true deviation - use limit value
+        if p_lower_bound <= 0.0
+            || random_value >= 1.0
+            || deviation > limit_value
+            || deviation.is_nan()
+        {
+            deviation = limit_value;
+        }
+
+        // * Then compute the variance for mehs.
+        let meh_variance = (meh_hit_window * meh_hit_window
+            + ok_hit_window * meh_hit_window
+            + ok_hit_window * ok_hit_window)
+            / 3.0;
+
+        // * Find the total deviation.
+        let deviation = (((relevant_n300 + relevant_n100) * deviation * deviation
+            + relevant_n50 * meh_variance)
+            / (relevant_n300 + relevant_n100 + relevant_n50))
+            .sqrt();
+
+        Some(deviation)
+    }
+
     fn get_combo_scaling_factor(&self) -> f64 {
         if self.attrs.max_combo == 0 {
             1.0
@@ -638,7 +1241,15 @@ impl OsuPpInner {
     }
 }
 
-fn calculate_effective_misses(attrs: &OsuDifficultyAttributes, state: &OsuScoreState) -> f64 {
+fn calculate_effective_misses(
+    attrs: &OsuDifficultyAttributes,
+    state: &OsuScoreState,
+    slider_breaks: Option<usize>,
+) -> f64 {
+    if let Some(slider_breaks) = slider_breaks {
+        return (state.n_misses + slider_breaks).min(state.total_hits()) as f64;
+    }
+
     // * Guess the number of misses + slider breaks from combo
     let mut combo_based_miss_count = 0.0;
 
@@ -657,50 +1268,296 @@ fn calculate_effective_misses(attrs: &OsuDifficultyAttributes, state: &OsuScoreS
     combo_based_miss_count.max(state.n_misses as f64)
 }
 
+fn standard_deviation(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let len = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / len;
+    let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / len;
+
+    Some(variance.sqrt())
+}
+
 fn calculate_miss_penalty(miss_count: f64, difficult_strain_count: f64) -> f64 {
     0.96 / ((miss_count / (4.0 * difficult_strain_count.ln().powf(0.94))) + 1.0)
 }
 
 /// Abstract type to provide flexibility when passing difficulty attributes to a performance calculation.
+///
+/// Implemented for all types implementing [`AttributeProvider`].
 pub trait OsuAttributeProvider {
     /// Provide the actual difficulty attributes.
     fn attributes(self) -> Option<OsuDifficultyAttributes>;
 }
 
-impl OsuAttributeProvider for OsuDifficultyAttributes {
+impl<T: AttributeProvider> OsuAttributeProvider for T {
     #[inline]
     fn attributes(self) -> Option<OsuDifficultyAttributes> {
-        Some(self)
+        self.mode_attributes().ok()
     }
 }
 
-impl OsuAttributeProvider for OsuPerformanceAttributes {
-    #[inline]
-    fn attributes(self) -> Option<OsuDifficultyAttributes> {
-        Some(self.difficulty)
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Beatmap;
+
+    #[test]
+    fn custom_ok_and_meh_windows() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let attrs = OsuStars::new(&map).mods(64).calculate();
+
+        let score = |pp: OsuPP<'_>| pp.mods(64).attributes(attrs).n100(20).n50(5).calculate();
+
+        let regular = score(OsuPP::new(&map));
+
+        let same = score(OsuPP::new(&map).hit_windows(
+            attrs.great_hit_window,
+            attrs.ok_hit_window,
+            attrs.meh_hit_window,
+        ));
+
+        assert_eq!(same.pp, regular.pp);
+        assert_eq!(same.speed_deviation, regular.speed_deviation);
+
+        let wide = score(OsuPP::new(&map).hit_windows(
+            attrs.great_hit_window,
+            attrs.ok_hit_window + 40.0,
+            attrs.meh_hit_window + 40.0,
+        ));
+
+        assert_eq!(wide.difficulty.ok_hit_window, attrs.ok_hit_window + 40.0);
+        assert_eq!(wide.difficulty.meh_hit_window, attrs.meh_hit_window + 40.0);
     }
-}
 
-impl OsuAttributeProvider for DifficultyAttributes {
-    #[inline]
-    fn attributes(self) -> Option<OsuDifficultyAttributes> {
-        #[allow(irrefutable_let_patterns)]
-        if let Self::Osu(attributes) = self {
-            Some(attributes)
-        } else {
-            None
+    #[test]
+    fn calculate_with_max_on_partial_play() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let tuning = PpTuning {
+            base_multiplier: 1.0,
+            ..Default::default()
+        };
+
+        let full = || {
+            OsuPP::new(&map)
+                .mods(64)
+                .tuning(tuning)
+                .hit_windows(30.0, 70.0, 110.0)
+        };
+
+        let (score, max) = full()
+            .passed_objects(100)
+            .n_misses(1)
+            .hit_errors(vec![-5.0, 10.0, 3.0])
+            .slider_breaks(2)
+            .calculate_with_max();
+
+        assert_eq!(
+            score.difficulty.n_circles + score.difficulty.n_sliders + score.difficulty.n_spinners,
+            100
+        );
+        assert_eq!(max, full().calculate());
+    }
+
+    #[test]
+    fn calculate_many_ignores_score_values() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let attrs = OsuStars::new(&map).mods(8).calculate();
+
+        let states = [
+            OsuScoreState {
+                max_combo: attrs.max_combo,
+                n300: attrs.n_circles + attrs.n_sliders + attrs.n_spinners,
+                n100: 0,
+                n50: 0,
+                n_misses: 0,
+            },
+            OsuScoreState {
+                max_combo: 250,
+                n300: 580,
+                n100: 15,
+                n50: 2,
+                n_misses: 4,
+            },
+        ];
+
+        let many = OsuPP::new(&map)
+            .mods(8)
+            .attributes(attrs)
+            .accuracy(90.0)
+            .slider_breaks(3)
+            .hit_errors(vec![-20.0, 15.0, 30.0])
+            .calculate_many(&states);
+
+        for (state, performance) in states.iter().zip(many) {
+            let expected = OsuPP::new(&map)
+                .mods(8)
+                .attributes(attrs)
+                .state(*state)
+                .calculate();
+
+            assert_eq!(performance, expected);
         }
     }
-}
 
-impl OsuAttributeProvider for PerformanceAttributes {
-    #[inline]
-    fn attributes(self) -> Option<OsuDifficultyAttributes> {
-        #[allow(irrefutable_let_patterns)]
-        if let Self::Osu(attributes) = self {
-            Some(attributes.difficulty)
-        } else {
-            None
+    #[test]
+    fn slider_breaks_clamped_to_hits() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let attrs = OsuStars::new(&map).passed_objects(100).calculate();
+
+        let state = OsuScoreState {
+            max_combo: 50,
+            n300: 90,
+            n100: 5,
+            n50: 0,
+            n_misses: 5,
+        };
+
+        let score = |slider_breaks: usize| {
+            OsuPP::new(&map)
+                .attributes(attrs)
+                .passed_objects(100)
+                .state(state)
+                .slider_breaks(slider_breaks)
+                .calculate()
+        };
+
+        assert_eq!(score(10).effective_miss_count, 15.0);
+        assert_eq!(score(95).effective_miss_count, 100.0);
+        assert_eq!(score(1000).effective_miss_count, 100.0);
+        assert_eq!(score(1000).pp, score(95).pp);
+    }
+
+    #[test]
+    fn v2_accuracy_includes_slider_judgements() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let attrs = OsuStars::new(&map).calculate();
+
+        let score = |pp: OsuPP<'_>| pp.attributes(attrs).n100(10).calculate();
+
+        let v1 = score(OsuPP::new(&map));
+        let v2 = score(OsuPP::new(&map).mods(u32::V2));
+
+        // Ticks and ends count towards the accuracy and sliders towards the length bonus
+        assert!(v2.pp_acc > v1.pp_acc);
+
+        // Slider judgements are ignored without ScoreV2
+        let v1_misses = score(
+            OsuPP::new(&map)
+                .slider_tick_misses(20)
+                .slider_end_misses(10),
+        );
+        assert_eq!(v1_misses.pp, v1.pp);
+
+        let tick_misses = score(OsuPP::new(&map).mods(u32::V2).slider_tick_misses(20));
+        let end_misses = score(OsuPP::new(&map).mods(u32::V2).slider_end_misses(20));
+
+        assert!(tick_misses.pp_acc < v2.pp_acc);
+        assert!(end_misses.pp_acc < tick_misses.pp_acc);
+
+        let all_misses = score(OsuPP::new(&map).mods(u32::V2).slider_end_misses(usize::MAX));
+        assert!(all_misses.pp_acc < end_misses.pp_acc);
+    }
+
+    #[test]
+    fn simulate_matches_osu_tools() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let state = OsuPP::new(&map)
+            .passed_objects(200)
+            .n_misses(3)
+            .simulate(95.0);
+
+        let expected = OsuScoreState {
+            max_combo: 288,
+            n300: 188,
+            n100: 3,
+            n50: 6,
+            n_misses: 3,
+        };
+
+        assert_eq!(state, expected);
+
+        // The target total of 52.5 is rounded to the nearest even integer like in C#
+        let state = OsuPP::new(&map).passed_objects(10).simulate(87.5);
+
+        let expected = OsuScoreState {
+            max_combo: 11,
+            n300: 8,
+            n100: 2,
+            n50: 0,
+            n_misses: 0,
+        };
+
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn deviation_uses_custom_windows() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let attrs = OsuStars::new(&map).calculate();
+
+        let score = |ok: f64, meh: f64| {
+            OsuPP::new(&map)
+                .attributes(attrs)
+                .hit_windows(attrs.great_hit_window, ok, meh)
+                .n100(20)
+                .n50(5)
+                .calculate()
+        };
+
+        let regular = score(attrs.ok_hit_window, attrs.meh_hit_window);
+        let wide = score(attrs.ok_hit_window + 40.0, attrs.meh_hit_window + 40.0);
+
+        assert_ne!(wide.speed_deviation, regular.speed_deviation);
+    }
+
+    #[test]
+    fn relax_star_rating() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let expected = OsuStars::new(&map).mods(128).relax(true).calculate();
+
+        let regular = OsuPP::new(&map).mods(128).calculate();
+        let relax = OsuPP::new(&map).mods(128).relax(true).calculate();
+
+        assert_eq!(relax.difficulty.stars, expected.stars);
+        assert_ne!(relax.difficulty.stars, regular.difficulty.stars);
+
+        let gradual = crate::osu::OsuGradualPerformanceAttributes::new(&map, 128)
+            .relax(true)
+            .process_next_n_objects(relax.state, usize::MAX)
+            .unwrap();
+
+        assert_eq!(gradual.difficulty.stars, expected.stars);
+        assert_eq!(gradual.pp, relax.pp);
+    }
+
+    #[test]
+    fn custom_mod_multipliers() {
+        #[derive(Debug)]
+        struct HalfNoFail;
+
+        impl ModMultipliers for HalfNoFail {
+            fn no_fail(&self, _: &ModMultiplierInput) -> f64 {
+                0.5
+            }
         }
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let nomod = OsuPP::new(&map).calculate();
+
+        let nf = OsuPP::new(&map)
+            .mods(1)
+            .mod_multipliers(Arc::new(HalfNoFail))
+            .calculate();
+
+        assert!((nf.pp - nomod.pp * 0.5).abs() < 1e-9);
+
+        let default = OsuPP::new(&map).mods(1).calculate();
+        assert!((default.pp - nomod.pp).abs() < 1e-9);
     }
 }
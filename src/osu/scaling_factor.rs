@@ -4,15 +4,36 @@ use super::NORMALIZED_RADIUS;
 
 const OBJECT_RADIUS: f32 = 64.0;
 
-#[derive(Copy, Clone, Debug)]
-pub(crate) struct ScalingFactor {
+/// Scales osu!pixel distances into the normalized units used by the difficulty calculation.
+///
+/// In normalized units, every hit object has a radius of [`NORMALIZED_RADIUS`],
+/// regardless of the circle size. Small circles receive an additional bonus factor.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{osu::ScalingFactor, parse::Pos2};
+///
+/// let scaling_factor = ScalingFactor::new(4.0);
+///
+/// let a = Pos2 { x: 100.0, y: 100.0 };
+/// let b = Pos2 { x: 200.0, y: 150.0 };
+///
+/// println!("Jump distance: {}", scaling_factor.normalized_dist(a, b));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScalingFactor {
     pub(crate) factor: f32,
     pub(crate) radius: f32,
     scale: f32,
 }
 
 impl ScalingFactor {
-    pub(crate) fn new(cs: f64) -> Self {
+    /// Create a new scaling factor for the given circle size.
+    ///
+    /// The circle size should already be adjusted by mods e.g. through
+    /// [`BeatmapAttributes`](crate::beatmap::BeatmapAttributes).
+    pub fn new(cs: f64) -> Self {
         let scale = (1.0 - 0.7 * (cs as f32 - 5.0) / 5.0) / 2.0;
 
         let radius = OBJECT_RADIUS * scale;
@@ -31,7 +52,33 @@ impl ScalingFactor {
         }
     }
 
-    pub(crate) fn stack_offset(&self, stack_height: f32) -> Pos2 {
+    /// The factor that osu!pixel distances are multiplied with.
+    #[inline]
+    pub fn factor(&self) -> f32 {
+        self.factor
+    }
+
+    /// The radius of hit objects in osu!pixels.
+    #[inline]
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Scale a position from osu!pixels into normalized units.
+    #[inline]
+    pub fn normalize(&self, pos: Pos2) -> Pos2 {
+        pos * self.factor
+    }
+
+    /// The distance between two positions in normalized units.
+    #[inline]
+    pub fn normalized_dist(&self, a: Pos2, b: Pos2) -> f32 {
+        (self.normalize(a) - self.normalize(b)).length()
+    }
+
+    /// The offset of a hit object in osu!pixels for the given stack height.
+    #[inline]
+    pub fn stack_offset(&self, stack_height: f32) -> Pos2 {
         Pos2::new(stack_height * self.scale * -6.4)
     }
 }
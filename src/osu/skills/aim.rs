@@ -12,6 +12,7 @@ pub(crate) struct Aim {
     pub(crate) strain_peaks: Vec<f64>,
     with_sliders: bool,
     object_strains: Vec<f64>,
+    slider_strains: Vec<f64>,
     difficulty: f64,
 }
 
@@ -27,6 +28,7 @@ impl Aim {
             strain_peaks: Vec::new(),
             with_sliders,
             object_strains: Vec::new(),
+            slider_strains: Vec::new(),
             difficulty: 0.0,
         }
     }
@@ -34,6 +36,20 @@ impl Aim {
     fn strain_decay(ms: f64) -> f64 {
         Self::STRAIN_DECAY_BASE.powf(ms / 1000.0)
     }
+
+    /// The amount of sliders weighted by how close their strain is to the hardest slider's.
+    pub(crate) fn count_difficult_sliders(&self) -> f64 {
+        let max_slider_strain = self.slider_strains.iter().copied().fold(0.0, f64::max);
+
+        if max_slider_strain <= 0.0 {
+            return 0.0;
+        }
+
+        self.slider_strains
+            .iter()
+            .map(|strain| 1.0 / (1.0 + (-(strain / max_slider_strain * 12.0 - 6.0)).exp()))
+            .sum()
+    }
 }
 
 impl Skill for Aim {
@@ -75,6 +91,10 @@ impl StrainSkill for Aim {
 
         self.object_strains.push(self.curr_strain);
 
+        if curr.base.is_slider() {
+            self.slider_strains.push(self.curr_strain);
+        }
+
         self.curr_strain
     }
 
@@ -0,0 +1,260 @@
+use crate::{
+    osu::{difficulty_object::OsuDifficultyObject, SECTION_LEN},
+    parse::Pos2,
+};
+
+use super::traits::reduced_difficulty;
+
+/// A skill that runs alongside the built-in osu!standard skills.
+///
+/// Implementors only have to evaluate the difficulty of single objects and hold a [`StrainState`];
+/// strain decay, section peaks, and the aggregation into a difficulty value are provided.
+/// Pass the skills to [`OsuStars::calculate_with_skills`](crate::OsuStars::calculate_with_skills)
+/// to process them.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{
+///     osu::{CustomSkill, SkillObject, StrainState},
+///     Beatmap, OsuStars,
+/// };
+///
+/// #[derive(Default)]
+/// struct Jumps(StrainState);
+///
+/// impl CustomSkill for Jumps {
+///     fn strain_decay_base(&self) -> f64 {
+///         0.15
+///     }
+///
+///     fn evaluate(&mut self, curr: &SkillObject, _: &[SkillObject]) -> f64 {
+///         curr.lazy_jump_dist / curr.strain_time
+///     }
+///
+///     fn state(&mut self) -> &mut StrainState {
+///         &mut self.0
+///     }
+/// }
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let mut jumps = Jumps::default();
+/// let attrs = OsuStars::new(&map).calculate_with_skills(&mut [&mut jumps]);
+///
+/// println!("Stars: {:.2}, jumps: {:.2}", attrs.stars, jumps.difficulty_value());
+/// ```
+pub trait CustomSkill {
+    /// The factor by which the strain decays per second.
+    fn strain_decay_base(&self) -> f64;
+
+    /// The difficulty of the current object on its own which is added to the decayed strain.
+    ///
+    /// `previous` contains all prior objects in chronological order.
+    fn evaluate(&mut self, curr: &SkillObject, previous: &[SkillObject]) -> f64;
+
+    /// The state of the skill's strains.
+    fn state(&mut self) -> &mut StrainState;
+
+    /// Process the current object.
+    fn process(&mut self, curr: &SkillObject, previous: &[SkillObject]) {
+        let decay_base = self.strain_decay_base();
+        let value = self.evaluate(curr, previous);
+        let prev_start_time = previous.last().map_or(0.0, |prev| prev.start_time);
+
+        self.state()
+            .process(curr, prev_start_time, decay_base, value);
+    }
+
+    /// The difficulty of all processed objects.
+    fn difficulty_value(&mut self) -> f64 {
+        self.state().difficulty_value()
+    }
+}
+
+/// The strains of a [`CustomSkill`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StrainState {
+    curr_strain: f64,
+    curr_section_peak: f64,
+    curr_section_end: f64,
+    strain_peaks: Vec<f64>,
+    object_strains: Vec<f64>,
+}
+
+impl StrainState {
+    /// Time in ms of the sections that strain peaks are collected in.
+    pub const SECTION_LEN: f64 = SECTION_LEN;
+    /// The amount of highest peaks that are reduced for the difficulty value.
+    pub const REDUCED_SECTION_COUNT: usize = 10;
+    /// The factor that the highest peak is reduced to.
+    pub const REDUCED_STRAIN_BASELINE: f64 = 0.75;
+    /// The weight by which each peak counts less than the previous one.
+    pub const DECAY_WEIGHT: f64 = 0.9;
+    /// The final multiplier of the difficulty value.
+    pub const DIFFICULTY_MULTIPLIER: f64 = 1.06;
+
+    /// The strain after the most recent object.
+    #[inline]
+    pub fn curr_strain(&self) -> f64 {
+        self.curr_strain
+    }
+
+    /// The strain after each processed object.
+    #[inline]
+    pub fn object_strains(&self) -> &[f64] {
+        &self.object_strains
+    }
+
+    /// The highest strain of each section, including the current one.
+    pub fn strain_peaks(&self) -> Vec<f64> {
+        let mut peaks = self.strain_peaks.clone();
+        peaks.push(self.curr_section_peak);
+
+        peaks
+    }
+
+    /// The weighted sum of the strain peaks, the highest peaks being reduced.
+    pub fn difficulty_value(&self) -> f64 {
+        let difficulty = reduced_difficulty(
+            self.strain_peaks(),
+            Self::REDUCED_SECTION_COUNT,
+            Self::REDUCED_STRAIN_BASELINE,
+            Self::DECAY_WEIGHT,
+        );
+
+        difficulty * Self::DIFFICULTY_MULTIPLIER
+    }
+
+    fn process(&mut self, curr: &SkillObject, prev_start_time: f64, decay_base: f64, value: f64) {
+        let decay = |ms: f64| decay_base.powf(ms / 1000.0);
+
+        // * The first object doesn't generate a strain, so we begin with an incremented section end
+        if curr.idx == 0 {
+            self.curr_section_end = (curr.start_time / SECTION_LEN).ceil() * SECTION_LEN;
+        }
+
+        while curr.start_time > self.curr_section_end {
+            self.strain_peaks.push(self.curr_section_peak);
+            self.curr_section_peak =
+                self.curr_strain * decay(self.curr_section_end - prev_start_time);
+            self.curr_section_end += SECTION_LEN;
+        }
+
+        self.curr_strain *= decay(curr.strain_time);
+        self.curr_strain += value;
+        self.object_strains.push(self.curr_strain);
+        self.curr_section_peak = self.curr_section_peak.max(self.curr_strain);
+    }
+}
+
+/// The kind of a [`SkillObject`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SkillObjectKind {
+    /// A hit circle.
+    Circle,
+    /// A slider.
+    Slider,
+    /// A spinner.
+    Spinner,
+}
+
+/// A hit object as processed by a [`CustomSkill`].
+///
+/// All times are in ms and adjusted to the clock rate,
+/// distances are in osu!pixels normalized to the circle size.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SkillObject {
+    /// The index among all processed objects. The map's first hit object is not processed.
+    pub idx: usize,
+    /// The kind of hit object.
+    pub kind: SkillObjectKind,
+    /// The stacked position of the hit object.
+    pub pos: Pos2,
+    /// The start time.
+    pub start_time: f64,
+    /// The time since the previous hit object.
+    pub delta_time: f64,
+    /// The time since the previous hit object, capped to at least 25ms.
+    pub strain_time: f64,
+    /// The distance from the previous object's lazy end position.
+    pub lazy_jump_dist: f64,
+    /// The minimum distance from the previous object, also considering its slider tail.
+    pub min_jump_dist: f64,
+    /// The time belonging to `min_jump_dist`.
+    pub min_jump_time: f64,
+    /// The distance travelled within the previous slider.
+    pub travel_dist: f64,
+    /// The time travelled within the previous slider.
+    pub travel_time: f64,
+    /// The angle between the previous three objects in radians, if any.
+    pub angle: Option<f64>,
+}
+
+impl From<&OsuDifficultyObject<'_>> for SkillObject {
+    fn from(obj: &OsuDifficultyObject<'_>) -> Self {
+        let kind = if obj.base.is_circle() {
+            SkillObjectKind::Circle
+        } else if obj.base.is_slider() {
+            SkillObjectKind::Slider
+        } else {
+            SkillObjectKind::Spinner
+        };
+
+        Self {
+            idx: obj.idx,
+            kind,
+            pos: obj.base.stacked_pos(),
+            start_time: obj.start_time,
+            delta_time: obj.delta_time,
+            strain_time: obj.strain_time,
+            lazy_jump_dist: obj.dists.lazy_jump_dist,
+            min_jump_dist: obj.dists.min_jump_dist,
+            min_jump_time: obj.dists.min_jump_time,
+            travel_dist: obj.dists.travel_dist,
+            travel_time: obj.dists.travel_time,
+            angle: obj.dists.angle,
+        }
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use crate::{Beatmap, OsuStars};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Density(StrainState);
+
+    impl CustomSkill for Density {
+        fn strain_decay_base(&self) -> f64 {
+            0.3
+        }
+
+        fn evaluate(&mut self, curr: &SkillObject, previous: &[SkillObject]) -> f64 {
+            assert_eq!(curr.idx, previous.len());
+
+            1.0
+        }
+
+        fn state(&mut self) -> &mut StrainState {
+            &mut self.0
+        }
+    }
+
+    #[test]
+    fn custom_skill_runs_alongside() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let mut density = Density::default();
+
+        let attrs = OsuStars::new(&map).calculate_with_skills(&mut [&mut density]);
+
+        assert_eq!(attrs, OsuStars::new(&map).calculate());
+        assert_eq!(density.0.object_strains().len(), map.hit_objects.len() - 1);
+        assert!(density.difficulty_value() > 0.0);
+    }
+}
@@ -34,6 +34,10 @@ impl Flashlight {
         }
     }
 
+    pub(crate) fn curr_strain(&self) -> f64 {
+        self.curr_strain
+    }
+
     fn strain_decay(ms: f64) -> f64 {
         Self::STRAIN_DECAY_BASE.powf(ms / 1000.0)
     }
@@ -216,8 +220,7 @@ impl FlashlightEvaluator {
             let pixel_travel_dist = osu_curr.dists.lazy_travel_dist as f64 / scaling_factor;
 
             // * Reward sliders based on velocity.
-            slider_bonus = ((pixel_travel_dist / osu_curr.dists.travel_time as f64
-                - Self::MIN_VELOCITY)
+            slider_bonus = ((pixel_travel_dist / osu_curr.dists.travel_time - Self::MIN_VELOCITY)
                 .max(0.0))
             .sqrt();
 
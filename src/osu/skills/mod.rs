@@ -1,5 +1,8 @@
 mod aim;
+mod custom;
 mod flashlight;
+#[cfg(feature = "rhythm")]
+mod rhythm;
 mod speed;
 mod traits;
 
@@ -12,12 +15,19 @@ pub(crate) use self::{
     traits::{OsuStrainSkill, Skill, StrainSkill},
 };
 
+#[cfg(feature = "rhythm")]
+pub(crate) use self::rhythm::Rhythm;
+
+pub use self::custom::{CustomSkill, SkillObject, SkillObjectKind, StrainState};
+
 #[derive(Clone, Debug)]
 pub(crate) struct Skills {
     pub aim: Aim,
     pub aim_no_sliders: Aim,
     pub speed: Speed,
     pub flashlight: Flashlight,
+    #[cfg(feature = "rhythm")]
+    pub rhythm: Rhythm,
 }
 
 impl Skills {
@@ -33,6 +43,8 @@ impl Skills {
             aim_no_sliders: Aim::new(false),
             speed: Speed::new(hit_window),
             flashlight: Flashlight::new(mods, radius, time_preempt, time_fade_in),
+            #[cfg(feature = "rhythm")]
+            rhythm: Rhythm::new(hit_window),
         }
     }
 
@@ -45,6 +57,8 @@ impl Skills {
         <Aim as Skill>::process(&mut self.aim_no_sliders, curr, diff_objects);
         <Speed as Skill>::process(&mut self.speed, curr, diff_objects);
         <Flashlight as Skill>::process(&mut self.flashlight, curr, diff_objects);
+        #[cfg(feature = "rhythm")]
+        <Rhythm as Skill>::process(&mut self.rhythm, curr, diff_objects);
     }
 }
 
@@ -0,0 +1,106 @@
+use crate::osu::difficulty_object::OsuDifficultyObject;
+
+use super::{previous_start_time, speed::RhythmEvaluator, OsuStrainSkill, Skill, StrainSkill};
+
+/// Experimental skill that only considers the rhythm complexity of objects,
+/// unlike [`Speed`](super::Speed) which uses it as multiplier for its tapping strain.
+#[derive(Clone, Debug)]
+pub(crate) struct Rhythm {
+    curr_strain: f64,
+    curr_section_peak: f64,
+    curr_section_end: f64,
+    pub(crate) strain_peaks: Vec<f64>,
+    object_strains: Vec<f64>,
+    hit_window: f64,
+    difficulty: f64,
+}
+
+impl Rhythm {
+    const SKILL_MULTIPLIER: f64 = 1.0;
+    const STRAIN_DECAY_BASE: f64 = 0.3;
+
+    pub(crate) fn new(hit_window: f64) -> Self {
+        Self {
+            curr_strain: 0.0,
+            curr_section_peak: 0.0,
+            curr_section_end: 0.0,
+            strain_peaks: Vec::new(),
+            object_strains: Vec::new(),
+            hit_window,
+            difficulty: 0.0,
+        }
+    }
+
+    fn strain_decay(ms: f64) -> f64 {
+        Self::STRAIN_DECAY_BASE.powf(ms / 1000.0)
+    }
+}
+
+impl Skill for Rhythm {
+    #[inline]
+    fn process(
+        &mut self,
+        curr: &OsuDifficultyObject<'_>,
+        diff_objects: &[OsuDifficultyObject<'_>],
+    ) {
+        <Self as StrainSkill>::process(self, curr, diff_objects)
+    }
+}
+
+impl StrainSkill for Rhythm {
+    #[inline]
+    fn strain_peaks_mut(&mut self) -> &mut Vec<f64> {
+        &mut self.strain_peaks
+    }
+
+    #[inline]
+    fn curr_section_peak(&mut self) -> &mut f64 {
+        &mut self.curr_section_peak
+    }
+
+    #[inline]
+    fn curr_section_end(&mut self) -> &mut f64 {
+        &mut self.curr_section_end
+    }
+
+    #[inline]
+    fn strain_value_at(
+        &mut self,
+        curr: &OsuDifficultyObject<'_>,
+        diff_objects: &[OsuDifficultyObject<'_>],
+    ) -> f64 {
+        // The evaluator yields a multiplier of at least 1.0 so only the excess is complexity
+        let complexity =
+            RhythmEvaluator::evaluate_diff_of(curr, diff_objects, self.hit_window) - 1.0;
+
+        self.curr_strain *= Self::strain_decay(curr.strain_time);
+        self.curr_strain += complexity.max(0.0) * Self::SKILL_MULTIPLIER;
+        self.object_strains.push(self.curr_strain);
+
+        self.curr_strain
+    }
+
+    #[inline]
+    fn calculate_initial_strain(
+        &self,
+        time: f64,
+        curr: &OsuDifficultyObject<'_>,
+        diff_objects: &[OsuDifficultyObject<'_>],
+    ) -> f64 {
+        self.curr_strain * Self::strain_decay(time - previous_start_time(diff_objects, curr.idx, 0))
+    }
+}
+
+impl OsuStrainSkill for Rhythm {
+    fn strains(&self) -> &Vec<f64> {
+        &self.object_strains
+    }
+
+    fn set_raw_difficulty_value(&mut self, value: f64) {
+        self.difficulty = value;
+    }
+
+    fn get_raw_difficulty_value(&self) -> f64 {
+        self.difficulty
+    }
+}
@@ -1,6 +1,6 @@
 use std::{cmp::Ordering, f64::consts::PI};
 
-use crate::{osu::difficulty_object::OsuDifficultyObject};
+use crate::osu::difficulty_object::OsuDifficultyObject;
 
 use super::{next, previous, previous_start_time, OsuStrainSkill, Skill, StrainSkill};
 
@@ -85,9 +85,8 @@ impl StrainSkill for Speed {
         diff_objects: &[OsuDifficultyObject<'_>],
     ) -> f64 {
         self.curr_strain *= Self::strain_decay(curr.strain_time);
-        self.curr_strain +=
-            SpeedEvaluator::evaluate_diff_of(curr, diff_objects, self.hit_window)
-                * Self::SKILL_MULTIPLIER;
+        self.curr_strain += SpeedEvaluator::evaluate_diff_of(curr, diff_objects, self.hit_window)
+            * Self::SKILL_MULTIPLIER;
         self.curr_rhythm = RhythmEvaluator::evaluate_diff_of(curr, diff_objects, self.hit_window);
 
         let total_strain = self.curr_strain * self.curr_rhythm;
@@ -174,7 +173,8 @@ impl SpeedEvaluator {
         };
 
         let travel_dist = osu_prev_obj.map_or(0.0, |obj| obj.dists.travel_dist);
-        let dist = {Self::SINGLE_SPACING_THRESHOLD.min(travel_dist + osu_curr_obj.dists.min_jump_dist)};
+        let dist =
+            { Self::SINGLE_SPACING_THRESHOLD.min(travel_dist + osu_curr_obj.dists.min_jump_dist) };
 
         (speed_bonus + speed_bonus * (dist / Self::SINGLE_SPACING_THRESHOLD).powf(3.5))
             * doubletapness
@@ -182,14 +182,14 @@ impl SpeedEvaluator {
     }
 }
 
-struct RhythmEvaluator;
+pub(super) struct RhythmEvaluator;
 
 impl RhythmEvaluator {
     // * 5 seconds of calculatingRhythmBonus max.
     const HISTORY_TIME_MAX: u32 = 5000;
     const RHYTHM_MULTIPLIER: f64 = 0.75;
 
-    fn evaluate_diff_of(
+    pub(super) fn evaluate_diff_of(
         curr: &OsuDifficultyObject<'_>,
         diff_objects: &[OsuDifficultyObject<'_>],
         hit_window: f64,
@@ -33,7 +33,7 @@ pub(crate) trait StrainSkill: Skill + Sized {
     ) {
         // * The first object doesn't generate a strain, so we begin with an incremented section end
         if curr.idx == 0 {
-            let section_len = SECTION_LEN as f64;
+            let section_len = SECTION_LEN;
             *self.curr_section_end() = (curr.start_time / section_len).ceil() * section_len;
         }
 
@@ -45,7 +45,7 @@ pub(crate) trait StrainSkill: Skill + Sized {
                 self.start_new_section_from(section_end, curr, diff_objects);
             }
 
-            *self.curr_section_end() += SECTION_LEN as f64;
+            *self.curr_section_end() += SECTION_LEN;
         }
 
         *self.curr_section_peak() = self
@@ -88,37 +88,12 @@ pub(crate) trait OsuStrainSkill: StrainSkill + Sized {
     const DIFFICULTY_MULTIPLER: f64 = 1.06;
 
     fn difficulty_value(&mut self) -> f64 {
-        let mut difficulty = 0.0;
-        let mut weight = 1.0;
-
-        // * Sections with 0 strain are excluded to avoid worst-case time complexity of the following sort (e.g. /b/2351871).
-        // * These sections will not contribute to the difficulty.
-        let mut peaks = self.get_curr_strain_peaks();
-
-        peaks.retain(|&peak| peak > 0.0);
-        peaks.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
-
-        let peak_iter = peaks.iter_mut().take(Self::REDUCED_SECTION_COUNT);
-
-        fn lerp(start: f64, end: f64, amount: f64) -> f64 {
-            start + (end - start) * amount
-        }
-
-        // * We are reducing the highest strains first to account for extreme difficulty spikes
-        for (i, strain) in peak_iter.enumerate() {
-            let clamped = (i as f32 / Self::REDUCED_SECTION_COUNT as f32).clamp(0.0, 1.0) as f64;
-            let scale = (lerp(1.0, 10.0, clamped)).log10();
-            *strain *= lerp(Self::REDUCED_STRAIN_BASELINE, 1.0, scale);
-        }
-
-        peaks.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
-
-        // * Difficulty is the weighted sum of the highest strains from every section.
-        // * We're sorting from highest to lowest strain.
-        for strain in peaks {
-            difficulty += strain * weight;
-            weight *= Self::DECAY_WEIGHT;
-        }
+        let difficulty = reduced_difficulty(
+            self.get_curr_strain_peaks(),
+            Self::REDUCED_SECTION_COUNT,
+            Self::REDUCED_STRAIN_BASELINE,
+            Self::DECAY_WEIGHT,
+        );
 
         self.set_raw_difficulty_value(difficulty);
         difficulty * Self::DIFFICULTY_MULTIPLER
@@ -147,3 +122,43 @@ pub(crate) trait OsuStrainSkill: StrainSkill + Sized {
         }
     }
 }
+
+/// Weighted sum of the strain peaks after reducing the highest ones.
+pub(crate) fn reduced_difficulty(
+    mut peaks: Vec<f64>,
+    reduced_section_count: usize,
+    reduced_strain_baseline: f64,
+    decay_weight: f64,
+) -> f64 {
+    let mut difficulty = 0.0;
+    let mut weight = 1.0;
+
+    // * Sections with 0 strain are excluded to avoid worst-case time complexity of the following sort (e.g. /b/2351871).
+    // * These sections will not contribute to the difficulty.
+    peaks.retain(|&peak| peak > 0.0);
+    peaks.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+    let peak_iter = peaks.iter_mut().take(reduced_section_count);
+
+    fn lerp(start: f64, end: f64, amount: f64) -> f64 {
+        start + (end - start) * amount
+    }
+
+    // * We are reducing the highest strains first to account for extreme difficulty spikes
+    for (i, strain) in peak_iter.enumerate() {
+        let clamped = (i as f32 / reduced_section_count as f32).clamp(0.0, 1.0) as f64;
+        let scale = (lerp(1.0, 10.0, clamped)).log10();
+        *strain *= lerp(reduced_strain_baseline, 1.0, scale);
+    }
+
+    peaks.sort_unstable_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+    // * Difficulty is the weighted sum of the highest strains from every section.
+    // * We're sorting from highest to lowest strain.
+    for strain in peaks {
+        difficulty += strain * weight;
+        weight *= decay_weight;
+    }
+
+    difficulty
+}
@@ -0,0 +1,177 @@
+use std::fmt;
+
+use crate::Mods;
+
+use super::{AUTOPILOT_MULTIPLIER, AUTOPILOT_SPEED_BONUS, PERFORMANCE_BASE_MULTIPLIER};
+
+/// Multipliers and exponents of the osu!standard performance calculation.
+///
+/// The default values are the ones of the regular calculation.
+/// The NF, SO, and RX multipliers are specified through [`ModMultipliers`] instead.
+/// Servers running customized formulas can adjust them through [`OsuPP::tuning`](crate::OsuPP::tuning).
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, OsuPP, osu::PpTuning};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let tuning = PpTuning {
+///     base_multiplier: 1.12,
+///     ..Default::default()
+/// };
+///
+/// let pp_result = OsuPP::new(&map).tuning(tuning).calculate();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PpTuning {
+    /// Multiplier applied to the total pp of every score.
+    pub base_multiplier: f64,
+    /// Multiplier applied to the total pp of AP scores.
+    pub ap_multiplier: f64,
+    /// Multiplier applied to the speed pp of AP scores.
+    pub ap_speed_multiplier: f64,
+    /// Exponent of the aim pp when combining the skill values.
+    pub aim_exponent: f64,
+    /// Exponent of the speed pp when combining the skill values.
+    pub speed_exponent: f64,
+    /// Exponent of the accuracy pp when combining the skill values.
+    pub acc_exponent: f64,
+    /// Exponent of the flashlight pp when combining the skill values.
+    pub flashlight_exponent: f64,
+    /// The combined skill values are taken to the power of the reciprocal of this value.
+    pub total_exponent: f64,
+}
+
+impl Default for PpTuning {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            base_multiplier: PERFORMANCE_BASE_MULTIPLIER,
+            ap_multiplier: AUTOPILOT_MULTIPLIER,
+            ap_speed_multiplier: AUTOPILOT_SPEED_BONUS,
+            aim_exponent: 1.1,
+            speed_exponent: 1.1,
+            acc_exponent: 1.1,
+            flashlight_exponent: 1.1,
+            total_exponent: 1.1,
+        }
+    }
+}
+
+const NF_MISS_PENALTY: f64 = 0.02;
+const NF_MIN_MULTIPLIER: f64 = 0.9;
+const SO_SPINNER_EXPONENT: f64 = 0.85;
+
+/// Score values that [`ModMultipliers`] can base their multipliers on.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ModMultiplierInput {
+    /// The mods of the score.
+    pub mods: u32,
+    /// The amount of hitobjects of the score.
+    pub total_hits: f64,
+    /// The amount of misses including estimated slider breaks.
+    pub effective_miss_count: f64,
+    /// The amount of spinners of the map.
+    pub n_spinners: usize,
+}
+
+/// Strategy for the multipliers that NF, SO, and RX apply to the total pp
+/// of the osu!standard performance calculation.
+///
+/// Each method is only called if the score contains the respective mod.
+/// The default implementations use the regular multipliers
+/// so only the relevant methods need to be overridden.
+/// Specify the strategy through [`OsuPP::mod_multipliers`](crate::OsuPP::mod_multipliers).
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use murasame_pp::{
+///     osu::{ModMultiplierInput, ModMultipliers},
+///     Beatmap, OsuPP,
+/// };
+///
+/// // Relax scores are worth 60% on this server
+/// #[derive(Debug)]
+/// struct RelaxPenalty;
+///
+/// impl ModMultipliers for RelaxPenalty {
+///     fn relax(&self, _: &ModMultiplierInput) -> f64 {
+///         0.6
+///     }
+/// }
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let pp_result = OsuPP::new(&map)
+///     .mods(128)
+///     .mod_multipliers(Arc::new(RelaxPenalty))
+///     .calculate();
+/// ```
+pub trait ModMultipliers: fmt::Debug + Send + Sync {
+    /// The multiplier for NF scores.
+    ///
+    /// By default, every effective miss reduces the pp by 2% down to 90%.
+    #[inline]
+    fn no_fail(&self, input: &ModMultiplierInput) -> f64 {
+        (1.0 - NF_MISS_PENALTY * input.effective_miss_count).max(NF_MIN_MULTIPLIER)
+    }
+
+    /// The multiplier for SO scores.
+    ///
+    /// By default, the pp are reduced based on the ratio of spinners.
+    #[inline]
+    fn spun_out(&self, input: &ModMultiplierInput) -> f64 {
+        if input.total_hits > 0.0 {
+            1.0 - (input.n_spinners as f64 / input.total_hits).powf(SO_SPINNER_EXPONENT)
+        } else {
+            1.0
+        }
+    }
+
+    /// The multiplier for RX scores.
+    ///
+    /// By default, the pp are unchanged since relax is already considered by the star rating
+    /// when calculated through [`OsuPP::relax`](crate::OsuPP::relax) or
+    /// [`OsuStars::relax`](crate::OsuStars::relax).
+    #[inline]
+    fn relax(&self, input: &ModMultiplierInput) -> f64 {
+        let _ = input;
+
+        1.0
+    }
+}
+
+/// The regular NF, SO, and RX multipliers.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DefaultModMultipliers;
+
+impl ModMultipliers for DefaultModMultipliers {}
+
+impl ModMultiplierInput {
+    pub(crate) fn multiplier(&self, strategy: &dyn ModMultipliers) -> f64 {
+        let mut multiplier = 1.0;
+
+        if self.mods.nf() {
+            multiplier *= strategy.no_fail(self);
+        }
+
+        if self.mods.so() {
+            multiplier *= strategy.spun_out(self);
+        }
+
+        if self.mods.rx() {
+            multiplier *= strategy.relax(self);
+        }
+
+        multiplier
+    }
+}
@@ -85,7 +85,9 @@ const MAX_COORDINATE_VALUE: i32 = 131_072;
 const KIAI_FLAG: i32 = 1 << 0;
 
 macro_rules! section {
-    ($map:ident, $func:ident, $reader:ident, $section:ident) => {{
+    ($map:ident, $func:ident, $reader:ident, $section:ident, $phase:ident) => {{
+        profile!($phase);
+
         #[cfg(not(any(feature = "async_std", feature = "async_tokio")))]
         if $map.$func(&mut $reader, &mut $section)? {
             break;
@@ -117,6 +119,7 @@ macro_rules! parse_general_body {
         let mut mode = None;
         let mut empty = true;
         let mut stack_leniency = None;
+        let mut audio_lead_in = None;
 
         while next_line!($reader)? != 0 {
             if let Some(bytes) = $reader.get_section() {
@@ -142,10 +145,17 @@ macro_rules! parse_general_body {
                     stack_leniency = Some(val);
                 }
             }
+
+            if key == b"AudioLeadIn" {
+                if let Some(val) = f64::parse_in_range(value) {
+                    audio_lead_in = Some(val);
+                }
+            }
         }
 
         $self.mode = mode.unwrap_or(GameMode::Osu);
         $self.stack_leniency = stack_leniency.unwrap_or(0.7);
+        $self.audio_lead_in = audio_lead_in.unwrap_or(0.0);
 
         Ok(empty)
     }};
@@ -167,7 +177,7 @@ macro_rules! parse_metadata_body {
             if key == b"Creator" {
                 $self.creator = value.to_string();
             } else if key == b"BeatmapID" {
-                if let Some(val) = u32::from_str(value).ok() {
+                if let Ok(val) = u32::from_str(value) {
                     $self.beatmap_id = val;
                 }
             }
@@ -574,7 +584,7 @@ macro_rules! parse_hitobjects_body {
                 point_split.clear();
                 point_split.extend(control_point_iter);
 
-                #[allow(clippy::blocks_in_if_conditions)]
+                #[allow(clippy::blocks_in_conditions)]
                 while {
                     end_idx += 1;
 
@@ -739,7 +749,7 @@ fn parse_custom_sound(sound: &str) -> u8 {
     sound
         .bytes()
         .try_fold(0_u8, |sound, byte| match byte {
-            b'0'..=b'9' => Some(sound.wrapping_mul(10).wrapping_add((byte & 0xF) as u8)),
+            b'0'..=b'9' => Some(sound.wrapping_mul(10).wrapping_add(byte & 0xF)),
             _ => None,
         })
         .unwrap_or(0)
@@ -777,12 +787,14 @@ macro_rules! parse_body {
 
         loop {
             match section {
-                Section::General => section!(map, parse_general, reader, section),
-                Section::Metadata => section!(map, parse_metadata, reader, section),
-                Section::Difficulty => section!(map, parse_difficulty, reader, section),
-                Section::Events => section!(map, parse_events, reader, section),
-                Section::TimingPoints => section!(map, parse_timingpoints, reader, section),
-                Section::HitObjects => section!(map, parse_hitobjects, reader, section),
+                Section::General => section!(map, parse_general, reader, section, General),
+                Section::Metadata => section!(map, parse_metadata, reader, section, Metadata),
+                Section::Difficulty => section!(map, parse_difficulty, reader, section, Difficulty),
+                Section::Events => section!(map, parse_events, reader, section, Events),
+                Section::TimingPoints => {
+                    section!(map, parse_timingpoints, reader, section, TimingPoints)
+                }
+                Section::HitObjects => section!(map, parse_hitobjects, reader, section, HitObjects),
                 Section::None => {
                     if next_line!(reader)? == 0 {
                         break;
@@ -869,7 +881,7 @@ mod slider_parsing {
         let mut start_idx = 0;
         let mut end_idx = 0;
 
-        #[allow(clippy::blocks_in_if_conditions)]
+        #[allow(clippy::blocks_in_conditions)]
         while {
             end_idx += 1;
 
@@ -144,7 +144,7 @@ impl<R> FileReader<R> {
                 let mut n = 0;
 
                 for byte in &self.buf[idx..] {
-                    if !(b'0'..=b'9').contains(byte) {
+                    if !byte.is_ascii_digit() {
                         break;
                     }
 
@@ -202,7 +202,7 @@ impl<R> FileReader<R> {
         }
 
         // necessary check for the edge case `//\r\n`
-        if self.buf.starts_with(&[b'/', b'/']) {
+        if self.buf.starts_with(b"//") {
             return self.buf.clear();
         }
 
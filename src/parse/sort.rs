@@ -68,7 +68,7 @@ fn depth_limited_quick_sort(
 
         depth_limit -= 1;
 
-        if j.saturating_sub(left) <= right - i {
+        if j as isize - left as isize <= right as isize - i as isize {
             if left < j {
                 depth_limited_quick_sort(keys, left, j, depth_limit);
             }
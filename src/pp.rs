@@ -1,17 +1,23 @@
+use std::{convert::TryFrom, error::Error as StdError, fmt};
+
 use crate::{
     catch::{CatchDifficultyAttributes, CatchPP, CatchPerformanceAttributes},
     mania::{ManiaDifficultyAttributes, ManiaPP, ManiaPerformanceAttributes},
     osu::{OsuDifficultyAttributes, OsuPP, OsuPerformanceAttributes},
     taiko::{TaikoDifficultyAttributes, TaikoPP, TaikoPerformanceAttributes},
-    Beatmap, DifficultyAttributes, GameMode, PerformanceAttributes, ScoreState,
+    Beatmap, CustomMod, DifficultyAttributes, GameMode, InvalidScore, PerformanceAttributes,
+    ScoreState,
 };
 
 /// Performance calculator on maps of any mode.
 ///
+/// [`AnyPP::new`] picks the calculator of the map's mode so that scores of
+/// different modes can be handled without matching on the mode first.
+///
 /// # Example
 ///
 /// ```no_run
-/// use rosu_pp::{AnyPP, Beatmap};
+/// use murasame_pp::{AnyPP, Beatmap};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -72,6 +78,157 @@ impl<'map> AnyPP<'map> {
         }
     }
 
+    /// Calculate the performance attributes for each of the given score states.
+    ///
+    /// The difficulty attributes are calculated only once and shared by all scores,
+    /// e.g. to recalculate all scores of a leaderboard.
+    #[inline]
+    pub fn calculate_many(self, states: &[ScoreState]) -> Vec<PerformanceAttributes> {
+        match self {
+            Self::Osu(o) => {
+                let states: Vec<_> = states.iter().cloned().map(From::from).collect();
+
+                o.calculate_many(&states)
+                    .into_iter()
+                    .map(PerformanceAttributes::Osu)
+                    .collect()
+            }
+            Self::Taiko(t) => {
+                let states: Vec<_> = states.iter().cloned().map(From::from).collect();
+
+                t.calculate_many(&states)
+                    .into_iter()
+                    .map(PerformanceAttributes::Taiko)
+                    .collect()
+            }
+            Self::Catch(f) => {
+                let states: Vec<_> = states.iter().cloned().map(From::from).collect();
+
+                f.calculate_many(&states)
+                    .into_iter()
+                    .map(PerformanceAttributes::Catch)
+                    .collect()
+            }
+            Self::Mania(m) => {
+                let states: Vec<_> = states.iter().cloned().map(From::from).collect();
+
+                m.calculate_many(&states)
+                    .into_iter()
+                    .map(PerformanceAttributes::Mania)
+                    .collect()
+            }
+        }
+    }
+
+    /// Calculate the performance attributes for the worst and best possible combo
+    /// in case the combo of the score is unknown.
+    ///
+    /// Only osu!standard and osu!catch consider combo so for the other modes
+    /// both attributes are the same.
+    #[inline]
+    pub fn calculate_range(self) -> (PerformanceAttributes, PerformanceAttributes) {
+        match self {
+            Self::Osu(o) => {
+                let (min, max) = o.calculate_range();
+
+                (
+                    PerformanceAttributes::Osu(min),
+                    PerformanceAttributes::Osu(max),
+                )
+            }
+            Self::Taiko(t) => {
+                let attrs = PerformanceAttributes::Taiko(t.calculate());
+
+                (attrs, attrs)
+            }
+            Self::Catch(f) => {
+                let (min, max) = f.calculate_range();
+
+                (
+                    PerformanceAttributes::Catch(min),
+                    PerformanceAttributes::Catch(max),
+                )
+            }
+            Self::Mania(m) => {
+                let attrs = PerformanceAttributes::Mania(m.calculate());
+
+                (attrs, attrs)
+            }
+        }
+    }
+
+    /// Calculate the performance attributes of the score and of an SS
+    /// on the same map with the same mods.
+    ///
+    /// The difficulty attributes are calculated only once and shared by both calculations
+    /// unless `passed_objects` is specified, in which case the SS is calculated on the full map.
+    #[inline]
+    pub fn calculate_with_max(self) -> (PerformanceAttributes, PerformanceAttributes) {
+        match self {
+            Self::Osu(o) => {
+                let (score, max) = o.calculate_with_max();
+
+                (
+                    PerformanceAttributes::Osu(score),
+                    PerformanceAttributes::Osu(max),
+                )
+            }
+            Self::Taiko(t) => {
+                let (score, max) = t.calculate_with_max();
+
+                (
+                    PerformanceAttributes::Taiko(score),
+                    PerformanceAttributes::Taiko(max),
+                )
+            }
+            Self::Catch(f) => {
+                let (score, max) = f.calculate_with_max();
+
+                (
+                    PerformanceAttributes::Catch(score),
+                    PerformanceAttributes::Catch(max),
+                )
+            }
+            Self::Mania(m) => {
+                let (score, max) = m.calculate_with_max();
+
+                (
+                    PerformanceAttributes::Mania(score),
+                    PerformanceAttributes::Mania(max),
+                )
+            }
+        }
+    }
+
+    /// Same as [`calculate`](AnyPP::calculate) but the specified values are
+    /// first validated against the map.
+    ///
+    /// Returns an [`InvalidScore`] describing the first inconsistent value.
+    #[inline]
+    pub fn try_calculate(self) -> Result<PerformanceAttributes, InvalidScore> {
+        match self {
+            Self::Osu(o) => o.try_calculate().map(PerformanceAttributes::Osu),
+            Self::Taiko(t) => t.try_calculate().map(PerformanceAttributes::Taiko),
+            Self::Catch(f) => f.try_calculate().map(PerformanceAttributes::Catch),
+            Self::Mania(m) => m.try_calculate().map(PerformanceAttributes::Mania),
+        }
+    }
+
+    /// Generate hitresults for the given accuracy between `0.0` and `100.0` the same way
+    /// [osu-tools](https://github.com/ppy/osu-tools)' `simulate` command does.
+    ///
+    /// See the `simulate` method of the mode's performance calculator for
+    /// which of the specified values are respected.
+    #[inline]
+    pub fn simulate(self, acc: f64) -> ScoreState {
+        match self {
+            Self::Osu(o) => o.simulate(acc).into(),
+            Self::Taiko(t) => t.simulate(acc).into(),
+            Self::Catch(f) => f.simulate(acc).into(),
+            Self::Mania(m) => m.simulate(acc).into(),
+        }
+    }
+
     /// Provide the result of a previous difficulty or performance calculation.
     /// If you already calculated the attributes for the current map-mod combination,
     /// be sure to put them in here so that they don't have to be recalculated.
@@ -140,6 +297,74 @@ impl<'map> AnyPP<'map> {
         }
     }
 
+    /// Override the map's approach rate before calculating its difficulty.
+    #[inline]
+    pub fn ar(self, ar: f32) -> Self {
+        match self {
+            Self::Osu(o) => Self::Osu(o.ar(ar)),
+            Self::Taiko(t) => Self::Taiko(t.ar(ar)),
+            Self::Catch(f) => Self::Catch(f.ar(ar)),
+            Self::Mania(m) => Self::Mania(m.ar(ar)),
+        }
+    }
+
+    /// Override the map's overall difficulty before calculating its difficulty.
+    #[inline]
+    pub fn od(self, od: f32) -> Self {
+        match self {
+            Self::Osu(o) => Self::Osu(o.od(od)),
+            Self::Taiko(t) => Self::Taiko(t.od(od)),
+            Self::Catch(f) => Self::Catch(f.od(od)),
+            Self::Mania(m) => Self::Mania(m.od(od)),
+        }
+    }
+
+    /// Override the map's circle size before calculating its difficulty.
+    #[inline]
+    pub fn cs(self, cs: f32) -> Self {
+        match self {
+            Self::Osu(o) => Self::Osu(o.cs(cs)),
+            Self::Taiko(t) => Self::Taiko(t.cs(cs)),
+            Self::Catch(f) => Self::Catch(f.cs(cs)),
+            Self::Mania(m) => Self::Mania(m.cs(cs)),
+        }
+    }
+
+    /// Override the map's drain rate before calculating its difficulty.
+    #[inline]
+    pub fn hp(self, hp: f32) -> Self {
+        match self {
+            Self::Osu(o) => Self::Osu(o.hp(hp)),
+            Self::Taiko(t) => Self::Taiko(t.hp(hp)),
+            Self::Catch(f) => Self::Catch(f.hp(hp)),
+            Self::Mania(m) => Self::Mania(m.hp(hp)),
+        }
+    }
+
+    /// Apply the effects of a [`CustomMod`] on top of the mods, e.g. a private server's own mod.
+    #[inline]
+    pub fn custom_mod(self, custom_mod: CustomMod) -> Self {
+        match self {
+            Self::Osu(o) => Self::Osu(o.custom_mod(custom_mod)),
+            Self::Taiko(t) => Self::Taiko(t.custom_mod(custom_mod)),
+            Self::Catch(f) => Self::Catch(f.custom_mod(custom_mod)),
+            Self::Mania(m) => Self::Mania(m.custom_mod(custom_mod)),
+        }
+    }
+
+    /// Specify how hitresults should be generated.
+    ///
+    /// Defaults to [`HitResultPriority::BestCase`]. Irrelevant for osu!catch.
+    #[inline]
+    pub fn hitresult_priority(self, priority: HitResultPriority) -> Self {
+        match self {
+            Self::Osu(o) => Self::Osu(o.hitresult_priority(priority)),
+            Self::Taiko(t) => Self::Taiko(t.hitresult_priority(priority)),
+            Self::Catch(_) => self,
+            Self::Mania(m) => Self::Mania(m.hitresult_priority(priority)),
+        }
+    }
+
     /// Provide parameters through a [`ScoreState`].
     #[inline]
     pub fn state(self, state: ScoreState) -> Self {
@@ -226,10 +451,12 @@ impl<'map> AnyPP<'map> {
     /// This value is only relevant for osu!catch for which it represents
     /// the amount of tiny droplet misses and osu!mania for which it.
     /// repesents the amount of n200.
+    ///
+    /// For osu!standard it is kept in case the calculator is converted to another mode.
     #[inline]
     pub fn n_katu(self, n_katu: usize) -> Self {
         match self {
-            Self::Osu(_) => self,
+            Self::Osu(o) => Self::Osu(o.n_katu(n_katu)),
             Self::Taiko(_) => self,
             Self::Catch(f) => Self::Catch(f.tiny_droplet_misses(n_katu)),
             Self::Mania(m) => Self::Mania(m.n200(n_katu)),
@@ -240,10 +467,12 @@ impl<'map> AnyPP<'map> {
     ///
     /// This value is only relevant for osu!mania for which it.
     /// repesents the amount of n320.
+    ///
+    /// For osu!standard it is kept in case the calculator is converted to another mode.
     #[inline]
     pub fn n_geki(self, n_geki: usize) -> Self {
         match self {
-            Self::Osu(_) => self,
+            Self::Osu(o) => Self::Osu(o.n_geki(n_geki)),
             Self::Taiko(_) => self,
             Self::Catch(_) => self,
             Self::Mania(m) => Self::Mania(m.n320(n_geki)),
@@ -251,6 +480,34 @@ impl<'map> AnyPP<'map> {
     }
 }
 
+impl<'map> From<OsuPP<'map>> for AnyPP<'map> {
+    #[inline]
+    fn from(calculator: OsuPP<'map>) -> Self {
+        Self::Osu(calculator)
+    }
+}
+
+impl<'map> From<TaikoPP<'map>> for AnyPP<'map> {
+    #[inline]
+    fn from(calculator: TaikoPP<'map>) -> Self {
+        Self::Taiko(calculator)
+    }
+}
+
+impl<'map> From<CatchPP<'map>> for AnyPP<'map> {
+    #[inline]
+    fn from(calculator: CatchPP<'map>) -> Self {
+        Self::Catch(calculator)
+    }
+}
+
+impl<'map> From<ManiaPP<'map>> for AnyPP<'map> {
+    #[inline]
+    fn from(calculator: ManiaPP<'map>) -> Self {
+        Self::Mania(calculator)
+    }
+}
+
 /// While generating remaining hitresults, decide how they should be distributed.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum HitResultPriority {
@@ -260,6 +517,14 @@ pub enum HitResultPriority {
     WorstCase,
 }
 
+/// The lowest and highest possible combo for the given amount of misses.
+pub(crate) fn combo_range(max_combo: usize, n_misses: usize) -> (usize, usize) {
+    let remaining = max_combo.saturating_sub(n_misses);
+    let min = (remaining + n_misses) / (n_misses + 1);
+
+    (min.min(remaining), remaining)
+}
+
 impl Default for HitResultPriority {
     #[inline]
     fn default() -> Self {
@@ -268,11 +533,57 @@ impl Default for HitResultPriority {
 }
 
 /// Abstract type to provide flexibility when passing difficulty attributes to a performance calculation.
+///
+/// Every type implementing this trait can also be passed to the performance
+/// calculator of each mode, e.g. a cache entry can be implemented once for all modes.
 pub trait AttributeProvider {
     /// Provide the actual difficulty attributes.
     fn attributes(self) -> DifficultyAttributes;
+
+    /// Provide the difficulty attributes of a specific mode.
+    ///
+    /// Returns a [`ModeMismatch`] if the attributes belong to another mode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use murasame_pp::{osu::OsuDifficultyAttributes, AttributeProvider, DifficultyAttributes};
+    ///
+    /// let attrs = DifficultyAttributes::Osu(OsuDifficultyAttributes::default());
+    ///
+    /// let osu: OsuDifficultyAttributes = attrs.mode_attributes().unwrap();
+    /// ```
+    #[inline]
+    fn mode_attributes<A>(self) -> Result<A, ModeMismatch>
+    where
+        Self: Sized,
+        A: TryFrom<DifficultyAttributes, Error = ModeMismatch>,
+    {
+        A::try_from(self.attributes())
+    }
+}
+
+/// The difficulty attributes of an [`AttributeProvider`] belong to a different mode than requested.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ModeMismatch {
+    /// The requested mode.
+    pub expected: GameMode,
+    /// The mode of the provided attributes.
+    pub actual: GameMode,
 }
 
+impl fmt::Display for ModeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected {:?} attributes but got {:?} attributes",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl StdError for ModeMismatch {}
+
 impl AttributeProvider for DifficultyAttributes {
     #[inline]
     fn attributes(self) -> DifficultyAttributes {
@@ -307,6 +618,21 @@ macro_rules! impl_attr_provider {
                 DifficultyAttributes::$mode(self.difficulty)
             }
         }
+
+        impl TryFrom<DifficultyAttributes> for $difficulty {
+            type Error = ModeMismatch;
+
+            #[inline]
+            fn try_from(attributes: DifficultyAttributes) -> Result<Self, Self::Error> {
+                match attributes {
+                    DifficultyAttributes::$mode(attrs) => Ok(attrs),
+                    other => Err(ModeMismatch {
+                        expected: GameMode::$mode,
+                        actual: other.mode(),
+                    }),
+                }
+            }
+        }
     };
 }
 
@@ -314,3 +640,132 @@ impl_attr_provider!(Catch: CatchDifficultyAttributes, CatchPerformanceAttributes
 impl_attr_provider!(Mania: ManiaDifficultyAttributes, ManiaPerformanceAttributes);
 impl_attr_provider!(Osu: OsuDifficultyAttributes, OsuPerformanceAttributes);
 impl_attr_provider!(Taiko: TaikoDifficultyAttributes, TaikoPerformanceAttributes);
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combo_range_splits_evenly() {
+        assert_eq!(combo_range(100, 0), (100, 100));
+        assert_eq!(combo_range(100, 1), (50, 99));
+        assert_eq!(combo_range(100, 2), (33, 98));
+        assert_eq!(combo_range(5, 10), (0, 0));
+    }
+
+    #[test]
+    fn geki_katu_survive_conversion() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let attrs = AnyPP::new(&map)
+            .n_geki(300)
+            .n_katu(20)
+            .n_misses(1)
+            .mode(GameMode::Mania)
+            .calculate();
+
+        let state = attrs.state();
+
+        assert_eq!(state.n_geki, 300);
+        assert_eq!(state.n_katu, 20);
+        assert_eq!(state.n_misses, 1);
+    }
+
+    #[test]
+    fn overrides_match_adjusted_map() {
+        for path in [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ] {
+            let map = Beatmap::from_path(path).unwrap();
+
+            let mut adjusted = map.clone();
+            adjusted.ar = 9.5;
+            adjusted.od = 8.0;
+            adjusted.cs = 4.0;
+            adjusted.hp = 5.0;
+
+            let overridden = AnyPP::new(&map)
+                .ar(9.5)
+                .od(8.0)
+                .cs(4.0)
+                .hp(5.0)
+                .mods(16)
+                .calculate();
+
+            let expected = AnyPP::new(&adjusted).mods(16).calculate();
+
+            assert_eq!(overridden, expected, "{}", path);
+        }
+    }
+
+    #[test]
+    fn custom_mod_clock_rate() {
+        for path in [
+            "./maps/2785319.osu",
+            "./maps/1028484.osu",
+            "./maps/2118524.osu",
+            "./maps/1974394.osu",
+        ] {
+            let map = Beatmap::from_path(path).unwrap();
+            let custom = CustomMod::new().clock_rate(1.25);
+
+            let with_custom = AnyPP::new(&map).mods(8).custom_mod(custom).calculate();
+            let expected = AnyPP::new(&map).mods(8).clock_rate(1.25).calculate();
+
+            assert_eq!(with_custom, expected, "{}", path);
+        }
+    }
+
+    #[test]
+    fn attribute_provider_per_mode() {
+        struct Cached(DifficultyAttributes);
+
+        impl AttributeProvider for Cached {
+            fn attributes(self) -> DifficultyAttributes {
+                self.0
+            }
+        }
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let attrs = OsuPP::new(&map).calculate();
+
+        let cached = Cached(DifficultyAttributes::Osu(attrs.difficulty));
+        assert_eq!(OsuPP::new(&map).attributes(cached).calculate(), attrs);
+
+        let cached = Cached(DifficultyAttributes::Osu(attrs.difficulty));
+        let err = cached
+            .mode_attributes::<TaikoDifficultyAttributes>()
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            ModeMismatch {
+                expected: GameMode::Taiko,
+                actual: GameMode::Osu,
+            }
+        );
+    }
+
+    #[test]
+    fn simulate_matches_mode_calculator() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let any = AnyPP::new(&map).n_misses(2).simulate(97.5);
+        let osu = OsuPP::new(&map).n_misses(2).simulate(97.5);
+        assert_eq!(any, ScoreState::from(osu));
+
+        let any = AnyPP::from(OsuPP::new(&map))
+            .hitresult_priority(HitResultPriority::WorstCase)
+            .accuracy(95.0)
+            .calculate();
+        let osu = OsuPP::new(&map)
+            .hitresult_priority(HitResultPriority::WorstCase)
+            .accuracy(95.0)
+            .calculate();
+        assert_eq!(any, PerformanceAttributes::Osu(osu));
+    }
+}
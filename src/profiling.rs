@@ -0,0 +1,166 @@
+use std::{
+    cell::RefCell,
+    time::{Duration, Instant},
+};
+
+thread_local! {
+    static TIMINGS: RefCell<Option<Timings>> = const { RefCell::new(None) };
+}
+
+/// Time spent in the individual phases of all calculations within [`measure`].
+///
+/// Durations accumulate so if a map is parsed twice, the parsing
+/// durations will be the sum of both parses.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Time spent parsing the `[General]` section.
+    pub general: Duration,
+    /// Time spent parsing the `[Metadata]` section.
+    pub metadata: Duration,
+    /// Time spent parsing the `[Difficulty]` section.
+    pub difficulty: Duration,
+    /// Time spent parsing the `[Events]` section.
+    pub events: Duration,
+    /// Time spent parsing the `[TimingPoints]` section.
+    pub timing_points: Duration,
+    /// Time spent parsing the `[HitObjects]` section, including sorting.
+    pub hit_objects: Duration,
+    /// Time spent converting maps into other modes.
+    pub conversion: Duration,
+    /// Time spent creating difficulty objects and processing them in skills.
+    pub skills: Duration,
+    /// Time spent evaluating the performance formula.
+    pub performance: Duration,
+}
+
+impl Timings {
+    /// Total time spent parsing across all sections.
+    #[inline]
+    pub fn parsing(&self) -> Duration {
+        self.general
+            + self.metadata
+            + self.difficulty
+            + self.events
+            + self.timing_points
+            + self.hit_objects
+    }
+
+    /// Total time spent across all phases.
+    #[inline]
+    pub fn total(&self) -> Duration {
+        self.parsing() + self.conversion + self.skills + self.performance
+    }
+
+    fn phase_mut(&mut self, phase: Phase) -> &mut Duration {
+        match phase {
+            Phase::General => &mut self.general,
+            Phase::Metadata => &mut self.metadata,
+            Phase::Difficulty => &mut self.difficulty,
+            Phase::Events => &mut self.events,
+            Phase::TimingPoints => &mut self.timing_points,
+            Phase::HitObjects => &mut self.hit_objects,
+            Phase::Conversion => &mut self.conversion,
+            Phase::Skills => &mut self.skills,
+            Phase::Performance => &mut self.performance,
+        }
+    }
+}
+
+/// Run `f` and record how much time each calculation phase took within it.
+///
+/// To include parsing in the timings, parse the map inside of `f`.
+///
+/// Only work on the current thread is recorded, async parsing that moves
+/// between threads may be missing from the timings.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{profiling, Beatmap, BeatmapExt};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let (attrs, timings) = profiling::measure(|| map.pp().mods(64).calculate());
+///
+/// println!(
+///     "{:.2}pp | skills: {:?} | performance: {:?}",
+///     attrs.pp(),
+///     timings.skills,
+///     timings.performance,
+/// );
+/// ```
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Timings) {
+    let prev = TIMINGS.with(|timings| timings.replace(Some(Timings::default())));
+    let value = f();
+    let timings = TIMINGS.with(|timings| timings.replace(prev));
+
+    (value, timings.unwrap_or_default())
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum Phase {
+    General,
+    Metadata,
+    Difficulty,
+    Events,
+    TimingPoints,
+    HitObjects,
+    Conversion,
+    Skills,
+    Performance,
+}
+
+/// Adds the time until it is dropped to the current [`Timings`], if any.
+#[derive(Debug)]
+pub(crate) struct Timer {
+    phase: Phase,
+    start: Instant,
+}
+
+impl Timer {
+    #[inline]
+    pub(crate) fn start(phase: Phase) -> Self {
+        Self {
+            phase,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+
+        TIMINGS.with(|timings| {
+            if let Some(timings) = timings.borrow_mut().as_mut() {
+                *timings.phase_mut(self.phase) += elapsed;
+            }
+        });
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Beatmap, BeatmapExt};
+
+    #[test]
+    fn records_phases() {
+        let (_, timings) = measure(|| {
+            let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+            map.pp().calculate()
+        });
+
+        assert!(timings.hit_objects > Duration::ZERO);
+        assert!(timings.skills > Duration::ZERO);
+        assert_eq!(timings.conversion, Duration::ZERO);
+
+        let (_, timings) = measure(|| ());
+
+        assert_eq!(timings, Timings::default());
+    }
+}
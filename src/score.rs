@@ -0,0 +1,722 @@
+use crate::{
+    analysis,
+    catch::{
+        fruit_or_juice::{FruitOrJuice, FruitParams},
+        CatchDifficultyAttributes,
+    },
+    curve::CurveBuffers,
+    osu::{
+        osu_object::{NestedObjectKind, ObjectParameters, OsuObject, OsuObjectKind},
+        OsuDifficultyAttributes,
+    },
+    parse::HitObjectKind,
+    AnyStars, Beatmap, GameMode, Mods, ScoreState,
+};
+
+/// Score of a hit circle or the judgement of sliders and spinners.
+const GREAT: u32 = 300;
+const OK: u32 = 100;
+const MEH: u32 = 50;
+
+const SLIDER_HEAD: u32 = 30;
+const SLIDER_TICK: u32 = 10;
+const SLIDER_REPEAT: u32 = 30;
+
+const SPINNER_SPIN: u32 = 100;
+const SPINNER_BONUS_SPIN: u32 = 1100;
+
+const TAIKO_OK: u32 = 150;
+
+const DROPLET: u32 = 100;
+const TINY_DROPLET: u32 = 10;
+
+const MANIA_MAX_SCORE: f64 = 1_000_000.0;
+
+/// The judgement of a hit object.
+///
+/// [`Perfect`](HitResult::Perfect) and [`Good`](HitResult::Good) only exist in osu!mania,
+/// for other modes they count as [`Great`](HitResult::Great) and [`Ok`](HitResult::Ok)
+/// respectively. Similarly, osu!taiko treats [`Meh`](HitResult::Meh) as [`Ok`](HitResult::Ok).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum HitResult {
+    /// n320 in osu!mania
+    Perfect,
+    /// n300
+    Great,
+    /// n200 in osu!mania
+    Good,
+    /// n100
+    Ok,
+    /// n50
+    Meh,
+    /// Miss
+    Miss,
+}
+
+/// Calculator for the legacy ScoreV1 total score of osu!stable.
+///
+/// Sliders and juice streams that are not missed are assumed to have
+/// all their ticks hit and spinners that are not missed are assumed
+/// to be spun as fast as possible. osu!taiko drumrolls, swells, and strong hits,
+/// as well as osu!catch bananas are not considered.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, score::{HitResult, ScoreV1}};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let calculator = ScoreV1::new(&map).mods(8); // HD
+///
+/// let max_score = calculator.max_score();
+/// let score = calculator.simulate(&[HitResult::Great, HitResult::Ok, HitResult::Miss]);
+///
+/// println!("{}/{}", score, max_score);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScoreV1<'map> {
+    map: &'map Beatmap,
+    mode: GameMode,
+    mods: u32,
+}
+
+impl<'map> ScoreV1<'map> {
+    /// Create a new ScoreV1 calculator for the map's mode.
+    #[inline]
+    pub fn new(map: &'map Beatmap) -> Self {
+        Self {
+            map,
+            mode: map.mode,
+            mods: 0,
+        }
+    }
+
+    /// Calculate the score for a converted map of the given mode.
+    ///
+    /// Only osu!standard maps can be converted.
+    #[inline]
+    pub fn mode(mut self, mode: GameMode) -> Self {
+        if self.map.mode == GameMode::Osu {
+            self.mode = mode;
+        }
+
+        self
+    }
+
+    /// Specify mods through their bit values.
+    ///
+    /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// The score multiplier of the mods.
+    ///
+    /// Relax and Autopilot have a multiplier of zero, leaving only the score without combo bonus.
+    pub fn mod_multiplier(&self) -> f64 {
+        let mods = self.mods;
+
+        if self.mode == GameMode::Mania {
+            return if mods.nf() { 0.5 } else { 1.0 }
+                * if mods.ez() { 0.5 } else { 1.0 }
+                * if mods.ht() { 0.5 } else { 1.0 };
+        }
+
+        if self.mode == GameMode::Osu && (mods.rx() || mods.ap()) {
+            return 0.0;
+        }
+
+        let (hr, dt) = match self.mode {
+            GameMode::Catch => (1.12, 1.06),
+            _ => (1.06, 1.12),
+        };
+
+        let mut multiplier = 1.0;
+
+        if mods.nf() {
+            multiplier *= 0.5;
+        }
+
+        if mods.ez() {
+            multiplier *= 0.5;
+        }
+
+        if mods.ht() {
+            multiplier *= 0.3;
+        }
+
+        if mods.hd() {
+            multiplier *= 1.06;
+        }
+
+        if mods.hr() {
+            multiplier *= hr;
+        }
+
+        if mods.dt() {
+            multiplier *= dt;
+        }
+
+        if mods.fl() {
+            multiplier *= 1.12;
+        }
+
+        if mods.so() {
+            multiplier *= 0.9;
+        }
+
+        multiplier
+    }
+
+    /// The difficulty multiplier of the map.
+    ///
+    /// Based on the unmodded HP, CS, OD, and the object density.
+    pub fn difficulty_multiplier(&self) -> u32 {
+        let map = self.map;
+
+        let break_len: f64 = map.breaks.iter().map(|b| b.end_time - b.start_time).sum();
+
+        let drain_len = ((map.last_object_end_time(1.0).round()
+            - map.first_object_time(1.0).round()
+            - break_len.round()) as i64
+            / 1000) as f64;
+
+        let object_to_drain_ratio = if drain_len > 0.0 {
+            (map.hit_objects.len() as f64 / drain_len * 8.0).clamp(0.0, 16.0)
+        } else {
+            16.0
+        };
+
+        let sum = map.hp as f64 + map.od as f64 + map.cs as f64 + object_to_drain_ratio;
+
+        (sum / 38.0 * 5.0).round() as u32
+    }
+
+    /// Calculate the maximum achievable total score.
+    pub fn max_score(&self) -> u64 {
+        if self.mode == GameMode::Mania {
+            return (MANIA_MAX_SCORE * self.mod_multiplier()) as u64;
+        }
+
+        let n_objects = match self.mode {
+            GameMode::Taiko => self.map.convert_mode(GameMode::Taiko).hit_objects.len(),
+            _ => self.map.hit_objects.len(),
+        };
+
+        self.simulate_with(|_| HitResult::Great, n_objects)
+    }
+
+    /// Calculate the total score for the given judgement of each hit object.
+    ///
+    /// For converts, the results are expected to correspond
+    /// to the hit objects of the converted map.
+    /// Hit objects without a result are not considered.
+    pub fn simulate(&self, results: &[HitResult]) -> u64 {
+        self.simulate_with(|i| results[i], results.len())
+    }
+
+    fn simulate_with(&self, result: impl Fn(usize) -> HitResult, take: usize) -> u64 {
+        let multiplier = self.difficulty_multiplier() as f64 * self.mod_multiplier();
+
+        match self.mode {
+            GameMode::Osu => self.simulate_osu(multiplier, result, take),
+            GameMode::Taiko => self.simulate_taiko(multiplier, result, take),
+            GameMode::Catch => self.simulate_catch(multiplier, result, take),
+            GameMode::Mania => self.simulate_mania(result, take),
+        }
+    }
+
+    fn simulate_osu(
+        &self,
+        multiplier: f64,
+        result: impl Fn(usize) -> HitResult,
+        take: usize,
+    ) -> u64 {
+        let mut attrs = OsuDifficultyAttributes::default();
+
+        let mut params = ObjectParameters {
+            map: self.map,
+            attrs: &mut attrs,
+            ticks: Vec::new(),
+            curve_bufs: CurveBuffers::default(),
+        };
+
+        let mut spinners = analysis::spinners(self.map, self.mods).into_iter();
+        let mut score = ComboScore::new(multiplier);
+
+        for (i, h) in self.map.hit_objects.iter().take(take).enumerate() {
+            let h = OsuObject::new(h, &mut params);
+            let value = match result(i) {
+                HitResult::Perfect | HitResult::Great => GREAT,
+                HitResult::Good | HitResult::Ok => OK,
+                HitResult::Meh => MEH,
+                HitResult::Miss => 0,
+            };
+
+            match h.kind {
+                OsuObjectKind::Circle => score.hit(value, true),
+                OsuObjectKind::Slider(ref slider) => {
+                    if value == 0 {
+                        score.hit(0, true);
+
+                        continue;
+                    }
+
+                    score.hit_without_multiplier(SLIDER_HEAD, true);
+
+                    for nested in slider.nested_iter() {
+                        let value = match nested.kind {
+                            NestedObjectKind::Tick => SLIDER_TICK,
+                            NestedObjectKind::Repeat | NestedObjectKind::Tail => SLIDER_REPEAT,
+                        };
+
+                        score.hit_without_multiplier(value, true);
+                    }
+
+                    score.hit(value, false);
+                }
+                OsuObjectKind::Spinner { .. } => {
+                    let spinner = spinners.next();
+
+                    if let Some(spinner) = spinner.filter(|_| value > 0) {
+                        score.bonus += (spinner.required_spins * SPINNER_SPIN
+                            + spinner.max_bonus_spins * SPINNER_BONUS_SPIN)
+                            as u64;
+                    }
+
+                    score.hit(value, true);
+                }
+            }
+        }
+
+        score.total()
+    }
+
+    fn simulate_taiko(
+        &self,
+        multiplier: f64,
+        result: impl Fn(usize) -> HitResult,
+        take: usize,
+    ) -> u64 {
+        let map = self.map.convert_mode(GameMode::Taiko);
+
+        let hits = map
+            .hit_objects
+            .iter()
+            .take(take)
+            .enumerate()
+            .filter(|(_, h)| matches!(h.kind, HitObjectKind::Circle));
+
+        let mut score = ComboScore::new(multiplier);
+
+        for (i, _) in hits {
+            let value = match result(i) {
+                HitResult::Perfect | HitResult::Great => GREAT,
+                HitResult::Good | HitResult::Ok | HitResult::Meh => TAIKO_OK,
+                HitResult::Miss => 0,
+            };
+
+            if value == 0 {
+                score.combo = 0;
+
+                continue;
+            }
+
+            // Combo bonus increases every 10 combo up until 100 combo
+            let bonus = (value / 35 * 2) as f64 * multiplier;
+            let tier = score.combo.min(100) / 10;
+            score.base += value as u64;
+            score.combo_score += bonus as u64 * tier;
+            score.combo += 1;
+        }
+
+        score.total()
+    }
+
+    fn simulate_catch(
+        &self,
+        multiplier: f64,
+        result: impl Fn(usize) -> HitResult,
+        take: usize,
+    ) -> u64 {
+        let mut params = FruitParams {
+            attributes: CatchDifficultyAttributes::default(),
+            curve_bufs: CurveBuffers::default(),
+            last_pos: None,
+            last_time: 0.0,
+            map: self.map,
+            ticks: Vec::new(),
+            with_hr: false,
+        };
+
+        let mut score = ComboScore::new(multiplier);
+
+        let objects = self
+            .map
+            .hit_objects
+            .iter()
+            .take(take)
+            .enumerate()
+            .filter(|(_, h)| !matches!(h.kind, HitObjectKind::Spinner { .. }));
+
+        for (i, h) in objects {
+            let CatchDifficultyAttributes {
+                n_fruits,
+                n_droplets,
+                n_tiny_droplets,
+                ..
+            } = params.attributes;
+
+            let _ = FruitOrJuice::new(h, &mut params);
+
+            let n_fruits = params.attributes.n_fruits - n_fruits;
+            let n_droplets = params.attributes.n_droplets - n_droplets;
+            let n_tiny_droplets = params.attributes.n_tiny_droplets - n_tiny_droplets;
+
+            if result(i) == HitResult::Miss {
+                score.hit(0, true);
+
+                continue;
+            }
+
+            // The head, then the droplets, then the repeats and tail
+            score.hit(GREAT, true);
+
+            for _ in 0..n_droplets {
+                score.hit_without_multiplier(DROPLET, true);
+            }
+
+            for _ in 1..n_fruits {
+                score.hit(GREAT, true);
+            }
+
+            score.base += (n_tiny_droplets as u32 * TINY_DROPLET) as u64;
+        }
+
+        score.total()
+    }
+
+    fn simulate_mania(&self, result: impl Fn(usize) -> HitResult, take: usize) -> u64 {
+        let map = self.map.convert_mode(GameMode::Mania);
+        let n_objects = map.hit_objects.len();
+
+        if n_objects == 0 {
+            return 0;
+        }
+
+        let note_value = MANIA_MAX_SCORE * self.mod_multiplier() * 0.5 / n_objects as f64;
+
+        let mut score = 0.0;
+        let mut bonus = 100.0_f64;
+
+        for i in 0..n_objects.min(take) {
+            let (value, bonus_value, bonus_gain, punishment) = match result(i) {
+                HitResult::Perfect => (320.0, 32.0, 2.0, 0.0),
+                HitResult::Great => (300.0, 32.0, 1.0, 0.0),
+                HitResult::Good => (200.0, 16.0, 0.0, 8.0),
+                HitResult::Ok => (100.0, 8.0, 0.0, 24.0),
+                HitResult::Meh => (50.0, 4.0, 0.0, 44.0),
+                HitResult::Miss => (0.0, 0.0, 0.0, f64::INFINITY),
+            };
+
+            bonus = (bonus + bonus_gain - punishment).clamp(0.0, 100.0);
+            score += note_value * value / 320.0;
+            score += note_value * bonus_value * bonus.sqrt() / 320.0;
+        }
+
+        score.round() as u64
+    }
+}
+
+/// Generate a plausible judgement for each hit object that is consistent
+/// with the hitresults and combo of a score.
+///
+/// The judgements are aligned with the hit objects of the map converted to `mode`
+/// so they can be passed to [`ScoreV1::simulate`] or used to visualize a score without replay.
+/// To generate judgements for an accuracy, create the hitresults with the `simulate` method
+/// of a performance calculator, e.g. [`OsuPP::simulate`](crate::OsuPP::simulate).
+///
+/// Misses are placed so that the longest streak of hits matches the score's combo
+/// while all other hitresults are spread evenly across the hits.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{score, Beatmap, GameMode, ScoreState};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let state = ScoreState {
+///     max_combo: 100,
+///     n300: 150,
+///     n100: 10,
+///     n_misses: 1,
+///     ..Default::default()
+/// };
+///
+/// for (i, judgement) in score::judgements(&map, GameMode::Osu, state).enumerate() {
+///     println!("{}: {:?}", i, judgement);
+/// }
+/// ```
+pub fn judgements(map: &Beatmap, mode: GameMode, state: ScoreState) -> Judgements {
+    let n_objects = map.convert_mode(mode).hit_objects.len();
+    let n_misses = state.n_misses.min(n_objects);
+    let n_hits = n_objects - n_misses;
+
+    let mut counts = match mode {
+        GameMode::Osu => vec![
+            (HitResult::Great, state.n300),
+            (HitResult::Ok, state.n100),
+            (HitResult::Meh, state.n50),
+        ],
+        GameMode::Taiko => vec![(HitResult::Great, state.n300), (HitResult::Ok, state.n100)],
+        // Droplets are not judged on their own so all caught objects are great
+        GameMode::Catch => vec![(HitResult::Great, n_hits)],
+        GameMode::Mania => vec![
+            (HitResult::Perfect, state.n_geki),
+            (HitResult::Great, state.n300),
+            (HitResult::Good, state.n_katu),
+            (HitResult::Ok, state.n100),
+            (HitResult::Meh, state.n50),
+        ],
+    };
+
+    // Match the amount of hits by adjusting the best hitresult
+    let others: usize = counts[1..].iter().map(|(_, count)| count).sum();
+    counts[0].1 = n_hits.saturating_sub(others);
+
+    let mut hits = spread_hits(&counts, n_hits).into_iter();
+
+    // Longest streak of hits in terms of hit objects
+    let streak = if n_misses == 0 || mode == GameMode::Mania {
+        0
+    } else {
+        let max_combo = AnyStars::new(map).mode(mode).calculate().max_combo();
+
+        (state.max_combo * n_objects)
+            .checked_div(max_combo)
+            .unwrap_or(0)
+            .min(n_hits)
+    };
+
+    let mut results = Vec::with_capacity(n_objects);
+    results.extend(hits.by_ref().take(streak));
+
+    // Distribute the remaining hits evenly between the misses
+    let remaining = n_hits - streak;
+
+    for i in 0..n_misses {
+        results.push(HitResult::Miss);
+        let segment = remaining * (i + 1) / n_misses - remaining * i / n_misses;
+        results.extend(hits.by_ref().take(segment));
+    }
+
+    // Without misses there is no segment so all hits remain
+    results.extend(hits);
+
+    Judgements {
+        results: results.into_iter(),
+    }
+}
+
+/// Order `n_hits` hitresults such that each kind is spread evenly.
+fn spread_hits(counts: &[(HitResult, usize)], n_hits: usize) -> Vec<HitResult> {
+    let mut placed = vec![0; counts.len()];
+
+    (0..n_hits)
+        .map(|i| {
+            let deficit = |(idx, (_, count)): &(usize, &(HitResult, usize))| {
+                (count * (i + 1)) as f64 / n_hits as f64 - placed[*idx] as f64
+            };
+
+            let (idx, _) = counts
+                .iter()
+                .enumerate()
+                .filter(|(idx, (_, count))| placed[*idx] < *count)
+                .max_by(|a, b| deficit(a).total_cmp(&deficit(b)))
+                .unwrap_or((0, &counts[0]));
+
+            placed[idx] += 1;
+
+            counts[idx].0
+        })
+        .collect()
+}
+
+/// Iterator over the judgement of each hit object, created by [`judgements`].
+#[derive(Clone, Debug)]
+pub struct Judgements {
+    results: std::vec::IntoIter<HitResult>,
+}
+
+impl Iterator for Judgements {
+    type Item = HitResult;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.results.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.results.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Judgements {
+    #[inline]
+    fn len(&self) -> usize {
+        self.results.len()
+    }
+}
+
+/// Accumulates score for modes whose hits are scaled by the current combo.
+struct ComboScore {
+    base: u64,
+    combo_score: u64,
+    bonus: u64,
+    combo: u64,
+    multiplier: f64,
+}
+
+impl ComboScore {
+    fn new(multiplier: f64) -> Self {
+        Self {
+            base: 0,
+            combo_score: 0,
+            bonus: 0,
+            combo: 0,
+            multiplier,
+        }
+    }
+
+    /// Add the value scaled by the combo before this hit.
+    fn hit(&mut self, value: u32, increase_combo: bool) {
+        if value == 0 {
+            self.combo = 0;
+
+            return;
+        }
+
+        // Integer division is intentional to match osu!stable
+        let combo_value = (value / 25) as f64 * self.multiplier;
+        self.combo_score += (self.combo.saturating_sub(1) as f64 * combo_value) as u64;
+        self.hit_without_multiplier(value, increase_combo);
+    }
+
+    fn hit_without_multiplier(&mut self, value: u32, increase_combo: bool) {
+        self.base += value as u64;
+        self.combo += increase_combo as u64;
+    }
+
+    fn total(&self) -> u64 {
+        self.base + self.combo_score + self.bonus
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn judgements_match_state() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let state = ScoreState {
+            max_combo: 500,
+            n300: 580,
+            n100: 15,
+            n50: 2,
+            n_misses: 4,
+            ..Default::default()
+        };
+
+        let results: Vec<_> = judgements(&map, GameMode::Osu, state).collect();
+        let count = |kind| results.iter().filter(|&&r| r == kind).count();
+
+        assert_eq!(results.len(), map.hit_objects.len());
+        assert_eq!(count(HitResult::Miss), 4);
+        assert_eq!(count(HitResult::Ok), 15);
+        assert_eq!(count(HitResult::Meh), 2);
+
+        let longest_streak = results
+            .split(|&r| r == HitResult::Miss)
+            .map(<[_]>::len)
+            .max();
+        let max_combo = AnyStars::new(&map).calculate().max_combo();
+        assert_eq!(longest_streak, Some(500 * results.len() / max_combo));
+        assert!(ScoreV1::new(&map).simulate(&results) < ScoreV1::new(&map).max_score());
+    }
+
+    #[test]
+    fn judgements_without_misses() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let state = ScoreState {
+            max_combo: 700,
+            n300: 590,
+            n100: 11,
+            ..Default::default()
+        };
+
+        for mode in [
+            GameMode::Osu,
+            GameMode::Taiko,
+            GameMode::Catch,
+            GameMode::Mania,
+        ] {
+            let results: Vec<_> = judgements(&map, mode, state).collect();
+
+            assert_eq!(
+                results.len(),
+                map.convert_mode(mode).hit_objects.len(),
+                "{:?}",
+                mode
+            );
+            assert!(!results.contains(&HitResult::Miss), "{:?}", mode);
+        }
+
+        let results: Vec<_> = judgements(&map, GameMode::Osu, state).collect();
+        let oks = results.iter().filter(|&&r| r == HitResult::Ok).count();
+        assert_eq!(oks, 11);
+    }
+
+    #[test]
+    fn max_score_is_upper_bound() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let calculator = ScoreV1::new(&map).mods(8);
+
+        let max_score = calculator.max_score();
+        let mut results = vec![HitResult::Great; map.hit_objects.len()];
+
+        assert_eq!(calculator.simulate(&results), max_score);
+
+        results[100] = HitResult::Miss;
+
+        assert!(calculator.simulate(&results) < max_score);
+        let nomod = ScoreV1::new(&map).max_score();
+
+        assert!(max_score > nomod);
+        assert!(ScoreV1::new(&map).mods(128).max_score() < nomod);
+    }
+
+    #[test]
+    fn mania_max_score() {
+        let map = Beatmap::from_path("./maps/1974394.osu").unwrap();
+        let calculator = ScoreV1::new(&map);
+
+        let results = vec![HitResult::Perfect; map.hit_objects.len()];
+
+        assert_eq!(calculator.max_score(), 1_000_000);
+        assert_eq!(calculator.simulate(&results), 1_000_000);
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{AnyStars, Beatmap};
+/// use murasame_pp::{AnyStars, Beatmap};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -21,6 +21,7 @@ use crate::{
 /// println!("Stars: {}", difficulty_attrs.stars());
 /// ```
 #[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum AnyStars<'map> {
     /// osu!standard difficulty calculator
     Osu(OsuStars<'map>),
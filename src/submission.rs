@@ -0,0 +1,320 @@
+use std::{collections::HashMap, error::Error as StdError, fmt, sync::Mutex};
+
+use crate::{
+    verify::{ScoreVerifier, Violation},
+    AnyPP, AnyStars, Beatmap, DifficultyAttributes, GameMode, Grade, InvalidScore,
+    PerformanceAttributes, ScoreState,
+};
+
+/// Identifies the difficulty attributes of a map for an [`AttributeCache`].
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct AttributeKey {
+    /// The id of the map.
+    pub map_id: u32,
+    /// The mode the attributes were calculated for.
+    pub mode: GameMode,
+    /// The mods the attributes were calculated with.
+    pub mods: u32,
+}
+
+/// Storage for difficulty attributes so that they only have to be calculated once per map and mods.
+pub trait AttributeCache {
+    /// Return the cached attributes for the key, if any.
+    fn get(&self, key: &AttributeKey) -> Option<DifficultyAttributes>;
+
+    /// Store freshly calculated attributes.
+    fn insert(&self, key: AttributeKey, attributes: DifficultyAttributes);
+}
+
+/// Never caches anything so attributes are calculated on every submission.
+impl AttributeCache for () {
+    #[inline]
+    fn get(&self, _: &AttributeKey) -> Option<DifficultyAttributes> {
+        None
+    }
+
+    #[inline]
+    fn insert(&self, _: AttributeKey, _: DifficultyAttributes) {}
+}
+
+impl AttributeCache for Mutex<HashMap<AttributeKey, DifficultyAttributes>> {
+    #[inline]
+    fn get(&self, key: &AttributeKey) -> Option<DifficultyAttributes> {
+        self.lock().ok()?.get(key).copied()
+    }
+
+    #[inline]
+    fn insert(&self, key: AttributeKey, attributes: DifficultyAttributes) {
+        if let Ok(mut cache) = self.lock() {
+            cache.insert(key, attributes);
+        }
+    }
+}
+
+/// Decisions that differ between servers when accepting scores.
+///
+/// All methods have default implementations so only the relevant ones need to be overridden.
+pub trait SubmissionPolicy {
+    /// Whether a score with the given violations should be accepted.
+    ///
+    /// By default, only scores without violations are accepted.
+    #[inline]
+    fn accept(&self, submission: &Submission, violations: &[Violation]) -> bool {
+        let _ = submission;
+
+        violations.is_empty()
+    }
+
+    /// Adjust the calculated pp of an accepted score, e.g. to not award pp for unranked mods.
+    ///
+    /// By default, the pp are unchanged.
+    #[inline]
+    fn pp(&self, submission: &Submission, pp: f64) -> f64 {
+        let _ = submission;
+
+        pp
+    }
+}
+
+/// Accepts only scores without violations and keeps their pp unchanged.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DefaultPolicy;
+
+impl SubmissionPolicy for DefaultPolicy {}
+
+/// A score submitted to a [`SubmissionContext`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Submission {
+    /// The id of the map, used for the [`AttributeCache`].
+    pub map_id: u32,
+    /// The mode of the score.
+    pub mode: GameMode,
+    /// The mods as bit values.
+    pub mods: u32,
+    /// The hitresults and max combo of the score.
+    pub state: ScoreState,
+    /// The accuracy between `0.0` and `100.0` as claimed by the client, if any.
+    pub accuracy: Option<f64>,
+}
+
+/// The outcome of an accepted [`Submission`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubmissionResult {
+    /// The performance attributes of the score.
+    pub performance: PerformanceAttributes,
+    /// The pp after the [`SubmissionPolicy`] adjusted them.
+    pub pp: f64,
+    /// The grade of the score.
+    pub grade: Grade,
+    /// The accuracy of the hitresults between `0.0` and `100.0`.
+    pub accuracy: f64,
+    /// Violations that the [`SubmissionPolicy`] accepted anyway.
+    pub violations: Vec<Violation>,
+}
+
+/// Validation, attribute caching, pp calculation, and grading of submitted scores in one call.
+///
+/// # Example
+///
+/// ```
+/// use std::{collections::HashMap, sync::Mutex};
+///
+/// use murasame_pp::{
+///     submission::{DefaultPolicy, Submission, SubmissionContext},
+///     Beatmap, GameMode, ScoreState,
+/// };
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let context = SubmissionContext::new(Mutex::new(HashMap::new()), DefaultPolicy);
+///
+/// let submission = Submission {
+///     map_id: 123,
+///     mode: GameMode::Osu,
+///     mods: 8 + 64,
+///     state: ScoreState::default(),
+///     accuracy: None,
+/// };
+///
+/// match context.submit(&map, &submission) {
+///     Ok(result) => println!("{:.2}pp ({:?})", result.pp, result.grade),
+///     Err(err) => println!("rejected: {}", err),
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SubmissionContext<C, P> {
+    cache: C,
+    policy: P,
+}
+
+impl<C: AttributeCache, P: SubmissionPolicy> SubmissionContext<C, P> {
+    /// Create a new context with the given cache and policy.
+    ///
+    /// Use `()` as cache to disable caching and [`DefaultPolicy`] as default policy.
+    #[inline]
+    pub fn new(cache: C, policy: P) -> Self {
+        Self { cache, policy }
+    }
+
+    /// Return a reference to the cache.
+    #[inline]
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+
+    /// Return a reference to the policy.
+    #[inline]
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    /// Validate the submission, calculate its pp, and grade it.
+    ///
+    /// The map must be the map of [`Submission::map_id`], otherwise cached attributes will mismatch.
+    pub fn submit(
+        &self,
+        map: &Beatmap,
+        submission: &Submission,
+    ) -> Result<SubmissionResult, SubmissionError> {
+        let Submission {
+            map_id,
+            mode,
+            mods,
+            state,
+            accuracy,
+        } = *submission;
+
+        let key = AttributeKey { map_id, mode, mods };
+
+        let attributes = match self.cache.get(&key) {
+            Some(attributes) => attributes,
+            None => {
+                let attributes = AnyStars::new(map).mode(mode).mods(mods).calculate();
+                self.cache.insert(key, attributes);
+
+                attributes
+            }
+        };
+
+        let mut verifier = ScoreVerifier::new(attributes).mods(mods).state(state);
+
+        if let Some(accuracy) = accuracy {
+            verifier = verifier.accuracy(accuracy);
+        }
+
+        let violations = verifier.verify();
+
+        if !self.policy.accept(submission, &violations) {
+            return Err(SubmissionError::Rejected(violations));
+        }
+
+        let performance = AnyPP::new(map)
+            .mode(mode)
+            .mods(mods)
+            .attributes(attributes)
+            .state(state)
+            .try_calculate()
+            .map_err(SubmissionError::Invalid)?;
+
+        Ok(SubmissionResult {
+            pp: self.policy.pp(submission, performance.pp()),
+            performance,
+            grade: state.grade(mode, mods),
+            accuracy: state.accuracy(mode) * 100.0,
+            violations,
+        })
+    }
+}
+
+/// The reason a [`Submission`] was not accepted.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubmissionError {
+    /// The [`SubmissionPolicy`] rejected the score due to the contained violations.
+    Rejected(Vec<Violation>),
+    /// The score could not be calculated.
+    Invalid(InvalidScore),
+}
+
+impl fmt::Display for SubmissionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rejected(violations) => {
+                f.write_str("score was rejected")?;
+
+                for (violation, i) in violations.iter().zip(0..) {
+                    let sep = if i == 0 { ": " } else { ", " };
+                    write!(f, "{}{}", sep, violation)?;
+                }
+
+                Ok(())
+            }
+            Self::Invalid(_) => f.write_str("score could not be calculated"),
+        }
+    }
+}
+
+impl StdError for SubmissionError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Rejected(_) => None,
+            Self::Invalid(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct LenientPolicy;
+
+    impl SubmissionPolicy for LenientPolicy {
+        fn accept(&self, _: &Submission, _: &[Violation]) -> bool {
+            true
+        }
+
+        fn pp(&self, submission: &Submission, pp: f64) -> f64 {
+            if submission.mods & 128 > 0 {
+                0.0
+            } else {
+                pp
+            }
+        }
+    }
+
+    #[test]
+    fn caches_and_applies_policy() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        let context = SubmissionContext::new(Mutex::new(HashMap::new()), DefaultPolicy);
+
+        let mut submission = Submission {
+            map_id: 1028484,
+            mode: GameMode::Taiko,
+            mods: 0,
+            state: ScoreState {
+                max_combo: 289,
+                n300: 289,
+                ..Default::default()
+            },
+            accuracy: Some(100.0),
+        };
+
+        let result = context.submit(&map, &submission).unwrap();
+        assert_eq!(result.grade, Grade::X);
+        assert!(result.pp > 0.0);
+        assert_eq!(context.cache().lock().unwrap().len(), 1);
+
+        submission.accuracy = Some(90.0);
+        let err = context.submit(&map, &submission).unwrap_err();
+        assert!(matches!(err, SubmissionError::Rejected(ref v) if v.len() == 1));
+
+        let lenient = SubmissionContext::new((), LenientPolicy);
+        submission.mods = 128;
+        let result = lenient.submit(&map, &submission).unwrap();
+        assert_eq!(result.pp, 0.0);
+        assert_eq!(result.violations.len(), 1);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{beatmap::DifficultyOverrides, Beatmap, CustomMod, TaikoStars};
+
+use super::{ScratchBuffers, TaikoDifficultyAttributes, TaikoTuning};
+
+/// Reusable difficulty calculator for osu!taiko maps.
+///
+/// In contrast to [`TaikoStars`], the calculator is configured only once
+/// and can then calculate the difficulty of many maps.
+/// Internal buffers are kept in between calculations so they
+/// don't need to be allocated again for every map.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, taiko::TaikoDifficultyCalculator};
+///
+/// # /*
+/// let maps: Vec<Beatmap> = ...
+/// # */
+/// # let maps = vec![Beatmap::default(), Beatmap::default()];
+///
+/// let mut calculator = TaikoDifficultyCalculator::new().mods(64); // DT
+///
+/// for map in maps.iter() {
+///     let attrs = calculator.calculate(map);
+///     println!("Stars: {}", attrs.stars);
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TaikoDifficultyCalculator {
+    mods: u32,
+    clock_rate: Option<f64>,
+    is_convert: bool,
+    tuning: TaikoTuning,
+    overrides: DifficultyOverrides,
+    bufs: ScratchBuffers,
+}
+
+impl TaikoDifficultyCalculator {
+    /// Create a new reusable difficulty calculator for osu!taiko maps.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Specify mods through their bit values.
+    ///
+    /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// Adjust the clock rate used in the calculation.
+    /// If none is specified, it will take the clock rate based on the mods
+    /// i.e. 1.5 for DT, 0.75 for HT and 1.0 otherwise.
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: f64) -> Self {
+        self.clock_rate = Some(clock_rate);
+
+        self
+    }
+
+    /// Specify whether all maps should be treated as converts i.e. osu!standard maps.
+    ///
+    /// osu!standard maps are always detected as converts.
+    #[inline]
+    pub fn is_convert(mut self, is_convert: bool) -> Self {
+        self.is_convert = is_convert;
+
+        self
+    }
+
+    /// Adjust the multipliers and convert penalties of the calculation.
+    #[inline]
+    pub fn tuning(mut self, tuning: TaikoTuning) -> Self {
+        self.tuning = tuning;
+
+        self
+    }
+
+    /// Override the approach rate of all maps before calculating their difficulty.
+    ///
+    /// Irrelevant for osu!taiko.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn ar(mut self, ar: f32) -> Self {
+        self.overrides.ar = Some(ar);
+
+        self
+    }
+
+    /// Override the overall difficulty of all maps before calculating their difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn od(mut self, od: f32) -> Self {
+        self.overrides.od = Some(od);
+
+        self
+    }
+
+    /// Override the circle size of all maps before calculating their difficulty.
+    ///
+    /// Irrelevant for osu!taiko.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn cs(mut self, cs: f32) -> Self {
+        self.overrides.cs = Some(cs);
+
+        self
+    }
+
+    /// Override the drain rate of all maps before calculating their difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn hp(mut self, hp: f32) -> Self {
+        self.overrides.hp = Some(hp);
+
+        self
+    }
+
+    /// Apply the effects of a [`CustomMod`] on top of the mods for all maps.
+    #[inline]
+    pub fn custom_mod(mut self, custom_mod: CustomMod) -> Self {
+        self.overrides.custom_mod = custom_mod;
+
+        self
+    }
+
+    /// Calculate all difficulty related values, including stars, for the given map.
+    pub fn calculate(&mut self, map: &Beatmap) -> TaikoDifficultyAttributes {
+        let mut calculator = TaikoStars::new(map)
+            .mods(self.mods)
+            .tuning(self.tuning)
+            .overrides(self.overrides);
+
+        if self.is_convert {
+            calculator = calculator.is_convert(true);
+        }
+
+        if let Some(clock_rate) = self.clock_rate {
+            calculator = calculator.clock_rate(clock_rate);
+        }
+
+        calculator.calculate_with_buffers(&mut self.bufs)
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TaikoPP;
+
+    #[test]
+    fn reused_buffers_and_overrides() {
+        let taiko = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        let osu = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let mut calculator = TaikoDifficultyCalculator::new().mods(64).od(9.0);
+
+        for map in [&taiko, &osu, &taiko] {
+            let expected = TaikoPP::new(map).mods(64).od(9.0).calculate().difficulty;
+
+            assert_eq!(calculator.calculate(map), expected);
+        }
+    }
+}
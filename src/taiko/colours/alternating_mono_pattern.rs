@@ -1,71 +1,51 @@
-use std::{
-    cell::RefCell,
-    fmt::{Debug, Formatter, Result as FmtResult},
-    rc::{Rc, Weak},
-};
-
 use crate::taiko::difficulty_object::TaikoDifficultyObject;
 
-use super::{mono_streak::MonoStreak, repeating_hit_patterns::RepeatingHitPatterns};
+use super::mono_streak::MonoStreak;
 
+#[derive(Clone, Debug, Default)]
 pub(crate) struct AlternatingMonoPattern {
-    pub(crate) mono_streaks: Vec<Rc<RefCell<MonoStreak>>>,
-    pub(crate) parent: Option<Weak<RefCell<RepeatingHitPatterns>>>,
+    /// Indices of [`ColourLists::mono_streaks`](super::ColourLists).
+    pub(crate) mono_streaks: Vec<usize>,
+    /// Index of [`ColourLists::repeating_hit_patterns`](super::ColourLists).
+    pub(crate) parent: Option<usize>,
     pub(crate) idx: usize,
 }
 
-impl Debug for AlternatingMonoPattern {
-    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(
-            f,
-            "(idx={}, mono_len={}, has_parent={})",
-            self.idx,
-            self.mono_streaks.len(),
-            self.parent.is_some()
-        )
-    }
-}
-
 impl AlternatingMonoPattern {
-    pub(crate) fn new() -> Rc<RefCell<Self>> {
-        let this = Self {
-            mono_streaks: Vec::new(),
-            parent: None,
-            idx: 0,
-        };
-
-        Rc::new(RefCell::new(this))
-    }
-
-    pub(crate) fn first_hit_object(&self) -> Option<Weak<RefCell<TaikoDifficultyObject>>> {
+    pub(crate) fn first_hit_object(&self, mono_streaks: &[MonoStreak]) -> Option<usize> {
         self.mono_streaks
             .first()
-            .and_then(|streak| streak.borrow().first_hit_object())
+            .and_then(|&idx| mono_streaks[idx].first_hit_object())
     }
 
-    pub(crate) fn is_repetition_of(&self, other: &Self) -> bool {
-        self.has_identical_mono_len(other)
+    pub(crate) fn is_repetition_of(
+        &self,
+        other: &Self,
+        mono_streaks: &[MonoStreak],
+        objects: &[TaikoDifficultyObject],
+    ) -> bool {
+        self.has_identical_mono_len(other, mono_streaks)
             && other.mono_streaks.len() == self.mono_streaks.len()
             && other
                 .mono_streaks
                 .first()
-                .map(|streak| streak.borrow().hit_kind())
+                .map(|&idx| mono_streaks[idx].hit_kind(objects))
                 == self
                     .mono_streaks
                     .first()
-                    .map(|streak| streak.borrow().hit_kind())
+                    .map(|&idx| mono_streaks[idx].hit_kind(objects))
     }
 
-    pub(crate) fn has_identical_mono_len(&self, other: &Self) -> bool {
+    pub(crate) fn has_identical_mono_len(&self, other: &Self, mono_streaks: &[MonoStreak]) -> bool {
         let other_len = other
             .mono_streaks
             .first()
-            .map(|streak| streak.borrow().run_len());
+            .map(|&idx| mono_streaks[idx].run_len());
 
         let self_len = self
             .mono_streaks
             .first()
-            .map(|streak| streak.borrow().run_len());
+            .map(|&idx| mono_streaks[idx].run_len());
 
         other_len == self_len
     }
@@ -1,8 +1,3 @@
-use std::{
-    cell::RefCell,
-    rc::{Rc, Weak},
-};
-
 pub(crate) use self::{
     alternating_mono_pattern::AlternatingMonoPattern, mono_streak::MonoStreak,
     preprocessor::ColourDifficultyPreprocessor, repeating_hit_patterns::RepeatingHitPatterns,
@@ -13,11 +8,21 @@ mod mono_streak;
 mod preprocessor;
 mod repeating_hit_patterns;
 
-#[derive(Clone, Debug, Default)]
+/// Indices into the lists of [`ColourLists`].
+#[derive(Copy, Clone, Debug, Default)]
 pub(crate) struct TaikoDifficultyColour {
-    pub(crate) mono_streak: Option<Weak<RefCell<MonoStreak>>>,
-    pub(crate) alternating_mono_pattern: Option<Weak<RefCell<AlternatingMonoPattern>>>,
-    pub(crate) repeating_hit_patterns: Option<Rc<RefCell<RepeatingHitPatterns>>>,
+    pub(crate) mono_streak: Option<usize>,
+    pub(crate) alternating_mono_pattern: Option<usize>,
+    pub(crate) repeating_hit_patterns: Option<usize>,
+}
+
+/// Owns all colour encodings of a map so that they can reference
+/// each other through indices instead of shared pointers.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ColourLists {
+    pub(crate) mono_streaks: Vec<MonoStreak>,
+    pub(crate) alternating_mono_patterns: Vec<AlternatingMonoPattern>,
+    pub(crate) repeating_hit_patterns: Vec<RepeatingHitPatterns>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -1,51 +1,41 @@
-use std::{
-    cell::RefCell,
-    fmt::{Debug, Formatter, Result as FmtResult},
-    rc::{Rc, Weak},
-};
-
-use crate::taiko::difficulty_object::TaikoDifficultyObject;
-
-use super::alternating_mono_pattern::AlternatingMonoPattern;
+use super::{alternating_mono_pattern::AlternatingMonoPattern, mono_streak::MonoStreak};
 
+#[derive(Clone, Debug, Default)]
 pub(crate) struct RepeatingHitPatterns {
-    pub(crate) alternating_mono_patterns: Vec<Rc<RefCell<AlternatingMonoPattern>>>,
-    pub(crate) prev: Option<Weak<RefCell<Self>>>,
+    /// Indices of [`ColourLists::alternating_mono_patterns`](super::ColourLists).
+    pub(crate) alternating_mono_patterns: Vec<usize>,
+    /// Index of the previous pattern in [`ColourLists::repeating_hit_patterns`](super::ColourLists).
+    pub(crate) prev: Option<usize>,
     pub(crate) repetition_interval: usize,
 }
 
-impl Debug for RepeatingHitPatterns {
-    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(
-            f,
-            "(interval={}, alt_len={}, has_prev={})",
-            self.repetition_interval,
-            self.alternating_mono_patterns.len(),
-            self.prev.is_some()
-        )
-    }
-}
-
 impl RepeatingHitPatterns {
     const MAX_REPETITION_INTERVAL: usize = 16;
 
-    pub(crate) fn new(prev: Option<Weak<RefCell<Self>>>) -> Rc<RefCell<Self>> {
-        let this = Self {
+    pub(crate) fn new(prev: Option<usize>) -> Self {
+        Self {
             alternating_mono_patterns: Vec::new(),
             prev,
             repetition_interval: 0,
-        };
-
-        Rc::new(RefCell::new(this))
+        }
     }
 
-    pub(crate) fn first_hit_object(&self) -> Option<Weak<RefCell<TaikoDifficultyObject>>> {
+    pub(crate) fn first_hit_object(
+        &self,
+        alternating_mono_patterns: &[AlternatingMonoPattern],
+        mono_streaks: &[MonoStreak],
+    ) -> Option<usize> {
         self.alternating_mono_patterns
             .first()
-            .and_then(|pattern| pattern.borrow().first_hit_object())
+            .and_then(|&idx| alternating_mono_patterns[idx].first_hit_object(mono_streaks))
     }
 
-    fn is_repetition_of(&self, other: &Self) -> bool {
+    fn is_repetition_of(
+        &self,
+        other: &Self,
+        alternating_mono_patterns: &[AlternatingMonoPattern],
+        mono_streaks: &[MonoStreak],
+    ) -> bool {
         if self.alternating_mono_patterns.len() != other.alternating_mono_patterns.len() {
             return false;
         }
@@ -54,37 +44,42 @@ impl RepeatingHitPatterns {
             .iter()
             .zip(other.alternating_mono_patterns.iter())
             .take(2)
-            .all(|(self_pat, other_pat)| {
-                self_pat
-                    .borrow()
-                    .has_identical_mono_len(&other_pat.borrow())
+            .all(|(&self_idx, &other_idx)| {
+                alternating_mono_patterns[self_idx]
+                    .has_identical_mono_len(&alternating_mono_patterns[other_idx], mono_streaks)
             })
     }
 
-    pub(crate) fn find_repetition_interval(&mut self) {
-        let mut other = match self.prev.as_ref().and_then(Weak::upgrade) {
+    /// Find the repetition interval of the pattern at index `idx`.
+    pub(crate) fn find_repetition_interval(
+        patterns: &mut [Self],
+        idx: usize,
+        alternating_mono_patterns: &[AlternatingMonoPattern],
+        mono_streaks: &[MonoStreak],
+    ) {
+        let this = &patterns[idx];
+
+        let mut other = match this.prev {
             Some(prev) => prev,
-            None => return self.repetition_interval = Self::MAX_REPETITION_INTERVAL + 1,
+            None => return patterns[idx].repetition_interval = Self::MAX_REPETITION_INTERVAL + 1,
         };
 
         let mut interval = 1;
 
         while interval < Self::MAX_REPETITION_INTERVAL {
-            if self.is_repetition_of(&other.borrow()) {
-                return self.repetition_interval = interval.min(Self::MAX_REPETITION_INTERVAL);
+            if this.is_repetition_of(&patterns[other], alternating_mono_patterns, mono_streaks) {
+                return patterns[idx].repetition_interval =
+                    interval.min(Self::MAX_REPETITION_INTERVAL);
             }
 
-            let next = match other.borrow().prev.as_ref().and_then(Weak::upgrade) {
+            other = match patterns[other].prev {
                 Some(prev) => prev,
                 None => break,
             };
 
-            // gotta love NLL...
-            other = next;
-
             interval += 1;
         }
 
-        self.repetition_interval = Self::MAX_REPETITION_INTERVAL + 1;
+        patterns[idx].repetition_interval = Self::MAX_REPETITION_INTERVAL + 1;
     }
 }
@@ -1,6 +1,9 @@
-use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+use std::cmp::Ordering;
 
-use super::{colours::TaikoDifficultyColour, taiko_object::TaikoObject};
+use super::{
+    colours::{ColourLists, TaikoDifficultyColour},
+    taiko_object::TaikoObject,
+};
 
 #[derive(Clone, Debug)]
 pub(crate) struct TaikoDifficultyObject {
@@ -113,21 +116,31 @@ fn closest_rhythm(
 
 #[derive(Clone, Debug, Default)]
 pub(crate) struct ObjectLists {
-    pub(crate) all: Vec<Rc<RefCell<TaikoDifficultyObject>>>,
+    pub(crate) all: Vec<TaikoDifficultyObject>,
     pub(crate) centres: Vec<usize>,
     pub(crate) rims: Vec<usize>,
     pub(crate) notes: Vec<usize>,
+    pub(crate) colours: ColourLists,
 }
 
 impl ObjectLists {
+    /// Remove all objects while keeping the allocated capacity.
+    pub(crate) fn clear(&mut self) {
+        self.all.clear();
+        self.centres.clear();
+        self.rims.clear();
+        self.notes.clear();
+        self.colours = ColourLists::default();
+    }
+
     pub(crate) fn prev_mono(
         &self,
         curr: usize,
         backwards_idx: usize,
-    ) -> Option<&'_ Rc<RefCell<TaikoDifficultyObject>>> {
+    ) -> Option<&'_ TaikoDifficultyObject> {
         let curr = &self.all[curr];
 
-        let prev = match curr.borrow().mono_idx {
+        let prev = match curr.mono_idx {
             MonoIndex::Centre(idx) => idx
                 .checked_sub(backwards_idx + 1)
                 .and_then(|idx| self.centres.get(idx))?,
@@ -145,10 +158,10 @@ impl ObjectLists {
         &self,
         curr: usize,
         forwards_idx: usize,
-    ) -> Option<&'_ Rc<RefCell<TaikoDifficultyObject>>> {
+    ) -> Option<&'_ TaikoDifficultyObject> {
         let curr = &self.all[curr];
 
-        let next = match curr.borrow().mono_idx {
+        let next = match curr.mono_idx {
             MonoIndex::Centre(idx) => self.centres.get(idx + (forwards_idx + 1))?,
             MonoIndex::Rim(idx) => self.rims.get(idx + (forwards_idx + 1))?,
             MonoIndex::None => return None,
@@ -161,9 +174,9 @@ impl ObjectLists {
         &self,
         curr: usize,
         backwards_idx: usize,
-    ) -> Option<&'_ Rc<RefCell<TaikoDifficultyObject>>> {
+    ) -> Option<&'_ TaikoDifficultyObject> {
         let curr = &self.all[curr];
-        let note_idx = curr.borrow().note_idx?;
+        let note_idx = curr.note_idx?;
         let idx = note_idx.checked_sub(backwards_idx + 1)?;
         let prev = self.notes.get(idx)?;
 
@@ -175,9 +188,9 @@ impl ObjectLists {
         &self,
         curr: usize,
         forwards_idx: usize,
-    ) -> Option<&'_ Rc<RefCell<TaikoDifficultyObject>>> {
+    ) -> Option<&'_ TaikoDifficultyObject> {
         let curr = &self.all[curr];
-        let note_idx = curr.borrow().note_idx?;
+        let note_idx = curr.note_idx?;
         let idx = note_idx + (forwards_idx + 1);
         let prev = self.notes.get(idx)?;
 
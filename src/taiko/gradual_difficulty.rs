@@ -1,13 +1,13 @@
-use std::{borrow::Cow, cell::RefCell, rc::Rc, vec::IntoIter};
+use std::borrow::Cow;
 
-use crate::{beatmap::BeatmapHitWindows, taiko::rescale, Beatmap, GameMode, Mods};
+use crate::{beatmap::DifficultyOverrides, Beatmap, GameMode, Mods, ATTRIBUTES_VERSION};
 
 use super::{
     colours::ColourDifficultyPreprocessor,
     difficulty_object::{MonoIndex, ObjectLists, TaikoDifficultyObject},
-    skills::{Peaks, PeaksDifficultyValues, Skill},
+    skills::{Peaks, Skill},
     taiko_object::IntoTaikoObjectIter,
-    TaikoDifficultyAttributes, DIFFICULTY_MULTIPLIER,
+    TaikoDifficultyAttributes, TaikoTuning,
 };
 
 /// Gradually calculate the difficulty attributes of an osu!taiko map.
@@ -16,13 +16,15 @@ use super::{
 /// On every call of [`Iterator::next`](std::iter::Iterator::next), the map's next hit object will
 /// be processed and the [`TaikoDifficultyAttributes`] will be updated and returned.
 ///
+/// The iterator does not borrow the map so it can be stored without lifetime constraints.
+///
 /// If you want to calculate performance attributes, use
 /// [`TaikoGradualPerformanceAttributes`](crate::taiko::TaikoGradualPerformanceAttributes) instead.
 ///
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{Beatmap, taiko::TaikoGradualDifficultyAttributes};
+/// use murasame_pp::{Beatmap, taiko::TaikoGradualDifficultyAttributes};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -43,47 +45,132 @@ use super::{
 #[derive(Clone, Debug)]
 pub struct TaikoGradualDifficultyAttributes {
     attrs: TaikoDifficultyAttributes,
-    hit_objects: IntoIter<Rc<RefCell<TaikoDifficultyObject>>>,
+    idx: usize,
     lists: ObjectLists,
     peaks: Peaks,
     total_hits: usize,
-    is_convert: bool,
-    pub(crate) started: bool,
+    tuning: TaikoTuning,
 }
 
 impl TaikoGradualDifficultyAttributes {
     /// Create a new difficulty attributes iterator for osu!taiko maps.
     pub fn new(map: &Beatmap, mods: u32) -> Self {
-        let map = map.convert_mode(GameMode::Taiko);
-        let is_convert = matches!(map, Cow::Owned(_));
+        Self::new_with_overrides(map, mods, None, None)
+    }
+
+    /// Same as [`new`](Self::new) but the clock rate of the mods and the map's
+    /// overall difficulty can be overridden, e.g. for custom rate mods.
+    ///
+    /// Both affect the hit window and thus the difficulty attributes.
+    pub fn new_with_overrides(
+        map: &Beatmap,
+        mods: u32,
+        clock_rate: Option<f64>,
+        od: Option<f32>,
+    ) -> Self {
+        Self::with_params(map, mods, clock_rate, od, None)
+    }
+
+    /// Same as [`new`](Self::new) but only the first `passed_objects` hits
+    /// are processed, e.g. for a failed play.
+    ///
+    /// Unlike stopping the iteration early, later hit objects don't influence
+    /// the difficulty so the last attributes match the ones of
+    /// [`TaikoStars::passed_objects`](crate::TaikoStars::passed_objects).
+    pub fn new_with_passed_objects(map: &Beatmap, mods: u32, passed_objects: usize) -> Self {
+        Self::with_params(map, mods, None, None, Some(passed_objects))
+    }
+
+    /// Adjust the multipliers and convert penalties of the calculation.
+    ///
+    /// Only affects attributes that are returned after the call.
+    #[inline]
+    pub fn tuning(mut self, tuning: TaikoTuning) -> Self {
+        self.tuning = tuning;
+
+        self
+    }
+
+    /// The amount of hits that the current attributes include.
+    pub(crate) fn processed_hits(&self) -> usize {
+        self.attrs.max_combo
+    }
+
+    fn with_params(
+        map: &Beatmap,
+        mods: u32,
+        clock_rate: Option<f64>,
+        od: Option<f32>,
+        passed_objects: Option<usize>,
+    ) -> Self {
+        let converted = map.convert_mode(GameMode::Taiko);
+        let is_convert = matches!(converted, Cow::Owned(_));
+
+        Self::with_converted(
+            converted.as_ref(),
+            is_convert,
+            mods,
+            clock_rate,
+            od,
+            passed_objects,
+        )
+    }
+
+    /// The map must already be converted to osu!taiko.
+    pub(crate) fn with_converted(
+        map: &Beatmap,
+        is_convert: bool,
+        mods: u32,
+        clock_rate: Option<f64>,
+        od: Option<f32>,
+        passed_objects: Option<usize>,
+    ) -> Self {
+        let overrides = DifficultyOverrides {
+            od,
+            ..Default::default()
+        };
+
         let peaks = Peaks::new();
-        let clock_rate = mods.clock_rate();
+        let clock_rate = clock_rate.unwrap_or_else(|| mods.clock_rate());
 
-        let BeatmapHitWindows { od: hit_window, .. } = map
-            .attributes()
+        let settings = overrides
+            .attributes(map)
             .mods(mods)
             .clock_rate(clock_rate)
-            .hit_windows();
+            .settings();
 
         let mut attrs = TaikoDifficultyAttributes {
             stamina: 0.0,
             rhythm: 0.0,
             colour: 0.0,
             peak: 0.0,
-            hit_window,
+            hit_window: settings.great_hit_window,
+            ok_hit_window: settings.ok_hit_window,
             stars: 0.0,
             max_combo: 0,
+            is_convert,
+            settings,
+            version: ATTRIBUTES_VERSION,
         };
 
-        if map.hit_objects.len() < 2 {
+        // Same as for `TaikoStars`, objects up to the next hit are still considered
+        let n_objects = passed_objects
+            .and_then(|passed_objects| {
+                map.taiko_objects()
+                    .enumerate()
+                    .filter(|(_, (h, _))| h.is_hit)
+                    .nth(passed_objects)
+            })
+            .map_or(map.hit_objects.len(), |(i, _)| i);
+
+        if n_objects < 2 {
             return Self {
-                hit_objects: Vec::new().into_iter(),
+                idx: 0,
                 lists: ObjectLists::default(),
                 peaks,
                 attrs,
                 total_hits: 0,
-                is_convert,
-                started: false,
+                tuning: TaikoTuning::default(),
             };
         }
 
@@ -93,6 +180,7 @@ impl TaikoGradualDifficultyAttributes {
 
         let mut diff_objects = map
             .taiko_objects()
+            .take(n_objects)
             .skip(2)
             .zip(map.hit_objects.iter().skip(1))
             .zip(map.hit_objects.iter())
@@ -122,7 +210,7 @@ impl TaikoGradualDifficultyAttributes {
                         lists.notes.push(idx);
                     }
 
-                    lists.all.push(Rc::new(RefCell::new(diff_obj)));
+                    lists.all.push(diff_obj);
 
                     lists
                 },
@@ -131,13 +219,12 @@ impl TaikoGradualDifficultyAttributes {
         ColourDifficultyPreprocessor::process_and_assign(&mut diff_objects);
 
         Self {
-            hit_objects: diff_objects.all.clone().into_iter(),
+            idx: 0,
             lists: diff_objects,
             peaks,
             attrs,
             total_hits,
-            is_convert,
-            started: false,
+            tuning: TaikoTuning::default(),
         }
     }
 }
@@ -146,53 +233,22 @@ impl Iterator for TaikoGradualDifficultyAttributes {
     type Item = TaikoDifficultyAttributes;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.started = true;
-
         loop {
-            let curr = self.hit_objects.next()?;
-            let borrowed = curr.borrow();
-            self.peaks.process(&borrowed, &self.lists);
+            let curr = self.lists.all.get(self.idx)?;
+            self.idx += 1;
+            self.peaks.process(curr, &self.lists);
 
-            if borrowed.base.is_hit {
+            if curr.base.is_hit {
                 self.attrs.max_combo += 1;
 
                 break;
             }
         }
 
-        let PeaksDifficultyValues {
-            mut colour_rating,
-            mut rhythm_rating,
-            mut stamina_rating,
-            mut combined_rating,
-        } = self.peaks.clone().difficulty_values();
-
-        colour_rating *= DIFFICULTY_MULTIPLIER;
-        rhythm_rating *= DIFFICULTY_MULTIPLIER;
-        stamina_rating *= DIFFICULTY_MULTIPLIER;
-        combined_rating *= DIFFICULTY_MULTIPLIER;
-
-        let mut star_rating = rescale(combined_rating * 1.4);
-
-        // * TODO: This is temporary measure as we don't detect abuse of multiple-input
-        // * playstyles of converts within the current system.
-        if self.is_convert {
-            star_rating *= 0.925;
-
-            // * For maps with low colour variance and high stamina requirement,
-            // * multiple inputs are more likely to be abused.
-            if colour_rating < 2.0 && stamina_rating > 8.0 {
-                star_rating *= 0.8;
-            }
-        }
-
-        self.attrs.stamina = stamina_rating;
-        self.attrs.colour = colour_rating;
-        self.attrs.rhythm = rhythm_rating;
-        self.attrs.peak = combined_rating;
-        self.attrs.stars = star_rating;
+        let values = self.peaks.clone().difficulty_values();
+        self.tuning.apply(&mut self.attrs, values);
 
-        Some(self.attrs.clone())
+        Some(self.attrs)
     }
 
     #[inline]
@@ -209,11 +265,11 @@ impl Iterator for TaikoGradualDifficultyAttributes {
 
         for _ in 0..skip {
             loop {
-                let curr = self.hit_objects.next()?;
-                let borrowed = curr.borrow();
-                self.peaks.process(&borrowed, &self.lists);
+                let curr = self.lists.all.get(self.idx)?;
+                self.idx += 1;
+                self.peaks.process(curr, &self.lists);
 
-                if borrowed.base.is_hit {
+                if curr.base.is_hit {
                     self.attrs.max_combo += 1;
 
                     break;
@@ -228,6 +284,6 @@ impl Iterator for TaikoGradualDifficultyAttributes {
 impl ExactSizeIterator for TaikoGradualDifficultyAttributes {
     #[inline]
     fn len(&self) -> usize {
-        self.hit_objects.len()
+        self.lists.all.len() - self.idx
     }
 }
@@ -1,12 +1,18 @@
-use crate::{Beatmap, TaikoPP};
+use std::borrow::Cow;
 
-use super::{TaikoGradualDifficultyAttributes, TaikoPerformanceAttributes};
+use crate::{gradual::PerformanceCache, Beatmap, GameMode, Grade, TaikoPP};
+
+use super::{
+    TaikoDifficultyAttributes, TaikoGradualDifficultyAttributes, TaikoPerformanceAttributes,
+    TaikoTuning,
+};
 
 /// Aggregation for a score's current state i.e. what was the
 /// maximum combo so far and what are the current hitresults.
 ///
 /// This struct is used for [`TaikoGradualPerformanceAttributes`].
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaikoScoreState {
     /// Maximum combo that the score has had so far.
     /// **Not** the maximum possible combo of the map so far.
@@ -46,6 +52,12 @@ impl TaikoScoreState {
 
         numerator as f64 / denominator as f64
     }
+
+    /// Determine the grade of this state for the given mods.
+    #[inline]
+    pub fn grade(&self, mods: u32) -> Grade {
+        Grade::from_ratios(self.n300, 0, self.n_misses, self.total_hits(), mods)
+    }
 }
 
 /// Gradually calculate the performance attributes of an osu!taiko map.
@@ -65,7 +77,7 @@ impl TaikoScoreState {
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{Beatmap, taiko::{TaikoGradualPerformanceAttributes, TaikoScoreState}};
+/// use murasame_pp::{Beatmap, taiko::{TaikoGradualPerformanceAttributes, TaikoScoreState}};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -136,17 +148,75 @@ impl TaikoScoreState {
 pub struct TaikoGradualPerformanceAttributes<'map> {
     difficulty: TaikoGradualDifficultyAttributes,
     performance: TaikoPP<'map>,
+    passed_hits: usize,
+    cache: PerformanceCache<TaikoDifficultyAttributes, TaikoScoreState, TaikoPerformanceAttributes>,
 }
 
 impl<'map> TaikoGradualPerformanceAttributes<'map> {
     /// Create a new gradual performance calculator for osu!taiko maps.
     pub fn new(map: &'map Beatmap, mods: u32) -> Self {
-        let difficulty = TaikoGradualDifficultyAttributes::new(map, mods);
-        let performance = TaikoPP::new(map).mods(mods).passed_objects(0);
+        Self::new_with_overrides(map, mods, None, None)
+    }
+
+    /// Same as [`new`](Self::new) but the clock rate of the mods and the map's
+    /// overall difficulty can be overridden, e.g. for custom rate mods.
+    ///
+    /// See [`TaikoGradualDifficultyAttributes::new_with_overrides`].
+    pub fn new_with_overrides(
+        map: &'map Beatmap,
+        mods: u32,
+        clock_rate: Option<f64>,
+        od: Option<f32>,
+    ) -> Self {
+        let difficulty =
+            TaikoGradualDifficultyAttributes::new_with_overrides(map, mods, clock_rate, od);
+
+        Self::with_difficulty(TaikoPP::new(map), mods, difficulty, clock_rate, od)
+    }
+
+    /// Same as [`new`](Self::new) but only the first `passed_objects` hits
+    /// are processed, e.g. for a failed play.
+    ///
+    /// See [`TaikoGradualDifficultyAttributes::new_with_passed_objects`].
+    pub fn new_with_passed_objects(map: &'map Beatmap, mods: u32, passed_objects: usize) -> Self {
+        let difficulty =
+            TaikoGradualDifficultyAttributes::new_with_passed_objects(map, mods, passed_objects);
+
+        Self::with_difficulty(TaikoPP::new(map), mods, difficulty, None, None)
+    }
+
+    /// Adjust the multipliers and convert penalties of the difficulty calculation.
+    ///
+    /// See [`TaikoGradualDifficultyAttributes::tuning`].
+    #[inline]
+    pub fn tuning(mut self, tuning: TaikoTuning) -> Self {
+        self.difficulty = self.difficulty.tuning(tuning);
+
+        self
+    }
+
+    fn with_difficulty(
+        performance: TaikoPP<'map>,
+        mods: u32,
+        difficulty: TaikoGradualDifficultyAttributes,
+        clock_rate: Option<f64>,
+        od: Option<f32>,
+    ) -> Self {
+        let mut performance = performance.mods(mods).passed_objects(0);
+
+        if let Some(clock_rate) = clock_rate {
+            performance = performance.clock_rate(clock_rate);
+        }
+
+        if let Some(od) = od {
+            performance = performance.od(od);
+        }
 
         Self {
             difficulty,
             performance,
+            passed_hits: 0,
+            cache: PerformanceCache::new(),
         }
     }
 
@@ -165,23 +235,111 @@ impl<'map> TaikoGradualPerformanceAttributes<'map> {
     /// If `n` is 0 it will be considered as 1.
     /// If there are still objects to be processed but `n` is larger than the amount
     /// of remaining objects, `n` will be considered as the amount of remaining objects.
+    ///
+    /// The skipped objects only update the strains, attributes are calculated
+    /// just once for the `n`-th object. Prefer this over repeated calls of
+    /// [`process_next_object`](`TaikoGradualPerformanceAttributes::process_next_object`)
+    /// when judgements arrive in bursts, e.g. from a spectator feed.
     pub fn process_next_n_objects(
         &mut self,
         state: TaikoScoreState,
         n: usize,
     ) -> Option<TaikoPerformanceAttributes> {
-        let sub = 2 * !self.difficulty.started as usize;
-        let difficulty = self.difficulty.nth(n.saturating_sub(sub))?;
+        self.passed_hits = self.passed_hits.saturating_add(n.max(1));
+
+        // The difficulty attributes start after the first few hits so
+        // they might already be ahead of the hits passed so far.
+        let behind = self
+            .passed_hits
+            .saturating_sub(self.difficulty.processed_hits());
+
+        let difficulty = match self.cache.difficulty() {
+            Some(difficulty) if behind == 0 => *difficulty,
+            _ => self.difficulty.nth(behind.max(1))?,
+        };
+
         let passed_objects = difficulty.max_combo;
 
+        if let Some(performance) = self.cache.get(&difficulty, &state) {
+            return Some(performance);
+        }
+
         let performance = self
             .performance
-            .clone()
+            .borrowed()
             .attributes(difficulty)
             .state(state)
             .passed_objects(passed_objects)
             .calculate();
 
+        self.cache.insert(difficulty, state, performance);
+
         Some(performance)
     }
 }
+
+impl TaikoGradualPerformanceAttributes<'static> {
+    /// Same as [`new`](TaikoGradualPerformanceAttributes::new) but the calculator
+    /// takes ownership of the map instead of borrowing it.
+    ///
+    /// Non-osu!taiko maps are converted up front. The resulting calculator is `'static`
+    /// so it can be stored in long-lived state, e.g. the session of a spectated score.
+    pub fn new_owned(map: Beatmap, mods: u32) -> Self {
+        let is_convert = map.mode != GameMode::Taiko;
+
+        let map = if is_convert {
+            map.convert_mode(GameMode::Taiko).into_owned()
+        } else {
+            map
+        };
+
+        let difficulty = TaikoGradualDifficultyAttributes::with_converted(
+            &map, is_convert, mods, None, None, None,
+        );
+
+        let performance = TaikoPP::from_converted(Cow::Owned(map));
+
+        Self::with_difficulty(performance, mods, difficulty, None, None)
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged_state_reuses_result() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+        let mut gradual = TaikoGradualPerformanceAttributes::new(&map, 0);
+
+        let state = TaikoScoreState {
+            max_combo: 1,
+            n300: 1,
+            n100: 0,
+            n_misses: 0,
+        };
+
+        let first = gradual.process_next_object(state).unwrap();
+
+        // The difficulty attributes still include the next hit so
+        // the inputs are unchanged and the previous result is reused
+        let second = gradual.process_next_object(state).unwrap();
+        assert_eq!(second, first);
+
+        let expected = TaikoGradualPerformanceAttributes::new(&map, 0)
+            .process_next_n_objects(state, 2)
+            .unwrap();
+        assert_eq!(second, expected);
+
+        let state = TaikoScoreState {
+            max_combo: 2,
+            n300: 2,
+            ..state
+        };
+
+        // A changed state is calculated again
+        let third = gradual.process_next_object(state).unwrap();
+        assert_ne!(third, second);
+    }
+}
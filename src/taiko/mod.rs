@@ -1,22 +1,33 @@
+mod calculator;
 mod colours;
 mod difficulty_object;
 mod gradual_difficulty;
 mod gradual_performance;
+mod object_strains;
 mod pp;
 mod rim;
 mod skills;
 mod taiko_object;
+mod tuning;
 
-use std::{borrow::Cow, cell::RefCell, rc::Rc};
+use std::{borrow::Cow, mem};
 
-pub use self::{gradual_difficulty::*, gradual_performance::*, pp::*};
+pub use self::{
+    calculator::*, gradual_difficulty::*, gradual_performance::*, object_strains::*, pp::*,
+    tuning::*,
+};
 
-use crate::{beatmap::BeatmapHitWindows, Beatmap, GameMode, Mods, OsuStars};
+use crate::{
+    analysis::{PatternSection, TaikoPatterns},
+    beatmap::{DifficultyOverrides, DifficultySettings},
+    util::{downsample, first_section_start, round_api},
+    Beatmap, GameMode, OsuStars, StrainAggregation, ATTRIBUTES_VERSION,
+};
 
 use self::{
     colours::ColourDifficultyPreprocessor,
     difficulty_object::{MonoIndex, ObjectLists, TaikoDifficultyObject},
-    skills::{Peaks, PeaksDifficultyValues, PeaksRaw, Skill},
+    skills::{Peaks, PeaksRaw, Skill},
     taiko_object::IntoTaikoObjectIter,
 };
 
@@ -29,7 +40,7 @@ const DIFFICULTY_MULTIPLIER: f64 = 1.35;
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{TaikoStars, Beatmap};
+/// use murasame_pp::{TaikoStars, Beatmap};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -49,6 +60,8 @@ pub struct TaikoStars<'map> {
     passed_objects: Option<usize>,
     clock_rate: Option<f64>,
     is_convert: bool,
+    tuning: TaikoTuning,
+    overrides: DifficultyOverrides,
 }
 
 impl<'map> TaikoStars<'map> {
@@ -64,6 +77,8 @@ impl<'map> TaikoStars<'map> {
             passed_objects: None,
             clock_rate: None,
             is_convert,
+            tuning: TaikoTuning::default(),
+            overrides: DifficultyOverrides::default(),
         }
     }
 
@@ -107,57 +122,62 @@ impl<'map> TaikoStars<'map> {
         self
     }
 
-    /// Calculate all difficulty related values, including stars.
+    /// Adjust the multipliers and convert penalties of the calculation.
     #[inline]
-    pub fn calculate(self) -> TaikoDifficultyAttributes {
-        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+    pub fn tuning(mut self, tuning: TaikoTuning) -> Self {
+        self.tuning = tuning;
 
-        let BeatmapHitWindows { od: hit_window, .. } = self
-            .map
-            .attributes()
-            .mods(self.mods)
-            .clock_rate(clock_rate)
-            .hit_windows();
+        self
+    }
 
-        let is_convert = self.is_convert || matches!(self.map, Cow::Owned(_));
+    /// Override the map's difficulty settings, e.g. for the Difficulty Adjust mod.
+    #[inline]
+    pub(crate) fn overrides(mut self, overrides: DifficultyOverrides) -> Self {
+        self.overrides = overrides;
 
-        let (peaks, max_combo) = calculate_skills(self);
+        self
+    }
 
-        let PeaksDifficultyValues {
-            mut colour_rating,
-            mut rhythm_rating,
-            mut stamina_rating,
-            mut combined_rating,
-        } = peaks.difficulty_values();
+    /// Calculate all difficulty related values, including stars.
+    #[inline]
+    pub fn calculate(self) -> TaikoDifficultyAttributes {
+        self.calculate_with_buffers(&mut ScratchBuffers::default())
+    }
 
-        colour_rating *= DIFFICULTY_MULTIPLIER;
-        rhythm_rating *= DIFFICULTY_MULTIPLIER;
-        stamina_rating *= DIFFICULTY_MULTIPLIER;
-        combined_rating *= DIFFICULTY_MULTIPLIER;
+    pub(crate) fn calculate_with_buffers(
+        self,
+        bufs: &mut ScratchBuffers,
+    ) -> TaikoDifficultyAttributes {
+        let clock_rate = self.overrides.clock_rate(self.clock_rate, self.mods);
 
-        let mut star_rating = rescale(combined_rating * 1.4);
+        let settings = self
+            .overrides
+            .attributes(&self.map)
+            .mods(self.mods)
+            .clock_rate(clock_rate)
+            .settings();
 
-        // * TODO: This is temporary measure as we don't detect abuse of multiple-input
-        // * playstyles of converts within the current system.
-        if is_convert {
-            star_rating *= 0.925;
+        let hit_window = settings.great_hit_window;
+        let ok_hit_window = settings.ok_hit_window;
 
-            // * For maps with low colour variance and high stamina requirement,
-            // * multiple inputs are more likely to be abused.
-            if colour_rating < 2.0 && stamina_rating > 8.0 {
-                star_rating *= 0.8;
-            }
-        }
+        let is_convert = self.is_convert || matches!(self.map, Cow::Owned(_));
+        let tuning = self.tuning;
+
+        let (peaks, max_combo) = calculate_skills(self, bufs);
 
-        TaikoDifficultyAttributes {
-            stamina: stamina_rating,
-            rhythm: rhythm_rating,
-            colour: colour_rating,
-            peak: combined_rating,
+        let mut attrs = TaikoDifficultyAttributes {
             hit_window,
-            stars: star_rating,
+            ok_hit_window,
             max_combo,
-        }
+            is_convert,
+            settings,
+            version: ATTRIBUTES_VERSION,
+            ..Default::default()
+        };
+
+        tuning.apply(&mut attrs, peaks.difficulty_values());
+
+        attrs
     }
 
     /// Calculate the skill strains.
@@ -165,7 +185,9 @@ impl<'map> TaikoStars<'map> {
     /// Suitable to plot the difficulty of a map over time.
     #[inline]
     pub fn strains(self) -> TaikoStrains {
-        let (peaks, _) = calculate_skills(self);
+        let mut bufs = ScratchBuffers::default();
+        let (peaks, _) = calculate_skills(self, &mut bufs);
+        let first_start_time = bufs.lists.all.first().map(|h| h.start_time);
 
         let PeaksRaw {
             colour,
@@ -175,19 +197,39 @@ impl<'map> TaikoStars<'map> {
 
         TaikoStrains {
             section_len: SECTION_LEN as f64,
+            start_time: first_section_start(first_start_time, SECTION_LEN as f64),
             color: colour,
             rhythm,
             stamina,
         }
     }
+
+    /// Calculate the strain of each skill for every difficulty object.
+    ///
+    /// Unlike [`strains`](Self::strains), the values are not aggregated into
+    /// sections which is useful to analyze patterns of the map.
+    #[inline]
+    pub fn object_strains(self) -> TaikoObjectStrains {
+        let (lists, _) = difficulty_objects(self, ObjectLists::default());
+
+        TaikoObjectStrains::new(lists)
+    }
+}
+
+/// Count the maximum combo of an already converted map.
+pub(crate) fn max_combo(map: &Beatmap) -> usize {
+    map.taiko_objects().filter(|(h, _)| h.is_hit).count()
 }
 
 /// The result of calculating the strains on a osu!taiko map.
 /// Suitable to plot the difficulty of a map over time.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaikoStrains {
     /// Time in ms inbetween two strains.
     pub section_len: f64,
+    /// Start time in ms of the first strain peak's section, adjusted by the clock rate.
+    pub start_time: f64,
     /// Strain peaks of the color skill.
     pub color: Vec<f64>,
     /// Strain peaks of the rhythm skill.
@@ -203,22 +245,144 @@ impl TaikoStrains {
     pub fn len(&self) -> usize {
         self.color.len()
     }
+
+    /// Merge the strain peaks into exactly `n_buckets` points, e.g. for a fixed-width graph.
+    ///
+    /// The `section_len` of the result is adjusted so that the `i`-th point
+    /// still starts at `start_time + i * section_len` ms.
+    pub fn downsample(&self, n_buckets: usize, aggregation: StrainAggregation) -> Self {
+        let section_len = if n_buckets > 0 {
+            self.section_len * self.len() as f64 / n_buckets as f64
+        } else {
+            self.section_len
+        };
+
+        Self {
+            section_len,
+            start_time: self.start_time,
+            color: downsample(&self.color, n_buckets, aggregation),
+            rhythm: downsample(&self.rhythm, n_buckets, aggregation),
+            stamina: downsample(&self.stamina, n_buckets, aggregation),
+        }
+    }
 }
 
-fn calculate_skills(params: TaikoStars<'_>) -> (Peaks, usize) {
+/// Buffers that can be reused across multiple difficulty calculations.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ScratchBuffers {
+    lists: ObjectLists,
+}
+
+fn calculate_skills(params: TaikoStars<'_>, bufs: &mut ScratchBuffers) -> (Peaks, usize) {
+    profile!(Skills);
+
+    let (diff_objects, max_combo) = difficulty_objects(params, mem::take(&mut bufs.lists));
+    let mut peaks = Peaks::new();
+
+    for hit_object in diff_objects.all.iter() {
+        peaks.process(hit_object, &diff_objects);
+    }
+
+    bufs.lists = diff_objects;
+
+    (peaks, max_combo)
+}
+
+/// Patterns with less notes are not ranked by density.
+const MIN_PATTERN_NOTES: usize = 4;
+
+/// Gather the statistics of the colour encodings with unsorted sections.
+pub(crate) fn patterns(params: TaikoStars<'_>) -> TaikoPatterns {
+    let (lists, _) = difficulty_objects(params, ObjectLists::default());
+    let colours = &lists.colours;
+
+    let notes_of = |streak: usize| {
+        colours.mono_streaks[streak]
+            .hit_objects
+            .iter()
+            .map(|&idx| &lists.all[idx])
+            .filter(|obj| obj.base.is_hit)
+    };
+
+    let mut mono_streak_lengths = Vec::new();
+
+    for streak in 0..colours.mono_streaks.len() {
+        let len = notes_of(streak).count();
+
+        if len == 0 {
+            continue;
+        } else if mono_streak_lengths.len() < len {
+            mono_streak_lengths.resize(len, 0);
+        }
+
+        mono_streak_lengths[len - 1] += 1;
+    }
+
+    let n_changes = lists
+        .notes
+        .windows(2)
+        .filter(|pair| lists.all[pair[0]].base.is_rim != lists.all[pair[1]].base.is_rim)
+        .count();
+
+    let colour_change_rate = if lists.notes.len() > 1 {
+        n_changes as f64 / (lists.notes.len() - 1) as f64
+    } else {
+        0.0
+    };
+
+    let densest_sections = colours
+        .repeating_hit_patterns
+        .iter()
+        .filter_map(|pattern| {
+            let mut notes = pattern
+                .alternating_mono_patterns
+                .iter()
+                .flat_map(|&idx| colours.alternating_mono_patterns[idx].mono_streaks.iter())
+                .flat_map(|&streak| notes_of(streak));
+
+            let start_time = notes.next()?.start_time;
+            let (n_notes, end_time) =
+                notes.fold((1, start_time), |(n, _), obj| (n + 1, obj.start_time));
+
+            if n_notes < MIN_PATTERN_NOTES || end_time <= start_time {
+                return None;
+            }
+
+            Some(PatternSection {
+                start_time,
+                end_time,
+                n_notes,
+                notes_per_second: (n_notes - 1) as f64 * 1000.0 / (end_time - start_time),
+            })
+        })
+        .collect();
+
+    TaikoPatterns {
+        mono_streak_lengths,
+        colour_change_rate,
+        densest_sections,
+    }
+}
+
+/// Create the preprocessed difficulty objects and count the max combo.
+///
+/// The given lists are cleared and then filled so that their allocations can be reused.
+fn difficulty_objects(params: TaikoStars<'_>, mut lists: ObjectLists) -> (ObjectLists, usize) {
     let TaikoStars {
         map,
         mods,
         passed_objects,
         clock_rate,
         is_convert: _,
+        tuning: _,
+        overrides,
     } = params;
 
     let mut take = passed_objects.unwrap_or(map.hit_objects.len());
-    let clock_rate = clock_rate.unwrap_or_else(|| mods.clock_rate());
+    let clock_rate = overrides.clock_rate(clock_rate, mods);
 
-    let mut peaks = Peaks::new();
     let mut max_combo = 0;
+    lists.clear();
 
     let mut diff_objects = map
         .taiko_objects()
@@ -239,7 +403,7 @@ fn calculate_skills(params: TaikoStars<'_>) -> (Peaks, usize) {
         .zip(map.hit_objects.iter())
         .enumerate()
         .fold(
-            ObjectLists::default(),
+            lists,
             |mut lists, (idx, (((base, base_start_time), last), last_last))| {
                 let diff_obj = TaikoDifficultyObject::new(
                     base,
@@ -261,7 +425,7 @@ fn calculate_skills(params: TaikoStars<'_>) -> (Peaks, usize) {
                     lists.notes.push(idx);
                 }
 
-                lists.all.push(Rc::new(RefCell::new(diff_obj)));
+                lists.all.push(diff_obj);
 
                 lists
             },
@@ -269,11 +433,7 @@ fn calculate_skills(params: TaikoStars<'_>) -> (Peaks, usize) {
 
     ColourDifficultyPreprocessor::process_and_assign(&mut diff_objects);
 
-    for hit_object in diff_objects.all.iter() {
-        peaks.process(&hit_object.borrow(), &diff_objects);
-    }
-
-    (peaks, max_combo)
+    (diff_objects, max_combo)
 }
 
 #[inline]
@@ -286,7 +446,8 @@ fn rescale(stars: f64) -> f64 {
 }
 
 /// The result of a difficulty calculation on an osu!taiko map.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaikoDifficultyAttributes {
     /// The difficulty corresponding to the stamina skill.
     pub stamina: f64,
@@ -298,22 +459,61 @@ pub struct TaikoDifficultyAttributes {
     pub peak: f64,
     /// The perceived hit window for an n300 inclusive of rate-adjusting mods (DT/HT/etc)
     pub hit_window: f64,
+    /// The perceived hit window for an n100 inclusive of rate-adjusting mods (DT/HT/etc)
+    pub ok_hit_window: f64,
     /// The final star rating.
     pub stars: f64,
     /// The maximum combo.
     pub max_combo: usize,
+    /// Whether the map was treated as a convert from osu!standard.
+    ///
+    /// The star rating of converts is nerfed, especially for maps with
+    /// low colour variance and high stamina requirement.
+    pub is_convert: bool,
+    /// The difficulty settings after applying mods.
+    pub settings: DifficultySettings,
+    /// The [`ATTRIBUTES_VERSION`](crate::ATTRIBUTES_VERSION) the attributes were calculated with.
+    ///
+    /// `0` if the attributes were not calculated by this crate.
+    pub version: u32,
 }
 
 impl TaikoDifficultyAttributes {
+    /// Round all floating point values to the precision of the osu!api
+    /// so that they can be compared to or stored alongside official values.
+    ///
+    /// Ties are rounded to the nearest even digit like osu!lazer does.
+    pub fn api_rounded(mut self) -> Self {
+        self.stamina = round_api(self.stamina);
+        self.rhythm = round_api(self.rhythm);
+        self.colour = round_api(self.colour);
+        self.peak = round_api(self.peak);
+        self.hit_window = round_api(self.hit_window);
+        self.ok_hit_window = round_api(self.ok_hit_window);
+        self.stars = round_api(self.stars);
+        self.settings = self.settings.api_rounded();
+
+        self
+    }
+
     /// Return the maximum combo.
     #[inline]
     pub fn max_combo(&self) -> usize {
         self.max_combo
     }
+
+    /// Return the amount of hit objects that receive a hitresult i.e. circles.
+    ///
+    /// Drum rolls and swells give no hitresults and are not counted.
+    #[inline]
+    pub fn n_objects(&self) -> usize {
+        self.max_combo
+    }
 }
 
 /// The result of a performance calculation on an osu!taiko map.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TaikoPerformanceAttributes {
     /// The difficulty attributes that were used for the performance calculation
     pub difficulty: TaikoDifficultyAttributes,
@@ -325,9 +525,25 @@ pub struct TaikoPerformanceAttributes {
     pub pp_difficulty: f64,
     /// Scaled miss count based on total hits.
     pub effective_miss_count: f64,
+    /// The hitresults and combo that were used for the performance calculation.
+    pub state: TaikoScoreState,
 }
 
 impl TaikoPerformanceAttributes {
+    /// Round all floating point values, including the difficulty attributes,
+    /// to the precision of the osu!api.
+    ///
+    /// See [`TaikoDifficultyAttributes::api_rounded`].
+    pub fn api_rounded(mut self) -> Self {
+        self.difficulty = self.difficulty.api_rounded();
+        self.pp = round_api(self.pp);
+        self.pp_acc = round_api(self.pp_acc);
+        self.pp_difficulty = round_api(self.pp_difficulty);
+        self.effective_miss_count = round_api(self.effective_miss_count);
+
+        self
+    }
+
     /// Return the star value.
     #[inline]
     pub fn stars(&self) -> f64 {
@@ -345,6 +561,12 @@ impl TaikoPerformanceAttributes {
     pub fn max_combo(&self) -> usize {
         self.difficulty.max_combo
     }
+
+    /// Return whether the map was treated as a convert from osu!standard.
+    #[inline]
+    pub fn is_convert(&self) -> bool {
+        self.difficulty.is_convert
+    }
 }
 
 impl From<TaikoPerformanceAttributes> for TaikoDifficultyAttributes {
@@ -362,6 +584,8 @@ impl<'map> From<OsuStars<'map>> for TaikoStars<'map> {
             mods,
             passed_objects,
             clock_rate,
+            overrides,
+            ..
         } = osu;
 
         Self {
@@ -370,6 +594,8 @@ impl<'map> From<OsuStars<'map>> for TaikoStars<'map> {
             passed_objects,
             clock_rate,
             is_convert: true,
+            tuning: TaikoTuning::default(),
+            overrides,
         }
     }
 }
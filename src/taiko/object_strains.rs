@@ -0,0 +1,96 @@
+use super::{
+    difficulty_object::ObjectLists,
+    skills::{Peaks, Skill},
+};
+
+/// The strains of a single osu!taiko difficulty object.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaikoObjectStrain {
+    /// The index of the hit object in the map.
+    ///
+    /// The first two hit objects have no difficulty object so this starts at 2.
+    pub idx: usize,
+    /// The start time of the hit object in ms, adjusted to the clock rate.
+    pub start_time: f64,
+    /// Whether the hit object is a circle, i.e. neither a drum roll nor a swell.
+    pub is_hit: bool,
+    /// Whether the hit object is a rim (kat).
+    pub is_rim: bool,
+    /// The strain of the colour skill after processing the hit object.
+    pub colour: f64,
+    /// The strain of the rhythm skill after processing the hit object.
+    pub rhythm: f64,
+    /// The strain of the stamina skill after processing the hit object.
+    pub stamina: f64,
+}
+
+/// Iterator over the strains of every difficulty object of an osu!taiko map.
+///
+/// Created through [`TaikoStars::object_strains`](crate::TaikoStars::object_strains).
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, TaikoStars};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+///
+/// for strain in TaikoStars::new(&map).mods(64).object_strains() {
+///     println!("{}ms: colour={}", strain.start_time, strain.colour);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TaikoObjectStrains {
+    lists: ObjectLists,
+    peaks: Peaks,
+    idx: usize,
+}
+
+impl TaikoObjectStrains {
+    pub(crate) fn new(lists: ObjectLists) -> Self {
+        Self {
+            lists,
+            peaks: Peaks::new(),
+            idx: 0,
+        }
+    }
+}
+
+impl Iterator for TaikoObjectStrains {
+    type Item = TaikoObjectStrain;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let curr = self.lists.all.get(self.idx)?;
+        self.idx += 1;
+        self.peaks.process(curr, &self.lists);
+        let (colour, rhythm, stamina) = self.peaks.curr_strains();
+
+        Some(TaikoObjectStrain {
+            idx: curr.idx + 2,
+            start_time: curr.start_time,
+            is_hit: curr.base.is_hit,
+            is_rim: curr.base.is_rim,
+            colour,
+            rhythm,
+            stamina,
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for TaikoObjectStrains {
+    #[inline]
+    fn len(&self) -> usize {
+        self.lists.all.len() - self.idx
+    }
+}
@@ -1,8 +1,13 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, f64::consts::SQRT_2};
 
-use super::{TaikoDifficultyAttributes, TaikoPerformanceAttributes, TaikoScoreState, TaikoStars};
+use super::{
+    TaikoDifficultyAttributes, TaikoPerformanceAttributes, TaikoScoreState, TaikoStars, TaikoTuning,
+};
 use crate::{
-    Beatmap, DifficultyAttributes, GameMode, HitResultPriority, Mods, OsuPP, PerformanceAttributes,
+    beatmap::DifficultyOverrides,
+    invalid_score,
+    util::{erf_inv, FloatExt},
+    AttributeProvider, Beatmap, CustomMod, GameMode, HitResultPriority, InvalidScore, Mods, OsuPP,
 };
 
 /// Performance calculator on osu!taiko maps.
@@ -10,7 +15,7 @@ use crate::{
 /// # Example
 ///
 /// ```
-/// use rosu_pp::{TaikoPP, Beatmap};
+/// use murasame_pp::{TaikoPP, Beatmap};
 ///
 /// # /*
 /// let map: Beatmap = ...
@@ -44,7 +49,11 @@ pub struct TaikoPP<'map> {
     acc: Option<f64>,
     passed_objects: Option<usize>,
     clock_rate: Option<f64>,
+    overrides: DifficultyOverrides,
+    great_hit_window: Option<f64>,
+    ok_hit_window: Option<f64>,
     hitresult_priority: Option<HitResultPriority>,
+    tuning: TaikoTuning,
 
     pub(crate) n300: Option<usize>,
     pub(crate) n100: Option<usize>,
@@ -55,8 +64,14 @@ impl<'map> TaikoPP<'map> {
     /// Create a new performance calculator for osu!taiko maps.
     #[inline]
     pub fn new(map: &'map Beatmap) -> Self {
+        Self::from_converted(map.convert_mode(GameMode::Taiko))
+    }
+
+    /// The map must already be converted to osu!taiko.
+    #[inline]
+    pub(crate) fn from_converted(map: Cow<'map, Beatmap>) -> Self {
         Self {
-            map: map.convert_mode(GameMode::Taiko),
+            map,
             attributes: None,
             mods: 0,
             combo: None,
@@ -64,9 +79,34 @@ impl<'map> TaikoPP<'map> {
             n_misses: None,
             passed_objects: None,
             clock_rate: None,
+            overrides: DifficultyOverrides::default(),
+            great_hit_window: None,
+            ok_hit_window: None,
             n300: None,
             n100: None,
             hitresult_priority: None,
+            tuning: TaikoTuning::default(),
+        }
+    }
+
+    /// Copy the calculator while borrowing its map instead of cloning it.
+    pub(crate) fn borrowed(&self) -> TaikoPP<'_> {
+        TaikoPP {
+            map: Cow::Borrowed(self.map.as_ref()),
+            attributes: self.attributes,
+            mods: self.mods,
+            combo: self.combo,
+            acc: self.acc,
+            n_misses: self.n_misses,
+            passed_objects: self.passed_objects,
+            clock_rate: self.clock_rate,
+            overrides: self.overrides,
+            great_hit_window: self.great_hit_window,
+            ok_hit_window: self.ok_hit_window,
+            n300: self.n300,
+            n100: self.n100,
+            hitresult_priority: self.hitresult_priority,
+            tuning: self.tuning,
         }
     }
 
@@ -92,6 +132,17 @@ impl<'map> TaikoPP<'map> {
         self
     }
 
+    /// Adjust the multipliers and convert penalties of the difficulty calculation.
+    ///
+    /// Has no effect if difficulty attributes are provided through
+    /// [`attributes`](TaikoPP::attributes).
+    #[inline]
+    pub fn tuning(mut self, tuning: TaikoTuning) -> Self {
+        self.tuning = tuning;
+
+        self
+    }
+
     /// Specify the max combo of the play.
     #[inline]
     pub fn combo(mut self, combo: usize) -> Self {
@@ -165,6 +216,78 @@ impl<'map> TaikoPP<'map> {
         self
     }
 
+    /// Override the map's approach rate before calculating its difficulty.
+    ///
+    /// Irrelevant for osu!taiko.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn ar(mut self, ar: f32) -> Self {
+        self.overrides.ar = Some(ar);
+
+        self
+    }
+
+    /// Override the map's overall difficulty before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn od(mut self, od: f32) -> Self {
+        self.overrides.od = Some(od);
+
+        self
+    }
+
+    /// Override the map's circle size before calculating its difficulty.
+    ///
+    /// Irrelevant for osu!taiko.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn cs(mut self, cs: f32) -> Self {
+        self.overrides.cs = Some(cs);
+
+        self
+    }
+
+    /// Override the map's drain rate before calculating its difficulty.
+    ///
+    /// Mods such as HR or EZ are still applied on top of the given value.
+    #[inline]
+    pub fn hp(mut self, hp: f32) -> Self {
+        self.overrides.hp = Some(hp);
+
+        self
+    }
+
+    /// Apply the effects of a [`CustomMod`] on top of the mods, e.g. a private server's own mod.
+    #[inline]
+    pub fn custom_mod(mut self, custom_mod: CustomMod) -> Self {
+        self.overrides.custom_mod = custom_mod;
+
+        self
+    }
+
+    /// Override the hit window for 300s in milliseconds.
+    ///
+    /// The window is used as it is, i.e. it is neither affected by mods nor by the clock rate.
+    #[inline]
+    pub fn great_hit_window(mut self, great_hit_window: f64) -> Self {
+        self.great_hit_window = Some(great_hit_window);
+
+        self
+    }
+
+    /// Override the hit window for 100s in milliseconds.
+    ///
+    /// The window is used as it is, i.e. it is neither affected by mods nor by the clock rate.
+    #[inline]
+    pub fn ok_hit_window(mut self, ok_hit_window: f64) -> Self {
+        self.ok_hit_window = Some(ok_hit_window);
+
+        self
+    }
+
     /// Provide parameters through a [`TaikoScoreState`].
     #[inline]
     pub fn state(mut self, state: TaikoScoreState) -> Self {
@@ -183,23 +306,168 @@ impl<'map> TaikoPP<'map> {
         self
     }
 
+    fn take_attributes(&mut self) -> TaikoDifficultyAttributes {
+        self.attributes
+            .take()
+            .unwrap_or_else(|| self.calculate_difficulty(self.passed_objects))
+    }
+
+    fn calculate_difficulty(&self, passed_objects: Option<usize>) -> TaikoDifficultyAttributes {
+        let mut calculator = TaikoStars::new(self.map.as_ref())
+            .mods(self.mods)
+            .overrides(self.overrides)
+            .is_convert(matches!(self.map, Cow::Owned(_)))
+            .tuning(self.tuning);
+
+        if let Some(passed_objects) = passed_objects {
+            calculator = calculator.passed_objects(passed_objects);
+        }
+
+        if let Some(clock_rate) = self.clock_rate {
+            calculator = calculator.clock_rate(clock_rate);
+        }
+
+        calculator.calculate()
+    }
+
+    /// Copy the settings of the calculator without the values of a specific score.
+    fn without_score(&self) -> TaikoPP<'_> {
+        TaikoPP {
+            map: Cow::Borrowed(self.map.as_ref()),
+            attributes: None,
+            mods: self.mods,
+            combo: None,
+            acc: None,
+            passed_objects: self.passed_objects,
+            clock_rate: self.clock_rate,
+            overrides: self.overrides,
+            great_hit_window: self.great_hit_window,
+            ok_hit_window: self.ok_hit_window,
+            hitresult_priority: None,
+            tuning: self.tuning,
+            n300: None,
+            n100: None,
+            n_misses: None,
+        }
+    }
+
+    /// Generate hitresults for the given accuracy between `0.0` and `100.0` the same way
+    /// [osu-tools](https://github.com/ppy/osu-tools)' `simulate` command does.
+    ///
+    /// Specified 100s, misses, and combo are respected. If 100s are specified,
+    /// the accuracy is ignored. Unlike [`accuracy`](TaikoPP::accuracy), infeasible
+    /// inputs are not corrected except that negative counts are clamped to zero.
+    pub fn simulate(mut self, acc: f64) -> TaikoScoreState {
+        let attrs = self.take_attributes();
+
+        let total = match self.passed_objects {
+            Some(passed_objects) => attrs.max_combo.min(passed_objects),
+            None => attrs.max_combo,
+        } as isize;
+
+        let n_misses = self.n_misses.unwrap_or(0) as isize;
+
+        let (n300, n100) = if let Some(n100) = self.n100 {
+            let n100 = n100 as isize;
+
+            (total - n100 - n_misses, n100)
+        } else {
+            // Let 300=2, 100=1, miss=0
+            let target_total = (acc / 100.0 * (total * 2) as f64).round_even() as isize;
+            let n300 = target_total - (total - n_misses);
+
+            (n300, total - n300 - n_misses)
+        };
+
+        TaikoScoreState {
+            max_combo: self.combo.unwrap_or(attrs.max_combo),
+            n300: n300.max(0) as usize,
+            n100: n100.max(0) as usize,
+            n_misses: n_misses as usize,
+        }
+    }
+
+    /// Same as [`calculate`](TaikoPP::calculate) but the specified values are
+    /// first validated against the map.
+    ///
+    /// Returns an [`InvalidScore`] describing the first inconsistent value, e.g. if
+    /// the combo exceeds the map's max combo or the hitresults exceed the amount of circles.
+    pub fn try_calculate(mut self) -> Result<TaikoPerformanceAttributes, InvalidScore> {
+        invalid_score::check_passed_objects(self.passed_objects, self.map.hit_objects.len())?;
+        invalid_score::check_accuracy(self.acc)?;
+
+        let attrs = self.take_attributes();
+
+        let n_objects = match self.passed_objects {
+            Some(passed_objects) => attrs.max_combo.min(passed_objects),
+            None => attrs.max_combo,
+        };
+
+        invalid_score::check_combo(self.combo, attrs.max_combo)?;
+
+        let hitresults = [
+            ("n300", self.n300),
+            ("n100", self.n100),
+            ("n_misses", self.n_misses),
+        ];
+
+        invalid_score::check_hitresults(&hitresults, n_objects)?;
+
+        Ok(self.attributes(attrs).calculate())
+    }
+
+    /// Calculate the performance attributes for each of the given score states.
+    ///
+    /// The difficulty attributes are calculated only once and shared by all scores.
+    /// Values of a specific score that were specified on the calculator itself,
+    /// e.g. hitresults or accuracy, are ignored.
+    pub fn calculate_many(mut self, states: &[TaikoScoreState]) -> Vec<TaikoPerformanceAttributes> {
+        let attrs = self.take_attributes();
+
+        states
+            .iter()
+            .map(|state| {
+                self.without_score()
+                    .attributes(attrs)
+                    .state(*state)
+                    .calculate()
+            })
+            .collect()
+    }
+
+    /// Calculate the performance attributes of the score and of an SS
+    /// on the same map with the same mods.
+    ///
+    /// The difficulty attributes are calculated only once and shared by both calculations
+    /// unless `passed_objects` is specified, in which case the SS is calculated on the full map.
+    pub fn calculate_with_max(
+        mut self,
+    ) -> (TaikoPerformanceAttributes, TaikoPerformanceAttributes) {
+        let attrs = self.take_attributes();
+
+        let max_attrs = match self.passed_objects {
+            Some(_) => self.calculate_difficulty(None),
+            None => attrs,
+        };
+
+        let mut max = self.without_score();
+        max.passed_objects = None;
+        let max = max.attributes(max_attrs).calculate();
+
+        (self.attributes(attrs).calculate(), max)
+    }
+
     /// Calculate all performance related values, including pp and stars.
     pub fn calculate(mut self) -> TaikoPerformanceAttributes {
-        let attrs = self.attributes.take().unwrap_or_else(|| {
-            let mut calculator = TaikoStars::new(self.map.as_ref())
-                .mods(self.mods)
-                .is_convert(matches!(self.map, Cow::Owned(_)));
+        let mut attrs = self.take_attributes();
 
-            if let Some(passed_objects) = self.passed_objects {
-                calculator = calculator.passed_objects(passed_objects);
-            }
-
-            if let Some(clock_rate) = self.clock_rate {
-                calculator = calculator.clock_rate(clock_rate);
-            }
+        if let Some(great_hit_window) = self.great_hit_window {
+            attrs.hit_window = great_hit_window;
+        }
 
-            calculator.calculate()
-        });
+        if let Some(ok_hit_window) = self.ok_hit_window {
+            attrs.ok_hit_window = ok_hit_window;
+        }
 
         let inner = TaikoPpInner {
             mods: self.mods,
@@ -236,9 +504,13 @@ impl<'map> TaikoPP<'map> {
                 (Some(_), None) => n100 += total_result_count.saturating_sub(n300 + n_misses),
                 (None, Some(_)) => n300 += total_result_count.saturating_sub(n100 + n_misses),
                 (None, None) => {
+                    let n_remaining = total_result_count.saturating_sub(n_misses);
                     let target_total = (acc * (total_result_count * 2) as f64).round() as usize;
-                    n300 = target_total - (total_result_count.saturating_sub(n_misses));
-                    n100 = total_result_count.saturating_sub(n300 + n_misses);
+
+                    // The accuracy might be unreachable with the given misses
+                    // so the amount of 300s is clamped to the remaining hits.
+                    n300 = target_total.saturating_sub(n_remaining).min(n_remaining);
+                    n100 = n_remaining - n300;
                 }
             }
         } else {
@@ -277,6 +549,8 @@ struct TaikoPpInner {
 
 impl TaikoPpInner {
     fn calculate(self) -> TaikoPerformanceAttributes {
+        profile!(Performance);
+
         // * The effectiveMissCount is calculated by gaining a ratio for totalSuccessfulHits
         // * and increasing the miss penalty for shorter object counts lower than 1000.
         let total_successful_hits = self.total_successful_hits();
@@ -308,6 +582,7 @@ impl TaikoPpInner {
             pp_acc: acc_value,
             pp_difficulty: diff_value,
             effective_miss_count,
+            state: self.state,
         }
     }
 
@@ -342,28 +617,67 @@ impl TaikoPpInner {
         diff_value * acc * acc
     }
 
-    #[inline]
     fn compute_accuracy_value(&self) -> f64 {
-        if self.attrs.hit_window <= 0.0 {
-            return 0.0;
-        }
+        let estimated_unstable_rate = match self.compute_deviation_upper_bound() {
+            Some(deviation) if self.attrs.hit_window > 0.0 => deviation * 10.0,
+            _ => return 0.0,
+        };
 
-        let mut acc_value = (60.0 / self.attrs.hit_window).powf(1.1)
-            * self.custom_accuracy().powi(8)
-            * self.attrs.stars.powf(0.4)
-            * 27.0;
+        let mut acc_value =
+            (70.0 / estimated_unstable_rate).powf(1.1) * self.attrs.stars.powf(0.4) * 100.0;
 
         let len_bonus = (self.total_hits() / 1500.0).powf(0.3).min(1.15);
-        acc_value *= len_bonus;
 
-        // * Slight HDFL Bonus for accuracy. A clamp is used to prevent against negative values
-        if self.mods.hd() && self.mods.fl() {
-            acc_value *= (1.075 * len_bonus).max(1.05);
+        // * Slight HDFL Bonus for accuracy. A clamp is used to prevent against negative values.
+        if self.mods.hd() && self.mods.fl() && !self.attrs.is_convert {
+            acc_value *= (1.05 * len_bonus).max(1.0);
         }
 
         acc_value
     }
 
+    /// Calculates the tap deviation for a player using the OD, object count,
+    /// and scores of 300s and 100s.
+    ///
+    /// Returns an upper bound with 99% confidence or `None` if there are no successful hits.
+    fn compute_deviation_upper_bound(&self) -> Option<f64> {
+        let total_successful_hits = self.total_successful_hits();
+
+        if total_successful_hits == 0 {
+            return None;
+        }
+
+        // * 99% critical value for the normal distribution (one-tailed).
+        const Z: f64 = 2.32634787404;
+
+        let n = self.total_hits();
+
+        // * We can be 99% confident that the deviation is not higher than this value
+        // * when given the proportion `p` of hits within the hit window `h`.
+        let deviation_upper_bound = |p: f64, h: f64| {
+            let p_lower_bound = (n * p + Z * Z / 2.0) / (n + Z * Z)
+                - Z / (n + Z * Z) * (n * p * (1.0 - p) + Z * Z / 4.0).sqrt();
+
+            h / (SQRT_2 * erf_inv(p_lower_bound))
+        };
+
+        // * The upper bound on deviation, calculated with the ratio of 300s to objects,
+        // * and the great hit window.
+        let deviation_great_window = (self.state.n300 > 0)
+            .then(|| deviation_upper_bound(self.state.n300 as f64 / n, self.attrs.hit_window));
+
+        // * The upper bound on deviation, calculated with the ratio of 300s + 100s to objects,
+        // * and the ok hit window. This will return a lower value than the first method when
+        // * the number of 100s is high, but the miss count is low.
+        let deviation_ok_window =
+            deviation_upper_bound(total_successful_hits as f64 / n, self.attrs.ok_hit_window);
+
+        Some(
+            deviation_great_window
+                .map_or(deviation_ok_window, |great| great.min(deviation_ok_window)),
+        )
+    }
+
     fn total_hits(&self) -> f64 {
         self.state.total_hits() as f64
     }
@@ -393,14 +707,26 @@ impl<'map> From<OsuPP<'map>> for TaikoPP<'map> {
             map,
             attributes: _,
             mods,
+            blinds: _,
+            relax: _,
+            hit_errors: _,
+            hit_windows: _,
+            slider_breaks: _,
+            slider_tick_misses: _,
+            slider_end_misses: _,
+            tuning: _,
+            mod_multipliers: _,
             acc,
             combo,
             n300,
             n100,
             n50: _,
             n_misses,
+            n_geki: _,
+            n_katu: _,
             passed_objects,
             clock_rate,
+            overrides,
             hitresult_priority,
         } = osu;
 
@@ -412,7 +738,11 @@ impl<'map> From<OsuPP<'map>> for TaikoPP<'map> {
             acc,
             passed_objects,
             clock_rate,
+            overrides,
+            great_hit_window: None,
+            ok_hit_window: None,
             hitresult_priority,
+            tuning: TaikoTuning::default(),
             n300,
             n100,
             n_misses,
@@ -421,46 +751,17 @@ impl<'map> From<OsuPP<'map>> for TaikoPP<'map> {
 }
 
 /// Abstract type to provide flexibility when passing difficulty attributes to a performance calculation.
+///
+/// Implemented for all types implementing [`AttributeProvider`].
 pub trait TaikoAttributeProvider {
     /// Provide the actual difficulty attributes.
     fn attributes(self) -> Option<TaikoDifficultyAttributes>;
 }
 
-impl TaikoAttributeProvider for TaikoDifficultyAttributes {
-    #[inline]
-    fn attributes(self) -> Option<TaikoDifficultyAttributes> {
-        Some(self)
-    }
-}
-
-impl TaikoAttributeProvider for TaikoPerformanceAttributes {
+impl<T: AttributeProvider> TaikoAttributeProvider for T {
     #[inline]
     fn attributes(self) -> Option<TaikoDifficultyAttributes> {
-        Some(self.difficulty)
-    }
-}
-
-impl TaikoAttributeProvider for DifficultyAttributes {
-    #[inline]
-    fn attributes(self) -> Option<TaikoDifficultyAttributes> {
-        #[allow(irrefutable_let_patterns)]
-        if let Self::Taiko(attributes) = self {
-            Some(attributes)
-        } else {
-            None
-        }
-    }
-}
-
-impl TaikoAttributeProvider for PerformanceAttributes {
-    #[inline]
-    fn attributes(self) -> Option<TaikoDifficultyAttributes> {
-        #[allow(irrefutable_let_patterns)]
-        if let Self::Taiko(attributes) = self {
-            Some(attributes.difficulty)
-        } else {
-            None
-        }
+        self.mode_attributes().ok()
     }
 }
 
@@ -480,8 +781,10 @@ mod test {
             colour: 1.0487315549761433,
             peak: 1.8881824429738323,
             hit_window: 35.0,
+            ok_hit_window: 80.0,
             stars: 2.9778030386845606,
             max_combo: 289,
+            ..Default::default()
         };
 
         (map, attrs)
@@ -560,4 +863,319 @@ mod test {
             expected.accuracy()
         );
     }
+
+    #[test]
+    fn calculate_with_max_matches_separate_calculations() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+
+        let (score, max) = TaikoPP::new(&map)
+            .mods(64)
+            .accuracy(95.0)
+            .n_misses(2)
+            .calculate_with_max();
+
+        assert_eq!(
+            score,
+            TaikoPP::new(&map)
+                .mods(64)
+                .accuracy(95.0)
+                .n_misses(2)
+                .calculate()
+        );
+        assert_eq!(max, TaikoPP::new(&map).mods(64).calculate());
+    }
+
+    #[test]
+    fn calculate_with_max_on_partial_convert() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        let tuning = TaikoTuning {
+            convert_multiplier: 1.0,
+            ..Default::default()
+        };
+
+        let (score, max) = TaikoPP::new(&map)
+            .tuning(tuning)
+            .passed_objects(100)
+            .n_misses(1)
+            .calculate_with_max();
+
+        assert_eq!(score.difficulty.max_combo, 100);
+        assert!(max.is_convert());
+        assert_eq!(max, TaikoPP::new(&map).tuning(tuning).calculate());
+    }
+
+    #[test]
+    fn state_reproduces_performance() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+
+        let performance = TaikoPP::new(&map)
+            .accuracy(95.0)
+            .combo(150)
+            .n_misses(2)
+            .calculate();
+
+        let reproduced = TaikoPP::new(&map)
+            .attributes(performance)
+            .state(performance.state)
+            .calculate();
+
+        assert_eq!(performance, reproduced);
+    }
+
+    #[test]
+    fn simulate_matches_osu_tools() {
+        let (map, attrs) = test_data();
+
+        let state = TaikoPP::new(&map)
+            .attributes(attrs)
+            .n_misses(2)
+            .simulate(95.0);
+
+        let expected = TaikoScoreState {
+            max_combo: 289,
+            n300: 262,
+            n100: 25,
+            n_misses: 2,
+        };
+
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn great_hit_window_override() {
+        let (map, attrs) = test_data();
+        let calculator = TaikoPP::new(&map).attributes(attrs).accuracy(97.0);
+
+        let regular = calculator.clone().calculate();
+        let same = calculator
+            .clone()
+            .great_hit_window(attrs.hit_window)
+            .calculate();
+        let strict = calculator.great_hit_window(20.0).calculate();
+
+        assert_eq!(regular.pp, same.pp);
+        assert!(strict.pp_acc > regular.pp_acc);
+        assert_eq!(strict.difficulty.hit_window, 20.0);
+    }
+
+    #[test]
+    fn gradual_clock_rate_and_od_overrides() {
+        let (map, _) = test_data();
+
+        let expected = TaikoPP::new(&map).clock_rate(1.2).od(9.0).calculate();
+
+        let last = crate::taiko::TaikoGradualDifficultyAttributes::new_with_overrides(
+            &map,
+            0,
+            Some(1.2),
+            Some(9.0),
+        )
+        .last()
+        .unwrap();
+
+        assert_eq!(last.hit_window, expected.difficulty.hit_window);
+        assert!((last.stars - expected.difficulty.stars).abs() < 1e-7);
+
+        let nomod = TaikoPP::new(&map).calculate();
+        assert!(last.hit_window < nomod.difficulty.hit_window);
+    }
+
+    #[test]
+    fn hitresults_unreachable_acc() {
+        let (map, attrs) = test_data();
+        let max_combo = attrs.max_combo();
+
+        let state = TaikoPP::new(&map)
+            .attributes(attrs)
+            .accuracy(20.0)
+            .generate_hitresults(max_combo);
+
+        assert_eq!(state.n300, 0);
+        assert_eq!(state.n100, max_combo);
+
+        let state = TaikoPP::new(&map)
+            .attributes(attrs)
+            .accuracy(100.0)
+            .n_misses(10)
+            .generate_hitresults(max_combo);
+
+        assert_eq!(state.n300, max_combo - 10);
+        assert_eq!(state.n100, 0);
+        assert_eq!(state.total_hits(), max_combo);
+    }
+
+    #[test]
+    fn gradual_passed_objects() {
+        let (map, _) = test_data();
+
+        let expected = TaikoStars::new(&map).passed_objects(100).calculate();
+        let gradual =
+            crate::taiko::TaikoGradualDifficultyAttributes::new_with_passed_objects(&map, 0, 100);
+
+        let last = gradual.last().unwrap();
+        assert_eq!(last.max_combo, 100);
+        assert_eq!(last, expected);
+
+        let mut gradual =
+            crate::taiko::TaikoGradualPerformanceAttributes::new_with_passed_objects(&map, 0, 100);
+
+        let state = TaikoScoreState {
+            max_combo: 100,
+            n300: 100,
+            ..Default::default()
+        };
+
+        let pp = gradual.process_next_n_objects(state, usize::MAX).unwrap();
+        assert_eq!(pp.difficulty, expected);
+        assert!(gradual.process_next_object(state).is_none());
+    }
+
+    #[test]
+    fn gradual_bursts_match_single_steps() {
+        let (map, _) = test_data();
+
+        let mut single = crate::taiko::TaikoGradualPerformanceAttributes::new(&map, 0);
+        let mut burst = crate::taiko::TaikoGradualPerformanceAttributes::new(&map, 0);
+        let mut state = TaikoScoreState::new();
+        let mut expected = None;
+
+        for _ in 0..50 {
+            state.n300 += 1;
+            state.max_combo += 1;
+            expected = single.process_next_object(state);
+        }
+
+        assert_eq!(burst.process_next_n_objects(state, 50), expected);
+
+        for _ in 0..25 {
+            state.n100 += 1;
+            state.max_combo += 1;
+            expected = single.process_next_object(state);
+        }
+
+        assert_eq!(burst.process_next_n_objects(state, 25), expected);
+    }
+
+    #[test]
+    fn gradual_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<crate::taiko::TaikoGradualDifficultyAttributes>();
+        assert_send_sync::<crate::taiko::TaikoGradualPerformanceAttributes<'_>>();
+    }
+
+    #[test]
+    fn gradual_owned_map() {
+        fn assert_static<T: 'static>(_: &T) {}
+
+        let (map, _) = test_data();
+        let osu_map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+
+        for map in [map, osu_map] {
+            let mut borrowed = crate::taiko::TaikoGradualPerformanceAttributes::new(&map, 8);
+            let mut owned =
+                crate::taiko::TaikoGradualPerformanceAttributes::new_owned(map.clone(), 8);
+            assert_static(&owned);
+
+            let mut state = TaikoScoreState::new();
+
+            loop {
+                state.n300 += 1;
+                state.max_combo += 1;
+
+                let expected = borrowed.process_next_object(state);
+                assert_eq!(owned.process_next_object(state), expected);
+
+                if expected.is_none() {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn object_strains_match_section_peaks() {
+        let (map, _) = test_data();
+
+        let object_strains: Vec<_> = TaikoStars::new(&map).object_strains().collect();
+        let strains = TaikoStars::new(&map).strains();
+
+        assert_eq!(object_strains.len(), map.hit_objects.len() - 2);
+        assert_eq!(object_strains[0].idx, 2);
+
+        let max = |values: &[f64]| values.iter().copied().fold(0.0, f64::max);
+        let colour: Vec<_> = object_strains.iter().map(|strain| strain.colour).collect();
+        let rhythm: Vec<_> = object_strains.iter().map(|strain| strain.rhythm).collect();
+        let stamina: Vec<_> = object_strains.iter().map(|strain| strain.stamina).collect();
+
+        assert_eq!(max(&colour), max(&strains.color));
+        assert_eq!(max(&rhythm), max(&strains.rhythm));
+        assert_eq!(max(&stamina), max(&strains.stamina));
+    }
+
+    #[test]
+    fn convert_flag() {
+        let (map, _) = test_data();
+        let attrs = TaikoPP::new(&map).calculate();
+        assert!(!attrs.is_convert());
+
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let attrs = TaikoPP::new(&map).calculate();
+        assert!(attrs.is_convert());
+
+        let gradual = crate::taiko::TaikoGradualDifficultyAttributes::new(&map, 0);
+        assert!(gradual.last().unwrap().is_convert);
+    }
+
+    #[test]
+    fn tuning() {
+        let map = Beatmap::from_path("./maps/2785319.osu").unwrap();
+        let default = TaikoPP::new(&map).calculate();
+        assert_eq!(
+            TaikoPP::new(&map)
+                .tuning(TaikoTuning::default())
+                .calculate(),
+            default
+        );
+
+        let tuning = TaikoTuning {
+            convert_multiplier: 1.0,
+            convert_mono_multiplier: 1.0,
+            ..Default::default()
+        };
+
+        let tuned = TaikoPP::new(&map).tuning(tuning).calculate();
+        assert_eq!(tuned.difficulty.peak, default.difficulty.peak);
+        assert_eq!(
+            tuned.stars(),
+            crate::taiko::rescale(tuned.difficulty.peak * 1.4)
+        );
+        assert!(tuned.pp() > default.pp());
+
+        let gradual = crate::taiko::TaikoGradualDifficultyAttributes::new(&map, 0).tuning(tuning);
+        assert_eq!(gradual.last(), Some(tuned.difficulty));
+    }
+
+    #[test]
+    fn ok_hit_window() {
+        let (map, _) = test_data();
+
+        let nomod = TaikoPP::new(&map).n100(40).calculate();
+        let hr = TaikoPP::new(&map).mods(16).n100(40).calculate();
+        let ez = TaikoPP::new(&map).mods(2).n100(40).calculate();
+
+        assert_eq!(nomod.difficulty.ok_hit_window, 80.0);
+        assert!(hr.difficulty.ok_hit_window < nomod.difficulty.ok_hit_window);
+        assert!(ez.difficulty.ok_hit_window > nomod.difficulty.ok_hit_window);
+        assert!(hr.pp_acc > nomod.pp_acc);
+        assert!(ez.pp_acc < nomod.pp_acc);
+
+        let calculator = TaikoPP::new(&map).attributes(nomod.difficulty).n100(150);
+        let regular = calculator.clone().calculate();
+        let strict = calculator.ok_hit_window(50.0).calculate();
+
+        assert!(strict.pp_acc > regular.pp_acc);
+        assert_eq!(strict.difficulty.ok_hit_window, 50.0);
+    }
 }
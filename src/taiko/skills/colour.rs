@@ -1,10 +1,5 @@
-use std::{
-    cell::RefCell,
-    rc::{Rc, Weak},
-};
-
 use crate::taiko::{
-    colours::{AlternatingMonoPattern, MonoStreak, RepeatingHitPatterns},
+    colours::{AlternatingMonoPattern, ColourLists, MonoStreak, RepeatingHitPatterns},
     difficulty_object::{ObjectLists, TaikoDifficultyObject},
 };
 
@@ -83,8 +78,8 @@ impl StrainDecaySkill for Colour {
     }
 
     #[inline]
-    fn strain_value_of(&mut self, curr: &TaikoDifficultyObject, _: &ObjectLists) -> f64 {
-        ColourEvaluator::evaluate_diff_of(curr)
+    fn strain_value_of(&mut self, curr: &TaikoDifficultyObject, hit_objects: &ObjectLists) -> f64 {
+        ColourEvaluator::evaluate_diff_of(curr, &hit_objects.colours)
     }
 }
 
@@ -97,62 +92,57 @@ impl ColourEvaluator {
         sigmoid * (height / 2.0) + middle
     }
 
-    fn evaluate_diff_of_mono_streak(mono_streak: Rc<RefCell<MonoStreak>>) -> f64 {
-        let mono_streak = mono_streak.borrow();
-
-        let parent_eval = mono_streak
-            .parent
-            .as_ref()
-            .and_then(Weak::upgrade)
-            .map_or(1.0, Self::evaluate_diff_of_alternating_mono_pattern);
+    fn evaluate_diff_of_mono_streak(mono_streak: &MonoStreak, colours: &ColourLists) -> f64 {
+        let parent_eval = mono_streak.parent.map_or(1.0, |idx| {
+            Self::evaluate_diff_of_alternating_mono_pattern(
+                &colours.alternating_mono_patterns[idx],
+                colours,
+            )
+        });
 
         Self::sigmoid(mono_streak.idx as f64, 2.0, 2.0, 0.5, 1.0) * parent_eval * 0.5
     }
 
     fn evaluate_diff_of_alternating_mono_pattern(
-        alternating_mono_pattern: Rc<RefCell<AlternatingMonoPattern>>,
+        alternating_mono_pattern: &AlternatingMonoPattern,
+        colours: &ColourLists,
     ) -> f64 {
-        let alternating_mono_pattern = alternating_mono_pattern.borrow();
-
-        let parent_eval = alternating_mono_pattern
-            .parent
-            .as_ref()
-            .and_then(Weak::upgrade)
-            .map_or(1.0, Self::evaluate_diff_of_repeating_hit_patterns);
+        let parent_eval = alternating_mono_pattern.parent.map_or(1.0, |idx| {
+            Self::evaluate_diff_of_repeating_hit_patterns(&colours.repeating_hit_patterns[idx])
+        });
 
         Self::sigmoid(alternating_mono_pattern.idx as f64, 2.0, 2.0, 0.5, 1.0) * parent_eval
     }
 
     fn evaluate_diff_of_repeating_hit_patterns(
-        repeating_hit_patterns: Rc<RefCell<RepeatingHitPatterns>>,
+        repeating_hit_patterns: &RepeatingHitPatterns,
     ) -> f64 {
-        let repetition_interval = repeating_hit_patterns.borrow().repetition_interval as f64;
+        let repetition_interval = repeating_hit_patterns.repetition_interval as f64;
 
         2.0 * (1.0 - Self::sigmoid(repetition_interval, 2.0, 2.0, 0.5, 1.0))
     }
 
-    fn evaluate_diff_of(hit_object: &TaikoDifficultyObject) -> f64 {
+    fn evaluate_diff_of(hit_object: &TaikoDifficultyObject, colours: &ColourLists) -> f64 {
         let colour = &hit_object.colour;
         let mut difficulty = 0.0;
 
         // * Difficulty for MonoStreak
-        if let Some(mono_streak) = colour.mono_streak.as_ref().and_then(Weak::upgrade) {
-            difficulty += Self::evaluate_diff_of_mono_streak(mono_streak);
+        if let Some(idx) = colour.mono_streak {
+            difficulty += Self::evaluate_diff_of_mono_streak(&colours.mono_streaks[idx], colours);
         }
 
         // * Difficulty for AlternatingMonoPattern
-        if let Some(alternating_mono_pattern) = colour
-            .alternating_mono_pattern
-            .as_ref()
-            .and_then(Weak::upgrade)
-        {
-            difficulty += Self::evaluate_diff_of_alternating_mono_pattern(alternating_mono_pattern);
+        if let Some(idx) = colour.alternating_mono_pattern {
+            difficulty += Self::evaluate_diff_of_alternating_mono_pattern(
+                &colours.alternating_mono_patterns[idx],
+                colours,
+            );
         }
 
         // * Difficulty for RepeatingHitPattern
-        if let Some(repeating_hit_patterns) = colour.repeating_hit_patterns.as_ref().map(Rc::clone)
-        {
-            difficulty += Self::evaluate_diff_of_repeating_hit_patterns(repeating_hit_patterns);
+        if let Some(idx) = colour.repeating_hit_patterns {
+            difficulty +=
+                Self::evaluate_diff_of_repeating_hit_patterns(&colours.repeating_hit_patterns[idx]);
         }
 
         difficulty
@@ -2,7 +2,9 @@ use std::cmp::Ordering;
 
 use crate::taiko::difficulty_object::{ObjectLists, TaikoDifficultyObject};
 
-use super::{colour::Colour, rhythm::Rhythm, stamina::Stamina, Skill, StrainSkill};
+use super::{
+    colour::Colour, rhythm::Rhythm, stamina::Stamina, Skill, StrainDecaySkill, StrainSkill,
+};
 
 #[derive(Clone, Debug)]
 pub(crate) struct Peaks {
@@ -50,6 +52,15 @@ impl Peaks {
         }
     }
 
+    /// The current strains of the colour, rhythm, and stamina skill.
+    pub(crate) fn curr_strains(&self) -> (f64, f64, f64) {
+        (
+            self.colour.curr_strain(),
+            self.rhythm.curr_strain(),
+            self.stamina.curr_strain(),
+        )
+    }
+
     fn norm(p: f64, values: impl IntoIterator<Item = f64>) -> f64 {
         values
             .into_iter()
@@ -103,7 +103,7 @@ impl StaminaEvaluator {
 
         if let Some(key_prev) = key_prev {
             // * Add a base strain to all objects
-            0.5 + Self::speed_bonus(curr.start_time - key_prev.borrow().start_time)
+            0.5 + Self::speed_bonus(curr.start_time - key_prev.start_time)
         } else {
             // * There is no previous hit object hit by the current key
             0.0
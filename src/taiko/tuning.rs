@@ -0,0 +1,100 @@
+use super::{rescale, skills::PeaksDifficultyValues, TaikoDifficultyAttributes};
+
+/// Multipliers and convert penalties of the osu!taiko difficulty calculation.
+///
+/// The default values are the ones of the regular calculation.
+/// Servers experimenting with osu!taiko balancing can adjust them through
+/// [`TaikoPP::tuning`](crate::TaikoPP::tuning), [`TaikoStars::tuning`](crate::TaikoStars::tuning),
+/// or [`TaikoGradualDifficultyAttributes::tuning`](crate::taiko::TaikoGradualDifficultyAttributes::tuning).
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, TaikoPP, taiko::TaikoTuning};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let tuning = TaikoTuning {
+///     convert_multiplier: 1.0,
+///     ..Default::default()
+/// };
+///
+/// let pp_result = TaikoPP::new(&map).tuning(tuning).calculate();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TaikoTuning {
+    /// Multiplier applied to the rating of every skill.
+    pub difficulty_multiplier: f64,
+    /// Multiplier applied to the combined rating before rescaling it to the star rating.
+    pub combined_rating_scale: f64,
+    /// Multiplier applied to the star rating of converts.
+    pub convert_multiplier: f64,
+    /// Converts with a colour rating below this value may receive the
+    /// [`convert_mono_multiplier`](TaikoTuning::convert_mono_multiplier).
+    pub convert_colour_threshold: f64,
+    /// Converts with a stamina rating above this value may receive the
+    /// [`convert_mono_multiplier`](TaikoTuning::convert_mono_multiplier).
+    pub convert_stamina_threshold: f64,
+    /// Additional multiplier for converts with low colour variance and high stamina requirement
+    /// because multiple inputs are more likely to be abused.
+    pub convert_mono_multiplier: f64,
+}
+
+impl Default for TaikoTuning {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            difficulty_multiplier: super::DIFFICULTY_MULTIPLIER,
+            combined_rating_scale: 1.4,
+            convert_multiplier: 0.925,
+            convert_colour_threshold: 2.0,
+            convert_stamina_threshold: 8.0,
+            convert_mono_multiplier: 0.8,
+        }
+    }
+}
+
+impl TaikoTuning {
+    /// Turn the skill values into ratings and the star rating of the attributes.
+    pub(crate) fn apply(
+        &self,
+        attrs: &mut TaikoDifficultyAttributes,
+        values: PeaksDifficultyValues,
+    ) {
+        let PeaksDifficultyValues {
+            mut colour_rating,
+            mut rhythm_rating,
+            mut stamina_rating,
+            mut combined_rating,
+        } = values;
+
+        colour_rating *= self.difficulty_multiplier;
+        rhythm_rating *= self.difficulty_multiplier;
+        stamina_rating *= self.difficulty_multiplier;
+        combined_rating *= self.difficulty_multiplier;
+
+        let mut star_rating = rescale(combined_rating * self.combined_rating_scale);
+
+        // * TODO: This is temporary measure as we don't detect abuse of multiple-input
+        // * playstyles of converts within the current system.
+        if attrs.is_convert {
+            star_rating *= self.convert_multiplier;
+
+            // * For maps with low colour variance and high stamina requirement,
+            // * multiple inputs are more likely to be abused.
+            if colour_rating < self.convert_colour_threshold
+                && stamina_rating > self.convert_stamina_threshold
+            {
+                star_rating *= self.convert_mono_multiplier;
+            }
+        }
+
+        attrs.stamina = stamina_rating;
+        attrs.colour = colour_rating;
+        attrs.rhythm = rhythm_rating;
+        attrs.peak = combined_rating;
+        attrs.stars = star_rating;
+    }
+}
@@ -0,0 +1,327 @@
+mod pp;
+
+use std::borrow::Cow;
+
+pub use self::pp::*;
+
+use crate::{beatmap::difficulty_range, parse::HitSound, Beatmap, GameMode, Mods};
+
+const SECTION_LEN: f64 = 400.0;
+const STAR_SCALING_FACTOR: f64 = 0.04125;
+const DECAY_WEIGHT: f64 = 0.9;
+const STRAIN_DECAY_BASE: f64 = 0.3;
+
+const COLOUR_CHANGE_BONUS: f64 = 0.75;
+const RHYTHM_CHANGE_BONUS: f64 = 1.0;
+const RHYTHM_CHANGE_BASE_THRESHOLD: f64 = 0.2;
+const RHYTHM_CHANGE_BASE: f64 = 2.0;
+
+/// Difficulty calculator on osu!taiko maps using the single-strain algorithm
+/// from before the 2020 rework.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, taiko_2020::TaikoStars};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+///
+/// let difficulty_attrs = TaikoStars::new(&map)
+///     .mods(8 + 64) // HDDT
+///     .calculate();
+///
+/// println!("Stars: {}", difficulty_attrs.stars);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TaikoStars<'map> {
+    map: Cow<'map, Beatmap>,
+    mods: u32,
+    passed_objects: Option<usize>,
+    clock_rate: Option<f64>,
+}
+
+impl<'map> TaikoStars<'map> {
+    /// Create a new difficulty calculator for osu!taiko maps.
+    #[inline]
+    pub fn new(map: &'map Beatmap) -> Self {
+        Self {
+            map: map.convert_mode(GameMode::Taiko),
+            mods: 0,
+            passed_objects: None,
+            clock_rate: None,
+        }
+    }
+
+    /// Specify mods through their bit values.
+    ///
+    /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// Amount of passed objects for partial plays, e.g. a fail.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects = Some(passed_objects);
+
+        self
+    }
+
+    /// Adjust the clock rate used in the calculation.
+    /// If none is specified, it will take the clock rate based on the mods
+    /// i.e. 1.5 for DT, 0.75 for HT and 1.0 otherwise.
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: f64) -> Self {
+        self.clock_rate = Some(clock_rate);
+
+        self
+    }
+
+    /// Calculate the star rating.
+    pub fn calculate(self) -> TaikoDifficultyAttributes {
+        let clock_rate = self.clock_rate.unwrap_or_else(|| self.mods.clock_rate());
+        let od = (self.map.od as f64 * self.mods.od_ar_hp_multiplier()).min(10.0);
+
+        let mut attrs = TaikoDifficultyAttributes {
+            stars: 0.0,
+            max_combo: 0,
+            hit_window: difficulty_range(od, 50.0, 35.0, 20.0).floor() / clock_rate,
+        };
+
+        let mut take = self.passed_objects.unwrap_or(usize::MAX);
+
+        let mut objects = self
+            .map
+            .hit_objects
+            .iter()
+            .zip(self.map.sounds.iter())
+            .take_while(|(h, _)| {
+                if h.is_circle() {
+                    if take == 0 {
+                        return false;
+                    }
+
+                    take -= 1;
+                    attrs.max_combo += 1;
+                }
+
+                true
+            })
+            .map(|(h, &sound)| {
+                DifficultyObject::new(h.start_time, h.is_circle(), sound, clock_rate)
+            });
+
+        let mut prev = match objects.next() {
+            Some(first) => first,
+            None => return attrs,
+        };
+
+        let mut strain_peaks = Vec::new();
+        let mut curr_section_end = (prev.time / SECTION_LEN).ceil() * SECTION_LEN;
+        let mut max_strain = prev.strain;
+
+        for mut curr in objects {
+            while curr.time > curr_section_end {
+                strain_peaks.push(max_strain);
+
+                let decay = STRAIN_DECAY_BASE.powf((curr_section_end - prev.time) / 1000.0);
+                max_strain = prev.strain * decay;
+                curr_section_end += SECTION_LEN;
+            }
+
+            curr.calculate_strain(&prev);
+            max_strain = max_strain.max(curr.strain);
+            prev = curr;
+        }
+
+        strain_peaks.push(max_strain);
+        strain_peaks.sort_unstable_by(|a, b| b.total_cmp(a));
+
+        let mut difficulty = 0.0;
+        let mut weight = 1.0;
+
+        for strain in strain_peaks {
+            difficulty += strain * weight;
+            weight *= DECAY_WEIGHT;
+        }
+
+        attrs.stars = difficulty * STAR_SCALING_FACTOR;
+
+        attrs
+    }
+}
+
+/// The result of a difficulty calculation with the pre-2020 osu!taiko algorithm.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaikoDifficultyAttributes {
+    /// The final star rating.
+    pub stars: f64,
+    /// The maximum combo.
+    pub max_combo: usize,
+    /// The hit window for an n300 inclusive of rate-adjusting mods (DT/HT/etc).
+    pub hit_window: f64,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum ColourSwitch {
+    None,
+    Even,
+    Odd,
+}
+
+#[derive(Clone, Debug)]
+struct DifficultyObject {
+    time: f64,
+    delta: f64,
+    is_hit: bool,
+    is_rim: bool,
+    strain: f64,
+    same_colour_count: usize,
+    last_colour_switch: ColourSwitch,
+}
+
+impl DifficultyObject {
+    fn new(start_time: f64, is_hit: bool, sound: u8, clock_rate: f64) -> Self {
+        Self {
+            time: start_time / clock_rate,
+            delta: 0.0,
+            is_hit,
+            is_rim: sound.clap() || sound.whistle(),
+            strain: 1.0,
+            same_colour_count: 1,
+            last_colour_switch: ColourSwitch::None,
+        }
+    }
+
+    fn calculate_strain(&mut self, prev: &Self) {
+        self.delta = self.time - prev.time;
+        let decay = STRAIN_DECAY_BASE.powf(self.delta / 1000.0);
+        let mut addition = 1.0;
+
+        if self.is_hit && prev.is_hit && self.delta < 1000.0 {
+            if self.has_colour_change(prev) {
+                addition += COLOUR_CHANGE_BONUS;
+            }
+
+            if self.has_rhythm_change(prev) {
+                addition += RHYTHM_CHANGE_BONUS;
+            }
+        } else {
+            self.last_colour_switch = ColourSwitch::None;
+            self.same_colour_count = 1;
+        }
+
+        // Very short intervals e.g. for drum rolls are less strenuous
+        let addition_factor = if self.delta < 50.0 {
+            0.4 + 0.6 * self.delta / 50.0
+        } else {
+            1.0
+        };
+
+        self.strain = prev.strain * decay + addition * addition_factor;
+    }
+
+    fn has_colour_change(&mut self, prev: &Self) -> bool {
+        if self.is_rim == prev.is_rim {
+            self.last_colour_switch = prev.last_colour_switch;
+            self.same_colour_count = prev.same_colour_count + 1;
+
+            return false;
+        }
+
+        self.last_colour_switch = if prev.same_colour_count & 1 == 0 {
+            ColourSwitch::Even
+        } else {
+            ColourSwitch::Odd
+        };
+
+        // Only switching after a different amount of notes than last time counts
+        prev.last_colour_switch != ColourSwitch::None
+            && prev.last_colour_switch != self.last_colour_switch
+    }
+
+    fn has_rhythm_change(&self, prev: &Self) -> bool {
+        if self.delta.abs() <= f64::EPSILON || prev.delta.abs() <= f64::EPSILON {
+            return false;
+        }
+
+        let ratio = (prev.delta / self.delta).max(self.delta / prev.delta);
+
+        if ratio >= 8.0 {
+            return false;
+        }
+
+        let difference = ratio.log(RHYTHM_CHANGE_BASE) % 1.0;
+
+        difference > RHYTHM_CHANGE_BASE_THRESHOLD && difference < 1.0 - RHYTHM_CHANGE_BASE_THRESHOLD
+    }
+}
+
+#[cfg(not(any(feature = "async_tokio", feature = "async_std")))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_stars_and_pp() {
+        let map = Beatmap::from_path("./maps/1028484.osu").unwrap();
+
+        let nomod = TaikoStars::new(&map).calculate();
+        let dt = TaikoStars::new(&map).mods(64).calculate();
+
+        assert_eq!(
+            nomod.max_combo,
+            crate::TaikoStars::new(&map).calculate().max_combo
+        );
+        assert!(nomod.stars > 0.0);
+        assert!(dt.stars > nomod.stars);
+        assert!(dt.hit_window < nomod.hit_window);
+
+        let ss = TaikoPP::new(&map).attributes(nomod).calculate();
+        assert_eq!(ss.state.n300, nomod.max_combo);
+
+        let partial = TaikoPP::new(&map)
+            .attributes(ss)
+            .accuracy(95.0)
+            .n_misses(3)
+            .calculate();
+
+        assert_eq!(partial.state.total_hits(), nomod.max_combo);
+        assert!((partial.state.accuracy() - 0.95).abs() < 0.01);
+        assert!(partial.pp < ss.pp);
+    }
+
+    #[test]
+    fn colour_and_rhythm_changes() {
+        const KAT: u8 = 2;
+
+        let objects = [(0.0, 0), (200.0, 0), (400.0, KAT), (600.0, 0), (900.0, 0)];
+        let mut objects = objects
+            .iter()
+            .map(|&(time, sound)| DifficultyObject::new(time, true, sound, 1.0));
+
+        let mut prev = objects.next().unwrap();
+        let mut colour_changes = Vec::new();
+        let mut rhythm_changes = Vec::new();
+
+        for mut curr in objects {
+            curr.delta = curr.time - prev.time;
+            colour_changes.push(curr.has_colour_change(&prev));
+            rhythm_changes.push(curr.has_rhythm_change(&prev));
+            curr.calculate_strain(&prev);
+            prev = curr;
+        }
+
+        // The first switch only sets the parity, the second one differs from it
+        assert_eq!(colour_changes, [false, false, true, false]);
+        // Only the 200ms -> 300ms interval is not a power of two
+        assert_eq!(rhythm_changes, [false, false, false, true]);
+    }
+}
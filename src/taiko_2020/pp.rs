@@ -0,0 +1,287 @@
+use crate::{taiko::TaikoScoreState, Beatmap, Mods};
+
+use super::{TaikoDifficultyAttributes, TaikoStars};
+
+/// Performance calculator on osu!taiko maps using the formulas
+/// from before the 2020 rework.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, taiko_2020::TaikoPP};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+///
+/// let pp_result = TaikoPP::new(&map)
+///     .mods(8 + 64) // HDDT
+///     .combo(1234)
+///     .accuracy(98.5)
+///     .n_misses(1)
+///     .calculate();
+///
+/// println!("PP: {} | Stars: {}", pp_result.pp, pp_result.stars());
+/// ```
+#[derive(Clone, Debug)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct TaikoPP<'map> {
+    map: &'map Beatmap,
+    attributes: Option<TaikoDifficultyAttributes>,
+    mods: u32,
+    combo: Option<usize>,
+    acc: Option<f64>,
+    passed_objects: Option<usize>,
+    clock_rate: Option<f64>,
+
+    n300: Option<usize>,
+    n100: Option<usize>,
+    n_misses: usize,
+}
+
+impl<'map> TaikoPP<'map> {
+    /// Create a new performance calculator for osu!taiko maps.
+    #[inline]
+    pub fn new(map: &'map Beatmap) -> Self {
+        Self {
+            map,
+            attributes: None,
+            mods: 0,
+            combo: None,
+            acc: None,
+            passed_objects: None,
+            clock_rate: None,
+            n300: None,
+            n100: None,
+            n_misses: 0,
+        }
+    }
+
+    /// Provide the result of a previous difficulty or performance calculation.
+    /// If you already calculated the attributes for the current map-mod combination,
+    /// be sure to put them in here so that they don't have to be recalculated.
+    #[inline]
+    pub fn attributes(mut self, attributes: impl Into<TaikoDifficultyAttributes>) -> Self {
+        self.attributes = Some(attributes.into());
+
+        self
+    }
+
+    /// Specify mods through their bit values.
+    ///
+    /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// Specify the max combo of the play.
+    #[inline]
+    pub fn combo(mut self, combo: usize) -> Self {
+        self.combo = Some(combo);
+
+        self
+    }
+
+    /// Specify the amount of 300s of a play.
+    #[inline]
+    pub fn n300(mut self, n300: usize) -> Self {
+        self.n300 = Some(n300);
+
+        self
+    }
+
+    /// Specify the amount of 100s of a play.
+    #[inline]
+    pub fn n100(mut self, n100: usize) -> Self {
+        self.n100 = Some(n100);
+
+        self
+    }
+
+    /// Specify the amount of misses of the play.
+    #[inline]
+    pub fn n_misses(mut self, n_misses: usize) -> Self {
+        self.n_misses = n_misses;
+
+        self
+    }
+
+    /// Set the accuracy between `0.0` and `100.0`.
+    ///
+    /// Specified 300s and 100s are ignored in favor of the accuracy.
+    #[inline]
+    pub fn accuracy(mut self, acc: f64) -> Self {
+        self.acc = Some(acc / 100.0);
+
+        self
+    }
+
+    /// Amount of passed objects for partial plays, e.g. a fail.
+    #[inline]
+    pub fn passed_objects(mut self, passed_objects: usize) -> Self {
+        self.passed_objects = Some(passed_objects);
+
+        self
+    }
+
+    /// Adjust the clock rate used in the calculation.
+    /// If none is specified, it will take the clock rate based on the mods
+    /// i.e. 1.5 for DT, 0.75 for HT and 1.0 otherwise.
+    #[inline]
+    pub fn clock_rate(mut self, clock_rate: f64) -> Self {
+        self.clock_rate = Some(clock_rate);
+
+        self
+    }
+
+    /// Calculate all performance related values, including pp and stars.
+    pub fn calculate(self) -> TaikoPerformanceAttributes {
+        let difficulty = self.attributes.unwrap_or_else(|| {
+            let mut calculator = TaikoStars::new(self.map).mods(self.mods);
+
+            if let Some(passed_objects) = self.passed_objects {
+                calculator = calculator.passed_objects(passed_objects);
+            }
+
+            if let Some(clock_rate) = self.clock_rate {
+                calculator = calculator.clock_rate(clock_rate);
+            }
+
+            calculator.calculate()
+        });
+
+        let total = match self.passed_objects {
+            Some(passed_objects) => difficulty.max_combo.min(passed_objects),
+            None => difficulty.max_combo,
+        };
+
+        let n_misses = self.n_misses.min(total);
+        let n_remaining = total - n_misses;
+
+        let (n300, n100) = match (self.acc, self.n300, self.n100) {
+            (Some(acc), ..) => {
+                let target_total = (acc * (total * 2) as f64).round() as usize;
+                let n300 = target_total.saturating_sub(n_remaining).min(n_remaining);
+
+                (n300, n_remaining - n300)
+            }
+            (None, Some(n300), _) => {
+                let n300 = n300.min(n_remaining);
+
+                (n300, n_remaining - n300)
+            }
+            (None, None, n100) => {
+                let n100 = n100.unwrap_or(0).min(n_remaining);
+
+                (n_remaining - n100, n100)
+            }
+        };
+
+        let state = TaikoScoreState {
+            max_combo: self.combo.unwrap_or(total - n_misses).min(total),
+            n300,
+            n100,
+            n_misses,
+        };
+
+        let mut multiplier = 1.1;
+
+        if self.mods.nf() {
+            multiplier *= 0.9;
+        }
+
+        if self.mods.hd() {
+            multiplier *= 1.1;
+        }
+
+        let acc = state.accuracy();
+        let pp_strain = self.compute_strain_value(&difficulty, n_misses, acc);
+        let pp_acc = compute_accuracy_value(&difficulty, acc);
+
+        let pp = (pp_strain.powf(1.1) + pp_acc.powf(1.1)).powf(1.0 / 1.1) * multiplier;
+
+        TaikoPerformanceAttributes {
+            difficulty,
+            pp,
+            pp_acc,
+            pp_strain,
+            state,
+        }
+    }
+
+    fn compute_strain_value(
+        &self,
+        attributes: &TaikoDifficultyAttributes,
+        n_misses: usize,
+        acc: f64,
+    ) -> f64 {
+        let exp_base = 5.0 * (attributes.stars / 0.0075).max(1.0) - 4.0;
+        let mut strain = exp_base * exp_base / 100_000.0;
+
+        let len_bonus = 1.0 + 0.1 * (attributes.max_combo as f64 / 1500.0).min(1.0);
+        strain *= len_bonus;
+        strain *= 0.985_f64.powi(n_misses as i32);
+
+        if self.mods.hd() {
+            strain *= 1.025;
+        }
+
+        if self.mods.fl() {
+            strain *= 1.05 * len_bonus;
+        }
+
+        strain * acc
+    }
+}
+
+fn compute_accuracy_value(attributes: &TaikoDifficultyAttributes, acc: f64) -> f64 {
+    if attributes.hit_window <= 0.0 {
+        return 0.0;
+    }
+
+    (150.0 / attributes.hit_window).powf(1.1)
+        * acc.powi(15)
+        * 22.0
+        * (attributes.max_combo as f64 / 1500.0).powf(0.3).min(1.15)
+}
+
+/// The result of a performance calculation with the pre-2020 osu!taiko formulas.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TaikoPerformanceAttributes {
+    /// The difficulty attributes that were used for the performance calculation.
+    pub difficulty: TaikoDifficultyAttributes,
+    /// The final performance points.
+    pub pp: f64,
+    /// The accuracy portion of the final pp.
+    pub pp_acc: f64,
+    /// The strain portion of the final pp.
+    pub pp_strain: f64,
+    /// The hitresults and combo that were used for the performance calculation.
+    pub state: TaikoScoreState,
+}
+
+impl TaikoPerformanceAttributes {
+    /// Return the star value.
+    #[inline]
+    pub fn stars(&self) -> f64 {
+        self.difficulty.stars
+    }
+
+    /// Return the maximum combo of the map.
+    #[inline]
+    pub fn max_combo(&self) -> usize {
+        self.difficulty.max_combo
+    }
+}
+
+impl From<TaikoPerformanceAttributes> for TaikoDifficultyAttributes {
+    #[inline]
+    fn from(attributes: TaikoPerformanceAttributes) -> Self {
+        attributes.difficulty
+    }
+}
@@ -0,0 +1,72 @@
+/// How strain peaks are merged when they are downsampled into fewer points.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StrainAggregation {
+    /// Use the highest strain peak of a bucket.
+    #[default]
+    Max,
+    /// Use the average of all strain peaks of a bucket.
+    Mean,
+}
+
+/// The start time of the first strain section.
+///
+/// Skills begin with the section that ends at the first multiple of `section_len`
+/// at or after the start time of the first difficulty object.
+pub(crate) fn first_section_start(first_start_time: Option<f64>, section_len: f64) -> f64 {
+    match first_start_time {
+        Some(time) => (time / section_len).ceil() * section_len - section_len,
+        None => 0.0,
+    }
+}
+
+/// Merge `peaks` into exactly `n_buckets` values.
+///
+/// Each bucket covers the same fraction of the peaks. If there are fewer peaks
+/// than buckets, a peak may contribute to multiple buckets.
+pub(crate) fn downsample(
+    peaks: &[f64],
+    n_buckets: usize,
+    aggregation: StrainAggregation,
+) -> Vec<f64> {
+    if peaks.is_empty() {
+        return Vec::new();
+    }
+
+    (0..n_buckets)
+        .map(|i| {
+            let start = i * peaks.len() / n_buckets;
+            let end = ((i + 1) * peaks.len() / n_buckets).max(start + 1);
+            let bucket = &peaks[start..end];
+
+            match aggregation {
+                StrainAggregation::Max => bucket.iter().copied().fold(0.0, f64::max),
+                StrainAggregation::Mean => bucket.iter().sum::<f64>() / bucket.len() as f64,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_bucket_count() {
+        let peaks = [1.0, 3.0, 2.0, 5.0, 4.0];
+
+        assert_eq!(downsample(&peaks, 2, StrainAggregation::Max), [3.0, 5.0]);
+        assert_eq!(
+            downsample(&peaks, 2, StrainAggregation::Mean),
+            [2.0, 11.0 / 3.0]
+        );
+        assert_eq!(downsample(&peaks, 7, StrainAggregation::Max).len(), 7);
+        assert!(downsample(&[], 3, StrainAggregation::Max).is_empty());
+    }
+
+    #[test]
+    fn section_starts_before_first_object() {
+        assert_eq!(first_section_start(Some(2810.0), 400.0), 2800.0);
+        assert_eq!(first_section_start(Some(2800.0), 400.0), 2400.0);
+        assert_eq!(first_section_start(None, 400.0), 0.0);
+    }
+}
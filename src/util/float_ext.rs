@@ -29,6 +29,17 @@ impl FloatExt for f64 {
     }
 }
 
+/// Decimal places of floating point values returned by the osu!api.
+const API_DECIMALS: i32 = 2;
+
+/// Round the value to [`API_DECIMALS`] decimal places, rounding ties to even.
+#[inline]
+pub(crate) fn round_api(value: f64) -> f64 {
+    let factor = 10_f64.powi(API_DECIMALS);
+
+    (value * factor).round_even() / factor
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -62,4 +73,11 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn round_api() {
+        assert_eq!(super::round_api(5.678), 5.68);
+        assert_eq!(super::round_api(0.125), 0.12);
+        assert_eq!(super::round_api(-1.004), -1.0);
+    }
 }
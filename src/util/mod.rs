@@ -1,12 +1,18 @@
 mod byte_hasher;
+mod downsample;
 mod float_ext;
 mod limited_queue;
 mod sorted_vec;
+mod special_functions;
 mod tandem_sort;
 
-pub use self::sorted_vec::SortedVec;
+pub use self::{downsample::StrainAggregation, sorted_vec::SortedVec};
 
 pub(crate) use self::{
-    byte_hasher::ByteHasher, float_ext::FloatExt, limited_queue::LimitedQueue,
+    byte_hasher::ByteHasher,
+    downsample::{downsample, first_section_start},
+    float_ext::{round_api, FloatExt},
+    limited_queue::LimitedQueue,
+    special_functions::{erf, erf_inv},
     tandem_sort::TandemSorter,
 };
@@ -0,0 +1,99 @@
+use std::f64::consts::PI;
+
+/// The error function.
+///
+/// Uses a Chebyshev approximation with a fractional error below `1.2e-7`.
+pub(crate) fn erf(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+
+    let erfc = t * poly.exp();
+
+    if x >= 0.0 {
+        1.0 - erfc
+    } else {
+        erfc - 1.0
+    }
+}
+
+/// The inverse of the error function.
+///
+/// Returns infinity for `x` of `1.0` or above and
+/// negative infinity for `x` of `-1.0` or below.
+pub(crate) fn erf_inv(x: f64) -> f64 {
+    if x <= -1.0 {
+        return f64::NEG_INFINITY;
+    } else if x >= 1.0 {
+        return f64::INFINITY;
+    } else if x == 0.0 {
+        return 0.0;
+    }
+
+    // Initial guess by Mike Giles, "Approximating the erfinv function"
+    let mut w = -((1.0 - x) * (1.0 + x)).ln();
+
+    let mut y = if w < 5.0 {
+        w -= 2.5;
+
+        let mut p = 2.810_226_36e-8;
+        p = 3.432_739_39e-7 + p * w;
+        p = -3.523_387_7e-6 + p * w;
+        p = -4.391_506_54e-6 + p * w;
+        p = 0.000_218_580_87 + p * w;
+        p = -0.001_253_725_03 + p * w;
+        p = -0.004_177_681_64 + p * w;
+        p = 0.246_640_727 + p * w;
+        p = 1.501_409_41 + p * w;
+
+        p * x
+    } else {
+        w = w.sqrt() - 3.0;
+
+        let mut p = -0.000_200_214_257;
+        p = 0.000_100_950_558 + p * w;
+        p = 0.001_349_343_22 + p * w;
+        p = -0.003_673_428_44 + p * w;
+        p = 0.005_739_507_73 + p * w;
+        p = -0.007_622_461_3 + p * w;
+        p = 0.009_438_870_47 + p * w;
+        p = 1.001_674_06 + p * w;
+        p = 2.832_976_82 + p * w;
+
+        p * x
+    };
+
+    // Refine the guess through Newton's method
+    for _ in 0..2 {
+        let derivative = 2.0 / PI.sqrt() * (-y * y).exp();
+
+        if derivative <= f64::EPSILON {
+            break;
+        }
+
+        y -= (erf(y) - x) / derivative;
+    }
+
+    y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn erf_inv_inverts_erf() {
+        for &x in [-0.99, -0.5, -0.1, 0.0, 0.3, 0.75, 0.999].iter() {
+            let y = erf(erf_inv(x));
+            assert!((x - y).abs() < 1e-6, "{} vs {}", x, y);
+        }
+    }
+}
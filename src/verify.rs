@@ -0,0 +1,372 @@
+use std::fmt;
+
+use crate::{
+    catch::CatchScoreState, mania::ManiaScoreState, osu::OsuScoreState, taiko::TaikoScoreState,
+    DifficultyAttributes, Mods, ScoreState,
+};
+
+/// Mod combinations that can never be active at the same time.
+const INCOMPATIBLE_MODS: [u32; 14] = [
+    u32::EZ | u32::HR,
+    u32::DT | u32::HT,
+    u32::NC | u32::HT,
+    u32::NF | u32::SD,
+    u32::NF | u32::PF,
+    u32::NF | u32::RX,
+    u32::NF | u32::AP,
+    u32::SD | u32::RX,
+    u32::SD | u32::AP,
+    u32::PF | u32::RX,
+    u32::PF | u32::AP,
+    u32::RX | u32::AP,
+    u32::RX | u32::SO,
+    u32::SO | u32::AP,
+];
+
+/// Allowed difference between a submitted accuracy and the accuracy of the hitresults.
+const ACCURACY_TOLERANCE: f64 = 0.01;
+
+/// Verify a submitted score against the attributes of its map.
+///
+/// Instead of stopping at the first problem, all violations are collected
+/// so that they can be used e.g. as a pre-filter for anti-cheat checks.
+///
+/// # Example
+///
+/// ```
+/// use murasame_pp::{Beatmap, BeatmapExt, ScoreState, verify::ScoreVerifier};
+///
+/// # /*
+/// let map: Beatmap = ...
+/// # */
+/// # let map = Beatmap::default();
+/// let attrs = map.stars().mods(8 + 64).calculate();
+///
+/// let state = ScoreState {
+///     max_combo: 1234,
+///     n300: 500,
+///     ..Default::default()
+/// };
+///
+/// let violations = ScoreVerifier::new(attrs)
+///     .mods(8 + 64)
+///     .state(state)
+///     .accuracy(98.5)
+///     .verify();
+///
+/// for violation in violations {
+///     println!("{}", violation);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScoreVerifier {
+    attributes: DifficultyAttributes,
+    mods: u32,
+    state: ScoreState,
+    acc: Option<f64>,
+}
+
+impl ScoreVerifier {
+    /// Create a new verifier for scores on the map of the given attributes.
+    ///
+    /// The attributes must be calculated with the same mods as the score's.
+    /// Their mode determines the mode of the score.
+    #[inline]
+    pub fn new(attributes: impl Into<DifficultyAttributes>) -> Self {
+        Self {
+            attributes: attributes.into(),
+            mods: 0,
+            state: ScoreState::default(),
+            acc: None,
+        }
+    }
+
+    /// Specify mods through their bit values.
+    ///
+    /// See [https://github.com/ppy/osu-api/wiki#mods](https://github.com/ppy/osu-api/wiki#mods)
+    #[inline]
+    pub fn mods(mut self, mods: u32) -> Self {
+        self.mods = mods;
+
+        self
+    }
+
+    /// Specify the hitresults and max combo of the score.
+    #[inline]
+    pub fn state(mut self, state: ScoreState) -> Self {
+        self.state = state;
+
+        self
+    }
+
+    /// Specify the submitted accuracy between `0.0` and `100.0`.
+    ///
+    /// If specified, it will be compared against the accuracy of the hitresults.
+    #[inline]
+    pub fn accuracy(mut self, acc: f64) -> Self {
+        self.acc = Some(acc);
+
+        self
+    }
+
+    /// Check the score and return all violations.
+    ///
+    /// An empty list means that the score is consistent with its map.
+    pub fn verify(self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        self.verify_mods(&mut violations);
+        self.verify_hitresults(&mut violations);
+
+        let max_combo = self.attributes.max_combo();
+
+        // Combo is irrelevant for osu!mania
+        if !matches!(self.attributes, DifficultyAttributes::Mania(_))
+            && self.state.max_combo > max_combo
+        {
+            violations.push(Violation::Combo {
+                combo: self.state.max_combo,
+                max_combo,
+            });
+        }
+
+        if let Some(claimed) = self.acc {
+            let actual = self.hitresult_accuracy() * 100.0;
+
+            if !(0.0..=100.0).contains(&claimed) || (claimed - actual).abs() > ACCURACY_TOLERANCE {
+                violations.push(Violation::Accuracy { claimed, actual });
+            }
+        }
+
+        violations
+    }
+
+    fn verify_mods(&self, violations: &mut Vec<Violation>) {
+        for &incompatible in INCOMPATIBLE_MODS.iter() {
+            if self.mods & incompatible == incompatible {
+                violations.push(Violation::IncompatibleMods(incompatible));
+            }
+        }
+
+        let unavailable = match self.attributes {
+            DifficultyAttributes::Osu(_) => 0,
+            DifficultyAttributes::Taiko(_) | DifficultyAttributes::Catch(_) => u32::AP | u32::SO,
+            DifficultyAttributes::Mania(_) => u32::RX | u32::AP | u32::SO,
+        };
+
+        if self.mods & unavailable > 0 {
+            violations.push(Violation::UnavailableMods(self.mods & unavailable));
+        }
+    }
+
+    fn verify_hitresults(&self, violations: &mut Vec<Violation>) {
+        let state = &self.state;
+
+        let (total, n_objects) = match &self.attributes {
+            DifficultyAttributes::Osu(attrs) => (
+                state.n300 + state.n100 + state.n50 + state.n_misses,
+                attrs.n_circles + attrs.n_sliders + attrs.n_spinners,
+            ),
+            DifficultyAttributes::Taiko(attrs) => {
+                (state.n300 + state.n100 + state.n_misses, attrs.max_combo)
+            }
+            DifficultyAttributes::Catch(attrs) => {
+                let n_tiny_droplets = state.n50 + state.n_katu;
+
+                if n_tiny_droplets > attrs.n_tiny_droplets {
+                    violations.push(Violation::TinyDropletCount {
+                        total: n_tiny_droplets,
+                        n_tiny_droplets: attrs.n_tiny_droplets,
+                    });
+                }
+
+                (state.n300 + state.n100 + state.n_misses, attrs.max_combo())
+            }
+            DifficultyAttributes::Mania(attrs) => (
+                state.n_geki + state.n300 + state.n_katu + state.n100 + state.n50 + state.n_misses,
+                attrs.n_objects,
+            ),
+        };
+
+        if total > n_objects {
+            violations.push(Violation::HitCount { total, n_objects });
+        }
+    }
+
+    fn hitresult_accuracy(&self) -> f64 {
+        let state = self.state;
+
+        match self.attributes {
+            DifficultyAttributes::Osu(_) => OsuScoreState::from(state).accuracy(),
+            DifficultyAttributes::Taiko(_) => TaikoScoreState::from(state).accuracy(),
+            DifficultyAttributes::Catch(_) => CatchScoreState::from(state).accuracy(),
+            DifficultyAttributes::Mania(_) => ManiaScoreState::from(state).accuracy(),
+        }
+    }
+}
+
+/// An inconsistency found by a [`ScoreVerifier`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// The hitresults add up to more than the amount of objects.
+    HitCount {
+        /// The sum of all hitresults.
+        total: usize,
+        /// The amount of objects of the map.
+        n_objects: usize,
+    },
+    /// The tiny droplet hits and misses add up to more than the amount of tiny droplets.
+    ///
+    /// Only relevant for osu!catch.
+    TinyDropletCount {
+        /// The sum of tiny droplet hits and misses.
+        total: usize,
+        /// The amount of tiny droplets of the map.
+        n_tiny_droplets: usize,
+    },
+    /// The combo is larger than the map's maximum combo.
+    Combo {
+        /// The submitted combo.
+        combo: usize,
+        /// The maximum combo of the map.
+        max_combo: usize,
+    },
+    /// The submitted accuracy does not match the accuracy of the hitresults.
+    Accuracy {
+        /// The submitted accuracy between `0.0` and `100.0`.
+        claimed: f64,
+        /// The accuracy of the hitresults between `0.0` and `100.0`.
+        actual: f64,
+    },
+    /// The contained mods can not be active at the same time.
+    IncompatibleMods(u32),
+    /// The contained mods are not available in the score's mode.
+    UnavailableMods(u32),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HitCount { total, n_objects } => write!(
+                f,
+                "hitresults add up to {} but the map only has {} objects",
+                total, n_objects
+            ),
+            Self::TinyDropletCount {
+                total,
+                n_tiny_droplets,
+            } => write!(
+                f,
+                "tiny droplets add up to {} but the map only has {}",
+                total, n_tiny_droplets
+            ),
+            Self::Combo { combo, max_combo } => {
+                write!(f, "combo {} exceeds the max combo {}", combo, max_combo)
+            }
+            Self::Accuracy { claimed, actual } => write!(
+                f,
+                "accuracy {:.2} does not match the hitresults' accuracy {:.2}",
+                claimed, actual
+            ),
+            Self::IncompatibleMods(mods) => write!(f, "incompatible mods {}", mods),
+            Self::UnavailableMods(mods) => write!(f, "mods {} are not available", mods),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mania::ManiaDifficultyAttributes, osu::OsuDifficultyAttributes};
+
+    #[test]
+    fn collects_all_violations() {
+        let attrs = OsuDifficultyAttributes {
+            n_circles: 10,
+            max_combo: 10,
+            ..Default::default()
+        };
+
+        let state = ScoreState {
+            max_combo: 11,
+            n300: 11,
+            ..Default::default()
+        };
+
+        let violations = ScoreVerifier::new(attrs)
+            .mods(u32::DT | u32::HT)
+            .state(state)
+            .accuracy(100.0)
+            .verify();
+
+        let expected = vec![
+            Violation::IncompatibleMods(u32::DT | u32::HT),
+            Violation::HitCount {
+                total: 11,
+                n_objects: 10,
+            },
+            Violation::Combo {
+                combo: 11,
+                max_combo: 10,
+            },
+        ];
+
+        assert_eq!(violations, expected);
+    }
+
+    #[test]
+    fn incompatible_mod_pairs() {
+        let pairs = [
+            u32::NF | u32::SD,
+            u32::NF | u32::PF,
+            u32::RX | u32::SO,
+            u32::NC | u32::HT,
+        ];
+
+        for &mods in pairs.iter() {
+            let violations = ScoreVerifier::new(OsuDifficultyAttributes::default())
+                .mods(mods)
+                .verify();
+
+            assert_eq!(violations, vec![Violation::IncompatibleMods(mods)]);
+        }
+
+        // HDHRSD is a valid combination
+        let violations = ScoreVerifier::new(OsuDifficultyAttributes::default())
+            .mods(u32::HD | u32::HR | u32::SD)
+            .verify();
+
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn mania_objects_from_attributes() {
+        let attrs = ManiaDifficultyAttributes {
+            n_objects: 10,
+            ..Default::default()
+        };
+
+        let state = ScoreState {
+            n_geki: 6,
+            n300: 4,
+            ..Default::default()
+        };
+
+        let violations = ScoreVerifier::new(attrs).state(state).verify();
+        assert!(violations.is_empty());
+
+        let state = ScoreState {
+            n_misses: 1,
+            ..state
+        };
+
+        let violations = ScoreVerifier::new(attrs).state(state).verify();
+
+        let expected = vec![Violation::HitCount {
+            total: 11,
+            n_objects: 10,
+        }];
+
+        assert_eq!(violations, expected);
+    }
+}